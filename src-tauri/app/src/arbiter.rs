@@ -0,0 +1,173 @@
+use chess::{Board, Color, Coord, Move};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ArbiterError {
+    #[error("The piece on {0} was touched and has to be moved")]
+    TouchMove(Coord),
+
+    #[error("Illegal move {mv}, attempt {attempts} for {side}")]
+    IllegalMove { mv: Move, side: Color, attempts: u32 },
+
+    #[error("{0} lost the game after too many illegal moves")]
+    Forfeit(Color),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ArbiterSettings {
+    pub touch_move: bool,
+    // a side loses once it reaches this many illegal moves, 0 only counts them
+    pub illegal_move_limit: u32,
+}
+
+impl Default for ArbiterSettings {
+    fn default() -> Self {
+        ArbiterSettings {
+            touch_move: true,
+            illegal_move_limit: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArbiterStatus {
+    pub settings: ArbiterSettings,
+    pub touched: Option<Coord>,
+    pub white_illegal_moves: u32,
+    pub black_illegal_moves: u32,
+    pub forfeited: Option<Color>,
+}
+
+// follows the over-the-board rules between selecting a piece and completing a move
+#[derive(Debug, Clone)]
+pub struct Arbiter {
+    settings: ArbiterSettings,
+    touched: Option<Coord>,
+    illegal_moves: [u32; 2],
+    forfeited: Option<Color>,
+}
+
+impl Arbiter {
+    pub fn new(settings: ArbiterSettings) -> Self {
+        Arbiter {
+            settings,
+            touched: None,
+            illegal_moves: [0, 0],
+            forfeited: None,
+        }
+    }
+
+    pub fn status(&self) -> ArbiterStatus {
+        return ArbiterStatus {
+            settings: self.settings,
+            touched: self.touched,
+            white_illegal_moves: self.illegal_moves[0],
+            black_illegal_moves: self.illegal_moves[1],
+            forfeited: self.forfeited,
+        };
+    }
+
+    // only the first touched piece that can move is binding, touching an immobile piece has no consequence
+    pub fn touch(&mut self, board: &Board, coord: Coord) -> Result<Vec<Move>, ArbiterError> {
+        self.ensure_playing()?;
+
+        let moves = movable(board, coord);
+
+        if !self.settings.touch_move || !board.side(board.turn()).all().is_set(coord) {
+            return Ok(moves);
+        }
+
+        match self.touched {
+            Some(touched) if touched != coord => return Err(ArbiterError::TouchMove(touched)),
+            None if !moves.is_empty() => self.touched = Some(coord),
+            _ => {}
+        }
+
+        return Ok(moves);
+    }
+
+    // the move is checked before it reaches the board, an illegal one counts against the side to move
+    pub fn check_move(&mut self, board: &Board, mv: &Move) -> Result<(), ArbiterError> {
+        self.ensure_playing()?;
+
+        if let Some(touched) = self.touched.filter(|touched| *touched != mv.from) {
+            return Err(ArbiterError::TouchMove(touched));
+        }
+
+        if movable(board, mv.from).iter().any(|legal| legal.from == mv.from && legal.to == mv.to) {
+            self.touched = None;
+            return Ok(());
+        }
+
+        let side = board.turn();
+        let attempts = &mut self.illegal_moves[side_index(side)];
+        *attempts += 1;
+
+        if self.settings.illegal_move_limit > 0 && *attempts >= self.settings.illegal_move_limit {
+            self.forfeited = Some(side);
+            return Err(ArbiterError::Forfeit(side));
+        }
+
+        return Err(ArbiterError::IllegalMove {
+            mv: *mv,
+            side,
+            attempts: *attempts,
+        });
+    }
+
+    fn ensure_playing(&self) -> Result<(), ArbiterError> {
+        return match self.forfeited {
+            Some(side) => Err(ArbiterError::Forfeit(side)),
+            None => Ok(()),
+        };
+    }
+}
+
+fn movable(board: &Board, coord: Coord) -> Vec<Move> {
+    return chess::get_moves(board.turn(), board).into_iter().filter(|mv| mv.from == coord).collect();
+}
+
+fn side_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touch_move_and_illegal_moves() {
+        let board = Board::new_game();
+        let mut arbiter = Arbiter::new(ArbiterSettings::default());
+
+        assert!(arbiter.touch(&board, Coord::new('e', 1)).unwrap().is_empty());
+        assert_eq!(2, arbiter.touch(&board, Coord::new('g', 1)).unwrap().len());
+        assert_eq!(Err(ArbiterError::TouchMove(Coord::new('g', 1))), arbiter.touch(&board, Coord::new('e', 2)).map(|_| ()));
+        assert_eq!(
+            Err(ArbiterError::TouchMove(Coord::new('g', 1))),
+            arbiter.check_move(&board, &Move::new(Coord::new('e', 2), Coord::new('e', 4)))
+        );
+
+        let illegal = Move::new(Coord::new('g', 1), Coord::new('g', 3));
+
+        assert_eq!(
+            Err(ArbiterError::IllegalMove {
+                mv: illegal,
+                side: Color::White,
+                attempts: 1
+            }),
+            arbiter.check_move(&board, &illegal)
+        );
+
+        arbiter.check_move(&board, &Move::new(Coord::new('g', 1), Coord::new('f', 3))).unwrap();
+
+        assert_eq!(None, arbiter.status().touched);
+        assert_eq!(Err(ArbiterError::Forfeit(Color::White)), arbiter.check_move(&board, &illegal));
+        assert_eq!(Err(ArbiterError::Forfeit(Color::White)), arbiter.touch(&board, Coord::new('e', 2)).map(|_| ()));
+    }
+}
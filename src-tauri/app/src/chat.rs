@@ -0,0 +1,124 @@
+use std::time::{Duration, Instant};
+
+use chess::Color;
+use serde::{Deserialize, Serialize};
+
+pub const MAX_LENGTH: usize = 500;
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+const RATE_LIMIT: usize = 5;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ChatError {
+    #[error("Chat messages cannot be empty")]
+    Empty,
+
+    #[error("Chat messages are limited to {MAX_LENGTH} characters")]
+    TooLong,
+
+    #[error("Too many messages, wait a moment before sending more")]
+    RateLimited,
+
+    #[error("The message belongs to game {0}, which is over")]
+    OtherGame(u64),
+}
+
+// the message as it is sent to the other player, tied to the game it was written in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub game: u64,
+    pub from: Color,
+    pub text: String,
+    pub sent_ms: u64,
+}
+
+// keeps the messages of the current game only, a message from a newer game starts over,
+// each side can send a few messages within a short window
+#[derive(Debug, Default)]
+pub struct Chat {
+    game: u64,
+    messages: Vec<ChatMessage>,
+    recent: Vec<(Color, Instant)>,
+}
+
+impl Chat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // used for messages written here as well as for those received from the other player
+    pub fn post(&mut self, mut message: ChatMessage, now: Instant) -> Result<ChatMessage, ChatError> {
+        message.text = message.text.trim().to_string();
+
+        if message.text.is_empty() {
+            return Err(ChatError::Empty);
+        }
+
+        if message.text.chars().count() > MAX_LENGTH {
+            return Err(ChatError::TooLong);
+        }
+
+        if message.game < self.game {
+            return Err(ChatError::OtherGame(message.game));
+        }
+
+        if message.game > self.game {
+            self.game = message.game;
+            self.messages.clear();
+            self.recent.clear();
+        }
+
+        self.recent.retain(|(_, sent)| now.saturating_duration_since(*sent) < RATE_WINDOW);
+
+        if self.recent.iter().filter(|(from, _)| *from == message.from).count() >= RATE_LIMIT {
+            return Err(ChatError::RateLimited);
+        }
+
+        self.recent.push((message.from, now));
+        self.messages.push(message.clone());
+
+        return Ok(message);
+    }
+
+    pub fn messages(&self, game: u64) -> &[ChatMessage] {
+        return if game == self.game { &self.messages } else { &[] };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(game: u64, from: Color, text: &str) -> ChatMessage {
+        return ChatMessage {
+            game,
+            from,
+            text: text.to_string(),
+            sent_ms: 0,
+        };
+    }
+
+    #[test]
+    fn limits_and_scopes_messages() {
+        let mut chat = Chat::new();
+        let now = Instant::now();
+
+        for _ in 0..RATE_LIMIT {
+            chat.post(message(1, Color::White, " hi "), now).unwrap();
+        }
+
+        assert_eq!(Err(ChatError::RateLimited), chat.post(message(1, Color::White, "hi"), now));
+        assert!(chat.post(message(1, Color::Black, "hello"), now).is_ok());
+        assert!(chat.post(message(1, Color::White, "again"), now + RATE_WINDOW).is_ok());
+
+        assert_eq!(Err(ChatError::Empty), chat.post(message(1, Color::Black, "  "), now));
+        assert_eq!(Err(ChatError::TooLong), chat.post(message(1, Color::Black, &"x".repeat(MAX_LENGTH + 1)), now));
+        assert_eq!("hi", chat.messages(1)[0].text);
+
+        chat.post(message(2, Color::Black, "new game"), now).unwrap();
+
+        assert_eq!(1, chat.messages(2).len());
+        assert!(chat.messages(1).is_empty());
+        assert_eq!(Err(ChatError::OtherGame(1)), chat.post(message(1, Color::White, "late"), now));
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use chess::{pgn, Board, Color};
+use serde::{Deserialize, Serialize};
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+// deadlines are wall clock times, so they keep running while the app is closed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrespondenceGame {
+    pub days_per_move: u32,
+    pub turn: Color,
+    pub deadline_ms: u64,
+    pub warned: bool,
+    pub flagged: Option<Color>,
+    pub pgn: String,
+}
+
+impl CorrespondenceGame {
+    pub fn new(days_per_move: u32, board: &Board, now_ms: u64) -> Self {
+        CorrespondenceGame {
+            days_per_move,
+            turn: board.turn(),
+            deadline_ms: now_ms + days_per_move as u64 * DAY_MS,
+            warned: false,
+            flagged: None,
+            pgn: pgn::write_pgn(board, &[]),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        return match fs::read_to_string(path) {
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        return Ok(());
+    }
+
+    pub fn remove(path: &Path) -> Result<()> {
+        return match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        };
+    }
+
+    pub fn remaining_ms(&self, now_ms: u64) -> u64 {
+        return self.deadline_ms.saturating_sub(now_ms);
+    }
+
+    // a move made in time gives the opponent a full period, a late one loses the game for the side that made it
+    pub fn record_move(&mut self, board: &Board, now_ms: u64) -> Option<Color> {
+        if self.flagged.is_none() && self.remaining_ms(now_ms) == 0 {
+            self.flagged = Some(self.turn);
+        }
+
+        if self.flagged.is_none() {
+            self.turn = board.turn();
+            self.deadline_ms = now_ms + self.days_per_move as u64 * DAY_MS;
+            self.warned = false;
+        }
+
+        self.pgn = pgn::write_pgn(board, &[]);
+
+        return self.flagged;
+    }
+
+    // the warning is given once per move, when a day or a quarter of the period is left, whichever is shorter
+    pub fn take_warning(&mut self, now_ms: u64) -> bool {
+        let near = DAY_MS.min(self.days_per_move as u64 * DAY_MS / 4);
+
+        if self.warned || self.flagged.is_some() || self.remaining_ms(now_ms) > near {
+            return false;
+        }
+
+        self.warned = true;
+
+        return true;
+    }
+}
+
+pub fn now_ms() -> u64 {
+    return SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis() as u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Coord, Move};
+
+    #[test]
+    fn deadlines_and_warnings() {
+        let mut board = Board::new_game();
+        let mut game = CorrespondenceGame::new(3, &board, 0);
+
+        assert_eq!((Color::White, 3 * DAY_MS), (game.turn, game.deadline_ms));
+        assert!(!game.take_warning(2 * DAY_MS));
+        assert!(game.take_warning(2 * DAY_MS + DAY_MS / 2));
+        assert!(!game.take_warning(2 * DAY_MS + DAY_MS / 2));
+
+        board.exec_move(&Move::new(Coord::new('e', 2), Coord::new('e', 4))).unwrap();
+
+        assert_eq!(None, game.record_move(&board, 2 * DAY_MS + DAY_MS / 2));
+        assert_eq!((Color::Black, 5 * DAY_MS + DAY_MS / 2, false), (game.turn, game.deadline_ms, game.warned));
+
+        board.exec_move(&Move::new(Coord::new('e', 7), Coord::new('e', 5))).unwrap();
+
+        assert_eq!(Some(Color::Black), game.record_move(&board, 6 * DAY_MS));
+        assert!(!game.take_warning(6 * DAY_MS));
+        assert!(game.pgn.contains("1. e4 e5"));
+    }
+}
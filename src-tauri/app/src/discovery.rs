@@ -0,0 +1,107 @@
+use std::{
+    io::{self, ErrorKind},
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+pub const DISCOVERY_PORT: u16 = 47_317;
+
+// every packet starts with the magic line, so unrelated broadcasts on the port are ignored
+const MAGIC: &str = "rusty-chess-game/1";
+const ADVERTISE_INTERVAL: Duration = Duration::from_secs(1);
+
+// what a host broadcasts, the port is the one its game listens on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Advertisement {
+    pub name: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredGame {
+    pub name: String,
+    pub address: String,
+}
+
+pub fn encode(advertisement: &Advertisement) -> Vec<u8> {
+    let json = serde_json::to_string(advertisement).expect("advertisement to serialize");
+    return format!("{MAGIC}\n{json}").into_bytes();
+}
+
+// the game is reached at the address the packet came from with the advertised port
+pub fn decode(packet: &[u8], from: SocketAddr) -> Option<DiscoveredGame> {
+    let text = std::str::from_utf8(packet).ok()?;
+    let json = text.strip_prefix(MAGIC)?.strip_prefix('\n')?;
+    let advertisement = serde_json::from_str::<Advertisement>(json).ok()?;
+
+    return Some(DiscoveredGame {
+        name: advertisement.name,
+        address: SocketAddr::new(from.ip(), advertisement.port).to_string(),
+    });
+}
+
+// broadcasts the advertisement to the local network until stopped
+pub fn advertise(advertisement: &Advertisement, stop: &AtomicBool) -> io::Result<()> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+
+    let packet = encode(advertisement);
+
+    while !stop.load(Ordering::Relaxed) {
+        socket.send_to(&packet, (Ipv4Addr::BROADCAST, DISCOVERY_PORT))?;
+        thread::sleep(ADVERTISE_INTERVAL);
+    }
+
+    return Ok(());
+}
+
+// listens for advertisements for the given time, hosts advertise every second, so a short wait finds them all
+pub fn discover(timeout: Duration) -> io::Result<Vec<DiscoveredGame>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT))?;
+    let deadline = Instant::now() + timeout;
+    let mut games: Vec<DiscoveredGame> = Vec::new();
+    let mut buffer = [0; 1024];
+
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()).filter(|remaining| !remaining.is_zero()) {
+        socket.set_read_timeout(Some(remaining))?;
+
+        match socket.recv_from(&mut buffer) {
+            Ok((length, from)) => {
+                if let Some(game) = decode(&buffer[..length], from).filter(|game| !games.contains(game)) {
+                    games.push(game);
+                }
+            }
+            Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    return Ok(games);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packets_round_trip() {
+        let advertisement = Advertisement {
+            name: "Friday blitz".to_string(),
+            port: 9000,
+        };
+        let from = "192.168.1.20:53211".parse().unwrap();
+
+        let game = decode(&encode(&advertisement), from).unwrap();
+
+        assert_eq!("Friday blitz", game.name);
+        assert_eq!("192.168.1.20:9000", game.address);
+        assert_eq!(None, decode(b"something else\n{}", from));
+        assert_eq!(None, decode(&encode(&advertisement)[..MAGIC.len() + 4], from));
+    }
+}
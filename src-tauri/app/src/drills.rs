@@ -0,0 +1,91 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use anyhow::Result;
+use chess::{training::DrillVerdict, Color};
+use serde::{Deserialize, Serialize};
+
+// how often one line of the repertoire was played through to its end and how often it was left early
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineProgress {
+    pub line: String,
+    pub player: Color,
+    pub completed: u32,
+    pub deviations: u32,
+}
+
+// the progress is only there once the line ended, by completing it or leaving the book
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrillReport {
+    pub verdict: DrillVerdict,
+    pub progress: Option<LineProgress>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrillProgress {
+    pub lines: Vec<LineProgress>,
+}
+
+impl DrillProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        return match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::new()),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        return Ok(());
+    }
+
+    // a deviation is kept under the line up to the position where the player left the book
+    pub fn record(&mut self, line: String, player: Color, completed: bool) -> &LineProgress {
+        let index = match self.lines.iter().position(|progress| progress.line == line && progress.player == player) {
+            Some(index) => index,
+            None => {
+                self.lines.push(LineProgress {
+                    line,
+                    player,
+                    completed: 0,
+                    deviations: 0,
+                });
+                self.lines.len() - 1
+            }
+        };
+
+        let progress = &mut self.lines[index];
+
+        match completed {
+            true => progress.completed += 1,
+            false => progress.deviations += 1,
+        }
+
+        return progress;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_lines() {
+        let mut progress = DrillProgress::new();
+
+        progress.record("e2e4 e7e5".to_string(), Color::White, false);
+        progress.record("e2e4 e7e5".to_string(), Color::White, true);
+        progress.record("e2e4 e7e5".to_string(), Color::Black, true);
+
+        assert_eq!(2, progress.lines.len());
+        assert_eq!((1, 1), (progress.lines[0].completed, progress.lines[0].deviations));
+        assert_eq!((1, 0), (progress.lines[1].completed, progress.lines[1].deviations));
+    }
+}
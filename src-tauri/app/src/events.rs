@@ -0,0 +1,234 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chess::{
+    clock::ClockSnapshot,
+    draw::DrawReason,
+    engine::{GameOutcome, IterationInfo, PvLine, SearchStats},
+    pgn,
+    review::GuessScore,
+    tree::MoveTree,
+    Board, Color, Coord, Move, Piece,
+};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    arbiter::ArbiterStatus, chat::ChatMessage, correspondence::CorrespondenceGame, guess::GuessStatus, hotseat::TournamentStatus, match_play::MatchStatus,
+    phase::Phase, simul::SimulProgress, BoardPayload, BoardView, OpenStudy, PlaybackStatus, Visibility,
+};
+
+// bumped whenever the shape of an existing event changes, so the frontend can detect a mismatch
+pub const EVENT_VERSION: u32 = 1;
+pub const EVENT_NAME: &str = "app-event";
+
+// every nth move is sent as a full snapshot, so a frontend that missed a delta recovers on its own
+const SNAPSHOT_INTERVAL: u64 = 20;
+
+static MOVES_SENT: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardDelta {
+    pub mv: Move,
+    pub piece: Piece,
+    pub captured: Option<Coord>,
+    pub promoted: Option<Piece>,
+    pub rook: Option<(Coord, Coord)>,
+    pub turn: Color,
+    pub white_checked: bool,
+    pub black_checked: bool,
+    pub winner: Option<Color>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MoveCue {
+    Move,
+    Capture,
+    Castle,
+    Promotion,
+    Check,
+    GameEnd,
+}
+
+impl BoardDelta {
+    // only a single move between the two boards can be described as a delta
+    pub fn new(before: &Board, after: &Board) -> Option<Self> {
+        let mv = after.last_move()?;
+        let mut previous = after.clone();
+
+        if previous.undo_move().is_err() || previous != *before {
+            return None;
+        }
+
+        let captured = if mv.en_passant {
+            Some(Coord::new(mv.to.column(), mv.from.row()))
+        } else {
+            before.piece_at(mv.to).map(|piece| piece.coord)
+        };
+
+        let rook = mv.castling.then(|| {
+            let (rook_from, rook_to) = if mv.to.column() > mv.from.column() { ('h', 'f') } else { ('a', 'd') };
+            (Coord::new(rook_from, mv.from.row()), Coord::new(rook_to, mv.from.row()))
+        });
+
+        return Some(BoardDelta {
+            mv,
+            piece: before.piece_at(mv.from)?,
+            captured,
+            promoted: if mv.promotion { after.piece_at(mv.to) } else { None },
+            rook,
+            turn: after.turn(),
+            white_checked: after.white_checked(),
+            black_checked: after.black_checked(),
+            winner: after.winner(),
+        });
+    }
+
+    // sounds are picked from these, a plain move only gets a cue when nothing more specific happened
+    pub fn cues(&self, after: &Board) -> Vec<MoveCue> {
+        let mut cues = Vec::new();
+
+        if self.captured.is_some() {
+            cues.push(MoveCue::Capture);
+        }
+
+        if self.rook.is_some() {
+            cues.push(MoveCue::Castle);
+        }
+
+        if self.promoted.is_some() {
+            cues.push(MoveCue::Promotion);
+        }
+
+        if self.white_checked || self.black_checked {
+            cues.push(MoveCue::Check);
+        }
+
+        if pgn::result(after) != "*" {
+            cues.push(MoveCue::GameEnd);
+        }
+
+        if cues.is_empty() {
+            cues.push(MoveCue::Move);
+        }
+
+        return cues;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "payload", rename_all = "camelCase")]
+pub enum AppEvent {
+    BoardUpdated(BoardPayload),
+    BoardDelta(BoardDelta),
+    MovePlayed { mv: Move, cues: Vec<MoveCue> },
+    TakebackRequested { by: Color, plies: usize },
+    TakebackAnswered { by: Color, accepted: bool },
+    ClockUpdated(Option<ClockSnapshot>),
+    CorrespondenceUpdated(Option<CorrespondenceGame>),
+    CorrespondenceDeadlineNear { turn: Color, deadline_ms: u64 },
+    GameEnded { winner: Option<Color>, result: &'static str },
+    DrawClaimed { reason: DrawReason },
+    DrawOffered { by: Color },
+    DrawOfferAnswered { by: Color, accepted: bool },
+    Resigned { by: Color },
+    ChatReceived(ChatMessage),
+    ArbiterUpdated(Option<ArbiterStatus>),
+    MoveTreeUpdated(MoveTree),
+    PhaseChanged(Phase),
+    StudyUpdated(Option<OpenStudy>),
+    EngineStarted,
+    EngineFinished,
+    AnalysisInfo(IterationInfo),
+    SearchStats(SearchStats),
+    SearchLines(Vec<PvLine>),
+    AutoPlayFinished(GameOutcome),
+    PlaybackUpdated(PlaybackStatus),
+    GuessScored { guess: GuessScore, actual: Move, status: GuessStatus },
+    SimulUpdated(SimulProgress),
+    MatchUpdated(Option<MatchStatus>),
+    TournamentUpdated(Option<TournamentStatus>),
+    Error { message: String },
+}
+
+#[derive(Clone, Serialize)]
+struct Envelope {
+    version: u32,
+    #[serde(flatten)]
+    event: AppEvent,
+}
+
+pub fn emit(app: &AppHandle, event: AppEvent) -> tauri::Result<()> {
+    return app.emit_all(EVENT_NAME, Envelope { version: EVENT_VERSION, event });
+}
+
+// single moves are sent as deltas, anything else like undos or loaded positions as a full snapshot,
+// deltas name the moved pieces, so with hidden pieces every move is sent as a filtered snapshot instead
+pub fn emit_change(app: &AppHandle, before: &Board, after: &Board, view: BoardView) -> tauri::Result<()> {
+    let Some(delta) = BoardDelta::new(before, after) else {
+        return emit_board(app, after, view);
+    };
+
+    emit(app, AppEvent::MovePlayed { mv: delta.mv, cues: delta.cues(after) })?;
+
+    if MOVES_SENT.fetch_add(1, Ordering::Relaxed) % SNAPSHOT_INTERVAL == 0 || view.visibility != Visibility::Full {
+        return emit_board(app, after, view);
+    }
+
+    emit(app, AppEvent::BoardDelta(delta))?;
+
+    return emit_game_end(app, after);
+}
+
+pub fn emit_board(app: &AppHandle, board: &Board, view: BoardView) -> tauri::Result<()> {
+    emit(app, AppEvent::BoardUpdated(BoardPayload::new(board, view)))?;
+    return emit_game_end(app, board);
+}
+
+// a finished game is announced right after the update that ended it
+fn emit_game_end(app: &AppHandle, board: &Board) -> tauri::Result<()> {
+    let result = pgn::result(board);
+
+    if result != "*" {
+        let (winner, result) = crate::draw_odds(app, board.winner(), result);
+        emit(app, AppEvent::GameEnded { winner, result })?;
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::PieceType;
+
+    #[test]
+    fn deltas_for_single_moves() {
+        let before = Board::from_fen("r3k3/1P6/8/3pP3/8/8/8/4K2R w Kq d6").unwrap();
+
+        let mut castled = before.clone();
+        castled.exec_move(&Move::castling(Coord::new('e', 1), Coord::new('g', 1))).unwrap();
+
+        let delta = BoardDelta::new(&before, &castled).unwrap();
+        assert_eq!(Some((Coord::new('h', 1), Coord::new('f', 1))), delta.rook);
+        assert_eq!(PieceType::King, delta.piece.piece_type);
+
+        let mut captured = before.clone();
+        captured.exec_move(&Move::en_passant(Coord::new('e', 5), Coord::new('d', 6))).unwrap();
+        assert_eq!(Some(Coord::new('d', 5)), BoardDelta::new(&before, &captured).unwrap().captured);
+
+        let mut promoted = before.clone();
+        promoted.exec_move(&Move::promotion(Coord::new('b', 7), Coord::new('a', 8))).unwrap();
+
+        let delta = BoardDelta::new(&before, &promoted).unwrap();
+        assert_eq!(Some(Coord::new('a', 8)), delta.captured);
+        assert_eq!(Some(PieceType::Queen), delta.promoted.map(|piece| piece.piece_type));
+
+        assert_eq!(vec![MoveCue::Capture, MoveCue::Promotion, MoveCue::Check], delta.cues(&promoted));
+        assert_eq!(vec![MoveCue::Castle], BoardDelta::new(&before, &castled).unwrap().cues(&castled));
+
+        assert!(BoardDelta::new(&before, &before).is_none());
+        assert!(BoardDelta::new(&castled, &promoted).is_none());
+    }
+}
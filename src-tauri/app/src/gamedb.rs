@@ -0,0 +1,519 @@
+use std::{io::BufRead, path::Path};
+
+use anyhow::Result;
+use chess::{
+    book::{Book, BookBuilder},
+    pgn::{self, PgnError, PgnGame, Termination},
+    rcg, san, Board, Color, EnPassantMode,
+};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::site_exports::{self, ImportError};
+
+// games are committed in batches, so a large import neither holds one huge transaction nor commits every game
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS games (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        white TEXT NOT NULL,
+        black TEXT NOT NULL,
+        date TEXT NOT NULL,
+        result TEXT NOT NULL,
+        eco TEXT,
+        pgn TEXT NOT NULL,
+        final_fen TEXT NOT NULL,
+        tags TEXT NOT NULL,
+        rcg BLOB,
+        termination TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS games_white ON games (white);
+    CREATE INDEX IF NOT EXISTS games_black ON games (black);
+    CREATE INDEX IF NOT EXISTS games_eco ON games (eco);
+
+    CREATE TABLE IF NOT EXISTS explorer_moves (
+        game_id INTEGER NOT NULL,
+        position TEXT NOT NULL,
+        san TEXT NOT NULL,
+        uci TEXT NOT NULL,
+        result TEXT NOT NULL
+    );
+
+    CREATE INDEX IF NOT EXISTS explorer_moves_position ON explorer_moves (position);
+";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameSummary {
+    pub id: i64,
+    pub white: String,
+    pub black: String,
+    pub date: String,
+    pub result: String,
+    pub eco: Option<String>,
+    pub termination: Option<Termination>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameRecord {
+    #[serde(flatten)]
+    pub summary: GameSummary,
+    pub pgn: String,
+    pub final_fen: String,
+    pub tags: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameFilter {
+    pub player: Option<String>,
+    pub eco: Option<String>,
+    pub result: Option<String>,
+    pub search: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplorerMove {
+    pub san: String,
+    pub uci: String,
+    pub count: u32,
+    pub white_wins: u32,
+    pub draws: u32,
+    pub black_wins: u32,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplorerStats {
+    pub count: u32,
+    pub moves: Vec<ExplorerMove>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSummary {
+    pub imported: u64,
+    pub skipped: u64,
+}
+
+pub struct GameDb {
+    connection: Connection,
+}
+
+impl GameDb {
+    pub fn open(path: &Path) -> Result<Self> {
+        return Self::init(Connection::open(path)?);
+    }
+
+    fn init(connection: Connection) -> Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+
+        let db = Self { connection };
+        db.add_missing_columns()?;
+        db.index_missing_games()?;
+
+        return Ok(db);
+    }
+
+    pub fn save(&self, board: &Board, tags: &[(String, String)]) -> Result<i64> {
+        let transaction = self.connection.unchecked_transaction()?;
+        let id = self.insert(board, tags, &pgn::write_pgn(board, tags))?;
+
+        transaction.commit()?;
+
+        return Ok(id);
+    }
+
+    // streams the games of a pgn file into the database, games that cannot be read are counted and skipped,
+    // exports of chess.com and lichess keep the clocks and evaluations in their comments
+    pub fn import_pgn(&self, reader: impl BufRead) -> Result<ImportSummary> {
+        return self.import_games(site_exports::read_pgn_export(reader));
+    }
+
+    // the games of the lichess api, one json object per line
+    pub fn import_lichess_ndjson(&self, reader: impl BufRead) -> Result<ImportSummary> {
+        return self.import_games(site_exports::read_lichess_ndjson(reader));
+    }
+
+    fn import_games(&self, games: impl Iterator<Item = std::result::Result<PgnGame, ImportError>>) -> Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+        let mut transaction = self.connection.unchecked_transaction()?;
+
+        for game in games {
+            let game = match game {
+                Ok(game) => game,
+                Err(ImportError::Pgn(PgnError::Io(err))) => return Err(err.into()),
+                Err(_) => {
+                    summary.skipped += 1;
+                    continue;
+                }
+            };
+
+            self.insert(&game.board, &game.tags, &pgn::write_tree_pgn(&game.tree, &game.tags))?;
+            summary.imported += 1;
+
+            if summary.imported % IMPORT_BATCH_SIZE as u64 == 0 {
+                transaction.commit()?;
+                transaction = self.connection.unchecked_transaction()?;
+            }
+        }
+
+        transaction.commit()?;
+
+        return Ok(summary);
+    }
+
+    // the pgn is stored as given, so the comments and variations of imported games are kept
+    fn insert(&self, board: &Board, tags: &[(String, String)], game_pgn: &str) -> Result<i64> {
+        let tag = |name: &str| tags.iter().find(|(key, _)| key == name).map(|(_, value)| value.clone());
+        let result = pgn::scored_result(board, tags);
+
+        self.connection.execute(
+            "INSERT INTO games (white, black, date, result, eco, pgn, final_fen, tags, rcg, termination)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                tag("White").unwrap_or_else(|| "?".to_string()),
+                tag("Black").unwrap_or_else(|| "?".to_string()),
+                tag("Date").unwrap_or_else(|| "????.??.??".to_string()),
+                result,
+                tag("ECO"),
+                game_pgn,
+                board.to_fen(),
+                serde_json::to_string(tags)?,
+                rcg::to_rcg(board, tags)?,
+                pgn::termination(board, tags).map(|termination| termination.as_str()),
+            ],
+        )?;
+
+        let id = self.connection.last_insert_rowid();
+
+        self.index_game(id, board, result)?;
+
+        return Ok(id);
+    }
+
+    // databases from before the compact format get the column, their games are read from the pgn instead,
+    // the termination of older games is left empty
+    fn add_missing_columns(&self) -> Result<()> {
+        let mut statement = self.connection.prepare("SELECT name FROM pragma_table_info('games')")?;
+        let columns = statement.query_map([], |row| row.get::<_, String>(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+
+        for (column, definition) in [("rcg", "rcg BLOB"), ("termination", "termination TEXT")] {
+            if !columns.iter().any(|existing| existing == column) {
+                self.connection.execute(&format!("ALTER TABLE games ADD COLUMN {definition}"), [])?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // games stored before the explorer existed are indexed when the database is opened
+    fn index_missing_games(&self) -> Result<()> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT id, pgn FROM games WHERE id NOT IN (SELECT DISTINCT game_id FROM explorer_moves)")?;
+
+        let games = statement
+            .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        for (id, game_pgn) in games {
+            if let Ok(game) = pgn::read_pgn(&game_pgn) {
+                self.index_game(id, &game.board, pgn::scored_result(&game.board, &game.tags))?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn index_game(&self, id: i64, board: &Board, result: &str) -> Result<()> {
+        let mut statement = self
+            .connection
+            .prepare("INSERT INTO explorer_moves (game_id, position, san, uci, result) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+
+        for window in board.history().windows(2) {
+            let (before, after) = (&window[0], &window[1]);
+
+            if let Some(mv) = after.last_move() {
+                statement.execute(params![id, position_key(before), san::to_san(before, &mv), mv.to_string(), result])?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // the score is the percentage of points won by the side playing the move
+    pub fn explorer_stats(&self, board: &Board) -> Result<ExplorerStats> {
+        let mut statement = self.connection.prepare(
+            "SELECT san, uci, COUNT(*),
+                    SUM(result = '1-0'), SUM(result = '1/2-1/2'), SUM(result = '0-1')
+             FROM explorer_moves
+             WHERE position = ?1
+             GROUP BY san, uci
+             ORDER BY COUNT(*) DESC, san",
+        )?;
+
+        let white_to_move = board.turn() == chess::Color::White;
+
+        let moves = statement
+            .query_map(params![position_key(board)], |row| {
+                let count: u32 = row.get(2)?;
+                let white_wins: u32 = row.get(3)?;
+                let draws: u32 = row.get(4)?;
+                let black_wins: u32 = row.get(5)?;
+
+                let wins = if white_to_move { white_wins } else { black_wins };
+
+                return Ok(ExplorerMove {
+                    san: row.get(0)?,
+                    uci: row.get(1)?,
+                    count,
+                    white_wins,
+                    draws,
+                    black_wins,
+                    score: (wins as f64 + draws as f64 / 2.0) / count as f64 * 100.0,
+                });
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        return Ok(ExplorerStats {
+            count: moves.iter().map(|mv| mv.count).sum(),
+            moves,
+        });
+    }
+
+    // every filter is optional, the player matches either side and search looks through names and moves
+    pub fn list(&self, filter: &GameFilter) -> Result<Vec<GameSummary>> {
+        let mut statement = self.connection.prepare(
+            "SELECT id, white, black, date, result, eco, termination FROM games
+             WHERE (?1 IS NULL OR white = ?1 OR black = ?1)
+               AND (?2 IS NULL OR eco LIKE ?2 || '%')
+               AND (?3 IS NULL OR result = ?3)
+               AND (?4 IS NULL OR white LIKE '%' || ?4 || '%' OR black LIKE '%' || ?4 || '%' OR pgn LIKE '%' || ?4 || '%')
+             ORDER BY id DESC
+             LIMIT ?5",
+        )?;
+
+        let rows = statement.query_map(
+            params![filter.player, filter.eco, filter.result, filter.search, filter.limit.unwrap_or(u32::MAX)],
+            read_summary,
+        )?;
+
+        return Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?);
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<GameRecord>> {
+        let record = self
+            .connection
+            .query_row(
+                "SELECT id, white, black, date, result, eco, termination, pgn, final_fen, tags FROM games WHERE id = ?1",
+                params![id],
+                |row| {
+                    return Ok((read_summary(row)?, row.get::<_, String>(7)?, row.get::<_, String>(8)?, row.get::<_, String>(9)?));
+                },
+            )
+            .optional()?;
+
+        let Some((summary, pgn, final_fen, tags)) = record else {
+            return Ok(None);
+        };
+
+        return Ok(Some(GameRecord {
+            summary,
+            pgn,
+            final_fen,
+            tags: serde_json::from_str(&tags)?,
+        }));
+    }
+
+    // the main line of a game, read from the compact copy of its moves where there is one
+    pub fn board(&self, id: i64) -> Result<Option<Board>> {
+        let game = self
+            .connection
+            .query_row("SELECT rcg, pgn FROM games WHERE id = ?1", params![id], |row| {
+                return Ok((row.get::<_, Option<Vec<u8>>>(0)?, row.get::<_, String>(1)?));
+            })
+            .optional()?;
+
+        return match game {
+            Some((Some(bytes), _)) => Ok(Some(rcg::read_rcg(&bytes)?.board)),
+            Some((None, game_pgn)) => Ok(Some(pgn::read_pgn(&game_pgn)?.board)),
+            None => Ok(None),
+        };
+    }
+
+    // a polyglot book of the games matching the filter, with a player only the moves of that player are taken
+    pub fn build_book(&self, filter: &GameFilter, max_plies: usize) -> Result<Book> {
+        let mut builder = BookBuilder::new(max_plies);
+
+        for summary in self.list(filter)? {
+            let Ok(Some(board)) = self.board(summary.id) else {
+                continue;
+            };
+
+            let player = filter
+                .player
+                .as_deref()
+                .map(|player| if summary.white == player { Color::White } else { Color::Black });
+
+            builder.add_game(&board, &summary.result, player);
+        }
+
+        return Ok(builder.build());
+    }
+
+    pub fn delete(&self, id: i64) -> Result<bool> {
+        let transaction = self.connection.unchecked_transaction()?;
+
+        self.connection.execute("DELETE FROM explorer_moves WHERE game_id = ?1", params![id])?;
+        let deleted = self.connection.execute("DELETE FROM games WHERE id = ?1", params![id])? > 0;
+
+        transaction.commit()?;
+
+        return Ok(deleted);
+    }
+}
+
+// positions are told apart by placement, turn, castling rights and a usable en passant square, not by the move counters
+fn position_key(board: &Board) -> String {
+    let fen = board.to_fen();
+    let fields = fen.split(' ').take(3).collect::<Vec<_>>().join(" ");
+    let en_passant = board.en_passant_square_for(EnPassantMode::Capturable).map(|coord| coord.to_string());
+
+    return format!("{} {}", fields, en_passant.as_deref().unwrap_or("-"));
+}
+
+fn read_summary(row: &Row) -> rusqlite::Result<GameSummary> {
+    return Ok(GameSummary {
+        id: row.get(0)?,
+        white: row.get(1)?,
+        black: row.get(2)?,
+        date: row.get(3)?,
+        result: row.get(4)?,
+        eco: row.get(5)?,
+        termination: row.get::<_, Option<String>>(6)?.as_deref().and_then(Termination::parse),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Coord, Move};
+
+    fn tags(white: &str, black: &str, eco: &str) -> Vec<(String, String)> {
+        return vec![
+            ("White".to_string(), white.to_string()),
+            ("Black".to_string(), black.to_string()),
+            ("ECO".to_string(), eco.to_string()),
+        ];
+    }
+
+    #[test]
+    fn save_filter_and_load() {
+        let db = GameDb::init(Connection::open_in_memory().unwrap()).unwrap();
+        let mut board = Board::new_game();
+
+        board.exec_move(&Move::new(Coord::new('e', 2), Coord::new('e', 4))).unwrap();
+        let first = db.save(&board, &tags("Alice", "Bob", "B00")).unwrap();
+
+        board.exec_move(&Move::new(Coord::new('c', 7), Coord::new('c', 5))).unwrap();
+        let second = db.save(&board, &tags("Bob", "Carol", "B20")).unwrap();
+
+        let ids = |filter: GameFilter| db.list(&filter).unwrap().into_iter().map(|game| game.id).collect::<Vec<_>>();
+
+        assert_eq!(vec![second, first], ids(GameFilter::default()));
+        assert_eq!(vec![second, first], ids(GameFilter { player: Some("Bob".to_string()), ..GameFilter::default() }));
+        assert_eq!(vec![second], ids(GameFilter { eco: Some("B2".to_string()), ..GameFilter::default() }));
+        assert_eq!(vec![first], ids(GameFilter { search: Some("lic".to_string()), ..GameFilter::default() }));
+        assert_eq!(vec![second], ids(GameFilter { limit: Some(1), ..GameFilter::default() }));
+
+        let record = db.get(second).unwrap().unwrap();
+
+        assert_eq!(board, pgn::read_pgn(&record.pgn).unwrap().board);
+        assert_eq!(Some(&board), db.board(second).unwrap().as_ref());
+
+        // games saved before the compact format are read from their pgn
+        db.connection.execute("UPDATE games SET rcg = NULL WHERE id = ?1", params![second]).unwrap();
+        assert_eq!(Some(&board), db.board(second).unwrap().as_ref());
+        assert_eq!(board.to_fen(), record.final_fen);
+        assert_eq!(None, record.summary.termination);
+        assert_eq!(tags("Bob", "Carol", "B20"), record.tags);
+
+        assert!(db.delete(first).unwrap());
+        assert_eq!(None, db.get(first).unwrap());
+    }
+
+    #[test]
+    fn explorer_stats() {
+        let db = GameDb::init(Connection::open_in_memory().unwrap()).unwrap();
+
+        for (first, second) in [(("e2", "e4"), ("e7", "e5")), (("e2", "e4"), ("c7", "c5")), (("d2", "d4"), ("d7", "d5"))] {
+            let mut board = Board::new_game();
+
+            for (from, to) in [first, second] {
+                board.exec_move(&Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap())).unwrap();
+            }
+
+            db.save(&board, &[]).unwrap();
+        }
+
+        let stats = db.explorer_stats(&Board::new_game()).unwrap();
+
+        assert_eq!(3, stats.count);
+        assert_eq!(vec!["e4", "d4"], stats.moves.iter().map(|mv| mv.san.as_str()).collect::<Vec<_>>());
+        assert_eq!(2, stats.moves[0].count);
+
+        let mut board = Board::new_game();
+        board.exec_move(&Move::new(Coord::new('e', 2), Coord::new('e', 4))).unwrap();
+
+        let stats = db.explorer_stats(&board).unwrap();
+
+        assert_eq!(vec!["c5", "e5"], stats.moves.iter().map(|mv| mv.san.as_str()).collect::<Vec<_>>());
+        assert_eq!("c7c5", stats.moves[0].uci);
+    }
+
+    #[test]
+    fn imports_pgn_files() {
+        let db = GameDb::init(Connection::open_in_memory().unwrap()).unwrap();
+        let pgn = "[White \"Alice\"]\n[Result \"1-0\"]\n[Termination \"time forfeit\"]\n\n1. e4 e5 2. Nf3 1-0\n\n[White \"Bob\"]\n\n1. e4 e4 *\n\n[White \"Carol\"]\n\n1. d4 { fine } *\n";
+
+        let summary = db.import_pgn(pgn.as_bytes()).unwrap();
+
+        assert_eq!(ImportSummary { imported: 2, skipped: 1 }, summary);
+
+        let games = db.list(&GameFilter::default()).unwrap();
+
+        assert_eq!(vec!["Carol", "Alice"], games.iter().map(|game| game.white.as_str()).collect::<Vec<_>>());
+        assert_eq!("1-0", games[1].result);
+        assert_eq!((Some(Termination::TimeForfeit), None), (games[1].termination, games[0].termination));
+        assert_eq!(2, db.explorer_stats(&Board::new_game()).unwrap().count);
+        assert!(db.get(games[0].id).unwrap().unwrap().pgn.contains("1. d4 {fine} *"));
+    }
+
+    #[test]
+    fn builds_book_for_player() {
+        let db = GameDb::init(Connection::open_in_memory().unwrap()).unwrap();
+        let game = |moves: &str| pgn::read_pgn(moves).unwrap().board;
+
+        db.save(&game("1. e4 c5 2. Nf3"), &[("White".to_string(), "Alice".to_string()), ("Result".to_string(), "1-0".to_string())]).unwrap();
+        db.save(&game("1. d4 e6 2. c4"), &[("Black".to_string(), "Alice".to_string()), ("Result".to_string(), "1/2-1/2".to_string())]).unwrap();
+
+        let filter = GameFilter { player: Some("Alice".to_string()), ..GameFilter::default() };
+        let book = db.build_book(&filter, 10).unwrap();
+
+        let mut board = Board::new_game();
+        assert_eq!(vec![(Move::new(Coord::new('e', 2), Coord::new('e', 4)), 2)], book.moves(&board));
+
+        board.exec_move(&Move::new(Coord::new('d', 2), Coord::new('d', 4))).unwrap();
+        assert_eq!(vec![(Move::new(Coord::new('e', 7), Coord::new('e', 6)), 1)], book.moves(&board));
+
+        assert_eq!(3, book.entries().len());
+        assert_eq!(2, db.build_book(&filter, 2).unwrap().entries().len());
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use chess::{Board, Move};
+
+// a fen fits easily, a pgn may hold a long annotated game but not a whole database
+pub const MAX_FEN_LENGTH: usize = 128;
+pub const MAX_PGN_LENGTH: usize = 1 << 20;
+pub const MAX_MOVE_INPUT_LENGTH: usize = 64;
+
+// the same command with the same payload from the same window within this window is dropped as a double click
+const REPEAT_WINDOW: Duration = Duration::from_millis(300);
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum InputError {
+    #[error("The {field} is {length} characters long, at most {max} are accepted")]
+    TooLong { field: &'static str, length: usize, max: usize },
+
+    #[error("It is not your turn to move")]
+    NotYourTurn,
+
+    #[error("The command {0} was repeated too quickly")]
+    Repeated(String),
+
+    #[error("The move {0} is not legal in this position")]
+    IllegalMove(Move),
+}
+
+pub fn check_length(field: &'static str, text: &str, max: usize) -> Result<(), InputError> {
+    let length = text.chars().count();
+
+    if length > max {
+        return Err(InputError::TooLong { field, length, max });
+    }
+
+    return Ok(());
+}
+
+// a move sent by the frontend is only played when it is exactly the legal move between its squares,
+// so a made up promotion or flag cannot put the board into a state no game reaches
+pub fn play_move(board: &mut Board, mv: &Move) -> Result<(), InputError> {
+    let legal = chess::resolve_move(board, mv.from, mv.to, mv.promotion.then_some(mv.promote_to));
+
+    if legal.as_ref() != Some(mv) {
+        return Err(InputError::IllegalMove(*mv));
+    }
+
+    board.exec_move(mv).map_err(|_| InputError::IllegalMove(*mv))?;
+
+    return Ok(());
+}
+
+// remembers when each window last sent a command with a given payload
+#[derive(Debug, Default)]
+pub struct CommandGuard {
+    recent: HashMap<(String, &'static str), (String, Instant)>,
+}
+
+impl CommandGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // a different payload is always let through, only the exact same command is rejected
+    pub fn check(&mut self, window: &str, command: &'static str, payload: &str, now: Instant) -> Result<(), InputError> {
+        self.recent.retain(|_, (_, at)| now.duration_since(*at) < REPEAT_WINDOW);
+
+        let key = (window.to_string(), command);
+
+        if self.recent.get(&key).is_some_and(|(last, _)| last == payload) {
+            return Err(InputError::Repeated(command.to_string()));
+        }
+
+        self.recent.insert(key, (payload.to_string(), now));
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Coord, PieceType};
+
+    #[test]
+    fn rejects_long_and_repeated_input() {
+        assert_eq!(Ok(()), check_length("fen", "8/8/8/8/8/8/8/8 w - - 0 1", MAX_FEN_LENGTH));
+        assert_eq!(
+            Err(InputError::TooLong { field: "fen", length: 200, max: MAX_FEN_LENGTH }),
+            check_length("fen", &"8".repeat(200), MAX_FEN_LENGTH)
+        );
+
+        let mut guard = CommandGuard::new();
+        let now = Instant::now();
+
+        assert_eq!(Ok(()), guard.check("main", "exec_move", "e2e4", now));
+        assert_eq!(Err(InputError::Repeated("exec_move".to_string())), guard.check("main", "exec_move", "e2e4", now));
+        assert_eq!(Ok(()), guard.check("main", "exec_move", "d2d4", now));
+        assert_eq!(Ok(()), guard.check("other", "exec_move", "d2d4", now));
+        assert_eq!(Ok(()), guard.check("main", "apply_fen", "d2d4", now));
+        assert_eq!(Ok(()), guard.check("main", "exec_move", "d2d4", now + REPEAT_WINDOW));
+    }
+
+    #[test]
+    fn plays_only_legal_moves() {
+        let mut board = Board::from_fen("4k3/1P6/8/8/8/8/8/R3K1B1 w Q - 0 1").unwrap();
+        let before = board.clone();
+
+        let promote_to_king = Move {
+            promotion: true,
+            promote_to: PieceType::King,
+            ..Move::new(Coord::new('b', 7), Coord::new('b', 8))
+        };
+        let unflagged_castling = Move::new(Coord::new('e', 1), Coord::new('c', 1));
+        let jumping_rook = Move::new(Coord::new('a', 1), Coord::new('f', 1));
+
+        for mv in [promote_to_king, unflagged_castling, jumping_rook] {
+            assert_eq!(Err(InputError::IllegalMove(mv)), play_move(&mut board, &mv));
+            assert_eq!(before, board);
+        }
+
+        let promotion = chess::resolve_move(&board, Coord::new('b', 7), Coord::new('b', 8), Some(PieceType::Knight)).unwrap();
+
+        assert_eq!(Ok(()), play_move(&mut board, &promotion));
+        assert_eq!(Some(PieceType::Knight), board.lookup(Coord::new('b', 8)));
+    }
+}
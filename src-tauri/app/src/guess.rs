@@ -0,0 +1,112 @@
+use chess::{review::EXACT_GUESS_POINTS, Board, Color, Move};
+use serde::Serialize;
+
+// the mainline of a game replayed for guessing, the board shows the game up to the move to guess
+#[derive(Debug, Clone)]
+pub struct GuessGame {
+    start: Board,
+    moves: Vec<Move>,
+    player: Color,
+    played: usize,
+    score: u32,
+    guesses: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuessStatus {
+    pub player: Color,
+    pub ply: usize,
+    pub plies: usize,
+    pub score: u32,
+    pub max_score: u32,
+    pub guesses: u32,
+    pub finished: bool,
+}
+
+impl GuessGame {
+    pub fn new(game: &Board, player: Color) -> Self {
+        let positions = game.history();
+
+        GuessGame {
+            start: positions[0].clone(),
+            moves: positions.iter().filter_map(|position| position.last_move()).collect(),
+            player,
+            played: 0,
+            score: 0,
+            guesses: 0,
+        }
+    }
+
+    pub fn start(&self) -> &Board {
+        return &self.start;
+    }
+
+    // the moves of the other side up to the player's next move, they are played on the board without a guess
+    pub fn advance(&mut self) -> Vec<Move> {
+        let from = self.played;
+
+        while self.played < self.moves.len() && self.turn() != self.player {
+            self.played += 1;
+        }
+
+        return self.moves[from..self.played].to_vec();
+    }
+
+    pub fn expected(&self) -> Option<Move> {
+        return self.moves.get(self.played).copied().filter(|_| self.turn() == self.player);
+    }
+
+    // the expected move counts as played after a guess, whether the guess was right or not
+    pub fn record(&mut self, points: u32) {
+        self.score += points;
+        self.guesses += 1;
+        self.played += 1;
+    }
+
+    pub fn status(&self) -> GuessStatus {
+        return GuessStatus {
+            player: self.player,
+            ply: self.played,
+            plies: self.moves.len(),
+            score: self.score,
+            max_score: self.guesses * EXACT_GUESS_POINTS,
+            guesses: self.guesses,
+            finished: self.expected().is_none(),
+        };
+    }
+
+    fn turn(&self) -> Color {
+        return match self.played % 2 {
+            0 => self.start.turn(),
+            _ => self.start.turn().invert(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::pgn::read_pgn;
+
+    #[test]
+    fn replays_for_one_side() {
+        let board = read_pgn("1. e4 e5 2. Nf3 Nc6 3. Bb5").unwrap().board;
+        let mut game = GuessGame::new(&board, Color::Black);
+
+        assert_eq!(1, game.advance().len());
+        assert_eq!("e7e5", game.expected().unwrap().to_string());
+
+        game.record(3);
+
+        assert_eq!(None, game.expected());
+        assert_eq!(1, game.advance().len());
+
+        game.record(0);
+        game.advance();
+
+        let status = game.status();
+
+        assert_eq!((3, 6, 2, 5, true), (status.score, status.max_score, status.guesses, status.ply, status.finished));
+    }
+}
@@ -0,0 +1,465 @@
+use std::{collections::HashSet, fmt::Write};
+
+use chess::{clock::ClockSettings, Color, Variant};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum TournamentError {
+    #[error("A tournament needs at least two players")]
+    TooFewPlayers,
+
+    #[error("The player name '{0}' is empty or taken twice")]
+    InvalidName(String),
+
+    #[error("A swiss tournament needs at least one round")]
+    NoRounds,
+
+    #[error("Round {0} still has games without a result")]
+    RoundUnfinished(usize),
+
+    #[error("All rounds of the tournament have been played")]
+    Finished,
+
+    #[error("There is no game {0} in the current round")]
+    UnknownPairing(usize),
+
+    #[error("'{0}' is not the result of a game")]
+    InvalidResult(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TournamentFormat {
+    RoundRobin,
+    Swiss { rounds: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TournamentSettings {
+    pub name: String,
+    pub players: Vec<String>,
+    pub format: TournamentFormat,
+    pub variant: Variant,
+    pub clock: Option<ClockSettings>,
+}
+
+// players are referred to by their position in the list of the settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pairing {
+    pub white: usize,
+    pub black: usize,
+    pub result: Option<String>,
+}
+
+// with an odd number of players one of them sits the round out and gets a point for it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Round {
+    pub number: usize,
+    pub pairings: Vec<Pairing>,
+    pub bye: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Standing {
+    pub rank: usize,
+    pub player: usize,
+    pub name: String,
+    pub points: f32,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub buchholz: f32,
+    pub sonneborn_berger: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TournamentStatus {
+    pub settings: TournamentSettings,
+    pub rounds: Vec<Round>,
+    pub total_rounds: usize,
+    pub standings: Vec<Standing>,
+    pub finished: bool,
+}
+
+// the games are played one after the other on the same board, a round is paired once the previous one is complete
+#[derive(Debug, Clone)]
+pub struct HotseatTournament {
+    settings: TournamentSettings,
+    rounds: Vec<Round>,
+}
+
+impl HotseatTournament {
+    pub fn new(settings: TournamentSettings) -> Result<Self, TournamentError> {
+        if settings.players.len() < 2 {
+            return Err(TournamentError::TooFewPlayers);
+        }
+
+        for (index, name) in settings.players.iter().enumerate() {
+            if name.trim().is_empty() || settings.players[..index].contains(name) {
+                return Err(TournamentError::InvalidName(name.clone()));
+            }
+        }
+
+        if settings.format == (TournamentFormat::Swiss { rounds: 0 }) {
+            return Err(TournamentError::NoRounds);
+        }
+
+        return Ok(HotseatTournament { settings, rounds: Vec::new() });
+    }
+
+    pub fn settings(&self) -> &TournamentSettings {
+        return &self.settings;
+    }
+
+    // a round robin takes one round less than there are players, one more when someone has to sit out each round
+    pub fn total_rounds(&self) -> usize {
+        let players = self.settings.players.len();
+
+        return match self.settings.format {
+            TournamentFormat::RoundRobin => players - 1 + players % 2,
+            TournamentFormat::Swiss { rounds } => rounds,
+        };
+    }
+
+    pub fn pairing(&self, index: usize) -> Result<&Pairing, TournamentError> {
+        return self.rounds.last().and_then(|round| round.pairings.get(index)).ok_or(TournamentError::UnknownPairing(index));
+    }
+
+    pub fn next_round(&mut self) -> Result<&Round, TournamentError> {
+        if let Some(round) = self.rounds.last().filter(|round| round.pairings.iter().any(|pairing| pairing.result.is_none())) {
+            return Err(TournamentError::RoundUnfinished(round.number));
+        }
+
+        if self.rounds.len() >= self.total_rounds() {
+            return Err(TournamentError::Finished);
+        }
+
+        let round = match self.settings.format {
+            TournamentFormat::RoundRobin => self.round_robin_round(),
+            TournamentFormat::Swiss { .. } => self.swiss_round(),
+        };
+
+        self.rounds.push(round);
+
+        return Ok(self.rounds.last().expect("round to be added"));
+    }
+
+    // a result can be corrected as long as its round is the current one
+    pub fn record(&mut self, index: usize, result: &str) -> Result<(), TournamentError> {
+        if !["1-0", "0-1", "1/2-1/2"].contains(&result) {
+            return Err(TournamentError::InvalidResult(result.to_string()));
+        }
+
+        let pairing = self
+            .rounds
+            .last_mut()
+            .and_then(|round| round.pairings.get_mut(index))
+            .ok_or(TournamentError::UnknownPairing(index))?;
+
+        pairing.result = Some(result.to_string());
+
+        return Ok(());
+    }
+
+    pub fn finished(&self) -> bool {
+        return self.rounds.len() == self.total_rounds() && self.rounds.iter().all(|round| round.pairings.iter().all(|pairing| pairing.result.is_some()));
+    }
+
+    // ties are broken by buchholz first in a swiss and by sonneborn-berger first in a round robin, where everyone has
+    // the same opponents and buchholz tells nothing apart
+    pub fn standings(&self) -> Vec<Standing> {
+        let players = self.settings.players.len();
+        let points = (0..players).map(|player| self.half_points(player)).collect::<Vec<_>>();
+
+        let mut standings = (0..players)
+            .map(|player| {
+                let games = self.games(player).collect::<Vec<_>>();
+                let count = |score: usize| games.iter().filter(|(_, scored)| *scored == score).count();
+
+                return Standing {
+                    rank: 0,
+                    player,
+                    name: self.settings.players[player].clone(),
+                    points: points[player] as f32 / 2.0,
+                    wins: count(2),
+                    draws: count(1),
+                    losses: count(0),
+                    buchholz: games.iter().map(|(opponent, _)| points[*opponent]).sum::<usize>() as f32 / 2.0,
+                    sonneborn_berger: games.iter().map(|(opponent, scored)| scored * points[*opponent]).sum::<usize>() as f32 / 4.0,
+                };
+            })
+            .collect::<Vec<_>>();
+
+        standings.sort_by(|a, b| {
+            let tiebreaks = match self.settings.format {
+                TournamentFormat::RoundRobin => b.sonneborn_berger.total_cmp(&a.sonneborn_berger).then(b.wins.cmp(&a.wins)),
+                TournamentFormat::Swiss { .. } => b.buchholz.total_cmp(&a.buchholz).then(b.sonneborn_berger.total_cmp(&a.sonneborn_berger)),
+            };
+
+            return b.points.total_cmp(&a.points).then(tiebreaks).then(a.player.cmp(&b.player));
+        });
+
+        for (rank, standing) in standings.iter_mut().enumerate() {
+            standing.rank = rank + 1;
+        }
+
+        return standings;
+    }
+
+    pub fn status(&self) -> TournamentStatus {
+        return TournamentStatus {
+            settings: self.settings.clone(),
+            rounds: self.rounds.clone(),
+            total_rounds: self.total_rounds(),
+            standings: self.standings(),
+            finished: self.finished(),
+        };
+    }
+
+    // the players ordered by their standing, each cell holds the row's points against the column or a dot when they
+    // have not met
+    pub fn cross_table(&self) -> String {
+        let standings = self.standings();
+        let width = self.settings.players.iter().map(String::len).max().unwrap_or(0).max("Player".len());
+
+        let mut table = format!("{}\n\n{:>2}  {:<width$}  {:>5}  {:>5}  {:>5}", self.settings.name, "#", "Player", "Pts", "Bhz", "SB");
+
+        for rank in 1..=standings.len() {
+            let _ = write!(table, "  {:>4}", rank);
+        }
+
+        table.push('\n');
+
+        for standing in &standings {
+            let _ = write!(
+                table,
+                "{:>2}  {:<width$}  {:>5.1}  {:>5.1}  {:>5.2}",
+                standing.rank, standing.name, standing.points, standing.buchholz, standing.sonneborn_berger
+            );
+
+            for opponent in &standings {
+                let scores = self.games(standing.player).filter(|(played, _)| *played == opponent.player).map(|(_, scored)| scored).collect::<Vec<_>>();
+
+                let _ = match scores.is_empty() {
+                    _ if opponent.player == standing.player => write!(table, "  {:>4}", "-"),
+                    true => write!(table, "  {:>4}", "."),
+                    false => write!(table, "  {:>4.1}", scores.iter().sum::<usize>() as f32 / 2.0),
+                };
+            }
+
+            table.push('\n');
+        }
+
+        return table;
+    }
+
+    // the opponents of a player's finished games with the half points the player scored against them
+    fn games(&self, player: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        return self.rounds.iter().flat_map(|round| &round.pairings).filter_map(move |pairing| {
+            let (opponent, color) = match player {
+                _ if pairing.white == player => (pairing.black, Color::White),
+                _ if pairing.black == player => (pairing.white, Color::Black),
+                _ => return None,
+            };
+
+            let scored = match (pairing.result.as_deref()?, color) {
+                ("1-0", Color::White) | ("0-1", Color::Black) => 2,
+                ("1/2-1/2", _) => 1,
+                _ => 0,
+            };
+
+            return Some((opponent, scored));
+        });
+    }
+
+    // a bye is worth a full point
+    fn half_points(&self, player: usize) -> usize {
+        let byes = self.rounds.iter().filter(|round| round.bye == Some(player)).count();
+
+        return self.games(player).map(|(_, scored)| scored).sum::<usize>() + 2 * byes;
+    }
+
+    // the circle method, one player stays in place while the others rotate around it, with an odd number of players
+    // whoever would meet the missing one has the bye
+    fn round_robin_round(&self) -> Round {
+        let number = self.rounds.len() + 1;
+        let players = self.settings.players.len();
+        let seats = players + players % 2;
+        let round = number - 1;
+
+        let mut pairings = Vec::new();
+        let mut bye = None;
+
+        for seat in 0..seats / 2 {
+            let first = if seat == 0 { seats - 1 } else { (round + seat) % (seats - 1) };
+            let second = (round + seats - 1 - seat) % (seats - 1);
+
+            // the player who stays in place changes colour every round, the others by the side of the circle they are on
+            let (white, black) = match seat == 0 && round % 2 == 0 {
+                true => (second, first),
+                false => (first, second),
+            };
+
+            match (white < players, black < players) {
+                (true, true) => pairings.push(Pairing { white, black, result: None }),
+                (true, false) => bye = Some(white),
+                _ => bye = Some(black),
+            }
+        }
+
+        return Round { number, pairings, bye };
+    }
+
+    // players are paired from the top of the standings down, each with the best placed player they have not met yet,
+    // the bye goes to the lowest placed player who has not had one
+    fn swiss_round(&self) -> Round {
+        let number = self.rounds.len() + 1;
+        let mut order = self.standings().into_iter().map(|standing| standing.player).collect::<Vec<_>>();
+
+        let bye = match order.len() % 2 {
+            0 => None,
+            _ => {
+                let position = order.iter().rposition(|player| self.rounds.iter().all(|round| round.bye != Some(*player))).unwrap_or(order.len() - 1);
+                Some(order.remove(position))
+            }
+        };
+
+        let met = self
+            .rounds
+            .iter()
+            .flat_map(|round| &round.pairings)
+            .flat_map(|pairing| [(pairing.white, pairing.black), (pairing.black, pairing.white)])
+            .collect::<HashSet<_>>();
+
+        // when everyone has met everyone they could be paired with, the standings decide alone
+        let pairs = pair_without_rematches(&order, &met).unwrap_or_else(|| order.chunks(2).map(|pair| (pair[0], pair[1])).collect());
+
+        let pairings = pairs
+            .into_iter()
+            .map(|(higher, lower)| {
+                let whites = |player: usize| self.rounds.iter().flat_map(|round| &round.pairings).filter(|pairing| pairing.white == player).count();
+
+                let higher_white = match whites(higher).cmp(&whites(lower)) {
+                    std::cmp::Ordering::Less => true,
+                    std::cmp::Ordering::Greater => false,
+                    std::cmp::Ordering::Equal => number % 2 == 1,
+                };
+
+                return match higher_white {
+                    true => Pairing { white: higher, black: lower, result: None },
+                    false => Pairing { white: lower, black: higher, result: None },
+                };
+            })
+            .collect();
+
+        return Round { number, pairings, bye };
+    }
+}
+
+fn pair_without_rematches(players: &[usize], met: &HashSet<(usize, usize)>) -> Option<Vec<(usize, usize)>> {
+    let Some((first, rest)) = players.split_first() else {
+        return Some(Vec::new());
+    };
+
+    for (index, opponent) in rest.iter().enumerate() {
+        if met.contains(&(*first, *opponent)) {
+            continue;
+        }
+
+        let remaining = rest.iter().enumerate().filter(|(other, _)| *other != index).map(|(_, player)| *player).collect::<Vec<_>>();
+
+        if let Some(mut pairs) = pair_without_rematches(&remaining, met) {
+            pairs.insert(0, (*first, *opponent));
+            return Some(pairs);
+        }
+    }
+
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(players: &[&str], format: TournamentFormat) -> TournamentSettings {
+        return TournamentSettings {
+            name: "Club championship".to_string(),
+            players: players.iter().map(|player| player.to_string()).collect(),
+            format,
+            variant: Variant::Standard,
+            clock: None,
+        };
+    }
+
+    fn play_round(tournament: &mut HotseatTournament, result: &str) -> Round {
+        let round = tournament.next_round().unwrap().clone();
+
+        for index in 0..round.pairings.len() {
+            tournament.record(index, result).unwrap();
+        }
+
+        return round;
+    }
+
+    #[test]
+    fn pairs_everyone_once_in_a_round_robin() {
+        assert_eq!(Err(TournamentError::InvalidName("Ann".to_string())), HotseatTournament::new(settings(&["Ann", "Ann"], TournamentFormat::RoundRobin)).map(|_| ()));
+
+        let mut tournament = HotseatTournament::new(settings(&["Ann", "Ben", "Cid"], TournamentFormat::RoundRobin)).unwrap();
+        let mut met = HashSet::new();
+        let mut byes = Vec::new();
+
+        assert_eq!(3, tournament.total_rounds());
+
+        while !tournament.finished() {
+            let round = play_round(&mut tournament, "1-0");
+
+            assert_eq!(1, round.pairings.len());
+            met.insert((round.pairings[0].white.min(round.pairings[0].black), round.pairings[0].white.max(round.pairings[0].black)));
+            byes.push(round.bye.unwrap());
+        }
+
+        byes.sort();
+
+        assert_eq!(3, met.len());
+        assert_eq!(vec![0, 1, 2], byes);
+        assert_eq!(Err(TournamentError::Finished), tournament.next_round().map(|_| ()));
+
+        // every player won with white, lost with black and had a bye
+        assert!(tournament.standings().iter().all(|standing| standing.points == 2.0 && standing.wins == 1));
+    }
+
+    #[test]
+    fn pairs_leaders_in_a_swiss() {
+        let mut tournament = HotseatTournament::new(settings(&["Ann", "Ben", "Cid", "Dan"], TournamentFormat::Swiss { rounds: 2 })).unwrap();
+
+        let first = play_round(&mut tournament, "1-0");
+        let winners = first.pairings.iter().map(|pairing| pairing.white).collect::<HashSet<_>>();
+
+        let second = tournament.next_round().unwrap().clone();
+
+        assert_eq!(Err(TournamentError::RoundUnfinished(2)), tournament.next_round().map(|_| ()));
+        assert!(second.pairings.iter().any(|pairing| winners.contains(&pairing.white) && winners.contains(&pairing.black)));
+        assert!(second.pairings.iter().all(|pairing| !first.pairings.iter().any(|earlier| {
+            (earlier.white, earlier.black) == (pairing.white, pairing.black) || (earlier.white, earlier.black) == (pairing.black, pairing.white)
+        })));
+
+        tournament.record(0, "1/2-1/2").unwrap();
+        tournament.record(1, "0-1").unwrap();
+
+        assert_eq!(Err(TournamentError::InvalidResult("*".to_string())), tournament.record(0, "*"));
+        assert!(tournament.finished());
+
+        let standings = tournament.standings();
+        let table = tournament.cross_table();
+
+        assert_eq!(1.5, standings[0].points);
+        assert!(table.starts_with("Club championship\n"));
+        assert_eq!(7, table.lines().count());
+    }
+}
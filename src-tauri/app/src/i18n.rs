@@ -0,0 +1,451 @@
+use std::{
+    error::Error,
+    fs,
+    io::ErrorKind,
+    path::Path,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use anyhow::Result;
+use chess::{
+    bot::{BotError, BotInfo},
+    draw::ClaimError,
+    positions::NamedPosition,
+    review::ReviewError,
+    san::MoveInputError,
+    training::TrainingError,
+    tree::TreeError,
+    FenError, MoveErr,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arbiter::ArbiterError,
+    chat::ChatError,
+    guard::InputError,
+    hotseat::TournamentError,
+    match_play::MatchError,
+    phase::{Action, Phase, PhaseError},
+    rating::RatingError,
+    sessions::SessionError,
+    settings::SettingsError,
+    simul::{self, SimulError},
+};
+
+// read while serializing errors, where no app state is at hand
+static LOCALE: AtomicU8 = AtomicU8::new(0);
+
+// english is what the strings are written in, so it needs no catalog, a key missing from a catalog falls back to it
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        return match self {
+            Locale::En => &[],
+            Locale::De => GERMAN,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocaleSettings {
+    pub locale: Locale,
+}
+
+impl LocaleSettings {
+    pub fn load(path: &Path) -> Result<Self> {
+        return match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        return Ok(());
+    }
+}
+
+pub fn set_locale(locale: Locale) {
+    LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    return Locale::ALL.get(LOCALE.load(Ordering::Relaxed) as usize).copied().unwrap_or_default();
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    return locale.catalog().iter().find(|(known, _)| *known == key).map(|(_, text)| *text);
+}
+
+// the text for a key in the current locale, placeholders like {square} are filled from the arguments
+fn text(key: &str, args: &[(&str, String)]) -> Option<String> {
+    let mut text = lookup(locale(), key)?.to_string();
+
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), value);
+    }
+
+    return Some(text);
+}
+
+// errors without a key, like the ones of engine internals, keep their english message
+pub fn error_message(err: &(dyn Error + 'static)) -> String {
+    return localized_error(err).unwrap_or_else(|| err.to_string());
+}
+
+fn localized_error(err: &(dyn Error + 'static)) -> Option<String> {
+    if let Some(err) = err.downcast_ref::<MoveErr>() {
+        return match err {
+            MoveErr::NoPieceAt(square) => text("error.noPieceAt", &[("square", square.to_string())]),
+            MoveErr::CannotMoveOpponentPiece => text("error.opponentPiece", &[]),
+            MoveErr::CannotCaptureOwnPiece => text("error.ownPiece", &[]),
+            MoveErr::CannotCaptureKing => text("error.captureKing", &[]),
+            MoveErr::NullMoveInCheck => text("error.passInCheck", &[]),
+            MoveErr::NotANullMove => text("error.notANullMove", &[]),
+            MoveErr::InvalidEnPassant(square) => text("error.invalidEnPassant", &[("square", square.to_string())]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<ClaimError>() {
+        return match err {
+            ClaimError::GameOver => text("error.gameOver", &[]),
+            ClaimError::IllegalMove(mv) => text("error.illegalIntendedMove", &[("move", mv.to_string())]),
+            ClaimError::NotClaimable => text("error.notClaimable", &[]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<MoveInputError>() {
+        return match err {
+            MoveInputError::Unreadable(input) => text("error.unreadableMove", &[("input", input.clone())]),
+            MoveInputError::Ambiguous { input, candidates } => {
+                text("error.ambiguousMove", &[("input", input.clone()), ("candidates", candidates.join(" / "))])
+            }
+            MoveInputError::Illegal(input) => text("error.illegalInput", &[("input", input.clone())]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<ArbiterError>() {
+        return match err {
+            ArbiterError::TouchMove(square) => text("error.touchMove", &[("square", square.to_string())]),
+            ArbiterError::IllegalMove { mv, side, attempts } => text(
+                "error.arbiterIllegalMove",
+                &[("move", mv.to_string()), ("attempts", attempts.to_string()), ("side", side_name(*side))],
+            ),
+            ArbiterError::Forfeit(side) => text("error.forfeit", &[("side", side_name(*side))]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<ChatError>() {
+        return match err {
+            ChatError::Empty => text("error.chatEmpty", &[]),
+            ChatError::TooLong => text("error.chatTooLong", &[("length", crate::chat::MAX_LENGTH.to_string())]),
+            ChatError::RateLimited => text("error.chatRateLimited", &[]),
+            ChatError::OtherGame(game) => text("error.chatOtherGame", &[("game", game.to_string())]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<TrainingError>() {
+        return match err {
+            TrainingError::MismatchedAnswer => text("error.mismatchedAnswer", &[]),
+            TrainingError::InvalidPosition(fen) => text("error.invalidTrainingPosition", &[("fen", fen.clone())]),
+            TrainingError::NoPosition => text("error.noTrainingPosition", &[]),
+            TrainingError::NotWinning => text("error.notWinning", &[]),
+            TrainingError::IllegalMove(mv) => text("error.illegalMove", &[("move", mv.to_string())]),
+            TrainingError::OutOfBook => text("error.outOfBook", &[]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<ReviewError>() {
+        return match err {
+            ReviewError::IllegalMove(mv) => text("error.illegalMove", &[("move", mv.to_string())]),
+            ReviewError::InvalidPosition(fen) => text("error.invalidPuzzlePosition", &[("fen", fen.clone())]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<TreeError>() {
+        return match err {
+            TreeError::UnknownNode(id) => text("error.unknownNode", &[("id", id.to_string())]),
+            TreeError::UnknownVariation(index) => text("error.unknownVariation", &[("index", index.to_string())]),
+            TreeError::NotInVariation => text("error.notInVariation", &[]),
+            TreeError::NoMove => text("error.glyphWithoutMove", &[]),
+        };
+    }
+
+    if let Some(BotError::UnknownBot(id)) = err.downcast_ref::<BotError>() {
+        return text("error.unknownBot", &[("id", id.clone())]);
+    }
+
+    if let Some(err) = err.downcast_ref::<InputError>() {
+        return match err {
+            InputError::TooLong { field, length, max } => {
+                text("error.inputTooLong", &[("field", field.to_string()), ("length", length.to_string()), ("max", max.to_string())])
+            }
+            InputError::NotYourTurn => text("error.notYourTurn", &[]),
+            InputError::Repeated(command) => text("error.repeatedCommand", &[("command", command.clone())]),
+        };
+    }
+
+    if let Some(PhaseError::NotAllowed { phase, action }) = err.downcast_ref::<PhaseError>() {
+        return text("error.phase", &[("action", action_name(*action)), ("phase", phase_name(*phase))]);
+    }
+
+    if let Some(err) = err.downcast_ref::<RatingError>() {
+        return match err {
+            RatingError::RatedGame(action) => text("error.ratedGame", &[("action", action_name(*action))]),
+            RatingError::CasualGame => text("error.casualGame", &[]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<MatchError>() {
+        return match err {
+            MatchError::EmptyMatch => text("error.emptyMatch", &[]),
+            MatchError::Decided => text("error.matchDecided", &[]),
+            MatchError::Unfinished => text("error.unfinishedGame", &[]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<TournamentError>() {
+        return match err {
+            TournamentError::TooFewPlayers => text("error.tooFewPlayers", &[]),
+            TournamentError::InvalidName(name) => text("error.invalidPlayerName", &[("name", name.clone())]),
+            TournamentError::NoRounds => text("error.noRounds", &[]),
+            TournamentError::RoundUnfinished(round) => text("error.roundUnfinished", &[("round", round.to_string())]),
+            TournamentError::Finished => text("error.tournamentFinished", &[]),
+            TournamentError::UnknownPairing(index) => text("error.unknownPairing", &[("index", index.to_string())]),
+            TournamentError::InvalidResult(result) => text("error.invalidResult", &[("result", result.clone())]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<SimulError>() {
+        return match err {
+            SimulError::BoardCount(count) => text("error.simulBoardCount", &[("count", count.to_string()), ("max", simul::MAX_BOARDS.to_string())]),
+            SimulError::UnknownBoard(index) => text("error.unknownSimulBoard", &[("board", index.to_string())]),
+            SimulError::EngineToMove(index) => text("error.simulEngineToMove", &[("board", index.to_string())]),
+            SimulError::Finished(index) => text("error.simulBoardFinished", &[("board", index.to_string())]),
+            SimulError::IllegalMove(index, mv) => text("error.simulIllegalMove", &[("board", index.to_string()), ("move", mv.to_string())]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<SettingsError>() {
+        return match err {
+            SettingsError::UnknownTheme(theme) => text("error.unknownTheme", &[("theme", theme.clone())]),
+            SettingsError::UnavailablePieceSet(set) => text("error.unavailablePieceSet", &[("set", set.clone())]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<SessionError>() {
+        return match err {
+            SessionError::ReadOnly(window) => text("error.readOnly", &[("window", window.clone())]),
+            SessionError::UnknownSession(window) => text("error.unknownSession", &[("window", window.clone())]),
+        };
+    }
+
+    if let Some(err) = err.downcast_ref::<FenError>() {
+        return text("error.invalidFen", &[("reason", err.to_string())]);
+    }
+
+    return None;
+}
+
+fn side_name(side: chess::Color) -> String {
+    return match side {
+        chess::Color::White => text("side.white", &[]).unwrap_or_else(|| side.to_string()),
+        chess::Color::Black => text("side.black", &[]).unwrap_or_else(|| side.to_string()),
+    };
+}
+
+fn phase_name(phase: Phase) -> String {
+    let key = match phase {
+        Phase::Setup => "phase.setup",
+        Phase::Playing => "phase.playing",
+        Phase::AwaitingPromotion { .. } => "phase.awaitingPromotion",
+        Phase::GameOver => "phase.gameOver",
+        Phase::Reviewing => "phase.reviewing",
+    };
+
+    return text(key, &[]).unwrap_or_else(|| phase.describe().to_string());
+}
+
+fn action_name(action: Action) -> String {
+    let key = match action {
+        Action::Move => "action.move",
+        Action::Promote => "action.promote",
+        Action::Undo => "action.undo",
+        Action::Navigate => "action.navigate",
+        Action::EditPosition => "action.editPosition",
+        Action::EngineMove => "action.engineMove",
+        Action::NewGame => "action.newGame",
+        Action::Review => "action.review",
+        Action::Hint => "action.hint",
+    };
+
+    return text(key, &[]).unwrap_or_else(|| action.describe().to_string());
+}
+
+pub fn position(position: &NamedPosition) -> NamedPosition {
+    let locale = locale();
+
+    return NamedPosition {
+        name: lookup(locale, &format!("position.{}.name", position.id)).unwrap_or(position.name),
+        description: lookup(locale, &format!("position.{}.description", position.id)).unwrap_or(position.description),
+        ..position.clone()
+    };
+}
+
+pub fn bot(info: BotInfo) -> BotInfo {
+    return BotInfo {
+        name: text(&format!("bot.{}.name", info.id), &[]).unwrap_or(info.name),
+        description: text(&format!("bot.{}.description", info.id), &[]).unwrap_or(info.description),
+        id: info.id,
+    };
+}
+
+// moves stay in english notation, which is what the rest of the app reads and writes
+const GERMAN: &[(&str, &str)] = &[
+    ("side.white", "Weiß"),
+    ("side.black", "Schwarz"),
+    ("error.noPieceAt", "Auf {square} steht keine Figur"),
+    ("error.opponentPiece", "Figuren des Gegners können nicht gezogen werden"),
+    ("error.ownPiece", "Eigene Figuren können nicht geschlagen werden"),
+    ("error.captureKing", "Der König kann nicht geschlagen werden"),
+    ("error.passInCheck", "Im Schach kann nicht gepasst werden"),
+    ("error.notANullMove", "Der letzte Zug war kein Nullzug"),
+    ("error.invalidEnPassant", "Auf {square} kann kein Bauer en passant geschlagen werden"),
+    ("error.gameOver", "Die Partie ist bereits beendet"),
+    ("error.illegalIntendedMove", "Der beabsichtigte Zug {move} ist nicht erlaubt"),
+    ("error.notClaimable", "Weder die 50-Züge-Regel noch dreifache Stellungswiederholung trifft zu"),
+    ("error.unreadableMove", "'{input}' ist kein Zug"),
+    ("error.ambiguousMove", "'{input}' ist mehrdeutig: {candidates}"),
+    ("error.illegalInput", "'{input}' ist in dieser Stellung nicht erlaubt"),
+    ("error.touchMove", "Die Figur auf {square} wurde berührt und muss gezogen werden"),
+    ("error.arbiterIllegalMove", "Unerlaubter Zug {move}, Versuch {attempts} für {side}"),
+    ("error.forfeit", "{side} hat die Partie nach zu vielen unerlaubten Zügen verloren"),
+    ("error.chatEmpty", "Chatnachrichten dürfen nicht leer sein"),
+    ("error.chatTooLong", "Chatnachrichten sind auf {length} Zeichen begrenzt"),
+    ("error.chatRateLimited", "Zu viele Nachrichten, bitte einen Moment warten"),
+    ("error.chatOtherGame", "Die Nachricht gehört zu Partie {game}, die bereits beendet ist"),
+    ("error.mismatchedAnswer", "Die Antwort passt nicht zur Aufgabe"),
+    ("error.invalidTrainingPosition", "Ungültige Trainingsstellung '{fen}'"),
+    ("error.noTrainingPosition", "Keine Trainingsstellung hat einen Schachzug"),
+    ("error.notWinning", "Die Stellung ist kein gewonnenes Tablebase-Endspiel"),
+    ("error.illegalMove", "Der Zug {move} ist nicht erlaubt"),
+    ("error.outOfBook", "Die Stellung steht nicht im Eröffnungsbuch"),
+    ("error.invalidPuzzlePosition", "Die Aufgabenstellung '{fen}' ist ungültig"),
+    ("error.unknownNode", "Es gibt keinen Zug mit der Nummer {id}"),
+    ("error.unknownVariation", "Nach dem aktuellen Zug gibt es keine Variante {index}"),
+    ("error.notInVariation", "Der aktuelle Zug gehört zur Hauptvariante"),
+    ("error.glyphWithoutMove", "Nur Züge können kommentiert werden"),
+    ("error.unknownBot", "Es gibt keinen Bot namens '{id}'"),
+    ("error.inputTooLong", "Die Eingabe ({field}) ist {length} Zeichen lang, erlaubt sind höchstens {max}"),
+    ("error.notYourTurn", "Die Gegenseite ist am Zug"),
+    ("error.repeatedCommand", "Der Befehl {command} wurde zu schnell wiederholt"),
+    ("error.phase", "{action} ist nicht möglich, {phase}"),
+    ("phase.setup", "während eine Stellung aufgebaut wird"),
+    ("phase.playing", "während gespielt wird"),
+    ("phase.awaitingPromotion", "solange die Umwandlungsfigur fehlt"),
+    ("phase.gameOver", "die Partie ist beendet"),
+    ("phase.reviewing", "während die Partie analysiert wird"),
+    ("action.move", "Ziehen"),
+    ("action.promote", "Eine Umwandlungsfigur wählen"),
+    ("action.undo", "Zurücknehmen"),
+    ("action.navigate", "Zu einem anderen Zug springen"),
+    ("action.editPosition", "Die Stellung ändern"),
+    ("action.engineMove", "Ein Zug der Engine"),
+    ("action.newGame", "Eine neue Partie"),
+    ("action.review", "Die Analyse"),
+    ("action.hint", "Ein Hinweis"),
+    ("error.ratedGame", "{action} ist in einer gewerteten Partie nicht erlaubt"),
+    ("error.casualGame", "Nur gewertete Partien zählen für die Wertung"),
+    ("error.emptyMatch", "Ein Wettkampf braucht mindestens eine Partie zum Sieg"),
+    ("error.matchDecided", "Der Wettkampf ist bereits entschieden"),
+    ("error.unfinishedGame", "Die Partie ist noch nicht beendet"),
+    ("error.tooFewPlayers", "Ein Turnier braucht mindestens zwei Spieler"),
+    ("error.invalidPlayerName", "Der Spielername '{name}' ist leer oder doppelt vergeben"),
+    ("error.noRounds", "Ein Schweizer-System-Turnier braucht mindestens eine Runde"),
+    ("error.roundUnfinished", "In Runde {round} fehlen noch Ergebnisse"),
+    ("error.tournamentFinished", "Alle Runden des Turniers sind gespielt"),
+    ("error.unknownPairing", "In der aktuellen Runde gibt es keine Partie {index}"),
+    ("error.invalidResult", "'{result}' ist kein Partieergebnis"),
+    ("error.simulBoardCount", "Ein Simultan wird auf 1 bis {max} Brettern gespielt, nicht auf {count}"),
+    ("error.unknownSimulBoard", "Im Simultan gibt es kein Brett {board}"),
+    ("error.simulEngineToMove", "Auf Brett {board} ist die Engine am Zug"),
+    ("error.simulBoardFinished", "Die Partie auf Brett {board} ist beendet"),
+    ("error.simulIllegalMove", "Der Zug {move} ist auf Brett {board} nicht erlaubt"),
+    ("error.unknownTheme", "Unbekanntes Brettdesign '{theme}'"),
+    ("error.unavailablePieceSet", "Der Figurensatz '{set}' ist nicht in der App enthalten"),
+    ("error.readOnly", "Das Fenster '{window}' kann die Partie nur ansehen"),
+    ("error.unknownSession", "Das Fenster '{window}' ist keine Zuschauersitzung"),
+    ("error.invalidFen", "Ungültige FEN: {reason}"),
+    ("bot.random.name", "Zufallsspieler"),
+    ("bot.random.description", "Spielt einen beliebigen erlaubten Zug"),
+    ("bot.greedy.name", "Gieriger Schläger"),
+    ("bot.greedy.description", "Schlägt sofort so viel Material wie möglich"),
+    ("position.ruy-lopez.name", "Spanische Partie"),
+    ("position.ruy-lopez.description", "1. e4 e5 2. Nf3 Nc6 3. Bb5, Druck auf den Springer, der e5 deckt."),
+    ("position.italian-game.name", "Italienische Partie"),
+    ("position.italian-game.description", "1. e4 e5 2. Nf3 Nc6 3. Bc4, der Läufer zielt auf f7."),
+    ("position.sicilian-najdorf.name", "Sizilianische Verteidigung, Najdorf-Variante"),
+    ("position.queens-gambit.name", "Damengambit"),
+    ("position.queens-gambit.description", "1. d4 d5 2. c4, der c-Bauer wird für Kontrolle über das Zentrum angeboten."),
+    ("position.kings-indian.name", "Königsindische Verteidigung"),
+    ("position.kings-indian.description", "1. d4 Nf6 2. c4 g6 3. Nc3 Bg7 4. e4 d6, ein hypermoderner Aufbau gegen das Zentrum."),
+    ("position.french-defence.name", "Französische Verteidigung"),
+    ("position.french-defence.description", "1. e4 e6 2. d4 d5, eine solide, aber beengte Verteidigung."),
+    ("position.lucena.name", "Lucena-Stellung"),
+    ("position.lucena.description", "Turm und Bauer gegen Turm, von Weiß durch den Brückenbau mit dem Turm gewonnen."),
+    ("position.philidor.name", "Philidor-Stellung"),
+    ("position.philidor.description", "Turm und Bauer gegen Turm, von Schwarz mit dem Turm auf der dritten Reihe gehalten."),
+    ("position.vancura.name", "Vancura-Stellung"),
+    ("position.vancura.description", "Turm und Randbauer gegen Turm, remis durch Angriff auf den Bauern von der Seite."),
+    ("position.reti-study.name", "Réti-Studie"),
+    ("position.reti-study.description", "Der weiße König holt den Bauern ein oder unterstützt den eigenen und hält eine scheinbar verlorene Stellung."),
+    ("position.saavedra.name", "Saavedra-Stellung"),
+    ("position.saavedra.description", "Weiß gewinnt durch Unterverwandlung in einen Turm, die das Patt vermeidet."),
+    ("position.back-rank-mate.name", "Grundreihenmatt"),
+    ("position.back-rank-mate.description", "Der König ist hinter den eigenen Bauern gefangen, Ra8 ist matt."),
+    ("position.smothered-mate.name", "Ersticktes Matt"),
+    ("position.smothered-mate.description", "Qg8+ Rxg8 Nf7# setzt den von eigenen Figuren umstellten König matt."),
+    ("position.legals-mate.name", "Seekadettenmatt"),
+    ("position.legals-mate.description", "Nxe5 gibt die Dame, Bxd1 Bxf7+ Ke7 Nd5# setzt mit den Leichtfiguren matt."),
+    ("position.greek-gift.name", "Klassisches Läuferopfer"),
+    ("position.greek-gift.description", "Bxh7+ mit Ng5+ und Qh5 danach bricht die Rochadestellung auf."),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::{Board, Coord, Move};
+
+    #[test]
+    fn german_with_english_fallback() {
+        let err = Board::new_game().exec_move(&Move::new(Coord::new('e', 4), Coord::new('e', 5))).unwrap_err();
+        let english = err.to_string();
+
+        assert_eq!(english, error_message(&err));
+
+        set_locale(Locale::De);
+
+        assert_eq!("Auf e4 steht keine Figur", error_message(&err));
+        assert_eq!("Spanische Partie", position(&chess::positions::POSITIONS[0]).name);
+        assert_eq!("Chatnachrichten sind auf 500 Zeichen begrenzt", error_message(&ChatError::TooLong));
+
+        // keys without a translation and errors without a key stay english
+        let book = chess::book::BookError::InvalidLength(3);
+
+        assert_eq!(chess::positions::POSITIONS[2].description, position(&chess::positions::POSITIONS[2]).description);
+        assert_eq!(book.to_string(), error_message(&book));
+
+        set_locale(Locale::En);
+    }
+}
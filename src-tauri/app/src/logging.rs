@@ -0,0 +1,101 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{level_filters::LevelFilter, Span};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::{fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, Registry};
+
+const LOG_FILE: &str = "rusty_chess.log";
+
+static GAME: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(&self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+// the log rolls over daily in the logs folder of the app data directory, the level survives restarts
+// so a problem can be reproduced with more detail
+pub struct Logging {
+    settings_path: PathBuf,
+    handle: reload::Handle<LevelFilter, Registry>,
+    _guard: WorkerGuard,
+}
+
+impl Logging {
+    pub fn init(dir: &Path) -> Result<Self> {
+        let settings_path = dir.join("log_level.json");
+        let level = load_level(&settings_path)?;
+
+        let (writer, guard) = tracing_appender::non_blocking(tracing_appender::rolling::daily(dir.join("logs"), LOG_FILE));
+        let (filter, handle) = reload::Layer::new(level.filter());
+
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(fmt::layer().with_writer(writer).with_ansi(false))
+            .try_init()?;
+
+        tracing::info!(version = env!("CARGO_PKG_VERSION"), ?level, "logging started");
+
+        return Ok(Logging {
+            settings_path,
+            handle,
+            _guard: guard,
+        });
+    }
+
+    pub fn set_level(&self, level: LogLevel) -> Result<()> {
+        self.handle.reload(level.filter())?;
+        fs::write(&self.settings_path, serde_json::to_string_pretty(&level)?)?;
+
+        tracing::info!(?level, "log level changed");
+
+        return Ok(());
+    }
+}
+
+fn load_level(path: &Path) -> Result<LogLevel> {
+    return match fs::read_to_string(path) {
+        Ok(json) => Ok(serde_json::from_str(&json)?),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(LogLevel::default()),
+        Err(err) => Err(err.into()),
+    };
+}
+
+// every new position on the board counts as a new game, so the log lines of one game can be told apart
+pub fn start_game(source: &str, fen: &str) {
+    let game = GAME.fetch_add(1, Ordering::Relaxed) + 1;
+    tracing::info!(game, source, fen, "game started");
+}
+
+pub fn current_game() -> u64 {
+    return GAME.load(Ordering::Relaxed);
+}
+
+pub fn game_span() -> Span {
+    return tracing::info_span!("game", id = GAME.load(Ordering::Relaxed));
+}
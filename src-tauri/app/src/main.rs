@@ -1,21 +1,93 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod arbiter;
+mod chat;
+mod correspondence;
+mod discovery;
+mod drills;
+mod events;
+mod gamedb;
+mod guard;
+mod guess;
+mod hotseat;
+mod i18n;
+mod logging;
+mod match_play;
+mod mistakes;
+mod overlay;
+mod phase;
+mod rating;
+mod sessions;
+mod settings;
+mod site_exports;
+mod simul;
+
 use anyhow::Result;
 
 use std::{
-    error::Error,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    fs::{self, File},
+    io::{BufReader, ErrorKind},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
 };
 
-use chess::{Board, Color, Coord, Move, Piece};
-use serde::Serialize;
-use tauri::{AppHandle, Manager, State};
+use chess::{
+    animation::{self, AnimationOptions},
+    book::Book,
+    bot::{Bot, BotInfo, BotKind, BotRegistry},
+    clock::{ClockSettings, ClockSnapshot, GameClock, MoveTime, TimeReport},
+    draw::{self, ClaimError, DrawReason},
+    engine::{
+        self, Adjudication, AutoPlay, AutoPlayStep, Conduct, ConductDecision, ConductSettings, EngineConfig, EngineOptions, EvalBreakdown, Engine, MemoryUsage,
+        Ponder, TimeBudget, TimeLeft, TraceLimits,
+    },
+    pgn::{self, Termination},
+    positions::{self, NamedPosition},
+    review::{self, GuessScore, MoveReview},
+    san,
+    study::{Chapter, Study},
+    tablebase::{self, Ending},
+    training::{self, DrillVerdict, EndgameTask, EndgameVerdict, TrainingAnswer, TrainingKind, TrainingResult, TrainingTask},
+    tree::{MoveTree, TreeError, ROOT},
+    Board, Color, Coord, Move, Odds, Piece, PieceType, Variant,
+};
+use arbiter::{Arbiter, ArbiterError, ArbiterSettings, ArbiterStatus};
+use chat::{Chat, ChatMessage};
+use correspondence::CorrespondenceGame;
+use discovery::{Advertisement, DiscoveredGame};
+use drills::{DrillProgress, DrillReport, LineProgress};
+use events::AppEvent;
+use gamedb::{ExplorerStats, GameDb, GameFilter, GameSummary, ImportSummary};
+use guard::{CommandGuard, InputError};
+use guess::{GuessGame, GuessStatus};
+use hotseat::{HotseatTournament, TournamentSettings, TournamentStatus};
+use i18n::{Locale, LocaleSettings};
+use logging::{LogLevel, Logging};
+use match_play::{Match, MatchSettings, MatchStatus};
+use mistakes::{MistakeAttempt, MistakeStore, MistakeTask, StoredMistake};
+use overlay::OverlaySettings;
+use phase::{Action, Phase};
+use rating::{PlayerStats, RatingError};
+use sessions::{Access, Sessions};
+use settings::{Appearance, MovePreferences, PieceSet, ThemeCatalog};
+use simul::{Simul, SimulProgress, SimulSettings};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State, Window};
+use tracing::Instrument;
 
 #[derive(Debug, thiserror::Error)]
 enum CommandError {
     #[error(transparent)]
     Error(#[from] anyhow::Error),
+
+    #[error(transparent)]
+    Claim(#[from] ClaimError),
 }
 
 impl Serialize for CommandError {
@@ -23,94 +95,3508 @@ impl Serialize for CommandError {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        let message = match self {
+            CommandError::Error(err) => i18n::error_message(err.as_ref()),
+            CommandError::Claim(err) => i18n::error_message(err),
+        };
+
+        serializer.serialize_str(&message)
     }
 }
 
 type CommandResult<T = ()> = anyhow::Result<T, CommandError>;
 
-#[derive(Serialize, Clone)]
+// training modes that hide part of the board, only the squares of hidden pieces are sent for silhouettes
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Visibility {
+    #[default]
+    Full,
+    Blindfold,
+    OwnPieces {
+        color: Color,
+    },
+    Silhouettes,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct BoardView {
+    orientation: Color,
+    visibility: Visibility,
+}
+
+#[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 struct BoardPayload {
+    variant: Variant,
     pieces: Vec<Piece>,
+    occupied: Vec<Coord>,
     turn: Color,
     white_checked: bool,
     black_checked: bool,
     winner: Option<Color>,
+    orientation: Color,
+    visibility: Visibility,
+    files: Vec<char>,
+    ranks: Vec<u8>,
 }
 
 impl BoardPayload {
-    pub fn new(board: &Board) -> Self {
+    // files are listed left to right and ranks top to bottom, as seen from the side at the bottom of the board
+    pub fn new(board: &Board, view: BoardView) -> Self {
+        let (files, ranks) = match view.orientation {
+            Color::White => (('a'..='h').collect(), (1..=8).rev().collect()),
+            Color::Black => (('a'..='h').rev().collect(), (1..=8).collect()),
+        };
+
+        let (pieces, occupied) = match view.visibility {
+            Visibility::Full => (board.pieces(), Vec::new()),
+            Visibility::Blindfold => (Vec::new(), Vec::new()),
+            Visibility::OwnPieces { color } => (board.pieces().into_iter().filter(|piece| piece.color == color).collect(), Vec::new()),
+            Visibility::Silhouettes => (Vec::new(), board.pieces().into_iter().map(|piece| piece.coord).collect()),
+        };
+
         return BoardPayload {
-            pieces: board.pieces(),
+            variant: board.variant(),
+            pieces,
+            occupied,
             turn: board.turn(),
             white_checked: board.white_checked(),
             black_checked: board.black_checked(),
-            winner: board.winner()
+            winner: board.winner(),
+            orientation: view.orientation,
+            visibility: view.visibility,
+            files,
+            ranks,
         };
     }
 }
 
+// the board is read by far more commands than change it, so reads only wait for a change that is in progress
 struct BoardState {
-    board: Arc<Mutex<Board>>,
+    board: Arc<RwLock<Board>>,
+    view: Arc<Mutex<BoardView>>,
+    tree: Arc<Mutex<MoveTree>>,
+    phase: Mutex<Phase>,
 }
 
-fn mutate_board<T, E>(app: AppHandle, state: State<BoardState>, mutation: T) -> Result<()>
-where
-    T: FnOnce(&mut Board) -> Result<(), E>,
-    E: Error + Send + Sync + 'static,
-{
-    let mut board = get_board(state);
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct EngineSettings {
+    depth: u8,
+    book: Option<PathBuf>,
+    #[serde(flatten)]
+    options: EngineOptions,
+    conduct: ConductSettings,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        EngineSettings {
+            depth: 4,
+            book: None,
+            options: EngineOptions::default(),
+            conduct: ConductSettings::default(),
+        }
+    }
+}
+
+impl EngineSettings {
+    fn load(path: &Path) -> Result<Self> {
+        return match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        return Ok(());
+    }
+}
+
+enum EngineWorker {
+    Idle(Engine),
+    Pondering(Box<Ponder>),
+}
+
+impl EngineWorker {
+    fn stop_handle(&self) -> Arc<AtomicBool> {
+        return match self {
+            EngineWorker::Idle(engine) => engine.stop_handle(),
+            EngineWorker::Pondering(ponder) => ponder.stop_handle(),
+        };
+    }
+
+    fn into_engine(self) -> Engine {
+        return match self {
+            EngineWorker::Idle(engine) => engine,
+            EngineWorker::Pondering(ponder) => ponder.stop(),
+        };
+    }
+}
+
+struct SearchTask {
+    stop: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl SearchTask {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+struct EngineState {
+    settings_path: PathBuf,
+    settings: Mutex<EngineSettings>,
+    worker: Mutex<Option<EngineWorker>>,
+    task: Mutex<Option<SearchTask>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AutoPlaySettings {
+    white: EngineConfig,
+    black: EngineConfig,
+    move_delay_ms: u64,
+    adjudication: Adjudication,
+}
+
+struct AutoPlayState {
+    stop: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+struct PlaybackState {
+    stop: Mutex<Option<Arc<AtomicBool>>>,
+    // read before every step, so a new speed applies while the game plays
+    delay_ms: Arc<AtomicU64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaybackStatus {
+    playing: bool,
+    delay_ms: u64,
+}
+
+struct TakebackRequest {
+    by: Color,
+    plies: usize,
+    board: Board,
+}
+
+// the engine's resignations and draw offers, kept for the game they were made in
+struct ConductState {
+    game: Mutex<GameConduct>,
+}
+
+struct GameConduct {
+    game: u64,
+    conduct: Conduct,
+    // the position after the engine's move that came with a draw offer, the offer stands as long as it does
+    offer: Option<Board>,
+    conclusion: Option<Conclusion>,
+}
+
+impl GameConduct {
+    fn new(game: u64, settings: ConductSettings) -> Self {
+        GameConduct {
+            game,
+            conduct: Conduct::new(settings),
+            offer: None,
+            conclusion: None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Conclusion {
+    Resigned(Color),
+    DrawAgreed,
+    Flagged(Color),
+    Forfeited(Color),
+}
+
+struct TakebackState {
+    pending: Mutex<Option<TakebackRequest>>,
+}
+
+struct SessionState {
+    sessions: Mutex<Sessions>,
+}
+
+struct ClockState {
+    clock: Mutex<Option<GameClock>>,
+}
+
+struct ArbiterState {
+    arbiter: Mutex<Option<Arbiter>>,
+}
+
+struct BotState {
+    registry: BotRegistry,
+    opponent: Mutex<Option<(String, Box<dyn Bot>)>>,
+}
+
+struct GuardState {
+    guard: Mutex<CommandGuard>,
+}
+
+struct ChatState {
+    chat: Mutex<Chat>,
+}
+
+struct DiscoveryState {
+    advertising: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+// the evaluation belongs to the game it was searched in and is shared with the overlay thread
+struct OverlayState {
+    stop: Mutex<Option<Arc<AtomicBool>>>,
+    eval: Arc<Mutex<Option<(u64, i32)>>>,
+}
+
+struct CorrespondenceState {
+    path: PathBuf,
+    game: Mutex<Option<CorrespondenceGame>>,
+}
+
+struct GameDbState {
+    db: Mutex<GameDb>,
+}
+
+// the open chapter is edited on the board, its tree is copied back into the study whenever the study is used
+struct StudyState {
+    open: Mutex<Option<OpenStudy>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenStudy {
+    study: Study,
+    chapter: usize,
+}
+
+// the rated game is the one started last with the flag set, starting any other game ends it
+struct RatingState {
+    path: PathBuf,
+    stats: Mutex<PlayerStats>,
+    rated_game: Mutex<Option<u64>>,
+}
+
+// the book is read once when a drill starts, the color is the side the player drills
+struct DrillState {
+    path: PathBuf,
+    progress: Mutex<DrillProgress>,
+    drill: Mutex<Option<(Book, Color)>>,
+}
+
+struct GuessState {
+    game: Mutex<Option<GuessGame>>,
+}
+
+// the game of the match that is being played, only its result can be recorded for the match
+struct MatchState {
+    current: Mutex<Option<Match>>,
+    game: Mutex<Option<u64>>,
+}
+
+// the game on the board belongs to the pairing of the current round it was started for
+struct TournamentState {
+    current: Mutex<Option<HotseatTournament>>,
+    game: Mutex<Option<(u64, usize)>>,
+}
+
+// the game in which black plays with draw odds
+struct ArmageddonState {
+    game: Mutex<Option<u64>>,
+}
+
+// the game a fork was taken from, set aside as it was until the fork is left,
+// it is only kept while the fork it was taken for is the game on the board
+struct ForkState {
+    original: Mutex<Option<ForkedGame>>,
+}
+
+// leaving the fork starts the original over as a new game, so what was tied to its id is carried along
+struct ForkedGame {
+    fork: u64,
+    board: Board,
+    tree: MoveTree,
+    clock: Option<GameClock>,
+    bot: Option<String>,
+    rated: bool,
+    armageddon: bool,
+}
+
+struct SimulState {
+    simul: Mutex<Option<Simul>>,
+    stop: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+struct MistakeState {
+    path: PathBuf,
+    store: Mutex<MistakeStore>,
+}
+
+struct AppearanceState {
+    path: PathBuf,
+    appearance: Mutex<Appearance>,
+}
+
+struct PreferencesState {
+    path: PathBuf,
+    preferences: Mutex<MovePreferences>,
+}
+
+struct LocaleState {
+    path: PathBuf,
+}
+
+struct LogState {
+    logging: Logging,
+}
+
+fn run_auto_play(app: AppHandle, board: Arc<RwLock<Board>>, view: Arc<Mutex<BoardView>>, settings: AutoPlaySettings, stop: Arc<AtomicBool>) -> Result<()> {
+    let _game = logging::game_span().entered();
+    let start = read(&board)?.clone();
+    let mut autoplay = AutoPlay::new(&start, settings.white, settings.black, settings.adjudication);
+
+    while !stop.load(Ordering::Relaxed) {
+        match autoplay.step()? {
+            AutoPlayStep::Moved(mv, result) => {
+                tracing::info!(%mv, score = result.score, depth = result.depth, "auto play move");
+
+                let mut board = write(&board)?;
+                let before = std::mem::replace(&mut *board, autoplay.board().clone());
+                record_move_tree(&app, &board, None)?;
+                events::emit_change(&app, &before, &board, *lock(&view)?)?;
+            }
+            AutoPlayStep::Finished(outcome) => {
+                tracing::info!(?outcome, "auto play finished");
+                events::emit(&app, AppEvent::AutoPlayFinished(outcome))?;
+                break;
+            }
+        }
+
+        thread::sleep(Duration::from_millis(settings.move_delay_ms));
+    }
+
+    return Ok(());
+}
+
+// one search at a time over the boards, the simul is only locked in between so the user keeps moving meanwhile
+fn run_simul(app: AppHandle, stop: Arc<AtomicBool>) -> Result<()> {
+    let simul_state = app.state::<SimulState>();
+    let mut engine = new_engine(&app);
+    lock(&app.state::<EngineState>().settings)?.options.apply(&mut engine);
+
+    while !stop.load(Ordering::Relaxed) {
+        let turn = match lock(&simul_state.simul)?.as_mut() {
+            Some(simul) if !simul.finished() => simul.next_turn(),
+            _ => break,
+        };
+
+        let Some(turn) = turn else {
+            thread::sleep(simul::IDLE_DELAY);
+            continue;
+        };
+
+        let result = engine.search_timed(&turn.board, turn.budget);
+        tracing::info!(board = turn.index, best_move = ?result.best_move, score = result.score, depth = result.depth, "simul search finished");
+
+        let Some(best_move) = result.best_move.filter(|_| !stop.load(Ordering::Relaxed)) else {
+            continue;
+        };
+
+        let progress = {
+            let mut simul = lock(&simul_state.simul)?;
+
+            let Some(simul) = simul.as_mut() else {
+                break;
+            };
 
-    mutation(&mut *board)?;
-    app.emit_all("update", BoardPayload::new(&*board))?;
+            if !simul.play_engine_move(&turn, &best_move)? {
+                continue;
+            }
+
+            simul.progress()
+        };
+
+        events::emit(&app, AppEvent::SimulUpdated(progress))?;
+    }
 
     return Ok(());
 }
 
-fn get_board(state: State<BoardState>) -> std::sync::MutexGuard<'_, Board> {
-    return state.inner().board.lock().unwrap();
+// steps along the line of the current move until it ends, one move at a time so the board animates every step
+fn run_playback(app: AppHandle, stop: Arc<AtomicBool>, delay_ms: Arc<AtomicU64>) -> Result<()> {
+    let _game = logging::game_span().entered();
+    let state = app.state::<BoardState>();
+
+    loop {
+        thread::sleep(Duration::from_millis(delay_ms.load(Ordering::Relaxed)));
+
+        if stop.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let view = *lock(&state.view)?;
+        let mut board = write(&state.board)?;
+
+        let next = {
+            let tree = lock(&state.tree)?;
+            let current = tree.node(tree.current())?;
+            current.children.first().map(|child| tree.node(*child)).transpose()?.and_then(|node| node.mv)
+        };
+
+        let Some(mv) = next else {
+            break;
+        };
+
+        let before = board.clone();
+        board.exec_move(&mv)?;
+
+        record_move_tree(&app, &board, None)?;
+        events::emit_change(&app, &before, &board, view)?;
+    }
+
+    let playback_state = app.state::<PlaybackState>();
+    let mut running = lock(&playback_state.stop)?;
+
+    // a playback that was restarted in the meantime keeps going
+    if running.as_ref().is_some_and(|running| Arc::ptr_eq(running, &stop)) {
+        *running = None;
+        drop(running);
+        emit_playback(&app, &playback_state)?;
+    }
+
+    return Ok(());
 }
 
-#[tauri::command]
-fn get_board_cmd(state: State<BoardState>) -> BoardPayload {
-    return BoardPayload::new(&*get_board(state));
+fn emit_playback(app: &AppHandle, playback_state: &PlaybackState) -> Result<()> {
+    events::emit(app, AppEvent::PlaybackUpdated(playback_status(playback_state)?))?;
+    return Ok(());
 }
 
-#[tauri::command]
-fn get_available_moves(coord: Coord, state: State<BoardState>) -> CommandResult<Vec<Move>> {
-    let board = get_board(state);
-    let all_moves = chess::get_moves(board.turn(), &*board);
-    let moves_from = all_moves.into_iter().filter(|mv| mv.from == coord).collect::<Vec<Move>>();
+fn playback_status(playback_state: &PlaybackState) -> Result<PlaybackStatus> {
+    return Ok(PlaybackStatus {
+        playing: lock(&playback_state.stop)?.is_some(),
+        delay_ms: playback_state.delay_ms.load(Ordering::Relaxed),
+    });
+}
 
-    return Ok(moves_from);
+fn stop_missed_ponder(engine_state: &EngineState, board: &Board) -> Result<()> {
+    let mut worker = lock(&engine_state.worker)?;
+
+    *worker = match worker.take() {
+        Some(EngineWorker::Pondering(ponder)) if !ponder.is_hit(board) => Some(EngineWorker::Idle(ponder.stop())),
+        other => other,
+    };
+
+    return Ok(());
 }
 
-#[tauri::command]
-fn exec_move(mv: Move, app: AppHandle, state: State<BoardState>) -> CommandResult {
-    mutate_board(app, state, |board| board.exec_move(&mv))?;
+fn new_engine(app: &AppHandle) -> Engine {
+    let app = app.clone();
+    let mut engine = Engine::new();
+
+    engine.set_listener(Some(Box::new(move |info| {
+        let _ = events::emit(&app, AppEvent::AnalysisInfo(info.clone()));
+    })));
+
+    return engine;
+}
+
+// the book is read on every move, so a rebuilt book is used without restarting the engine
+fn book_move(settings: &EngineSettings, board: &Board) -> Result<Option<Move>> {
+    let Some(path) = &settings.book else {
+        return Ok(None);
+    };
+
+    // a book that cannot be read leaves the move to the search instead of failing every engine move
+    let book = match fs::read(path).map_err(anyhow::Error::from).and_then(|bytes| Ok(Book::from_bytes(&bytes)?)) {
+        Ok(book) => book,
+        Err(err) => {
+            tracing::warn!(%err, path = %path.display(), "the opening book could not be read");
+            return Ok(None);
+        }
+    };
+
+    return Ok(book.pick(board, correspondence::now_ms()));
+}
+
+// the selected bot answers with a move of its own, the engine is not asked at all
+fn bot_move(app: &AppHandle, board: &Board) -> Result<Option<Move>> {
+    let bot_state = app.state::<BotState>();
+    let mut opponent = lock(&bot_state.opponent)?;
+
+    let Some((id, bot)) = opponent.as_mut() else {
+        return Ok(None);
+    };
+
+    let clock = lock(&app.state::<ClockState>().clock)?.as_ref().map(|clock| clock.snapshot(Instant::now()));
+    let bot_move = bot.choose_move(board, clock.as_ref()).ok_or_else(|| anyhow::anyhow!("The bot '{id}' has no move to play"))?;
+
+    return Ok(Some(bot_move));
+}
+
+// under a running clock the engine thinks as long as its remaining time allows instead of to the configured depth
+fn time_budget(app: &AppHandle, board: &Board) -> Result<Option<TimeBudget>> {
+    let clock_state = app.state::<ClockState>();
+    let clock = lock(&clock_state.clock)?;
+
+    let Some(snapshot) = clock.as_ref().map(|clock| clock.snapshot(Instant::now())).filter(|snapshot| snapshot.flagged.is_none()) else {
+        return Ok(None);
+    };
+
+    return Ok(Some(engine::allocate(board, TimeLeft::from_clock(&snapshot, board.turn()))));
+}
+
+// the search runs on a blocking task without holding the board lock, so board queries stay responsive
+async fn play_engine_move(app: AppHandle, state: State<'_, BoardState>, engine_state: State<'_, EngineState>) -> Result<Option<Move>> {
+    let settings = lock(&engine_state.settings)?.clone();
+    let board = get_board(state.clone())?.clone();
+    let cancelled = Arc::new(AtomicBool::new(false));
+
+    // moves of a bot opponent and from the opening book are played right away without a search
+    let bot_move = bot_move(&app, &board)?;
+    let instant_move = match bot_move {
+        Some(bot_move) => Some(bot_move),
+        None => book_move(&settings, &board)?,
+    };
+
+    if let Some(instant_move) = instant_move {
+        if lock(&engine_state.task)?.is_some() {
+            return Err(anyhow::anyhow!("The engine is already searching"));
+        }
+
+        let view = *lock(&state.view)?;
+        let mut current = get_board_mut(state)?;
+
+        // the move is discarded like a search when the board changed since it was chosen
+        if *current != board {
+            return Ok(None);
+        }
+
+        tracing::info!(%instant_move, bot = bot_move.is_some(), "engine played move without a search");
+        stop_missed_ponder(&engine_state, &board)?;
+
+        current.exec_move(&instant_move)?;
+        let move_time = press_clock(&app, &board, &current)?;
+        record_correspondence(&app, &board, &current)?;
+        record_move_tree(&app, &current, move_time)?;
+        settle_phase(&app, &current)?;
+        events::emit_change(&app, &board, &current, view)?;
+
+        return Ok(Some(instant_move));
+    }
+
+    let worker = {
+        let mut task = lock(&engine_state.task)?;
+
+        if task.is_some() {
+            return Err(anyhow::anyhow!("The engine is already searching"));
+        }
+
+        let worker = lock(&engine_state.worker)?.take().unwrap_or_else(|| EngineWorker::Idle(new_engine(&app)));
+
+        *task = Some(SearchTask {
+            stop: worker.stop_handle(),
+            cancelled: cancelled.clone(),
+        });
+
+        worker
+    };
+
+    events::emit(&app, AppEvent::EngineStarted)?;
+
+    let budget = time_budget(&app, &board)?;
+    let search_board = board.clone();
+    let (depth, options) = (settings.depth, settings.options.clone());
+    let span = tracing::info_span!("engine_move", depth, budget_ms = budget.map(|budget| budget.soft.as_millis() as u64));
+    let search = tauri::async_runtime::spawn_blocking(move || match worker {
+        EngineWorker::Pondering(ponder) if ponder.is_hit(&search_board) => span.in_scope(|| ponder.ponderhit()),
+        other => {
+            let _search = span.entered();
+            let mut engine = other.into_engine();
+            options.apply(&mut engine);
+
+            let result = match budget {
+                Some(budget) => engine.search_timed(&search_board, budget),
+                None => engine.search(&search_board, depth),
+            };
+
+            (engine, result)
+        }
+    });
+
+    let searched = search.await;
+
+    lock(&engine_state.task)?.take();
+    events::emit(&app, AppEvent::EngineFinished)?;
+
+    let (engine, result) = searched?;
+
+    tracing::info!(best_move = ?result.best_move, score = result.score, depth = result.depth, nodes = result.nodes, "engine search finished");
+
+    let eval = if board.turn() == Color::White { result.score } else { -result.score };
+    *lock(&app.state::<OverlayState>().eval)? = Some((logging::current_game(), eval));
+
+    events::emit(&app, AppEvent::SearchStats(result.stats.clone()))?;
+
+    if settings.options.multi_pv > 1 {
+        events::emit(&app, AppEvent::SearchLines(result.lines.clone()))?;
+    }
+
+    let view = *lock(&state.view)?;
+    let mut current = get_board_mut(state)?;
+    let mut worker = lock(&engine_state.worker)?;
+
+    // the search is discarded when it was cancelled or the board changed in the meantime
+    let best_move = match result.best_move {
+        Some(best_move) if !cancelled.load(Ordering::Relaxed) && *current == board => best_move,
+        _ => {
+            *worker = Some(EngineWorker::Idle(engine));
+            return Ok(None);
+        }
+    };
+
+    let decision = game_conduct(&app.state::<ConductState>(), settings.conduct)?.conduct.after_search(&board, result.score);
+
+    if decision == Some(ConductDecision::Resign) {
+        tracing::info!(score = result.score, "engine resigned");
+        *worker = Some(EngineWorker::Idle(engine));
+        conclude(&app, Conclusion::Resigned(board.turn()))?;
+
+        return Ok(None);
+    }
+
+    current.exec_move(&best_move)?;
+    let move_time = press_clock(&app, &board, &current)?;
+    record_correspondence(&app, &board, &current)?;
+    record_move_tree(&app, &current, move_time)?;
+    settle_phase(&app, &current)?;
+    events::emit_change(&app, &board, &current, view)?;
+
+    if decision == Some(ConductDecision::OfferDraw) {
+        game_conduct(&app.state::<ConductState>(), settings.conduct)?.offer = Some(current.clone());
+        events::emit(&app, AppEvent::DrawOffered { by: board.turn() })?;
+    }
+
+    *worker = match result.pv.get(1) {
+        Some(expected) if settings.options.ponder => Some(EngineWorker::Pondering(Box::new(Ponder::start(engine, &current, expected, settings.depth)?))),
+        _ => Some(EngineWorker::Idle(engine)),
+    };
+
+    return Ok(Some(best_move));
+}
+
+// the engine's view starts over with every game and whenever its settings change
+fn game_conduct(conduct_state: &ConductState, settings: ConductSettings) -> Result<MutexGuard<'_, GameConduct>> {
+    let mut conduct = lock(&conduct_state.game)?;
+    let game = logging::current_game();
+
+    if conduct.game != game || conduct.conduct.settings() != settings {
+        *conduct = GameConduct::new(game, settings);
+    }
+
+    return Ok(conduct);
+}
+
+// any other game started since the fork was taken leaves nothing to go back to
+fn forked_game(fork_state: &ForkState) -> Result<MutexGuard<'_, Option<ForkedGame>>> {
+    let mut original = lock(&fork_state.original)?;
+
+    if original.as_ref().is_some_and(|original| original.fork != logging::current_game()) {
+        *original = None;
+    }
+
+    return Ok(original);
+}
+
+fn conclude(app: &AppHandle, conclusion: Conclusion) -> Result<()> {
+    let settings = lock(&app.state::<EngineState>().settings)?.conduct;
+    game_conduct(&app.state::<ConductState>(), settings)?.conclusion = Some(conclusion);
+
+    let (winner, result) = match conclusion {
+        Conclusion::Resigned(Color::White) | Conclusion::Flagged(Color::White) | Conclusion::Forfeited(Color::White) => (Some(Color::Black), "0-1"),
+        Conclusion::Resigned(Color::Black) | Conclusion::Flagged(Color::Black) | Conclusion::Forfeited(Color::Black) => (Some(Color::White), "1-0"),
+        Conclusion::DrawAgreed => draw_odds(app, None, "1/2-1/2"),
+    };
+
+    if let Conclusion::Resigned(by) = conclusion {
+        events::emit(app, AppEvent::Resigned { by })?;
+    }
+
+    set_phase(app, Phase::GameOver)?;
+
+    events::emit(app, AppEvent::GameEnded { winner, result })?;
+
     return Ok(());
 }
 
-#[tauri::command]
-fn undo(app: AppHandle, state: State<BoardState>) -> CommandResult {
-    mutate_board(app, state, |board| board.undo_move())?;
+fn mutate_board<T>(window: Window, state: State<BoardState>, mutation: T) -> Result<()>
+where
+    T: FnOnce(&mut Board) -> Result<()>,
+{
+    let _game = logging::game_span().entered();
+
+    ensure_writable(&window)?;
+
+    let view = *lock(&state.view)?;
+    let mut board = get_board_mut(state)?;
+    let before = board.clone();
+
+    if let Err(err) = mutation(&mut *board) {
+        tracing::warn!(%err, window = window.label(), "board change rejected");
+        return Err(err);
+    }
+
+    tracing::debug!(fen = %board.to_fen(), "board changed");
+    let move_time = press_clock(&window.app_handle(), &before, &board)?;
+    record_correspondence(&window.app_handle(), &before, &board)?;
+    record_move_tree(&window.app_handle(), &board, move_time)?;
+    settle_phase(&window.app_handle(), &board)?;
+    events::emit_change(&window.app_handle(), &before, &board, view)?;
+
     return Ok(());
 }
 
-#[tauri::command]
-fn apply_fen(fen: &str, app: AppHandle, state: State<BoardState>) -> CommandResult {
-    mutate_board(app, state, |board| board.apply_fen(fen))?;
+fn set_phase(app: &AppHandle, phase: Phase) -> Result<()> {
+    let state = app.state::<BoardState>();
+    let previous = std::mem::replace(&mut *lock(&state.phase)?, phase);
+
+    if previous != phase {
+        events::emit(app, AppEvent::PhaseChanged(phase))?;
+    }
+
     return Ok(());
 }
 
-fn main() {
-    let board = Board::new_game();
-    let state = BoardState { board: Arc::new(Mutex::new(board)) };
+fn settle_phase(app: &AppHandle, board: &Board) -> Result<()> {
+    let concluded = is_concluded(app)?;
+    let phase = lock(&app.state::<BoardState>().phase)?.settle(board, concluded);
+    return set_phase(app, phase);
+}
 
-    tauri::Builder::default()
-        .manage(state)
-        .invoke_handler(tauri::generate_handler![get_board_cmd, get_available_moves, exec_move, undo, apply_fen])
+// a new game or a loaded one leaves setting up and reviewing behind
+fn restart_phase(app: &AppHandle) -> Result<()> {
+    let concluded = is_concluded(app)?;
+    let phase = Phase::resume(&*read(&app.state::<BoardState>().board)?, concluded);
+    return set_phase(app, phase);
+}
+
+// whether the current game already ended off the board, going back in it does not make it playable again
+fn is_concluded(app: &AppHandle) -> Result<bool> {
+    let settings = lock(&app.state::<EngineState>().settings)?.conduct;
+    return Ok(game_conduct(&app.state::<ConductState>(), settings)?.conclusion.is_some());
+}
+
+fn is_armageddon(app: &AppHandle) -> Result<bool> {
+    return Ok(*lock(&app.state::<ArmageddonState>().game)? == Some(logging::current_game()));
+}
+
+// a draw in an armageddon game is announced as the win black gets for it, a poisoned lock only loses that
+// in the announcement since the saved game is tagged separately
+fn draw_odds(app: &AppHandle, winner: Option<Color>, result: &'static str) -> (Option<Color>, &'static str) {
+    return match result {
+        "1/2-1/2" if is_armageddon(app).unwrap_or(false) => (Some(Color::Black), "0-1"),
+        _ => (winner, result),
+    };
+}
+
+fn is_rated(app: &AppHandle) -> Result<bool> {
+    return Ok(*lock(&app.state::<RatingState>().rated_game)? == Some(logging::current_game()));
+}
+
+fn ensure_casual(app: &AppHandle, action: Action) -> Result<()> {
+    if !rating::allowed_in_rated_game(action) && is_rated(app)? {
+        return Err(RatingError::RatedGame(action).into());
+    }
+
+    return Ok(());
+}
+
+// the engine's search blocks everything the player could do to the board, its own move is checked against the phase only
+fn ensure_action(app: &AppHandle, action: Action) -> Result<()> {
+    if action != Action::EngineMove {
+        ensure_human_turn(&app.state::<EngineState>())?;
+    }
+
+    ensure_casual(app, action)?;
+
+    lock(&app.state::<BoardState>().phase)?.check(action)?;
+
+    // a flagged clock ends the game for good, even in a position loaded onto it
+    if matches!(action, Action::Move | Action::Promote | Action::EngineMove) {
+        if let Some(flagged) = lock(&app.state::<ClockState>().clock)?.as_ref().and_then(GameClock::flagged) {
+            return Err(anyhow::anyhow!("{flagged:?} ran out of time, no more moves can be played"));
+        }
+    }
+
+    return Ok(());
+}
+
+// the tree keeps every line the board went through, so a move played after going back starts a variation,
+// a move made on the clock keeps the time that was left in its comment
+fn record_move_tree(app: &AppHandle, after: &Board, move_time: Option<MoveTime>) -> Result<()> {
+    let state = app.state::<BoardState>();
+    let mut tree = lock(&state.tree)?;
+
+    tree.sync(after);
+
+    if let Some(move_time) = move_time {
+        let current = tree.current();
+        let comment = pgn::with_clock(tree.node(current)?.comment.as_deref(), move_time.remaining_ms);
+        tree.set_comment(current, Some(comment))?;
+    }
+    events::emit(app, AppEvent::MoveTreeUpdated(tree.clone()))?;
+
+    return Ok(());
+}
+
+fn store_study_chapter(open: &mut OpenStudy, state: &BoardState) -> Result<()> {
+    open.study.chapter_mut(open.chapter)?.tree = lock(&state.tree)?.clone();
+    return Ok(());
+}
+
+// the chapter's tree takes over the board at the move it was left on
+fn show_study_chapter(window: Window, state: State<BoardState>, engine_state: State<EngineState>, open: &mut OpenStudy, chapter: usize) -> Result<OpenStudy> {
+    let tree = open.study.chapter(chapter)?.tree.clone();
+    let board_tree = state.tree.clone();
+    let app = window.app_handle();
+
+    mutate_board(window, state, |board| {
+        *board = tree.board_at(tree.current())?;
+        logging::start_game("study", &board.to_fen());
+        stop_missed_ponder(&engine_state, board)?;
+        *lock(&board_tree)? = tree;
+
+        return Ok(());
+    })?;
+
+    open.chapter = chapter;
+    events::emit(&app, AppEvent::StudyUpdated(Some(open.clone())))?;
+
+    return Ok(open.clone());
+}
+
+// changes to the tree that leave the board where it is
+fn update_move_tree<T>(window: &Window, state: &BoardState, update: T) -> Result<()>
+where
+    T: FnOnce(&mut MoveTree) -> Result<(), TreeError>,
+{
+    ensure_writable(window)?;
+
+    let mut tree = lock(&state.tree)?;
+    update(&mut tree)?;
+
+    events::emit(&window.app_handle(), AppEvent::MoveTreeUpdated(tree.clone()))?;
+
+    return Ok(());
+}
+
+// the board takes on the line leading to the move the tree went to
+fn navigate_tree<T>(window: Window, state: State<BoardState>, engine_state: State<EngineState>, navigation: T) -> Result<()>
+where
+    T: FnOnce(&mut MoveTree) -> Result<Board, TreeError>,
+{
+    ensure_action(&window.app_handle(), Action::Navigate)?;
+    let tree = state.tree.clone();
+
+    return mutate_board(window, state, |board| {
+        *board = navigation(&mut *lock(&tree)?)?;
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    });
+}
+
+// the clock runs from the first move on, any other change to the board only hands it to the side to move,
+// the time of the move is returned unless the side to move had already run out of it, which ends the game
+fn press_clock(app: &AppHandle, before: &Board, after: &Board) -> Result<Option<MoveTime>> {
+    let clock_state = app.state::<ClockState>();
+    let mut guard = lock(&clock_state.clock)?;
+
+    let Some(clock) = guard.as_mut() else {
+        return Ok(None);
+    };
+
+    let now = Instant::now();
+
+    // anything but a single move, like an undo or a new position, hands the clock to the side to move now
+    if events::BoardDelta::new(before, after).is_none() {
+        let plies = after.history().len() - 1;
+
+        if clock.turn() != after.turn() || clock.move_times().len() > plies.saturating_sub(clock.start_ply()) {
+            clock.set_turn(after.turn(), plies, now);
+            events::emit(app, AppEvent::ClockUpdated(Some(clock.snapshot(now))))?;
+        }
+
+        return Ok(None);
+    }
+
+    clock.start(now);
+    let flagged = clock.press(now);
+
+    events::emit(app, AppEvent::ClockUpdated(Some(clock.snapshot(now))))?;
+
+    let move_time = clock.move_times().last().copied();
+    drop(guard);
+
+    if let Some(flagged) = flagged {
+        tracing::info!(?flagged, "clock flagged");
+        conclude(app, Conclusion::Flagged(flagged))?;
+
+        return Ok(None);
+    }
+
+    return Ok(move_time);
+}
+
+fn record_correspondence(app: &AppHandle, before: &Board, after: &Board) -> Result<()> {
+    if events::BoardDelta::new(before, after).is_none() {
+        return Ok(());
+    }
+
+    let correspondence_state = app.state::<CorrespondenceState>();
+    let mut game = lock(&correspondence_state.game)?;
+
+    let Some(game) = game.as_mut() else {
+        return Ok(());
+    };
+
+    game.record_move(after, correspondence::now_ms());
+    game.save(&correspondence_state.path)?;
+
+    events::emit(app, AppEvent::CorrespondenceUpdated(Some(game.clone())))?;
+
+    return Ok(());
+}
+
+// runs for the lifetime of the app, a deadline that came close while the app was closed is reported on startup
+fn watch_correspondence_deadlines(app: AppHandle) -> Result<()> {
+    loop {
+        check_correspondence_deadline(&app)?;
+        thread::sleep(Duration::from_secs(60));
+    }
+}
+
+fn check_correspondence_deadline(app: &AppHandle) -> Result<()> {
+    let correspondence_state = app.state::<CorrespondenceState>();
+    let mut game = lock(&correspondence_state.game)?;
+
+    let Some(game) = game.as_mut() else {
+        return Ok(());
+    };
+
+    if game.take_warning(correspondence::now_ms()) {
+        game.save(&correspondence_state.path)?;
+
+        events::emit(
+            app,
+            AppEvent::CorrespondenceDeadlineNear {
+                turn: game.turn,
+                deadline_ms: game.deadline_ms,
+            },
+        )?;
+    }
+
+    return Ok(());
+}
+
+// a new clock starts with the side to move on the board
+fn set_clock(app: &AppHandle, settings: Option<ClockSettings>) -> Result<()> {
+    let clock_state = app.state::<ClockState>();
+    let (turn, plies) = read(&app.state::<BoardState>().board).map(|board| (board.turn(), board.history().len() - 1))?;
+    let clock = settings.map(|settings| GameClock::new(settings, turn, plies));
+    let snapshot = clock.as_ref().map(|clock| clock.snapshot(Instant::now()));
+
+    *lock(&clock_state.clock)? = clock;
+    events::emit(app, AppEvent::ClockUpdated(snapshot))?;
+
+    return Ok(());
+}
+
+// the time controls are written along with the game unless the caller already tagged them
+fn clock_tags(clock_state: &ClockState, tags: &[(String, String)]) -> Result<Vec<(String, String)>> {
+    let mut tags = tags.to_vec();
+
+    if let Some(clock) = lock(&clock_state.clock)?.as_ref() {
+        for (name, value) in clock.settings().pgn_tags() {
+            if !tags.iter().any(|(key, _)| *key == name) {
+                tags.push((name, value));
+            }
+        }
+    }
+
+    return Ok(tags);
+}
+
+// a game that ended off the board gets the termination and, unless it was given, the result that goes with it,
+// an armageddon game is tagged as one so its draws are scored for black
+fn termination_tags(app: &AppHandle, board: &Board, tags: &[(String, String)]) -> Result<Vec<(String, String)>> {
+    let mut tags = tags.to_vec();
+
+    if is_armageddon(app)? && !pgn::is_armageddon(&tags) {
+        tags.push((pgn::ARMAGEDDON_TAG.to_string(), "1".to_string()));
+    }
+
+    if pgn::result(board) != "*" || tags.iter().any(|(key, _)| key == "Termination") {
+        return Ok(tags);
+    }
+
+    let settings = lock(&app.state::<EngineState>().settings)?.conduct;
+    let conclusion = game_conduct(&app.state::<ConductState>(), settings)?.conclusion;
+    let flagged = lock(&app.state::<ClockState>().clock)?.as_ref().and_then(GameClock::flagged);
+    let forfeited = lock(&app.state::<ArbiterState>().arbiter)?.as_ref().and_then(|arbiter| arbiter.status().forfeited);
+
+    let (termination, winner) = match (conclusion, flagged, forfeited) {
+        (Some(Conclusion::Resigned(by)), _, _) => (Termination::Resignation, Some(by.invert())),
+        (Some(Conclusion::DrawAgreed), _, _) => (Termination::Agreement, None),
+        (Some(Conclusion::Flagged(flagged)), _, _) | (None, Some(flagged), _) => (Termination::TimeForfeit, Some(flagged.invert())),
+        (Some(Conclusion::Forfeited(forfeited)), _, _) | (None, None, Some(forfeited)) => (Termination::RulesInfraction, Some(forfeited.invert())),
+        (None, None, None) => return Ok(tags),
+    };
+
+    if !tags.iter().any(|(key, _)| key == "Result") {
+        let result = match winner {
+            Some(Color::White) => "1-0",
+            Some(Color::Black) => "0-1",
+            None => "1/2-1/2",
+        };
+
+        tags.push(("Result".to_string(), result.to_string()));
+    }
+
+    tags.push(("Termination".to_string(), termination.to_string()));
+
+    return Ok(tags);
+}
+
+// without an arbiter every move goes straight to the board, a forfeit ends the game like a resignation would
+fn arbitrate(app: &AppHandle, board: &Board, mv: &Move) -> Result<()> {
+    let arbiter_state = app.state::<ArbiterState>();
+    let mut guard = lock(&arbiter_state.arbiter)?;
+
+    let Some(arbiter) = guard.as_mut() else {
+        return Ok(());
+    };
+
+    let checked = arbiter.check_move(board, mv);
+    events::emit(app, AppEvent::ArbiterUpdated(Some(arbiter.status())))?;
+    drop(guard);
+
+    if let Err(ArbiterError::Forfeit(side)) = &checked {
+        conclude(app, Conclusion::Forfeited(*side))?;
+    }
+
+    checked?;
+
+    return Ok(());
+}
+
+// read-only sessions receive every event, but any command that changes state is rejected for them here
+fn ensure_writable(window: &Window) -> Result<()> {
+    lock(&window.state::<SessionState>().sessions)?.ensure_writable(window.label())?;
+    return Ok(());
+}
+
+// drops a command that arrives again with the same payload right after the first one, like a double click
+fn ensure_not_repeated(window: &Window, command: &'static str, payload: &str) -> Result<()> {
+    lock(&window.state::<GuardState>().guard)?.check(window.label(), command, payload, Instant::now())?;
+    return Ok(());
+}
+
+// while the engine searches its move the board belongs to it
+fn ensure_human_turn(engine_state: &EngineState) -> Result<()> {
+    if lock(&engine_state.task)?.is_some() {
+        return Err(InputError::NotYourTurn.into());
+    }
+
+    return Ok(());
+}
+
+fn get_board(state: State<'_, BoardState>) -> Result<RwLockReadGuard<'_, Board>> {
+    return read(&state.inner().board);
+}
+
+fn get_board_mut(state: State<'_, BoardState>) -> Result<RwLockWriteGuard<'_, Board>> {
+    return write(&state.inner().board);
+}
+
+// a poisoned lock means a previous command panicked, which is reported to the frontend instead of panicking again
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>> {
+    return mutex.lock().map_err(|_| anyhow::anyhow!("The application state is unavailable"));
+}
+
+fn read<T>(lock: &RwLock<T>) -> Result<RwLockReadGuard<'_, T>> {
+    return lock.read().map_err(|_| anyhow::anyhow!("The application state is unavailable"));
+}
+
+fn write<T>(lock: &RwLock<T>) -> Result<RwLockWriteGuard<'_, T>> {
+    return lock.write().map_err(|_| anyhow::anyhow!("The application state is unavailable"));
+}
+
+#[tauri::command]
+fn get_session_access(window: Window, session_state: State<SessionState>) -> CommandResult<Access> {
+    return Ok(lock(&session_state.sessions)?.access(window.label()));
+}
+
+// the label is registered before the frontend opens the window, so it is read-only from its first command
+#[tauri::command]
+fn open_read_only_session(label: &str, window: Window, session_state: State<SessionState>) -> CommandResult {
+    lock(&session_state.sessions)?.open_read_only(window.label(), label).map_err(anyhow::Error::from)?;
+    return Ok(());
+}
+
+#[tauri::command]
+fn close_read_only_session(label: &str, window: Window, session_state: State<SessionState>) -> CommandResult {
+    lock(&session_state.sessions)?.close(window.label(), label).map_err(anyhow::Error::from)?;
+    return Ok(());
+}
+
+#[tauri::command]
+fn get_board_cmd(state: State<BoardState>) -> CommandResult<BoardPayload> {
+    let view = *lock(&state.view)?;
+    return Ok(BoardPayload::new(&*get_board(state)?, view));
+}
+
+#[tauri::command]
+fn flip_board(window: Window, state: State<BoardState>) -> CommandResult {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+
+    let view = {
+        let mut view = lock(&state.view)?;
+        view.orientation = view.orientation.invert();
+        *view
+    };
+
+    events::emit(&app, AppEvent::BoardUpdated(BoardPayload::new(&*get_board(state)?, view))).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn set_visibility(visibility: Visibility, window: Window, state: State<BoardState>) -> CommandResult {
+    ensure_writable(&window)?;
+
+    let view = {
+        let mut view = lock(&state.view)?;
+        view.visibility = visibility;
+        *view
+    };
+
+    events::emit_board(&window.app_handle(), &*get_board(state)?, view).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn get_available_moves(coord: Coord, state: State<BoardState>) -> CommandResult<Vec<Move>> {
+    let board = get_board(state)?;
+    let all_moves = chess::get_moves(board.turn(), &*board);
+    let moves_from = all_moves.into_iter().filter(|mv| mv.from == coord).collect::<Vec<Move>>();
+
+    return Ok(moves_from);
+}
+
+#[tauri::command]
+fn get_move_targets(coord: Coord, state: State<BoardState>) -> CommandResult<Vec<Coord>> {
+    let board = get_board(state)?;
+    return Ok(chess::get_move_targets(board.turn(), &board, coord).into_iter().collect());
+}
+
+#[tauri::command]
+fn get_arbiter(arbiter_state: State<ArbiterState>) -> CommandResult<Option<ArbiterStatus>> {
+    return Ok(lock(&arbiter_state.arbiter)?.as_ref().map(Arbiter::status));
+}
+
+// changing the settings starts the arbiter afresh, without settings moves are no longer arbitrated
+#[tauri::command]
+fn set_arbiter(settings: Option<ArbiterSettings>, window: Window, arbiter_state: State<ArbiterState>) -> CommandResult<Option<ArbiterStatus>> {
+    ensure_writable(&window)?;
+
+    let status = settings.map(|settings| Arbiter::new(settings).status());
+    *lock(&arbiter_state.arbiter)? = settings.map(Arbiter::new);
+
+    events::emit(&window.app_handle(), AppEvent::ArbiterUpdated(status.clone())).map_err(anyhow::Error::from)?;
+
+    return Ok(status);
+}
+
+// selecting a piece counts as touching it when touch-move is enforced
+#[tauri::command]
+fn touch_piece(coord: Coord, window: Window, state: State<BoardState>, arbiter_state: State<ArbiterState>) -> CommandResult<Vec<Move>> {
+    let board = get_board(state)?.clone();
+    let mut arbiter = lock(&arbiter_state.arbiter)?;
+
+    let Some(arbiter) = arbiter.as_mut() else {
+        return Ok(chess::get_moves(board.turn(), &board).into_iter().filter(|mv| mv.from == coord).collect());
+    };
+
+    ensure_writable(&window)?;
+
+    let touched = arbiter.touch(&board, coord);
+    events::emit(&window.app_handle(), AppEvent::ArbiterUpdated(Some(arbiter.status()))).map_err(anyhow::Error::from)?;
+
+    return Ok(touched.map_err(anyhow::Error::from)?);
+}
+
+#[tauri::command]
+fn exec_move(mv: Move, window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult {
+    let _move = tracing::info_span!("move", %mv).entered();
+    let app = window.app_handle();
+    ensure_action(&app, Action::Move)?;
+    ensure_not_repeated(&window, "exec_move", &mv.to_string())?;
+
+    mutate_board(window, state, |board| {
+        arbitrate(&app, board, &mv)?;
+        guard::play_move(board, &mv)?;
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    return Ok(());
+}
+
+// dropped pieces only carry their squares, the flags of the move come from the matching legal move
+// only resolves the typed move, it is played like any other move afterwards
+#[tauri::command]
+fn parse_move_input(text: &str, state: State<BoardState>) -> CommandResult<Move> {
+    guard::check_length("move", text, guard::MAX_MOVE_INPUT_LENGTH).map_err(anyhow::Error::from)?;
+    return Ok(san::parse_move_input(&*get_board(state)?, text).map_err(anyhow::Error::from)?);
+}
+
+#[tauri::command]
+fn parse_spoken_move(phrase: &str, state: State<BoardState>) -> CommandResult<Move> {
+    guard::check_length("move", phrase, guard::MAX_MOVE_INPUT_LENGTH).map_err(anyhow::Error::from)?;
+    return Ok(san::parse_spoken_move(&*get_board(state)?, phrase).map_err(anyhow::Error::from)?);
+}
+
+#[tauri::command]
+fn try_move(
+    from: Coord,
+    to: Coord,
+    promotion: Option<PieceType>,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+) -> CommandResult<Option<Move>> {
+    let _move = tracing::info_span!("move", %from, %to, ?promotion).entered();
+    let app = window.app_handle();
+    let promotion = lock(&app.state::<PreferencesState>().preferences)?.promotion(promotion);
+    let phase = *lock(&state.phase)?;
+
+    // the piece for a pending promotion completes exactly the move that was started
+    match phase {
+        Phase::AwaitingPromotion { from: pending_from, to: pending_to } if (pending_from, pending_to) == (from, to) && promotion.is_some() => {
+            ensure_action(&app, Action::Promote)?;
+        }
+        _ => ensure_action(&app, Action::Move)?,
+    }
+
+    ensure_not_repeated(&window, "try_move", &format!("{from}{to}{promotion:?}"))?;
+
+    if promotion.is_none() && chess::resolve_move(&*get_board(state.clone())?, from, to, None).is_some_and(|mv| mv.promotion) {
+        ensure_writable(&window)?;
+        set_phase(&app, Phase::AwaitingPromotion { from, to })?;
+
+        return Ok(None);
+    }
+
+    let mut played = None;
+
+    mutate_board(window, state, |board| {
+        played = chess::resolve_move(board, from, to, promotion);
+        arbitrate(&app, board, &played.unwrap_or(Move::new(from, to)))?;
+
+        if let Some(mv) = &played {
+            board.exec_move(mv)?;
+            stop_missed_ponder(&engine_state, board)?;
+        }
+
+        return Ok(());
+    })?;
+
+    return Ok(played);
+}
+
+#[tauri::command]
+fn get_phase(state: State<BoardState>) -> CommandResult<Phase> {
+    return Ok(*lock(&state.phase)?);
+}
+
+// while setting up, the position can be changed as often as needed without starting to play
+#[tauri::command]
+fn start_setup(window: Window) -> CommandResult {
+    ensure_writable(&window)?;
+    ensure_action(&window.app_handle(), Action::EditPosition)?;
+    set_phase(&window.app_handle(), Phase::Setup)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn start_review(window: Window) -> CommandResult {
+    ensure_writable(&window)?;
+    ensure_action(&window.app_handle(), Action::Review)?;
+    set_phase(&window.app_handle(), Phase::Reviewing)?;
+
+    return Ok(());
+}
+
+// ends setting up or reviewing, the game goes on from the position on the board
+#[tauri::command]
+fn resume_play(window: Window, state: State<BoardState>) -> CommandResult {
+    ensure_writable(&window)?;
+
+    if let Phase::Setup | Phase::Reviewing = *lock(&state.phase)? {
+        restart_phase(&window.app_handle())?;
+    }
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn cancel_promotion(window: Window, state: State<BoardState>) -> CommandResult {
+    ensure_writable(&window)?;
+
+    if let Phase::AwaitingPromotion { .. } = *lock(&state.phase)? {
+        restart_phase(&window.app_handle())?;
+    }
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn undo(window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult {
+    ensure_action(&window.app_handle(), Action::Undo)?;
+
+    mutate_board(window, state, |board| {
+        board.undo_move()?;
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    return Ok(());
+}
+
+// an invalid claim leaves the board untouched, a valid one plays the intended move and ends the game
+#[tauri::command]
+fn claim_draw(mv: Option<Move>, window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult<DrawReason> {
+    let app = window.app_handle();
+    ensure_action(&app, Action::Move)?;
+    let reason = draw::check_draw_claim(&*get_board(state.clone())?, mv.as_ref())?;
+
+    if let Some(mv) = mv {
+        mutate_board(window, state, |board| {
+            board.exec_move(&mv)?;
+            stop_missed_ponder(&engine_state, board)?;
+
+            return Ok(());
+        })?;
+    } else {
+        ensure_writable(&window)?;
+    }
+
+    set_phase(&app, Phase::GameOver)?;
+    events::emit(&app, AppEvent::DrawClaimed { reason }).map_err(anyhow::Error::from)?;
+
+    let (winner, result) = draw_odds(&app, None, "1/2-1/2");
+    events::emit(&app, AppEvent::GameEnded { winner, result }).map_err(anyhow::Error::from)?;
+
+    return Ok(reason);
+}
+
+// the engine answers a draw offer right away, it agrees once its score has been level for a while,
+// like a move an offer is only possible while the game is still going
+#[tauri::command]
+fn offer_draw(
+    by: Color,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    conduct_state: State<ConductState>,
+) -> CommandResult<bool> {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+    ensure_action(&app, Action::Move)?;
+
+    let board = get_board(state)?.clone();
+    let settings = lock(&engine_state.settings)?.conduct;
+
+    let accepted = game_conduct(&conduct_state, settings)?.conduct.accepts_draw(&board);
+
+    events::emit(&app, AppEvent::DrawOfferAnswered { by: by.invert(), accepted }).map_err(anyhow::Error::from)?;
+
+    if accepted {
+        conclude(&app, Conclusion::DrawAgreed)?;
+    }
+
+    return Ok(accepted);
+}
+
+// the engine's offer lapses as soon as the position changes
+#[tauri::command]
+fn accept_draw(window: Window, state: State<BoardState>, engine_state: State<EngineState>, conduct_state: State<ConductState>) -> CommandResult {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+    ensure_action(&app, Action::Move)?;
+
+    let board = get_board(state)?.clone();
+    let settings = lock(&engine_state.settings)?.conduct;
+
+    let offer = game_conduct(&conduct_state, settings)?.offer.take();
+
+    if offer.as_ref() != Some(&board) {
+        return Err(anyhow::anyhow!("The engine has not offered a draw in this position").into());
+    }
+
+    events::emit(&app, AppEvent::DrawOfferAnswered { by: board.turn(), accepted: true }).map_err(anyhow::Error::from)?;
+    conclude(&app, Conclusion::DrawAgreed)?;
+
+    return Ok(());
+}
+
+// the requesting side takes back its own last move, plus the opponent's reply when it is already to move again
+#[tauri::command]
+fn request_takeback(by: Color, window: Window, state: State<BoardState>, takeback_state: State<TakebackState>) -> CommandResult {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+    ensure_action(&app, Action::Undo)?;
+
+    let board = get_board(state)?.clone();
+
+    if board.last_move().is_none() {
+        return Err(anyhow::anyhow!("There is no move to take back").into());
+    }
+
+    let plies = if board.turn() == by { 2 } else { 1 };
+    *lock(&takeback_state.pending)? = Some(TakebackRequest { by, plies, board });
+
+    events::emit(&app, AppEvent::TakebackRequested { by, plies }).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+// only the opponent of the requesting side can answer, in networked games that is the remote player
+fn take_takeback(takeback_state: &TakebackState, by: Color) -> Result<TakebackRequest> {
+    let mut pending = lock(&takeback_state.pending)?;
+
+    match pending.as_ref() {
+        None => return Err(anyhow::anyhow!("No takeback was requested")),
+        Some(request) if request.by == by => return Err(anyhow::anyhow!("A takeback has to be answered by the opponent")),
+        Some(_) => return Ok(pending.take().expect("a pending takeback")),
+    }
+}
+
+#[tauri::command]
+fn accept_takeback(
+    by: Color,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    takeback_state: State<TakebackState>,
+) -> CommandResult {
+    ensure_writable(&window)?;
+    ensure_action(&window.app_handle(), Action::Undo)?;
+    let request = take_takeback(&takeback_state, by)?;
+
+    mutate_board(window.clone(), state, |board| {
+        if *board != request.board {
+            return Err(anyhow::anyhow!("The position changed since the takeback was requested"));
+        }
+
+        for _ in 0..request.plies {
+            if board.last_move().is_some() {
+                board.undo_move()?;
+            }
+        }
+
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    events::emit(&window.app_handle(), AppEvent::TakebackAnswered { by, accepted: true }).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn decline_takeback(by: Color, window: Window, takeback_state: State<TakebackState>) -> CommandResult {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+
+    take_takeback(&takeback_state, by)?;
+
+    events::emit(&app, AppEvent::TakebackAnswered { by, accepted: false }).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn apply_fen(fen: &str, window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult {
+    guard::check_length("fen", fen, guard::MAX_FEN_LENGTH).map_err(anyhow::Error::from)?;
+    ensure_action(&window.app_handle(), Action::EditPosition)?;
+    ensure_not_repeated(&window, "apply_fen", fen)?;
+
+    mutate_board(window, state, |board| {
+        board.apply_fen(fen)?;
+        logging::start_game("fen", fen);
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn copy_fen(state: State<BoardState>) -> CommandResult<String> {
+    return Ok(get_board(state)?.to_fen());
+}
+
+#[tauri::command]
+fn copy_pgn(state: State<BoardState>, clock_state: State<ClockState>, window: Window) -> CommandResult<String> {
+    let tags = termination_tags(&window.app_handle(), &*get_board(state.clone())?, &clock_tags(&clock_state, &[])?)?;
+    return Ok(pgn::write_tree_pgn(&*lock(&state.tree)?, &tags));
+}
+
+// how long each side thought about its moves in the current game, empty without a clock
+#[tauri::command]
+fn get_time_usage(clock_state: State<ClockState>) -> CommandResult<Option<TimeReport>> {
+    return Ok(lock(&clock_state.clock)?.as_ref().map(GameClock::time_report));
+}
+
+#[tauri::command]
+fn get_clock(clock_state: State<ClockState>) -> CommandResult<Option<ClockSnapshot>> {
+    return Ok(lock(&clock_state.clock)?.as_ref().map(|clock| clock.snapshot(Instant::now())));
+}
+
+// a single line with eight ranks is treated as fen, everything else as pgn
+#[tauri::command]
+fn paste_fen_or_pgn(text: &str, window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult {
+    let text = text.trim();
+    let is_fen = !text.contains('\n') && text.split(' ').next().is_some_and(|pieces| pieces.matches('/').count() == 7);
+
+    let (field, max) = if is_fen { ("fen", guard::MAX_FEN_LENGTH) } else { ("pgn", guard::MAX_PGN_LENGTH) };
+
+    guard::check_length(field, text, max).map_err(anyhow::Error::from)?;
+    ensure_action(&window.app_handle(), Action::NewGame)?;
+    ensure_not_repeated(&window, "paste_fen_or_pgn", text)?;
+
+    let app = window.app_handle();
+    let tree = state.tree.clone();
+
+    mutate_board(window, state, |board| {
+        if is_fen {
+            board.apply_fen(text)?;
+            *lock(&tree)? = MoveTree::new(board);
+        } else {
+            let game = pgn::read_pgn(text)?;
+            *board = game.board;
+            *lock(&tree)? = game.tree;
+        }
+
+        logging::start_game("paste", &board.to_fen());
+
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    restart_phase(&app)?;
+
+    return Ok(());
+}
+
+// announces a hosted game on the local network, a running advertisement is replaced
+#[tauri::command]
+fn start_advertising(name: String, port: u16, window: Window, discovery_state: State<DiscoveryState>) -> CommandResult {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+
+    let stop = Arc::new(AtomicBool::new(false));
+
+    if let Some(previous) = lock(&discovery_state.advertising)?.replace(stop.clone()) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    let advertisement = Advertisement { name, port };
+
+    thread::spawn(move || {
+        if let Err(err) = discovery::advertise(&advertisement, &stop) {
+            tracing::warn!(%err, "advertising the game failed");
+            let _ = events::emit(&app, AppEvent::Error { message: err.to_string() });
+        }
+    });
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn stop_advertising(window: Window, discovery_state: State<DiscoveryState>) -> CommandResult {
+    ensure_writable(&window)?;
+
+    if let Some(stop) = lock(&discovery_state.advertising)?.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+
+    return Ok(());
+}
+
+// only one overlay runs at a time, starting another one replaces it
+#[tauri::command]
+fn start_overlay(settings: OverlaySettings, window: Window, state: State<BoardState>, overlay_state: State<OverlayState>) -> CommandResult {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+
+    let stop = Arc::new(AtomicBool::new(false));
+
+    if let Some(previous) = lock(&overlay_state.stop)?.replace(stop.clone()) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    let (board, eval) = (state.board.clone(), overlay_state.eval.clone());
+
+    thread::spawn(move || {
+        let current = || {
+            let board = board.read().ok()?.clone();
+            let eval = eval.lock().ok()?.filter(|(game, _)| *game == logging::current_game()).map(|(_, eval)| eval);
+
+            return Some((board, eval));
+        };
+
+        if let Err(err) = overlay::run(&settings, &stop, current) {
+            tracing::warn!(%err, "the stream overlay failed");
+            let _ = events::emit(&app, AppEvent::Error { message: err.to_string() });
+        }
+    });
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn stop_overlay(window: Window, overlay_state: State<OverlayState>) -> CommandResult {
+    ensure_writable(&window)?;
+
+    if let Some(stop) = lock(&overlay_state.stop)?.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+
+    return Ok(());
+}
+
+#[tauri::command]
+async fn discover_games(timeout_ms: u64) -> CommandResult<Vec<DiscoveredGame>> {
+    let timeout = Duration::from_millis(timeout_ms);
+    let games = tauri::async_runtime::spawn_blocking(move || discovery::discover(timeout)).await.map_err(anyhow::Error::from)?;
+
+    return Ok(games.map_err(anyhow::Error::from)?);
+}
+
+// the chat belongs to the game on the board and starts over with every new game
+#[tauri::command]
+fn get_chat(chat_state: State<ChatState>) -> CommandResult<Vec<ChatMessage>> {
+    return Ok(lock(&chat_state.chat)?.messages(logging::current_game()).to_vec());
+}
+
+#[tauri::command]
+fn send_chat(from: Color, text: String, window: Window, chat_state: State<ChatState>) -> CommandResult<ChatMessage> {
+    ensure_writable(&window)?;
+
+    let message = ChatMessage {
+        game: logging::current_game(),
+        from,
+        text,
+        sent_ms: correspondence::now_ms(),
+    };
+
+    let message = lock(&chat_state.chat)?.post(message, Instant::now()).map_err(anyhow::Error::from)?;
+    events::emit(&window.app_handle(), AppEvent::ChatReceived(message.clone())).map_err(anyhow::Error::from)?;
+
+    return Ok(message);
+}
+
+#[tauri::command]
+fn get_move_tree(state: State<BoardState>) -> CommandResult<MoveTree> {
+    return Ok(lock(&state.tree)?.clone());
+}
+
+#[tauri::command]
+fn goto_move(id: usize, window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult {
+    navigate_tree(window, state, engine_state, |tree| tree.goto(id))?;
+    return Ok(());
+}
+
+// the position at a ply of the main line becomes a new game against the engine with a fresh clock of the same
+// time control, the game it was taken from is set aside with its clock paused until the fork is left
+#[tauri::command]
+fn fork_game(
+    ply: usize,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    bot_state: State<BotState>,
+    fork_state: State<ForkState>,
+) -> CommandResult {
+    let app = window.app_handle();
+    ensure_writable(&window)?;
+    ensure_action(&app, Action::NewGame)?;
+
+    let tree = lock(&state.tree)?.clone();
+    let id = match ply {
+        0 => ROOT,
+        ply => *tree.mainline().get(ply - 1).ok_or_else(|| anyhow::anyhow!("The game has no ply {ply}"))?,
+    };
+    let start = tree.board_at(id).map_err(anyhow::Error::from)?;
+
+    let now = Instant::now();
+    let clock = lock(&app.state::<ClockState>().clock)?.clone().map(|mut clock| {
+        clock.pause(now);
+        clock
+    });
+
+    let original = ForkedGame {
+        fork: 0,
+        board: get_board(state.clone())?.clone(),
+        tree,
+        clock: clock.clone(),
+        bot: lock(&bot_state.opponent)?.take().map(|(id, _)| id),
+        rated: is_rated(&app)?,
+        armageddon: is_armageddon(&app)?,
+    };
+
+    // forking a fork still leads back to the game the first fork was taken from
+    forked_game(&fork_state)?.get_or_insert(original);
+
+    let board_tree = state.tree.clone();
+
+    mutate_board(window, state, |board| {
+        *board = start;
+        *lock(&board_tree)? = MoveTree::new(board);
+        logging::start_game("fork", &board.to_fen());
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    if let Some(original) = lock(&fork_state.original)?.as_mut() {
+        original.fork = logging::current_game();
+    }
+
+    restart_phase(&app)?;
+    set_clock(&app, clock.map(|clock| clock.settings()))?;
+    tracing::info!(ply, "game forked");
+
+    return Ok(());
+}
+
+// the game the fork was taken from comes back as it was, against the opponent it was played against
+#[tauri::command]
+fn leave_fork(
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    bot_state: State<BotState>,
+    fork_state: State<ForkState>,
+) -> CommandResult {
+    let app = window.app_handle();
+    ensure_writable(&window)?;
+    ensure_action(&app, Action::NewGame)?;
+
+    let original = forked_game(&fork_state)?.take().ok_or_else(|| anyhow::anyhow!("There is no fork to leave"))?;
+
+    *lock(&bot_state.opponent)? = match original.bot {
+        Some(id) => Some((id.clone(), bot_state.registry.create(&id).map_err(anyhow::Error::from)?)),
+        None => None,
+    };
+
+    let board_tree = state.tree.clone();
+
+    mutate_board(window, state, |board| {
+        *board = original.board;
+        *lock(&board_tree)? = original.tree;
+        logging::start_game("fork left", &board.to_fen());
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    *lock(&app.state::<RatingState>().rated_game)? = original.rated.then(logging::current_game);
+    *lock(&app.state::<ArmageddonState>().game)? = original.armageddon.then(logging::current_game);
+    restart_phase(&app)?;
+
+    let snapshot = original.clock.as_ref().map(|clock| clock.snapshot(Instant::now()));
+    *lock(&app.state::<ClockState>().clock)? = original.clock;
+    events::emit(&app, AppEvent::ClockUpdated(snapshot)).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+// index 0 follows the line of the current move, higher indices enter its variations
+#[tauri::command]
+fn enter_variation(index: usize, window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult {
+    navigate_tree(window, state, engine_state, |tree| tree.enter_variation(index))?;
+    return Ok(());
+}
+
+#[tauri::command]
+fn exit_variation(window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult {
+    navigate_tree(window, state, engine_state, MoveTree::exit_variation)?;
+    return Ok(());
+}
+
+#[tauri::command]
+fn delete_variation(window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult {
+    navigate_tree(window, state, engine_state, MoveTree::delete_variation)?;
+    return Ok(());
+}
+
+// the board stays where it is, only the order of the lines changes
+#[tauri::command]
+fn promote_variation(window: Window, state: State<BoardState>) -> CommandResult {
+    update_move_tree(&window, &state, MoveTree::promote_variation)?;
+    return Ok(());
+}
+
+#[tauri::command]
+fn set_move_comment(id: usize, comment: Option<String>, window: Window, state: State<BoardState>) -> CommandResult {
+    update_move_tree(&window, &state, |tree| tree.set_comment(id, comment))?;
+    return Ok(());
+}
+
+#[tauri::command]
+fn set_move_nags(id: usize, nags: Vec<u8>, window: Window, state: State<BoardState>) -> CommandResult {
+    update_move_tree(&window, &state, |tree| tree.set_nags(id, nags))?;
+    return Ok(());
+}
+
+#[tauri::command]
+fn get_correspondence(correspondence_state: State<CorrespondenceState>) -> CommandResult<Option<CorrespondenceGame>> {
+    return Ok(lock(&correspondence_state.game)?.clone());
+}
+
+// the current game continues as a correspondence game, with a full period for the side to move
+#[tauri::command]
+fn start_correspondence(
+    days_per_move: u32,
+    window: Window,
+    state: State<BoardState>,
+    correspondence_state: State<CorrespondenceState>,
+) -> CommandResult<CorrespondenceGame> {
+    ensure_writable(&window)?;
+
+    if days_per_move == 0 {
+        return Err(anyhow::anyhow!("A correspondence game needs at least one day per move").into());
+    }
+
+    let game = CorrespondenceGame::new(days_per_move, &*get_board(state)?, correspondence::now_ms());
+    game.save(&correspondence_state.path)?;
+    *lock(&correspondence_state.game)? = Some(game.clone());
+
+    events::emit(&window.app_handle(), AppEvent::CorrespondenceUpdated(Some(game.clone()))).map_err(anyhow::Error::from)?;
+
+    return Ok(game);
+}
+
+#[tauri::command]
+fn stop_correspondence(window: Window, correspondence_state: State<CorrespondenceState>) -> CommandResult {
+    ensure_writable(&window)?;
+
+    CorrespondenceGame::remove(&correspondence_state.path)?;
+    *lock(&correspondence_state.game)? = None;
+
+    events::emit(&window.app_handle(), AppEvent::CorrespondenceUpdated(None)).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn get_study(state: State<BoardState>, study_state: State<StudyState>) -> CommandResult<Option<OpenStudy>> {
+    let mut open = lock(&study_state.open)?;
+
+    if let Some(open) = open.as_mut() {
+        store_study_chapter(open, &state)?;
+    }
+
+    return Ok(open.clone());
+}
+
+// the game on the board becomes the first chapter
+#[tauri::command]
+fn new_study(name: &str, window: Window, state: State<BoardState>, study_state: State<StudyState>) -> CommandResult<OpenStudy> {
+    ensure_writable(&window)?;
+
+    let mut study = Study::new(name);
+    study.add_chapter(Chapter {
+        name: "Chapter 1".to_string(),
+        tags: Vec::new(),
+        tree: lock(&state.tree)?.clone(),
+    });
+
+    let open = OpenStudy { study, chapter: 0 };
+    *lock(&study_state.open)? = Some(open.clone());
+
+    events::emit(&window.app_handle(), AppEvent::StudyUpdated(Some(open.clone()))).map_err(anyhow::Error::from)?;
+
+    return Ok(open);
+}
+
+#[tauri::command]
+fn open_study(path: &str, window: Window, state: State<BoardState>, engine_state: State<EngineState>, study_state: State<StudyState>) -> CommandResult<OpenStudy> {
+    ensure_writable(&window)?;
+
+    let file = File::open(path).map_err(anyhow::Error::from)?;
+    let mut study = Study::read_pgn(BufReader::new(file)).map_err(anyhow::Error::from)?;
+
+    if study.chapters.is_empty() {
+        study.add_chapter(Chapter::new("Chapter 1", &Board::new_game()));
+    }
+
+    let mut open = lock(&study_state.open)?;
+    *open = Some(OpenStudy { study, chapter: 0 });
+
+    return Ok(show_study_chapter(window, state, engine_state, open.as_mut().expect("study to be open"), 0)?);
+}
+
+#[tauri::command]
+fn save_study(path: &str, state: State<BoardState>, study_state: State<StudyState>) -> CommandResult {
+    let mut open = lock(&study_state.open)?;
+    let open = open.as_mut().ok_or_else(|| anyhow::anyhow!("No study is open"))?;
+
+    store_study_chapter(open, &state)?;
+    fs::write(path, open.study.to_pgn()).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+// a chapter without a fen starts from the standard position
+#[tauri::command]
+fn add_study_chapter(
+    name: &str,
+    fen: Option<String>,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    study_state: State<StudyState>,
+) -> CommandResult<OpenStudy> {
+    ensure_writable(&window)?;
+    let mut start = Board::new_game();
+
+    if let Some(fen) = fen {
+        start.apply_fen(&fen).map_err(anyhow::Error::from)?;
+    }
+
+    let mut open = lock(&study_state.open)?;
+    let open = open.as_mut().ok_or_else(|| anyhow::anyhow!("No study is open"))?;
+
+    store_study_chapter(open, &state)?;
+    let chapter = open.study.add_chapter(Chapter::new(name, &start));
+
+    return Ok(show_study_chapter(window, state, engine_state, open, chapter)?);
+}
+
+#[tauri::command]
+fn select_study_chapter(
+    index: usize,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    study_state: State<StudyState>,
+) -> CommandResult<OpenStudy> {
+    ensure_writable(&window)?;
+
+    let mut open = lock(&study_state.open)?;
+    let open = open.as_mut().ok_or_else(|| anyhow::anyhow!("No study is open"))?;
+
+    open.study.chapter(index).map_err(anyhow::Error::from)?;
+    store_study_chapter(open, &state)?;
+
+    return Ok(show_study_chapter(window, state, engine_state, open, index)?);
+}
+
+// removing the open chapter puts its neighbour on the board, the last chapter of a study stays
+#[tauri::command]
+fn remove_study_chapter(
+    index: usize,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    study_state: State<StudyState>,
+) -> CommandResult<OpenStudy> {
+    ensure_writable(&window)?;
+
+    let mut open = lock(&study_state.open)?;
+    let open = open.as_mut().ok_or_else(|| anyhow::anyhow!("No study is open"))?;
+
+    if open.study.chapters.len() == 1 {
+        return Err(anyhow::anyhow!("A study needs at least one chapter").into());
+    }
+
+    store_study_chapter(open, &state)?;
+    open.study.remove_chapter(index).map_err(anyhow::Error::from)?;
+
+    let chapter = match open.chapter {
+        chapter if chapter > index => chapter - 1,
+        chapter => chapter.min(open.study.chapters.len() - 1),
+    };
+
+    return Ok(show_study_chapter(window, state, engine_state, open, chapter)?);
+}
+
+#[tauri::command]
+fn close_study(window: Window, study_state: State<StudyState>) -> CommandResult {
+    ensure_writable(&window)?;
+    *lock(&study_state.open)? = None;
+
+    events::emit(&window.app_handle(), AppEvent::StudyUpdated(None)).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+// nothing of a rated game can be taken back, edited or hinted at, and only rated games count for the rating
+#[tauri::command]
+fn new_game(
+    variant: Variant,
+    odds: Option<Odds>,
+    clock: Option<ClockSettings>,
+    bot: Option<String>,
+    rated: Option<bool>,
+    armageddon: Option<bool>,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    bot_state: State<BotState>,
+) -> CommandResult {
+    let app = window.app_handle();
+    ensure_writable(&window)?;
+    ensure_action(&app, Action::NewGame)?;
+    let tree = state.tree.clone();
+
+    // without a bot the engine is the opponent again
+    let opponent = match bot {
+        Some(id) => Some((id.clone(), bot_state.registry.create(&id).map_err(anyhow::Error::from)?)),
+        None => None,
+    };
+
+    *lock(&bot_state.opponent)? = opponent;
+
+    mutate_board(window, state, |board| {
+        *board = match odds {
+            Some(odds) if variant == Variant::Standard => Board::new_with_odds(odds),
+            _ => Board::new_variant_game(variant),
+        };
+        *lock(&tree)? = MoveTree::new(board);
+        logging::start_game("new game", &board.to_fen());
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    restart_phase(&app)?;
+    *lock(&app.state::<RatingState>().rated_game)? = rated.unwrap_or(false).then(logging::current_game);
+
+    // an armageddon game without a clock of its own gets the usual five against four minutes
+    let armageddon = armageddon.unwrap_or(false);
+    let clock = match armageddon {
+        true => Some(clock.unwrap_or_else(ClockSettings::armageddon)),
+        false => clock,
+    };
+
+    *lock(&app.state::<ArmageddonState>().game)? = armageddon.then(logging::current_game);
+    set_clock(&app, clock)?;
+
+    let arbiter_state = app.state::<ArbiterState>();
+    let mut arbiter = lock(&arbiter_state.arbiter)?;
+
+    if let Some(arbiter) = arbiter.as_mut() {
+        *arbiter = Arbiter::new(arbiter.status().settings);
+        events::emit(&app, AppEvent::ArbiterUpdated(Some(arbiter.status()))).map_err(anyhow::Error::from)?;
+    }
+
+    return Ok(());
+}
+
+// without a path the engine's book is drilled, as black the book's first move is played right away
+#[tauri::command]
+fn start_opening_drill(
+    player: Color,
+    path: Option<PathBuf>,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    drill_state: State<DrillState>,
+) -> CommandResult<Option<Move>> {
+    let path = match path {
+        Some(path) => path,
+        None => lock(&engine_state.settings)?.book.clone().ok_or_else(|| anyhow::anyhow!("No opening book is set"))?,
+    };
+
+    let book = Book::from_bytes(&fs::read(&path).map_err(anyhow::Error::from)?).map_err(anyhow::Error::from)?;
+    let reply = match player {
+        Color::White => None,
+        Color::Black => book.pick(&Board::new_game(), correspondence::now_ms()),
+    };
+    let tree = state.tree.clone();
+
+    mutate_board(window, state, |board| {
+        *board = Board::new_game();
+        *lock(&tree)? = MoveTree::new(board);
+        logging::start_game("opening drill", &board.to_fen());
+
+        if let Some(reply) = reply {
+            board.exec_move(&reply)?;
+        }
+
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    *lock(&drill_state.drill)? = Some((book, player));
+
+    return Ok(reply);
+}
+
+// a move off the book is not played, so the player can try again from the same position
+#[tauri::command]
+fn play_drill_move(
+    mv: Move,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    drill_state: State<DrillState>,
+) -> CommandResult<DrillReport> {
+    ensure_action(&window.app_handle(), Action::Move)?;
+
+    let board = get_board(state.clone())?.clone();
+    let drill = lock(&drill_state.drill)?;
+    let (book, player) = drill.as_ref().ok_or_else(|| anyhow::anyhow!("No opening drill is running"))?;
+    let player = *player;
+
+    if board.turn() != player {
+        return Err(anyhow::anyhow!("It is not the drilled side's turn").into());
+    }
+
+    let verdict = training::judge_drill_move(book, &board, &mv, correspondence::now_ms()).map_err(anyhow::Error::from)?;
+    drop(drill);
+
+    let line = match &verdict {
+        DrillVerdict::Deviation { .. } => Some((training::line_key(&board), false)),
+        DrillVerdict::InBook { played, reply, complete } => {
+            for mv in std::iter::once(*played).chain(*reply) {
+                mutate_board(window.clone(), state.clone(), |board| {
+                    board.exec_move(&mv)?;
+                    stop_missed_ponder(&engine_state, board)?;
+
+                    return Ok(());
+                })?;
+            }
+
+            let line = training::line_key(&*get_board(state.clone())?);
+            complete.then_some((line, true))
+        }
+    };
+
+    let progress = match line {
+        Some((line, completed)) => {
+            let mut progress = lock(&drill_state.progress)?;
+            let line = progress.record(line, player, completed).clone();
+            progress.save(&drill_state.path)?;
+
+            Some(line)
+        }
+        None => None,
+    };
+
+    return Ok(DrillReport { verdict, progress });
+}
+
+#[tauri::command]
+fn get_drill_progress(drill_state: State<DrillState>) -> CommandResult<Vec<LineProgress>> {
+    return Ok(lock(&drill_state.progress)?.lines.clone());
+}
+
+#[tauri::command]
+fn list_positions() -> Vec<NamedPosition> {
+    return positions::POSITIONS.iter().map(i18n::position).collect();
+}
+
+#[tauri::command]
+fn list_bots(bot_state: State<BotState>) -> Vec<BotInfo> {
+    return bot_state.registry.list().into_iter().map(i18n::bot).collect();
+}
+
+#[tauri::command]
+fn get_bot(bot_state: State<BotState>) -> CommandResult<Option<String>> {
+    return Ok(lock(&bot_state.opponent)?.as_ref().map(|(id, _)| id.clone()));
+}
+
+#[tauri::command]
+fn get_locale() -> Locale {
+    return i18n::locale();
+}
+
+// errors and descriptions sent after this are in the new locale, strings the frontend already holds are not
+#[tauri::command]
+fn set_locale(locale: Locale, locale_state: State<LocaleState>) -> CommandResult {
+    i18n::set_locale(locale);
+    LocaleSettings { locale }.save(&locale_state.path)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn get_training_task(kind: TrainingKind) -> CommandResult<TrainingTask> {
+    return Ok(training::new_task(kind, correspondence::now_ms()).map_err(anyhow::Error::from)?);
+}
+
+#[tauri::command]
+fn check_training_answer(task: TrainingTask, answer: TrainingAnswer) -> CommandResult<TrainingResult> {
+    return Ok(training::check_answer(&task, &answer).map_err(anyhow::Error::from)?);
+}
+
+#[tauri::command]
+fn start_endgame_training(ending: Ending, window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult<EndgameTask> {
+    let app = window.app_handle();
+    ensure_action(&app, Action::NewGame)?;
+    let task = training::new_endgame(ending, correspondence::now_ms()).map_err(anyhow::Error::from)?;
+
+    mutate_board(window, state, |board| {
+        board.set_variant(Variant::Standard);
+        board.apply_fen(&task.fen)?;
+        logging::start_game("endgame training", &task.fen);
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    restart_phase(&app)?;
+
+    return Ok(task);
+}
+
+// the move is judged before it is played, the tablebase answers with the most stubborn defence
+#[tauri::command]
+fn play_endgame_move(mv: Move, window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult<EndgameVerdict> {
+    ensure_action(&window.app_handle(), Action::Move)?;
+    let verdict = training::judge_endgame_move(&*get_board(state.clone())?, &mv).map_err(anyhow::Error::from)?;
+
+    mutate_board(window.clone(), state.clone(), |board| {
+        board.exec_move(&mv)?;
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    let defence = tablebase::best_move(&*get_board(state.clone())?);
+
+    if let Some(defence) = defence {
+        mutate_board(window, state, |board| {
+            board.exec_move(&defence)?;
+            stop_missed_ponder(&engine_state, board)?;
+
+            return Ok(());
+        })?;
+    }
+
+    return Ok(verdict);
+}
+
+#[tauri::command]
+fn load_position(id: &str, window: Window, state: State<BoardState>, engine_state: State<EngineState>) -> CommandResult {
+    let position = positions::find_position(id).ok_or_else(|| anyhow::anyhow!("Unknown position '{id}'"))?;
+    let app = window.app_handle();
+    ensure_action(&app, Action::NewGame)?;
+    let tree = state.tree.clone();
+
+    mutate_board(window, state, |board| {
+        board.set_variant(Variant::Standard);
+        board.apply_fen(position.fen)?;
+        *lock(&tree)? = MoveTree::new(board);
+        logging::start_game(position.id, position.fen);
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    restart_phase(&app)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+async fn engine_move(window: Window, state: State<'_, BoardState>, engine_state: State<'_, EngineState>) -> CommandResult<Option<Move>> {
+    ensure_writable(&window)?;
+    ensure_action(&window.app_handle(), Action::EngineMove)?;
+    return Ok(play_engine_move(window.app_handle(), state, engine_state).instrument(logging::game_span()).await?);
+}
+
+#[tauri::command]
+fn cancel_engine_move(engine_state: State<EngineState>, window: Window) -> CommandResult {
+    ensure_writable(&window)?;
+
+    if let Some(task) = lock(&engine_state.task)?.as_ref() {
+        task.cancel();
+    }
+
+    return Ok(());
+}
+
+// the engine would play the player's side of the current game too, so like any analysis it is kept out of rated games
+#[tauri::command]
+fn start_auto_play(settings: AutoPlaySettings, window: Window, state: State<BoardState>, auto_play_state: State<AutoPlayState>) -> CommandResult {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+    ensure_casual(&app, Action::Hint)?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+
+    if let Some(previous) = lock(&auto_play_state.stop)?.replace(stop.clone()) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    let board = state.board.clone();
+    let view = state.view.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(err) = run_auto_play(app.clone(), board, view, settings, stop) {
+            let _ = events::emit(&app, AppEvent::Error { message: i18n::error_message(err.as_ref()) });
+        }
+    });
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn stop_auto_play(auto_play_state: State<AutoPlayState>, window: Window) -> CommandResult {
+    ensure_writable(&window)?;
+
+    if let Some(stop) = lock(&auto_play_state.stop)?.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn get_playback(playback_state: State<PlaybackState>) -> CommandResult<PlaybackStatus> {
+    return Ok(playback_status(&playback_state)?);
+}
+
+// plays the moves that follow the current one, a running playback starts over from the board as it is now
+#[tauri::command]
+fn start_playback(window: Window, playback_state: State<PlaybackState>) -> CommandResult<PlaybackStatus> {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+
+    let stop = Arc::new(AtomicBool::new(false));
+
+    if let Some(previous) = lock(&playback_state.stop)?.replace(stop.clone()) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    let delay_ms = playback_state.delay_ms.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(err) = run_playback(app.clone(), stop, delay_ms) {
+            let _ = events::emit(&app, AppEvent::Error { message: i18n::error_message(err.as_ref()) });
+        }
+    });
+
+    emit_playback(&window.app_handle(), &playback_state)?;
+
+    return Ok(playback_status(&playback_state)?);
+}
+
+#[tauri::command]
+fn pause_playback(window: Window, playback_state: State<PlaybackState>) -> CommandResult<PlaybackStatus> {
+    ensure_writable(&window)?;
+
+    if let Some(stop) = lock(&playback_state.stop)?.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+
+    emit_playback(&window.app_handle(), &playback_state)?;
+
+    return Ok(playback_status(&playback_state)?);
+}
+
+#[tauri::command]
+fn set_playback_speed(delay_ms: u64, window: Window, playback_state: State<PlaybackState>) -> CommandResult<PlaybackStatus> {
+    ensure_writable(&window)?;
+
+    if delay_ms == 0 {
+        return Err(anyhow::anyhow!("The playback needs a delay between moves").into());
+    }
+
+    playback_state.delay_ms.store(delay_ms, Ordering::Relaxed);
+    emit_playback(&window.app_handle(), &playback_state)?;
+
+    return Ok(playback_status(&playback_state)?);
+}
+
+#[tauri::command]
+fn explain_eval(state: State<BoardState>, window: Window) -> CommandResult<EvalBreakdown> {
+    ensure_casual(&window.app_handle(), Action::Hint)?;
+    return Ok(chess::engine::explain(&*get_board(state)?));
+}
+
+#[tauri::command]
+fn export_animation(path: &str, options: AnimationOptions, state: State<BoardState>) -> CommandResult {
+    let board = get_board(state)?.clone();
+    let gif = animation::export_gif(&board, &options).map_err(anyhow::Error::from)?;
+
+    std::fs::write(path, gif).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+// the tree is written as graphviz for a .dot or .gv file and as json otherwise, the search runs with its own engine
+// so it does not wait for a move the engine is thinking about
+#[tauri::command]
+async fn export_search_tree(
+    path: PathBuf,
+    depth: Option<u8>,
+    limits: TraceLimits,
+    state: State<'_, BoardState>,
+    engine_state: State<'_, EngineState>,
+    window: Window,
+) -> CommandResult {
+    ensure_casual(&window.app_handle(), Action::Hint)?;
+    let settings = lock(&engine_state.settings)?.clone();
+    let board = get_board(state)?.clone();
+    let depth = depth.unwrap_or(settings.depth);
+
+    let trace = tauri::async_runtime::spawn_blocking(move || {
+        let mut engine = Engine::new();
+        settings.options.apply(&mut engine);
+
+        return engine.trace_search(&board, depth, limits).1;
+    })
+    .await
+    .map_err(anyhow::Error::from)?;
+
+    let content = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("dot" | "gv") => trace.to_dot(),
+        _ => serde_json::to_string_pretty(&trace).map_err(anyhow::Error::from)?,
+    };
+
+    fs::write(&path, content).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn save_game(
+    tags: Vec<(String, String)>,
+    state: State<BoardState>,
+    db_state: State<GameDbState>,
+    clock_state: State<ClockState>,
+    window: Window,
+) -> CommandResult<i64> {
+    ensure_writable(&window)?;
+    let board = get_board(state)?.clone();
+    let tags = termination_tags(&window.app_handle(), &board, &clock_tags(&clock_state, &tags)?)?;
+    return Ok(lock(&db_state.db)?.save(&board, &tags)?);
+}
+
+// async so a large file is read off the main thread, the file is streamed one game at a time
+#[tauri::command]
+async fn import_pgn(path: String, db_state: State<'_, GameDbState>, window: Window) -> CommandResult<ImportSummary> {
+    ensure_writable(&window)?;
+    let file = File::open(&path).map_err(anyhow::Error::from)?;
+
+    return Ok(lock(&db_state.db)?.import_pgn(BufReader::new(file))?);
+}
+
+// a file saved from the lichess api with the ndjson content type, pgn exports of both sites go through import_pgn
+#[tauri::command]
+async fn import_lichess_games(path: String, db_state: State<'_, GameDbState>, window: Window) -> CommandResult<ImportSummary> {
+    ensure_writable(&window)?;
+    let file = File::open(&path).map_err(anyhow::Error::from)?;
+
+    return Ok(lock(&db_state.db)?.import_lichess_ndjson(BufReader::new(file))?);
+}
+
+#[tauri::command]
+fn list_games(filter: GameFilter, db_state: State<GameDbState>) -> CommandResult<Vec<GameSummary>> {
+    return Ok(lock(&db_state.db)?.list(&filter)?);
+}
+
+#[tauri::command]
+fn load_game(id: i64, window: Window, state: State<BoardState>, engine_state: State<EngineState>, db_state: State<GameDbState>) -> CommandResult {
+    let record = lock(&db_state.db)?.get(id)?.ok_or_else(|| anyhow::anyhow!("Unknown game {id}"))?;
+    let game = pgn::read_pgn(&record.pgn).map_err(anyhow::Error::from)?;
+    let clock = ClockSettings::from_pgn_tags(&game.tags);
+    let app = window.app_handle();
+    ensure_action(&app, Action::NewGame)?;
+    let tree = state.tree.clone();
+
+    mutate_board(window, state, |board| {
+        *board = game.board;
+        *lock(&tree)? = game.tree;
+        logging::start_game("saved game", &board.to_fen());
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    restart_phase(&app)?;
+    set_clock(&app, clock)?;
+
+    return Ok(());
+}
+
+// a game counts once it is decided on the board, the player resigns or the engine resigned or agreed to a draw,
+// against the engine's current depth
+#[tauri::command]
+fn record_rated_game(
+    player: Color,
+    resigned: bool,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    rating_state: State<RatingState>,
+    conduct_state: State<ConductState>,
+    window: Window,
+) -> CommandResult<PlayerStats> {
+    ensure_writable(&window)?;
+
+    if !is_rated(&window.app_handle())? {
+        return Err(anyhow::Error::from(RatingError::CasualGame).into());
+    }
+
+    let board = get_board(state)?.clone();
+    let settings = lock(&engine_state.settings)?.clone();
+    let depth = settings.depth;
+    let conclusion = game_conduct(&conduct_state, settings.conduct)?.conclusion;
+
+    let draw = match is_armageddon(&window.app_handle())? {
+        true if player == Color::Black => 1.0,
+        true => 0.0,
+        false => 0.5,
+    };
+
+    let score = match board.winner() {
+        _ if resigned => 0.0,
+        _ if conclusion == Some(Conclusion::DrawAgreed) => draw,
+        _ if conclusion == Some(Conclusion::Resigned(player.invert())) => 1.0,
+        _ if conclusion == Some(Conclusion::Flagged(player.invert())) => 1.0,
+        _ if conclusion == Some(Conclusion::Flagged(player)) => 0.0,
+        _ if conclusion == Some(Conclusion::Forfeited(player.invert())) => 1.0,
+        _ if conclusion == Some(Conclusion::Forfeited(player)) => 0.0,
+        Some(winner) if winner == player => 1.0,
+        Some(_) => 0.0,
+        None if chess::get_moves(board.turn(), &board).is_empty() => draw,
+        None => return Err(anyhow::anyhow!("The game is not finished yet").into()),
+    };
+
+    let mut stats = lock(&rating_state.stats)?;
+    stats.record(depth, score);
+    stats.save(&rating_state.path)?;
+
+    return Ok(stats.clone());
+}
+
+// the game is searched off the main thread, its mistakes are added to the ones still to be trained
+#[tauri::command]
+async fn review_game_mistakes(
+    id: i64,
+    player: Color,
+    depth: Option<u8>,
+    engine_state: State<'_, EngineState>,
+    db_state: State<'_, GameDbState>,
+    mistake_state: State<'_, MistakeState>,
+    window: Window,
+) -> CommandResult<Vec<MoveReview>> {
+    ensure_writable(&window)?;
+    let record = lock(&db_state.db)?.get(id)?.ok_or_else(|| anyhow::anyhow!("Unknown game {id}"))?;
+    let board = pgn::read_pgn(&record.pgn).map_err(anyhow::Error::from)?.board;
+    let settings = lock(&engine_state.settings)?.clone();
+    let depth = depth.unwrap_or(settings.depth);
+
+    let (reviews, puzzles) = tauri::async_runtime::spawn_blocking(move || {
+        let mut engine = Engine::new();
+        settings.options.apply(&mut engine);
+
+        let reviews = review::review_game(&engine, &board, depth);
+        let puzzles = review::mistake_puzzles(&board, &reviews, player);
+
+        return (reviews, puzzles);
+    })
+    .await
+    .map_err(anyhow::Error::from)?;
+
+    let mut store = lock(&mistake_state.store)?;
+    store.add(id, puzzles);
+    store.save(&mistake_state.path)?;
+
+    return Ok(reviews);
+}
+
+#[tauri::command]
+fn get_mistakes(mistake_state: State<MistakeState>) -> CommandResult<Vec<StoredMistake>> {
+    return Ok(lock(&mistake_state.store)?.mistakes.clone());
+}
+
+// without an index the mistake that is furthest from being learned is set up
+#[tauri::command]
+fn start_mistake_puzzle(
+    index: Option<usize>,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    mistake_state: State<MistakeState>,
+) -> CommandResult<MistakeTask> {
+    let store = lock(&mistake_state.store)?;
+    let index = index.or_else(|| store.next()).ok_or_else(|| anyhow::anyhow!("There are no mistakes left to train"))?;
+    let mistake = store.mistakes.get(index).ok_or_else(|| anyhow::anyhow!("Unknown mistake {index}"))?.clone();
+    drop(store);
+
+    mutate_board(window, state, |board| {
+        board.set_variant(Variant::Standard);
+        board.apply_fen(&mistake.puzzle.fen)?;
+        logging::start_game("mistake review", &mistake.puzzle.fen);
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    return Ok(MistakeTask { index, mistake });
+}
+
+// the move is only judged, not played, so the position can be tried again
+#[tauri::command]
+async fn attempt_mistake_puzzle(
+    index: usize,
+    mv: Move,
+    engine_state: State<'_, EngineState>,
+    mistake_state: State<'_, MistakeState>,
+    window: Window,
+) -> CommandResult<MistakeAttempt> {
+    ensure_writable(&window)?;
+    let puzzle = lock(&mistake_state.store)?.mistakes.get(index).ok_or_else(|| anyhow::anyhow!("Unknown mistake {index}"))?.puzzle.clone();
+    let settings = lock(&engine_state.settings)?.clone();
+    let best = puzzle.best;
+
+    let correct = tauri::async_runtime::spawn_blocking(move || {
+        let mut engine = Engine::new();
+        settings.options.apply(&mut engine);
+
+        return review::judge_attempt(&engine, &puzzle, &mv, settings.depth);
+    })
+    .await
+    .map_err(anyhow::Error::from)?
+    .map_err(anyhow::Error::from)?;
+
+    let mut store = lock(&mistake_state.store)?;
+    let mistake = store.record(index, correct).ok_or_else(|| anyhow::anyhow!("Unknown mistake {index}"))?.clone();
+    store.save(&mistake_state.path)?;
+
+    return Ok(MistakeAttempt { correct, best, mistake });
+}
+
+// the moves of the other side are played right away, the board stops before the player's first move
+#[tauri::command]
+fn start_guess_the_move(
+    id: i64,
+    player: Color,
+    window: Window,
+    state: State<BoardState>,
+    engine_state: State<EngineState>,
+    db_state: State<GameDbState>,
+    guess_state: State<GuessState>,
+) -> CommandResult<GuessStatus> {
+    let record = lock(&db_state.db)?.get(id)?.ok_or_else(|| anyhow::anyhow!("Unknown game {id}"))?;
+    let board = pgn::read_pgn(&record.pgn).map_err(anyhow::Error::from)?.board;
+    let mut game = GuessGame::new(&board, player);
+    let moves = game.advance();
+    let tree = state.tree.clone();
+
+    mutate_board(window, state, |board| {
+        *board = game.start().clone();
+        *lock(&tree)? = MoveTree::new(board);
+        logging::start_game("guess the move", &board.to_fen());
+
+        for mv in &moves {
+            board.exec_move(mv)?;
+        }
+
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    let status = game.status();
+    *lock(&guess_state.game)? = Some(game);
+
+    return Ok(status);
+}
+
+// the guess is scored off the main thread, then the move of the game is played whether the guess was right or not
+#[tauri::command]
+async fn guess_move(
+    mv: Move,
+    window: Window,
+    state: State<'_, BoardState>,
+    engine_state: State<'_, EngineState>,
+    guess_state: State<'_, GuessState>,
+) -> CommandResult<GuessScore> {
+    ensure_writable(&window)?;
+
+    let actual = lock(&guess_state.game)?
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("No game is being guessed"))?
+        .expected()
+        .ok_or_else(|| anyhow::anyhow!("There are no moves left to guess"))?;
+    let board = get_board(state.clone())?.clone();
+    let settings = lock(&engine_state.settings)?.clone();
+
+    let guess = tauri::async_runtime::spawn_blocking(move || {
+        let mut engine = Engine::new();
+        settings.options.apply(&mut engine);
+
+        return review::score_guess(&engine, &board, &mv, &actual, settings.depth);
+    })
+    .await
+    .map_err(anyhow::Error::from)?
+    .map_err(anyhow::Error::from)?;
+
+    let (moves, status) = {
+        let mut game = lock(&guess_state.game)?;
+        let game = game.as_mut().ok_or_else(|| anyhow::anyhow!("No game is being guessed"))?;
+        game.record(guess.points);
+
+        (game.advance(), game.status())
+    };
+
+    mutate_board(window.clone(), state, |board| {
+        for mv in std::iter::once(&actual).chain(&moves) {
+            board.exec_move(mv)?;
+        }
+
+        stop_missed_ponder(&engine_state, board)?;
+
+        return Ok(());
+    })?;
+
+    events::emit(&window.app_handle(), AppEvent::GuessScored { guess, actual, status }).map_err(anyhow::Error::from)?;
+
+    return Ok(guess);
+}
+
+// the engine plays its moves on its own until the simul is over or stopped, a running simul is replaced
+#[tauri::command]
+fn start_simul(settings: SimulSettings, window: Window, simul_state: State<SimulState>) -> CommandResult<SimulProgress> {
+    ensure_writable(&window)?;
+
+    let simul = Simul::new(settings).map_err(anyhow::Error::from)?;
+    let progress = simul.progress();
+    *lock(&simul_state.simul)? = Some(simul);
+
+    let stop = Arc::new(AtomicBool::new(false));
+
+    if let Some(previous) = lock(&simul_state.stop)?.replace(stop.clone()) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    let app = window.app_handle();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(err) = run_simul(app.clone(), stop) {
+            let _ = events::emit(&app, AppEvent::Error { message: i18n::error_message(err.as_ref()) });
+        }
+    });
+
+    return Ok(progress);
+}
+
+#[tauri::command]
+fn play_simul_move(index: usize, mv: Move, window: Window, simul_state: State<SimulState>) -> CommandResult<SimulProgress> {
+    ensure_writable(&window)?;
+    ensure_not_repeated(&window, "play_simul_move", &format!("{index} {mv}"))?;
+
+    let progress = {
+        let mut simul = lock(&simul_state.simul)?;
+        let simul = simul.as_mut().ok_or_else(|| anyhow::anyhow!("No simul is being played"))?;
+        simul.play_user_move(index, &mv).map_err(anyhow::Error::from)?;
+
+        simul.progress()
+    };
+
+    events::emit(&window.app_handle(), AppEvent::SimulUpdated(progress.clone())).map_err(anyhow::Error::from)?;
+
+    return Ok(progress);
+}
+
+#[tauri::command]
+fn get_simul_progress(simul_state: State<SimulState>) -> CommandResult<Option<SimulProgress>> {
+    return Ok(lock(&simul_state.simul)?.as_ref().map(Simul::progress));
+}
+
+#[tauri::command]
+fn stop_simul(window: Window, simul_state: State<SimulState>) -> CommandResult {
+    ensure_writable(&window)?;
+
+    if let Some(stop) = lock(&simul_state.stop)?.take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+
+    lock(&simul_state.simul)?.take();
+
+    return Ok(());
+}
+
+// every game of a match is a new game against the same opponent, the board turns to the player's side
+fn start_match_game(play: &Match, window: &Window) -> CommandResult {
+    let app = window.app_handle();
+    let settings = play.settings();
+
+    new_game(settings.variant, None, settings.clock, settings.bot.clone(), None, None, window.clone(), app.state(), app.state(), app.state())?;
+
+    let state = app.state::<BoardState>();
+    let view = {
+        let mut view = lock(&state.view)?;
+        view.orientation = play.player_color();
+        *view
+    };
+
+    events::emit(&app, AppEvent::BoardUpdated(BoardPayload::new(&*get_board(state)?, view))).map_err(anyhow::Error::from)?;
+    *lock(&app.state::<MatchState>().game)? = Some(logging::current_game());
+
+    return Ok(());
+}
+
+// the first game starts right away, without a bot the engine plays with its current settings
+#[tauri::command]
+fn start_match(settings: MatchSettings, window: Window, engine_state: State<EngineState>, bot_state: State<BotState>, match_state: State<MatchState>) -> CommandResult<MatchStatus> {
+    let opponent = match &settings.bot {
+        Some(id) => bot_state.registry.list().into_iter().find(|info| info.id == *id).map_or_else(|| id.clone(), |info| info.name),
+        None => format!("Engine depth {}", lock(&engine_state.settings)?.depth),
+    };
+
+    let play = Match::new(settings, &opponent).map_err(anyhow::Error::from)?;
+    start_match_game(&play, &window)?;
+
+    let status = play.status();
+    *lock(&match_state.current)? = Some(play);
+
+    events::emit(&window.app_handle(), AppEvent::MatchUpdated(Some(status.clone()))).map_err(anyhow::Error::from)?;
+
+    return Ok(status);
+}
+
+// the result of the current game counts for the match, the next game starts unless the match is decided
+#[tauri::command]
+fn record_match_game(window: Window, state: State<BoardState>, clock_state: State<ClockState>, match_state: State<MatchState>) -> CommandResult<MatchStatus> {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+
+    if *lock(&match_state.game)? != Some(logging::current_game()) {
+        return Err(anyhow::anyhow!("The current game is not part of a match").into());
+    }
+
+    let status = {
+        let mut current = lock(&match_state.current)?;
+        let play = current.as_mut().ok_or_else(|| anyhow::anyhow!("No match is being played"))?;
+
+        let board = get_board(state.clone())?.clone();
+        let tags = termination_tags(&app, &board, &clock_tags(&clock_state, &play.tags())?)?;
+        let pgn = pgn::write_tree_pgn(&*lock(&state.tree)?, &tags);
+
+        play.record(pgn::scored_result(&board, &tags), pgn).map_err(anyhow::Error::from)?;
+        lock(&match_state.game)?.take();
+
+        if !play.finished() {
+            start_match_game(play, &window)?;
+        }
+
+        play.status()
+    };
+
+    events::emit(&app, AppEvent::MatchUpdated(Some(status.clone()))).map_err(anyhow::Error::from)?;
+
+    return Ok(status);
+}
+
+#[tauri::command]
+fn get_match(match_state: State<MatchState>) -> CommandResult<Option<MatchStatus>> {
+    return Ok(lock(&match_state.current)?.as_ref().map(Match::status));
+}
+
+#[tauri::command]
+fn export_match(path: &str, match_state: State<MatchState>) -> CommandResult {
+    let current = lock(&match_state.current)?;
+    let play = current.as_ref().ok_or_else(|| anyhow::anyhow!("No match is being played"))?;
+
+    fs::write(path, play.export_pgn()).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+// the game on the board stays, it just no longer counts for a match
+#[tauri::command]
+fn end_match(window: Window, match_state: State<MatchState>) -> CommandResult {
+    ensure_writable(&window)?;
+
+    lock(&match_state.current)?.take();
+    lock(&match_state.game)?.take();
+
+    events::emit(&window.app_handle(), AppEvent::MatchUpdated(None)).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+fn tournament_updated(app: &AppHandle, tournament: &HotseatTournament) -> Result<TournamentStatus> {
+    let status = tournament.status();
+    events::emit(app, AppEvent::TournamentUpdated(Some(status.clone())))?;
+
+    return Ok(status);
+}
+
+// the first round is paired right away
+#[tauri::command]
+fn create_tournament(settings: TournamentSettings, window: Window, tournament_state: State<TournamentState>) -> CommandResult<TournamentStatus> {
+    ensure_writable(&window)?;
+
+    let mut tournament = HotseatTournament::new(settings).map_err(anyhow::Error::from)?;
+    tournament.next_round().map_err(anyhow::Error::from)?;
+
+    let status = tournament_updated(&window.app_handle(), &tournament)?;
+    *lock(&tournament_state.current)? = Some(tournament);
+    lock(&tournament_state.game)?.take();
+
+    return Ok(status);
+}
+
+#[tauri::command]
+fn next_tournament_round(window: Window, tournament_state: State<TournamentState>) -> CommandResult<TournamentStatus> {
+    ensure_writable(&window)?;
+
+    let mut current = lock(&tournament_state.current)?;
+    let tournament = current.as_mut().ok_or_else(|| anyhow::anyhow!("No tournament is being played"))?;
+
+    tournament.next_round().map_err(anyhow::Error::from)?;
+    lock(&tournament_state.game)?.take();
+
+    return Ok(tournament_updated(&window.app_handle(), tournament)?);
+}
+
+// both players sit at the same board, so it is shown from white's side
+#[tauri::command]
+fn play_tournament_game(pairing: usize, window: Window, tournament_state: State<TournamentState>) -> CommandResult {
+    let app = window.app_handle();
+    let settings = {
+        let current = lock(&tournament_state.current)?;
+        let tournament = current.as_ref().ok_or_else(|| anyhow::anyhow!("No tournament is being played"))?;
+        tournament.pairing(pairing).map_err(anyhow::Error::from)?;
+
+        tournament.settings().clone()
+    };
+
+    new_game(settings.variant, None, settings.clock, None, None, None, window.clone(), app.state(), app.state(), app.state())?;
+
+    let state = app.state::<BoardState>();
+    let view = {
+        let mut view = lock(&state.view)?;
+        view.orientation = Color::White;
+        *view
+    };
+
+    events::emit(&app, AppEvent::BoardUpdated(BoardPayload::new(&*get_board(state)?, view))).map_err(anyhow::Error::from)?;
+    *lock(&tournament_state.game)? = Some((logging::current_game(), pairing));
+
+    return Ok(());
+}
+
+// the result of the game on the board goes to the pairing it was started for
+#[tauri::command]
+fn record_tournament_game(window: Window, state: State<BoardState>, clock_state: State<ClockState>, tournament_state: State<TournamentState>) -> CommandResult<TournamentStatus> {
+    ensure_writable(&window)?;
+    let app = window.app_handle();
+
+    let pairing = match *lock(&tournament_state.game)? {
+        Some((game, pairing)) if game == logging::current_game() => pairing,
+        _ => return Err(anyhow::anyhow!("The current game is not part of the tournament").into()),
+    };
+
+    let board = get_board(state)?.clone();
+    let tags = termination_tags(&app, &board, &clock_tags(&clock_state, &[])?)?;
+
+    let mut current = lock(&tournament_state.current)?;
+    let tournament = current.as_mut().ok_or_else(|| anyhow::anyhow!("No tournament is being played"))?;
+
+    match pgn::scored_result(&board, &tags) {
+        "*" => return Err(anyhow::anyhow!("The game is not finished yet").into()),
+        result => tournament.record(pairing, result).map_err(anyhow::Error::from)?,
+    }
+
+    lock(&tournament_state.game)?.take();
+
+    return Ok(tournament_updated(&app, tournament)?);
+}
+
+// for games played elsewhere or decided without a game, like a forfeit
+#[tauri::command]
+fn set_tournament_result(pairing: usize, result: &str, window: Window, tournament_state: State<TournamentState>) -> CommandResult<TournamentStatus> {
+    ensure_writable(&window)?;
+
+    let mut current = lock(&tournament_state.current)?;
+    let tournament = current.as_mut().ok_or_else(|| anyhow::anyhow!("No tournament is being played"))?;
+
+    tournament.record(pairing, result).map_err(anyhow::Error::from)?;
+
+    return Ok(tournament_updated(&window.app_handle(), tournament)?);
+}
+
+#[tauri::command]
+fn get_tournament(tournament_state: State<TournamentState>) -> CommandResult<Option<TournamentStatus>> {
+    return Ok(lock(&tournament_state.current)?.as_ref().map(HotseatTournament::status));
+}
+
+#[tauri::command]
+fn export_cross_table(path: &str, tournament_state: State<TournamentState>) -> CommandResult {
+    let current = lock(&tournament_state.current)?;
+    let tournament = current.as_ref().ok_or_else(|| anyhow::anyhow!("No tournament is being played"))?;
+
+    fs::write(path, tournament.cross_table()).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+#[tauri::command]
+fn end_tournament(window: Window, tournament_state: State<TournamentState>) -> CommandResult {
+    ensure_writable(&window)?;
+
+    lock(&tournament_state.current)?.take();
+    lock(&tournament_state.game)?.take();
+
+    events::emit(&window.app_handle(), AppEvent::TournamentUpdated(None)).map_err(anyhow::Error::from)?;
+
+    return Ok(());
+}
+
+// release builds embed the frontend, a debug build is served by the dev server and takes every set as shipped
+fn available_piece_sets(app: &AppHandle) -> Vec<PieceSet> {
+    let resolver = app.asset_resolver();
+
+    return settings::PIECE_SETS
+        .into_iter()
+        .filter(|set| cfg!(debug_assertions) || set.files().into_iter().all(|file| resolver.get(file).is_some()))
+        .collect();
+}
+
+#[tauri::command]
+fn list_themes(window: Window, appearance_state: State<AppearanceState>) -> CommandResult<ThemeCatalog> {
+    return Ok(ThemeCatalog {
+        themes: settings::THEMES.to_vec(),
+        piece_sets: available_piece_sets(&window.app_handle()),
+        selected: lock(&appearance_state.appearance)?.clone(),
+    });
+}
+
+#[tauri::command]
+fn set_theme(theme: String, piece_set: String, window: Window, appearance_state: State<AppearanceState>) -> CommandResult<Appearance> {
+    let mut appearance = lock(&appearance_state.appearance)?;
+    appearance.select(&theme, &piece_set, &available_piece_sets(&window.app_handle())).map_err(anyhow::Error::from)?;
+    appearance.save(&appearance_state.path)?;
+
+    return Ok(appearance.clone());
+}
+
+#[tauri::command]
+fn get_move_preferences(preferences_state: State<PreferencesState>) -> CommandResult<MovePreferences> {
+    return Ok(*lock(&preferences_state.preferences)?);
+}
+
+#[tauri::command]
+fn set_move_preferences(preferences: MovePreferences, preferences_state: State<PreferencesState>) -> CommandResult<MovePreferences> {
+    let mut current = lock(&preferences_state.preferences)?;
+    *current = preferences;
+    current.save(&preferences_state.path)?;
+
+    return Ok(*current);
+}
+
+#[tauri::command]
+fn get_player_stats(rating_state: State<RatingState>) -> CommandResult<PlayerStats> {
+    return Ok(lock(&rating_state.stats)?.clone());
+}
+
+#[tauri::command]
+fn get_explorer_stats(fen: &str, db_state: State<GameDbState>, window: Window) -> CommandResult<ExplorerStats> {
+    ensure_casual(&window.app_handle(), Action::Hint)?;
+    let board = Board::from_fen(fen)?;
+    return Ok(lock(&db_state.db)?.explorer_stats(&board)?);
+}
+
+#[tauri::command]
+fn delete_game(id: i64, db_state: State<GameDbState>, window: Window) -> CommandResult<bool> {
+    ensure_writable(&window)?;
+    return Ok(lock(&db_state.db)?.delete(id)?);
+}
+
+// writes the book to the given path and returns the number of entries
+#[tauri::command]
+fn build_book(path: &str, filter: GameFilter, max_plies: usize, db_state: State<GameDbState>) -> CommandResult<usize> {
+    let book = lock(&db_state.db)?.build_book(&filter, max_plies)?;
+    fs::write(path, book.to_bytes()).map_err(anyhow::Error::from)?;
+
+    return Ok(book.entries().len());
+}
+
+#[tauri::command]
+fn get_engine_settings(engine_state: State<EngineState>) -> CommandResult<EngineSettings> {
+    return Ok(lock(&engine_state.settings)?.clone());
+}
+
+// the memory the engine takes with the current hash size and threads, without having to start it
+#[tauri::command]
+fn get_engine_memory(engine_state: State<EngineState>) -> CommandResult<MemoryUsage> {
+    let options = lock(&engine_state.settings)?.options.clone();
+    return Ok(engine::memory_usage(options.hash_mb, options.threads));
+}
+
+#[tauri::command]
+fn set_engine_settings(settings: EngineSettings, engine_state: State<EngineState>, window: Window) -> CommandResult {
+    ensure_writable(&window)?;
+    update_engine_settings(&engine_state, settings)?;
+    return Ok(());
+}
+
+#[tauri::command]
+fn set_engine_option(name: &str, value: &str, engine_state: State<EngineState>, window: Window) -> CommandResult<EngineSettings> {
+    ensure_writable(&window)?;
+    let mut settings = lock(&engine_state.settings)?.clone();
+    settings.options.set(name, value).map_err(anyhow::Error::from)?;
+
+    update_engine_settings(&engine_state, settings.clone())?;
+
+    return Ok(settings);
+}
+
+#[tauri::command]
+fn set_log_level(level: LogLevel, log_state: State<LogState>) -> CommandResult {
+    log_state.logging.set_level(level)?;
+    return Ok(());
+}
+
+fn update_engine_settings(engine_state: &EngineState, settings: EngineSettings) -> Result<()> {
+    if !settings.options.ponder {
+        let mut worker = lock(&engine_state.worker)?;
+        *worker = worker.take().map(|worker| EngineWorker::Idle(worker.into_engine()));
+    }
+
+    settings.save(&engine_state.settings_path)?;
+    *lock(&engine_state.settings)? = settings;
+
+    return Ok(());
+}
+
+// scripted opponents are registered here and can then be picked for a new game
+fn bot_registry() -> BotRegistry {
+    let mut registry = BotRegistry::new();
+
+    // the reference bots are the easiest opponents, each game gets a new seed
+    for kind in BotKind::ALL {
+        registry.register(kind.info(), Box::new(move || kind.create(correspondence::now_ms())));
+    }
+
+    return registry;
+}
+
+fn main() {
+    let board = Board::new_game();
+    let state = BoardState {
+        tree: Arc::new(Mutex::new(MoveTree::from_board(&board))),
+        phase: Mutex::new(Phase::start(&board)),
+        board: Arc::new(RwLock::new(board)),
+        view: Arc::new(Mutex::new(BoardView {
+            orientation: Color::White,
+            visibility: Visibility::Full,
+        })),
+    };
+    let auto_play_state = AutoPlayState { stop: Mutex::new(None) };
+    let playback_state = PlaybackState {
+        stop: Mutex::new(None),
+        delay_ms: Arc::new(AtomicU64::new(1000)),
+    };
+    let takeback_state = TakebackState { pending: Mutex::new(None) };
+    let conduct_state = ConductState {
+        game: Mutex::new(GameConduct::new(logging::current_game(), ConductSettings::default())),
+    };
+    let clock_state = ClockState { clock: Mutex::new(None) };
+    let arbiter_state = ArbiterState { arbiter: Mutex::new(None) };
+    let study_state = StudyState { open: Mutex::new(None) };
+    let guess_state = GuessState { game: Mutex::new(None) };
+    let match_state = MatchState {
+        current: Mutex::new(None),
+        game: Mutex::new(None),
+    };
+    let tournament_state = TournamentState {
+        current: Mutex::new(None),
+        game: Mutex::new(None),
+    };
+    let armageddon_state = ArmageddonState { game: Mutex::new(None) };
+    let fork_state = ForkState { original: Mutex::new(None) };
+    let simul_state = SimulState {
+        simul: Mutex::new(None),
+        stop: Mutex::new(None),
+    };
+    let chat_state = ChatState { chat: Mutex::new(Chat::new()) };
+    let guard_state = GuardState {
+        guard: Mutex::new(CommandGuard::new()),
+    };
+    let discovery_state = DiscoveryState { advertising: Mutex::new(None) };
+    let overlay_state = OverlayState {
+        stop: Mutex::new(None),
+        eval: Arc::new(Mutex::new(None)),
+    };
+    let bot_state = BotState {
+        registry: bot_registry(),
+        opponent: Mutex::new(None),
+    };
+    let session_state = SessionState {
+        sessions: Mutex::new(Sessions::new()),
+    };
+
+    tauri::Builder::default()
+        .manage(state)
+        .manage(auto_play_state)
+        .manage(playback_state)
+        .manage(takeback_state)
+        .manage(conduct_state)
+        .manage(session_state)
+        .manage(clock_state)
+        .manage(arbiter_state)
+        .manage(study_state)
+        .manage(guess_state)
+        .manage(match_state)
+        .manage(tournament_state)
+        .manage(armageddon_state)
+        .manage(fork_state)
+        .manage(simul_state)
+        .manage(chat_state)
+        .manage(guard_state)
+        .manage(discovery_state)
+        .manage(overlay_state)
+        .manage(bot_state)
+        .setup(|app| {
+            let dir = app.path_resolver().app_data_dir().ok_or_else(|| anyhow::anyhow!("No app data directory"))?;
+            fs::create_dir_all(&dir)?;
+
+            app.manage(LogState { logging: Logging::init(&dir)? });
+
+            let settings_path = dir.join("engine.json");
+            let settings = EngineSettings::load(&settings_path)?;
+            app.manage(EngineState {
+                settings_path,
+                settings: Mutex::new(settings),
+                worker: Mutex::new(None),
+                task: Mutex::new(None),
+            });
+
+            let db = GameDb::open(&dir.join("games.sqlite"))?;
+            app.manage(GameDbState { db: Mutex::new(db) });
+
+            let path = dir.join("rating.json");
+            let stats = PlayerStats::load(&path)?;
+            app.manage(RatingState {
+                path,
+                stats: Mutex::new(stats),
+                rated_game: Mutex::new(None),
+            });
+
+            let path = dir.join("locale.json");
+            i18n::set_locale(LocaleSettings::load(&path)?.locale);
+            app.manage(LocaleState { path });
+
+            let path = dir.join("appearance.json");
+            let appearance = Appearance::load(&path)?;
+            app.manage(AppearanceState {
+                path,
+                appearance: Mutex::new(appearance),
+            });
+
+            let path = dir.join("preferences.json");
+            let preferences = MovePreferences::load(&path)?;
+            app.manage(PreferencesState {
+                path,
+                preferences: Mutex::new(preferences),
+            });
+
+            let path = dir.join("drills.json");
+            let progress = DrillProgress::load(&path)?;
+            app.manage(DrillState {
+                path,
+                progress: Mutex::new(progress),
+                drill: Mutex::new(None),
+            });
+
+            let path = dir.join("mistakes.json");
+            let store = MistakeStore::load(&path)?;
+            app.manage(MistakeState { path, store: Mutex::new(store) });
+
+            // a running correspondence game is picked up where it was left
+            let path = dir.join("correspondence.json");
+            let game = CorrespondenceGame::load(&path)?;
+
+            if let Some(game) = &game {
+                *write(&app.state::<BoardState>().board)? = pgn::read_pgn(&game.pgn)?.board;
+            }
+
+            app.manage(CorrespondenceState { path, game: Mutex::new(game) });
+
+            let handle = app.handle();
+            thread::spawn(move || {
+                if let Err(err) = watch_correspondence_deadlines(handle.clone()) {
+                    tracing::error!(%err, "correspondence deadlines are no longer watched");
+                    let _ = events::emit(&handle, AppEvent::Error { message: err.to_string() });
+                }
+            });
+
+            return Ok(());
+        })
+        .invoke_handler(tauri::generate_handler![
+            get_board_cmd,
+            get_session_access,
+            open_read_only_session,
+            close_read_only_session,
+            flip_board,
+            set_visibility,
+            get_available_moves,
+            get_move_targets,
+            get_arbiter,
+            set_arbiter,
+            touch_piece,
+            exec_move,
+            try_move,
+            parse_move_input,
+            parse_spoken_move,
+            undo,
+            get_phase,
+            start_setup,
+            start_review,
+            resume_play,
+            cancel_promotion,
+            request_takeback,
+            accept_takeback,
+            decline_takeback,
+            claim_draw,
+            offer_draw,
+            accept_draw,
+            apply_fen,
+            copy_fen,
+            copy_pgn,
+            get_clock,
+            get_time_usage,
+            get_correspondence,
+            start_correspondence,
+            stop_correspondence,
+            paste_fen_or_pgn,
+            get_chat,
+            start_advertising,
+            stop_advertising,
+            start_overlay,
+            stop_overlay,
+            discover_games,
+            send_chat,
+            get_move_tree,
+            goto_move,
+            fork_game,
+            leave_fork,
+            enter_variation,
+            exit_variation,
+            delete_variation,
+            promote_variation,
+            set_move_comment,
+            set_move_nags,
+            get_study,
+            new_study,
+            open_study,
+            save_study,
+            add_study_chapter,
+            select_study_chapter,
+            remove_study_chapter,
+            close_study,
+            new_game,
+            list_positions,
+            list_bots,
+            get_bot,
+            get_locale,
+            set_locale,
+            load_position,
+            get_training_task,
+            check_training_answer,
+            start_endgame_training,
+            start_opening_drill,
+            play_drill_move,
+            get_drill_progress,
+            play_endgame_move,
+            engine_move,
+            cancel_engine_move,
+            start_auto_play,
+            stop_auto_play,
+            get_playback,
+            start_playback,
+            pause_playback,
+            set_playback_speed,
+            explain_eval,
+            export_animation,
+            export_search_tree,
+            save_game,
+            import_pgn,
+            import_lichess_games,
+            list_games,
+            load_game,
+            delete_game,
+            get_explorer_stats,
+            build_book,
+            record_rated_game,
+            get_player_stats,
+            list_themes,
+            set_theme,
+            get_move_preferences,
+            set_move_preferences,
+            start_guess_the_move,
+            guess_move,
+            start_match,
+            record_match_game,
+            get_match,
+            export_match,
+            end_match,
+            create_tournament,
+            next_tournament_round,
+            play_tournament_game,
+            record_tournament_game,
+            set_tournament_result,
+            get_tournament,
+            export_cross_table,
+            end_tournament,
+            start_simul,
+            play_simul_move,
+            get_simul_progress,
+            stop_simul,
+            review_game_mistakes,
+            get_mistakes,
+            start_mistake_puzzle,
+            attempt_mistake_puzzle,
+            get_engine_settings,
+            get_engine_memory,
+            set_engine_settings,
+            set_engine_option,
+            set_log_level
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,218 @@
+use chess::{clock::ClockSettings, Color, Variant};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum MatchError {
+    #[error("A match needs at least one game to win")]
+    EmptyMatch,
+
+    #[error("The match is already decided")]
+    Decided,
+
+    #[error("The game is not finished yet")]
+    Unfinished,
+}
+
+// the points are counted in full points, a draw is worth half a point to each side
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "games", rename_all = "camelCase")]
+pub enum MatchFormat {
+    BestOf(u32),
+    FirstTo(u32),
+}
+
+// without a bot the engine plays with its current settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchSettings {
+    pub format: MatchFormat,
+    pub first_color: Color,
+    pub variant: Variant,
+    pub clock: Option<ClockSettings>,
+    pub bot: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchGame {
+    pub round: usize,
+    pub player_color: Color,
+    pub result: String,
+    pub pgn: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchStatus {
+    pub settings: MatchSettings,
+    pub opponent: String,
+    pub games: Vec<MatchGame>,
+    pub player_score: f32,
+    pub opponent_score: f32,
+    pub player_color: Color,
+    pub finished: bool,
+}
+
+// the player's colour alternates from game to game, starting with the one chosen for the first game
+#[derive(Debug, Clone)]
+pub struct Match {
+    settings: MatchSettings,
+    opponent: String,
+    games: Vec<MatchGame>,
+}
+
+impl Match {
+    pub fn new(settings: MatchSettings, opponent: &str) -> Result<Self, MatchError> {
+        if matches!(settings.format, MatchFormat::BestOf(0) | MatchFormat::FirstTo(0)) {
+            return Err(MatchError::EmptyMatch);
+        }
+
+        return Ok(Match {
+            settings,
+            opponent: opponent.to_string(),
+            games: Vec::new(),
+        });
+    }
+
+    pub fn settings(&self) -> &MatchSettings {
+        return &self.settings;
+    }
+
+    pub fn round(&self) -> usize {
+        return self.games.len() + 1;
+    }
+
+    pub fn player_color(&self) -> Color {
+        return match self.games.len() % 2 {
+            0 => self.settings.first_color,
+            _ => self.settings.first_color.invert(),
+        };
+    }
+
+    // the tags a game of the match is written with, the player is named after the side they play
+    pub fn tags(&self) -> Vec<(String, String)> {
+        let (white, black) = match self.player_color() {
+            Color::White => ("Player".to_string(), self.opponent.clone()),
+            Color::Black => (self.opponent.clone(), "Player".to_string()),
+        };
+
+        return vec![
+            ("Event".to_string(), format!("Match against {}", self.opponent)),
+            ("Round".to_string(), self.round().to_string()),
+            ("White".to_string(), white),
+            ("Black".to_string(), black),
+        ];
+    }
+
+    // the result is the one of the game as written in its pgn, from white's point of view
+    pub fn record(&mut self, result: &str, pgn: String) -> Result<(), MatchError> {
+        if self.finished() {
+            return Err(MatchError::Decided);
+        }
+
+        if !["1-0", "0-1", "1/2-1/2"].contains(&result) {
+            return Err(MatchError::Unfinished);
+        }
+
+        self.games.push(MatchGame {
+            round: self.round(),
+            player_color: self.player_color(),
+            result: result.to_string(),
+            pgn,
+        });
+
+        return Ok(());
+    }
+
+    // a best of n is over once the leader cannot be caught in the games that are left
+    pub fn finished(&self) -> bool {
+        let (player, opponent) = self.half_points();
+
+        return match self.settings.format {
+            MatchFormat::BestOf(games) => {
+                let left = 2 * (games as usize).saturating_sub(self.games.len());
+                self.games.len() >= games as usize || player > opponent + left || opponent > player + left
+            }
+            MatchFormat::FirstTo(points) => player.max(opponent) >= 2 * points as usize,
+        };
+    }
+
+    pub fn status(&self) -> MatchStatus {
+        let (player, opponent) = self.half_points();
+
+        return MatchStatus {
+            settings: self.settings.clone(),
+            opponent: self.opponent.clone(),
+            games: self.games.clone(),
+            player_score: player as f32 / 2.0,
+            opponent_score: opponent as f32 / 2.0,
+            player_color: self.player_color(),
+            finished: self.finished(),
+        };
+    }
+
+    // every game of the match one after the other, ready to be read back as a pgn file
+    pub fn export_pgn(&self) -> String {
+        return self.games.iter().map(|game| game.pgn.trim_end()).collect::<Vec<_>>().join("\n\n") + "\n";
+    }
+
+    fn half_points(&self) -> (usize, usize) {
+        return self.games.iter().fold((0, 0), |(player, opponent), game| match (game.result.as_str(), game.player_color) {
+            ("1-0", Color::White) | ("0-1", Color::Black) => (player + 2, opponent),
+            ("1-0", Color::Black) | ("0-1", Color::White) => (player, opponent + 2),
+            _ => (player + 1, opponent + 1),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(format: MatchFormat) -> MatchSettings {
+        return MatchSettings {
+            format,
+            first_color: Color::White,
+            variant: Variant::Standard,
+            clock: None,
+            bot: None,
+        };
+    }
+
+    #[test]
+    fn scores_best_of_matches() {
+        assert_eq!(Err(MatchError::EmptyMatch), Match::new(settings(MatchFormat::BestOf(0)), "Engine").map(|_| ()));
+
+        let mut play = Match::new(settings(MatchFormat::BestOf(3)), "Engine").unwrap();
+
+        assert_eq!(("Player", "Engine"), (play.tags()[2].1.as_str(), play.tags()[3].1.as_str()));
+        assert_eq!(Err(MatchError::Unfinished), play.record("*", String::new()));
+
+        play.record("1-0", String::new()).unwrap();
+
+        assert_eq!(Color::Black, play.player_color());
+        assert_eq!("2", play.tags()[1].1);
+
+        // a win with black decides the match before the third game
+        play.record("0-1", String::new()).unwrap();
+
+        let status = play.status();
+
+        assert_eq!((2.0, 0.0, true), (status.player_score, status.opponent_score, status.finished));
+        assert_eq!(Err(MatchError::Decided), play.record("1-0", String::new()));
+    }
+
+    #[test]
+    fn scores_first_to_matches() {
+        let mut play = Match::new(settings(MatchFormat::FirstTo(2)), "Greedy capturer").unwrap();
+
+        for result in ["1/2-1/2", "1-0", "1/2-1/2"] {
+            play.record(result, format!("[Result \"{result}\"]\n\n{result}\n")).unwrap();
+        }
+
+        let status = play.status();
+
+        assert_eq!((1.0, 2.0, true), (status.player_score, status.opponent_score, status.finished));
+        assert_eq!(3, play.export_pgn().matches("[Result").count());
+    }
+}
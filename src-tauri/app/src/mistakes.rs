@@ -0,0 +1,151 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use anyhow::Result;
+use chess::{review::MistakePuzzle, Move};
+use serde::{Deserialize, Serialize};
+
+// a puzzle counts as learned once it was solved this many times in a row
+const LEARNED_STREAK: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredMistake {
+    pub game: i64,
+    #[serde(flatten)]
+    pub puzzle: MistakePuzzle,
+    pub attempts: u32,
+    pub solved: u32,
+    pub streak: u32,
+}
+
+// the puzzle handed to the board, the index is the one to answer it with
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MistakeTask {
+    pub index: usize,
+    pub mistake: StoredMistake,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MistakeAttempt {
+    pub correct: bool,
+    pub best: Move,
+    pub mistake: StoredMistake,
+}
+
+impl StoredMistake {
+    pub fn learned(&self) -> bool {
+        return self.streak >= LEARNED_STREAK;
+    }
+}
+
+// the mistakes found in the player's own games, with how the player did on them since
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MistakeStore {
+    pub mistakes: Vec<StoredMistake>,
+}
+
+impl MistakeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        return match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::new()),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        return Ok(());
+    }
+
+    // reviewing a game again keeps the progress on the mistakes that were already known, returns how many are new
+    pub fn add(&mut self, game: i64, puzzles: Vec<MistakePuzzle>) -> usize {
+        let mut added = 0;
+
+        for puzzle in puzzles {
+            if self.mistakes.iter().any(|mistake| mistake.game == game && mistake.puzzle.ply == puzzle.ply) {
+                continue;
+            }
+
+            self.mistakes.push(StoredMistake {
+                game,
+                puzzle,
+                attempts: 0,
+                solved: 0,
+                streak: 0,
+            });
+            added += 1;
+        }
+
+        return added;
+    }
+
+    // the mistake that is furthest from being learned, the least tried first
+    pub fn next(&self) -> Option<usize> {
+        return self
+            .mistakes
+            .iter()
+            .enumerate()
+            .filter(|(_, mistake)| !mistake.learned())
+            .min_by_key(|(_, mistake)| (mistake.streak, mistake.attempts))
+            .map(|(index, _)| index);
+    }
+
+    pub fn record(&mut self, index: usize, solved: bool) -> Option<&StoredMistake> {
+        let mistake = self.mistakes.get_mut(index)?;
+
+        mistake.attempts += 1;
+
+        if solved {
+            mistake.solved += 1;
+            mistake.streak += 1;
+        } else {
+            mistake.streak = 0;
+        }
+
+        return Some(mistake);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::Board;
+
+    #[test]
+    fn tracks_progress() {
+        let board = Board::new_game();
+        let mv = |index: usize| chess::get_moves(board.turn(), &board)[index];
+        let puzzle = |index: usize| MistakePuzzle {
+            ply: index + 1,
+            fen: board.to_fen(),
+            played: mv(index),
+            best: mv(index + 1),
+            loss: 300,
+        };
+
+        let mut store = MistakeStore::new();
+
+        assert_eq!(2, store.add(1, vec![puzzle(0), puzzle(2)]));
+        assert_eq!(0, store.add(1, vec![puzzle(0)]));
+        assert_eq!(Some(0), store.next());
+
+        store.record(0, true);
+
+        assert_eq!(Some(1), store.next());
+
+        store.record(1, false);
+        store.record(0, true);
+
+        assert!(store.mistakes[0].learned());
+        assert_eq!(Some(1), store.next());
+        assert_eq!((1, 0, 0), (store.mistakes[1].attempts, store.mistakes[1].solved, store.mistakes[1].streak));
+    }
+}
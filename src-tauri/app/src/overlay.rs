@@ -0,0 +1,208 @@
+use std::{
+    fs,
+    io::{self, ErrorKind, Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+use chess::{
+    animation::{self, AnimationOptions},
+    pgn, Board, Color, Move,
+};
+use serde::{Deserialize, Serialize};
+
+// how often the position is looked at and waiting requests are answered
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+// the page a browser source in obs is pointed at, it reloads the image and the text on its own
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>body { margin: 0; background: transparent; color: white; font: 20px sans-serif; } img { display: block; }</style>
+</head>
+<body>
+<img id="board" src="/position.gif">
+<div id="eval"></div>
+<script>
+setInterval(async () => {
+    const overlay = await (await fetch('/overlay.json')).json();
+    document.getElementById('board').src = '/position.gif?' + encodeURIComponent(overlay.fen);
+    document.getElementById('eval').textContent = overlay.eval === null ? '' : (overlay.eval / 100).toFixed(2);
+}, 1000);
+</script>
+</body>
+</html>
+"#;
+
+// without a directory nothing is written, without a port nothing is served
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlaySettings {
+    pub directory: Option<PathBuf>,
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub image: AnimationOptions,
+}
+
+// the evaluation is in centipawns from white's side, as the last search of the game left it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayFrame {
+    pub fen: String,
+    pub turn: Color,
+    pub last_move: Option<Move>,
+    pub eval: Option<i32>,
+    pub result: &'static str,
+}
+
+impl OverlayFrame {
+    pub fn new(board: &Board, eval: Option<i32>) -> Self {
+        return OverlayFrame {
+            fen: board.to_fen(),
+            turn: board.turn(),
+            last_move: board.last_move(),
+            eval,
+            result: pgn::result(board),
+        };
+    }
+}
+
+// keeps the files and the endpoint up to date with what the position source returns, until stopped
+pub fn run<F>(settings: &OverlaySettings, stop: &AtomicBool, mut source: F) -> io::Result<()>
+where
+    F: FnMut() -> Option<(Board, Option<i32>)>,
+{
+    if let Some(directory) = &settings.directory {
+        fs::create_dir_all(directory)?;
+    }
+
+    let listener = match settings.port {
+        Some(port) => Some(TcpListener::bind((Ipv4Addr::LOCALHOST, port))?),
+        None => None,
+    };
+
+    if let Some(listener) = &listener {
+        listener.set_nonblocking(true)?;
+    }
+
+    let mut shown: Option<(OverlayFrame, Vec<u8>)> = None;
+
+    while !stop.load(Ordering::Relaxed) {
+        if let Some((board, eval)) = source() {
+            let frame = OverlayFrame::new(&board, eval);
+
+            if shown.as_ref().map(|(shown, _)| shown) != Some(&frame) {
+                let image = animation::position_gif(&board, &settings.image).map_err(|err| io::Error::new(ErrorKind::InvalidInput, err))?;
+
+                if let Some(directory) = &settings.directory {
+                    write_files(directory, &frame, &image)?;
+                }
+
+                shown = Some((frame, image));
+            }
+        }
+
+        if let (Some(listener), Some((frame, image))) = (&listener, &shown) {
+            serve_waiting(listener, frame, image)?;
+        }
+
+        thread::sleep(REFRESH_INTERVAL);
+    }
+
+    return Ok(());
+}
+
+// the files are replaced one by one, obs picks up each of them when it changes
+fn write_files(directory: &PathBuf, frame: &OverlayFrame, image: &[u8]) -> io::Result<()> {
+    fs::write(directory.join("position.gif"), image)?;
+    fs::write(directory.join("position.fen"), &frame.fen)?;
+    fs::write(directory.join("eval.txt"), frame.eval.map(format_eval).unwrap_or_default())?;
+    fs::write(directory.join("overlay.json"), serde_json::to_string(frame)?)?;
+
+    return Ok(());
+}
+
+fn serve_waiting(listener: &TcpListener, frame: &OverlayFrame, image: &[u8]) -> io::Result<()> {
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                // a client that goes away in the middle of a request does not stop the overlay
+                if let Err(err) = respond(stream, frame, image) {
+                    tracing::debug!(%err, "overlay request failed");
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::WouldBlock => return Ok(()),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, frame: &OverlayFrame, image: &[u8]) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut buffer = [0; 1024];
+    let length = stream.read(&mut buffer)?;
+    let request = String::from_utf8_lossy(&buffer[..length]);
+
+    let (status, content_type, body) = route(&request, frame, image);
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+
+    return Ok(());
+}
+
+// only the path of the request line counts, a query string is there to get past caches
+fn route(request: &str, frame: &OverlayFrame, image: &[u8]) -> (&'static str, &'static str, Vec<u8>) {
+    let path = request.lines().next().and_then(|line| line.strip_prefix("GET ")).and_then(|rest| rest.split([' ', '?']).next());
+
+    return match path {
+        Some("/") => ("200 OK", "text/html; charset=utf-8", PAGE.as_bytes().to_vec()),
+        Some("/position.gif") => ("200 OK", "image/gif", image.to_vec()),
+        Some("/fen") => ("200 OK", "text/plain; charset=utf-8", frame.fen.clone().into_bytes()),
+        Some("/eval") => ("200 OK", "text/plain; charset=utf-8", frame.eval.map(format_eval).unwrap_or_default().into_bytes()),
+        Some("/overlay.json") => ("200 OK", "application/json", serde_json::to_vec(frame).unwrap_or_default()),
+        _ => ("404 Not Found", "text/plain; charset=utf-8", b"not found".to_vec()),
+    };
+}
+
+// in pawns with the sign of the side that is better, like +1.25
+fn format_eval(eval: i32) -> String {
+    return format!("{:+.2}", eval as f32 / 100.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_overlay_requests() {
+        let frame = OverlayFrame::new(&Board::new_game(), Some(35));
+        let image = [b'G', b'I', b'F'];
+
+        assert_eq!("+0.35", format_eval(35));
+        assert_eq!("-2.00", format_eval(-200));
+
+        let (status, content_type, body) = route("GET /position.gif?abc HTTP/1.1\r\nHost: localhost\r\n\r\n", &frame, &image);
+        assert_eq!(("200 OK", "image/gif", image.to_vec()), (status, content_type, body));
+
+        let (_, _, body) = route("GET /fen HTTP/1.1\r\n\r\n", &frame, &image);
+        assert_eq!(frame.fen.as_bytes(), body.as_slice());
+
+        let (_, _, body) = route("GET /overlay.json HTTP/1.1\r\n\r\n", &frame, &image);
+        assert!(String::from_utf8(body).unwrap().contains("\"eval\":35"));
+
+        assert_eq!("404 Not Found", route("GET /secret HTTP/1.1\r\n\r\n", &frame, &image).0);
+        assert_eq!("404 Not Found", route("POST / HTTP/1.1\r\n\r\n", &frame, &image).0);
+    }
+}
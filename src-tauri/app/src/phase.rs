@@ -0,0 +1,160 @@
+use chess::{Board, Coord};
+use serde::Serialize;
+
+// what the app is doing with the board, every command that changes it names its action and is checked against this
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "phase", rename_all = "camelCase")]
+pub enum Phase {
+    Setup,
+    Playing,
+    AwaitingPromotion { from: Coord, to: Coord },
+    GameOver,
+    Reviewing,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+    Move,
+    Promote,
+    Undo,
+    Navigate,
+    EditPosition,
+    EngineMove,
+    NewGame,
+    Review,
+    Hint,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PhaseError {
+    #[error("Cannot {} while {}", .action.describe(), .phase.describe())]
+    NotAllowed { phase: Phase, action: Action },
+}
+
+impl Phase {
+    // the phase a game starts in with this position, a position without moves left is already over
+    pub fn start(board: &Board) -> Self {
+        if board.winner().is_some() || chess::get_moves(board.turn(), board).is_empty() {
+            return Phase::GameOver;
+        }
+
+        return Phase::Playing;
+    }
+
+    // a game that was concluded off the board, like by resignation, stays over whatever position is shown
+    pub fn resume(board: &Board, concluded: bool) -> Self {
+        if concluded {
+            return Phase::GameOver;
+        }
+
+        return Phase::start(board);
+    }
+
+    // after the board changed, setting up and reviewing last until they are left explicitly
+    pub fn settle(self, board: &Board, concluded: bool) -> Self {
+        return match self {
+            Phase::Setup | Phase::Reviewing => self,
+            _ => Phase::resume(board, concluded),
+        };
+    }
+
+    pub fn allows(&self, action: Action) -> bool {
+        return match self {
+            Phase::Setup => matches!(action, Action::EditPosition | Action::NewGame),
+            Phase::Playing => action != Action::Promote,
+            Phase::AwaitingPromotion { .. } => matches!(action, Action::Promote | Action::NewGame),
+            Phase::GameOver => !matches!(action, Action::Move | Action::Promote | Action::EngineMove),
+            Phase::Reviewing => !matches!(action, Action::Promote | Action::EngineMove),
+        };
+    }
+
+    pub fn check(&self, action: Action) -> Result<(), PhaseError> {
+        if !self.allows(action) {
+            return Err(PhaseError::NotAllowed { phase: *self, action });
+        }
+
+        return Ok(());
+    }
+
+    pub fn describe(&self) -> &'static str {
+        return match self {
+            Phase::Setup => "setting up a position",
+            Phase::Playing => "playing",
+            Phase::AwaitingPromotion { .. } => "waiting for the promotion piece",
+            Phase::GameOver => "the game is over",
+            Phase::Reviewing => "reviewing the game",
+        };
+    }
+}
+
+impl Action {
+    pub fn describe(&self) -> &'static str {
+        return match self {
+            Action::Move => "move",
+            Action::Promote => "choose a promotion piece",
+            Action::Undo => "take back moves",
+            Action::Navigate => "go to another move",
+            Action::EditPosition => "change the position",
+            Action::EngineMove => "let the engine move",
+            Action::NewGame => "start a new game",
+            Action::Review => "review the game",
+            Action::Hint => "ask for a hint",
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chess::pgn::read_pgn;
+
+    #[test]
+    fn checks_actions_against_the_phase() {
+        let mated = read_pgn("1. f3 e5 2. g4 Qh4#").unwrap().board;
+        let board = Board::new_game();
+
+        assert_eq!(Phase::Playing, Phase::start(&board));
+        assert_eq!(Phase::GameOver, Phase::start(&mated));
+        assert_eq!(Phase::GameOver, Phase::Playing.settle(&mated, false));
+        assert_eq!(Phase::Reviewing, Phase::Reviewing.settle(&mated, false));
+        assert_eq!(Phase::Playing, Phase::GameOver.settle(&board, false));
+
+        assert_eq!(
+            Err(PhaseError::NotAllowed { phase: Phase::GameOver, action: Action::Move }),
+            Phase::GameOver.check(Action::Move)
+        );
+        assert_eq!(Ok(()), Phase::GameOver.check(Action::Undo));
+        assert_eq!(Ok(()), Phase::Setup.check(Action::EditPosition));
+        assert!(!Phase::Setup.allows(Action::Move));
+
+        let promotion = Phase::AwaitingPromotion {
+            from: Coord::new('e', 7),
+            to: Coord::new('e', 8),
+        };
+
+        assert!(promotion.allows(Action::Promote));
+        assert!(!promotion.allows(Action::Undo));
+        assert!(!Phase::Playing.allows(Action::Promote));
+    }
+
+    #[test]
+    fn a_concluded_game_stays_over() {
+        let mut board = read_pgn("1. e4 e5 2. Nf3").unwrap().board;
+
+        // resigned in the position on the board, then going back a move
+        let resigned = Phase::Playing.settle(&board, true);
+        board.undo_move().unwrap();
+        let navigated = resigned.settle(&board, true);
+
+        assert_eq!(Phase::GameOver, navigated);
+        assert!(navigated.allows(Action::Navigate));
+        assert_eq!(
+            Err(PhaseError::NotAllowed { phase: Phase::GameOver, action: Action::Move }),
+            navigated.check(Action::Move)
+        );
+        assert!(!navigated.allows(Action::EngineMove));
+
+        assert_eq!(Phase::GameOver, Phase::resume(&board, true));
+        assert_eq!(Phase::Reviewing, Phase::Reviewing.settle(&board, true));
+    }
+}
@@ -0,0 +1,147 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::phase::Action;
+
+const INITIAL_RATING: f64 = 1200.0;
+const PROVISIONAL_GAMES: u32 = 20;
+const PROVISIONAL_K_FACTOR: f64 = 40.0;
+const K_FACTOR: f64 = 20.0;
+
+// approximate strength of the built-in engine at each search depth
+const ENGINE_RATINGS: [f64; 8] = [800.0, 1100.0, 1400.0, 1650.0, 1850.0, 2000.0, 2150.0, 2300.0];
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum RatingError {
+    #[error("Cannot {} in a rated game", .0.describe())]
+    RatedGame(Action),
+
+    #[error("Only rated games count for the rating")]
+    CasualGame,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatedGame {
+    pub engine_depth: u8,
+    pub engine_rating: f64,
+    pub score: f64,
+    pub rating_after: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerStats {
+    pub rating: f64,
+    pub peak_rating: f64,
+    pub games: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub history: Vec<RatedGame>,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        PlayerStats {
+            rating: INITIAL_RATING,
+            peak_rating: INITIAL_RATING,
+            games: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl PlayerStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        return match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::new()),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        return Ok(());
+    }
+
+    // the score is 1 for a win, 0.5 for a draw and 0 for a loss from the player's point of view
+    pub fn record(&mut self, engine_depth: u8, score: f64) {
+        let engine_rating = engine_rating(engine_depth);
+        let k_factor = if self.games < PROVISIONAL_GAMES { PROVISIONAL_K_FACTOR } else { K_FACTOR };
+
+        self.rating += k_factor * (score - expected_score(self.rating, engine_rating));
+        self.peak_rating = self.peak_rating.max(self.rating);
+        self.games += 1;
+
+        match score {
+            s if s > 0.5 => self.wins += 1,
+            s if s < 0.5 => self.losses += 1,
+            _ => self.draws += 1,
+        }
+
+        self.history.push(RatedGame {
+            engine_depth,
+            engine_rating,
+            score,
+            rating_after: self.rating,
+        });
+    }
+}
+
+// taking moves back, asking for hints or changing the position would make the result of a rated game meaningless
+pub fn allowed_in_rated_game(action: Action) -> bool {
+    return !matches!(action, Action::Undo | Action::Navigate | Action::EditPosition | Action::Hint);
+}
+
+pub fn engine_rating(depth: u8) -> f64 {
+    let index = (depth.max(1) as usize - 1).min(ENGINE_RATINGS.len() - 1);
+    return ENGINE_RATINGS[index];
+}
+
+pub fn expected_score(rating: f64, opponent_rating: f64) -> f64 {
+    return 1.0 / (1.0 + 10f64.powf((opponent_rating - rating) / 400.0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rating_updates() {
+        let mut stats = PlayerStats::new();
+
+        assert_eq!(0.5, expected_score(1500.0, 1500.0));
+
+        stats.record(2, 1.0);
+        assert!(stats.rating > INITIAL_RATING);
+        assert_eq!(stats.rating, stats.peak_rating);
+
+        stats.record(8, 0.0);
+        stats.record(1, 0.5);
+
+        assert!(stats.rating < stats.peak_rating);
+        assert_eq!((3, 1, 1, 1), (stats.games, stats.wins, stats.draws, stats.losses));
+        assert_eq!(engine_rating(8), engine_rating(20));
+        assert_eq!(stats.rating, stats.history.last().unwrap().rating_after);
+    }
+
+    #[test]
+    fn restricts_rated_games() {
+        assert!(allowed_in_rated_game(Action::Move));
+        assert!(allowed_in_rated_game(Action::NewGame));
+        assert!(!allowed_in_rated_game(Action::Undo));
+        assert!(!allowed_in_rated_game(Action::EditPosition));
+        assert!(!allowed_in_rated_game(Action::Hint));
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum SessionError {
+    #[error("The window '{0}' can only view the game")]
+    ReadOnly(String),
+
+    #[error("The window '{0}' is not a read-only session")]
+    UnknownSession(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Access {
+    ReadWrite,
+    ReadOnly,
+}
+
+// windows are identified by their label, every window is read-write unless it was opened as a read-only session
+#[derive(Debug, Default)]
+pub struct Sessions {
+    read_only: HashSet<String>,
+}
+
+impl Sessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn access(&self, label: &str) -> Access {
+        return if self.read_only.contains(label) { Access::ReadOnly } else { Access::ReadWrite };
+    }
+
+    pub fn ensure_writable(&self, label: &str) -> Result<(), SessionError> {
+        return match self.access(label) {
+            Access::ReadWrite => Ok(()),
+            Access::ReadOnly => Err(SessionError::ReadOnly(label.to_string())),
+        };
+    }
+
+    // a read-only session can neither open further sessions nor close its own
+    pub fn open_read_only(&mut self, by: &str, label: &str) -> Result<(), SessionError> {
+        self.ensure_writable(by)?;
+        self.read_only.insert(label.to_string());
+
+        return Ok(());
+    }
+
+    pub fn close(&mut self, by: &str, label: &str) -> Result<(), SessionError> {
+        self.ensure_writable(by)?;
+
+        if !self.read_only.remove(label) {
+            return Err(SessionError::UnknownSession(label.to_string()));
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_only_sessions() {
+        let mut sessions = Sessions::new();
+
+        sessions.open_read_only("main", "analysis").unwrap();
+
+        assert_eq!(Access::ReadWrite, sessions.access("main"));
+        assert_eq!(Access::ReadOnly, sessions.access("analysis"));
+        assert_eq!(Err(SessionError::ReadOnly("analysis".to_string())), sessions.ensure_writable("analysis"));
+        assert_eq!(Err(SessionError::ReadOnly("analysis".to_string())), sessions.open_read_only("analysis", "spectator"));
+        assert_eq!(Err(SessionError::ReadOnly("analysis".to_string())), sessions.close("analysis", "analysis"));
+
+        sessions.close("main", "analysis").unwrap();
+
+        assert_eq!(Access::ReadWrite, sessions.access("analysis"));
+        assert_eq!(Err(SessionError::UnknownSession("analysis".to_string())), sessions.close("main", "analysis"));
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fs, io::ErrorKind, path::Path};
+
+use anyhow::Result;
+use chess::PieceType;
+use serde::{Deserialize, Serialize};
+
+const COLORS: [&str; 2] = ["white", "black"];
+const PIECES: [&str; 6] = ["king", "queen", "rook", "bishop", "knight", "pawn"];
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum SettingsError {
+    #[error("Unknown board theme '{0}'")]
+    UnknownTheme(String),
+
+    #[error("The piece set '{0}' is not shipped with the app")]
+    UnavailablePieceSet(String),
+}
+
+// the colors of the light and dark squares
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardTheme {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub light: &'static str,
+    pub dark: &'static str,
+}
+
+pub const THEMES: [BoardTheme; 4] = [
+    BoardTheme {
+        id: "brown",
+        name: "Brown",
+        light: "#ffce9e",
+        dark: "#d18b47",
+    },
+    BoardTheme {
+        id: "green",
+        name: "Green",
+        light: "#eeeed2",
+        dark: "#769656",
+    },
+    BoardTheme {
+        id: "blue",
+        name: "Blue",
+        light: "#dee3e6",
+        dark: "#8ca2ad",
+    },
+    BoardTheme {
+        id: "grey",
+        name: "Grey",
+        light: "#e0e0e0",
+        dark: "#a0a0a0",
+    },
+];
+
+// one image per piece under the path, named like white_king.png
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PieceSet {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub path: &'static str,
+    pub extension: &'static str,
+}
+
+impl PieceSet {
+    pub fn files(&self) -> Vec<String> {
+        return COLORS
+            .iter()
+            .flat_map(|color| PIECES.iter().map(move |piece| format!("{}/{color}_{piece}.{}", self.path, self.extension)))
+            .collect();
+    }
+}
+
+pub const PIECE_SETS: [PieceSet; 1] = [PieceSet {
+    id: "classic",
+    name: "Classic",
+    path: "/pieces",
+    extension: "png",
+}];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Appearance {
+    pub theme: String,
+    pub piece_set: String,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Appearance {
+            theme: THEMES[0].id.to_string(),
+            piece_set: PIECE_SETS[0].id.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThemeCatalog {
+    pub themes: Vec<BoardTheme>,
+    pub piece_sets: Vec<PieceSet>,
+    pub selected: Appearance,
+}
+
+impl Appearance {
+    pub fn load(path: &Path) -> Result<Self> {
+        return match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        return Ok(());
+    }
+
+    // only the piece sets whose images were found among the app's assets can be chosen
+    pub fn select(&mut self, theme: &str, piece_set: &str, available: &[PieceSet]) -> Result<(), SettingsError> {
+        if !THEMES.iter().any(|known| known.id == theme) {
+            return Err(SettingsError::UnknownTheme(theme.to_string()));
+        }
+
+        if !available.iter().any(|set| set.id == piece_set) {
+            return Err(SettingsError::UnavailablePieceSet(piece_set.to_string()));
+        }
+
+        self.theme = theme.to_string();
+        self.piece_set = piece_set.to_string();
+
+        return Ok(());
+    }
+}
+
+// how moves are entered, only the promotion choice is applied here, the others are followed by the board itself
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MovePreferences {
+    pub auto_queen: bool,
+    pub confirm_moves: bool,
+    pub premoves: bool,
+    pub legal_move_dots: bool,
+}
+
+impl Default for MovePreferences {
+    fn default() -> Self {
+        MovePreferences {
+            auto_queen: false,
+            confirm_moves: false,
+            premoves: true,
+            legal_move_dots: true,
+        }
+    }
+}
+
+impl MovePreferences {
+    pub fn load(path: &Path) -> Result<Self> {
+        return match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        };
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        return Ok(());
+    }
+
+    // the piece a move to the last rank promotes to when none was chosen, none leaves the choice to the player
+    pub fn promotion(&self, chosen: Option<PieceType>) -> Option<PieceType> {
+        return chosen.or(self.auto_queen.then_some(PieceType::Queen));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_known_themes() {
+        let mut appearance = Appearance::default();
+
+        assert_eq!(12, PIECE_SETS[0].files().len());
+        assert_eq!("/pieces/black_knight.png", PIECE_SETS[0].files()[10]);
+
+        assert_eq!(Ok(()), appearance.select("green", "classic", &PIECE_SETS));
+        assert_eq!(Err(SettingsError::UnknownTheme("pink".to_string())), appearance.select("pink", "classic", &PIECE_SETS));
+        assert_eq!(Err(SettingsError::UnavailablePieceSet("classic".to_string())), appearance.select("blue", "classic", &[]));
+        assert_eq!(("green", "classic"), (appearance.theme.as_str(), appearance.piece_set.as_str()));
+    }
+
+    #[test]
+    fn promotes_to_queen_when_asked() {
+        let preferences = MovePreferences::default();
+        let auto_queen = MovePreferences { auto_queen: true, ..preferences };
+
+        assert_eq!(None, preferences.promotion(None));
+        assert_eq!(Some(PieceType::Queen), auto_queen.promotion(None));
+        assert_eq!(Some(PieceType::Knight), auto_queen.promotion(Some(PieceType::Knight)));
+        assert_eq!(auto_queen, serde_json::from_str(r#"{"autoQueen": true}"#).unwrap());
+    }
+}
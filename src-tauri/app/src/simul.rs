@@ -0,0 +1,282 @@
+use std::time::Duration;
+
+use chess::{engine::TimeBudget, pgn, Board, Color, Move};
+use serde::{Deserialize, Serialize};
+
+pub const MAX_BOARDS: usize = 20;
+
+// however many boards wait for the engine, it thinks at least this long on each of them
+const MIN_BOARD_TIME_MS: u64 = 50;
+
+// while the user has not moved on any board the engine looks again after this
+pub const IDLE_DELAY: Duration = Duration::from_millis(100);
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum SimulError {
+    #[error("A simul is played on 1 to {MAX_BOARDS} boards, not {0}")]
+    BoardCount(usize),
+
+    #[error("There is no board {0} in the simul")]
+    UnknownBoard(usize),
+
+    #[error("The engine is to move on board {0}")]
+    EngineToMove(usize),
+
+    #[error("The game on board {0} is over")]
+    Finished(usize),
+
+    #[error("The move {1} is illegal on board {0}")]
+    IllegalMove(usize, Move),
+}
+
+// the time is what the engine may spend on one round over the boards that wait for it
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulSettings {
+    pub boards: usize,
+    pub engine_color: Color,
+    pub round_time_ms: u64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SimulResult {
+    EngineWon,
+    UserWon,
+    Draw,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulBoardStatus {
+    pub index: usize,
+    pub fen: String,
+    pub turn: Color,
+    pub last_move: Option<Move>,
+    pub result: Option<SimulResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulProgress {
+    pub settings: SimulSettings,
+    pub boards: Vec<SimulBoardStatus>,
+    pub engine_wins: usize,
+    pub user_wins: usize,
+    pub draws: usize,
+    pub waiting_for_engine: usize,
+    pub finished: bool,
+}
+
+// a board the engine is to move on, searched without holding the simul
+#[derive(Debug, Clone)]
+pub struct SimulTurn {
+    pub index: usize,
+    pub board: Board,
+    pub budget: TimeBudget,
+}
+
+// the engine walks the boards in a circle like a simul giver, skipping the ones where the user has not moved yet
+#[derive(Debug, Clone)]
+pub struct Simul {
+    settings: SimulSettings,
+    boards: Vec<Board>,
+    next: usize,
+}
+
+impl Simul {
+    pub fn new(settings: SimulSettings) -> Result<Self, SimulError> {
+        if settings.boards == 0 || settings.boards > MAX_BOARDS {
+            return Err(SimulError::BoardCount(settings.boards));
+        }
+
+        return Ok(Simul {
+            settings,
+            boards: vec![Board::new_game(); settings.boards],
+            next: 0,
+        });
+    }
+
+    pub fn board(&self, index: usize) -> Result<&Board, SimulError> {
+        return self.boards.get(index).ok_or(SimulError::UnknownBoard(index));
+    }
+
+    // the round time is shared by the boards that wait, so a round takes about as long with many boards as with few
+    pub fn next_turn(&mut self) -> Option<SimulTurn> {
+        let count = self.boards.len();
+        let waiting = (0..count).filter(|index| self.waits_for_engine(*index)).count() as u64;
+        let index = (0..count).map(|offset| (self.next + offset) % count).find(|index| self.waits_for_engine(*index))?;
+
+        self.next = (index + 1) % count;
+
+        let share = Duration::from_millis((self.settings.round_time_ms / waiting).max(MIN_BOARD_TIME_MS));
+
+        return Some(SimulTurn {
+            index,
+            board: self.boards[index].clone(),
+            budget: TimeBudget { soft: share, hard: share },
+        });
+    }
+
+    pub fn play_user_move(&mut self, index: usize, mv: &Move) -> Result<(), SimulError> {
+        if self.board(index)?.turn() == self.settings.engine_color {
+            return Err(SimulError::EngineToMove(index));
+        }
+
+        return self.play(index, mv);
+    }
+
+    // false when the board is no longer the one that was searched, the move is dropped then
+    pub fn play_engine_move(&mut self, turn: &SimulTurn, mv: &Move) -> Result<bool, SimulError> {
+        if *self.board(turn.index)? != turn.board {
+            return Ok(false);
+        }
+
+        self.play(turn.index, mv)?;
+
+        return Ok(true);
+    }
+
+    pub fn progress(&self) -> SimulProgress {
+        let boards = self
+            .boards
+            .iter()
+            .enumerate()
+            .map(|(index, board)| SimulBoardStatus {
+                index,
+                fen: board.to_fen(),
+                turn: board.turn(),
+                last_move: board.last_move(),
+                result: self.result(board),
+            })
+            .collect::<Vec<_>>();
+
+        let count = |result: SimulResult| boards.iter().filter(|board| board.result == Some(result)).count();
+
+        return SimulProgress {
+            settings: self.settings,
+            engine_wins: count(SimulResult::EngineWon),
+            user_wins: count(SimulResult::UserWon),
+            draws: count(SimulResult::Draw),
+            waiting_for_engine: (0..self.boards.len()).filter(|index| self.waits_for_engine(*index)).count(),
+            finished: self.finished(),
+            boards,
+        };
+    }
+
+    pub fn finished(&self) -> bool {
+        return self.boards.iter().all(|board| self.result(board).is_some());
+    }
+
+    // only the legal move between the squares is played, with exactly the flags it was sent with
+    fn play(&mut self, index: usize, mv: &Move) -> Result<(), SimulError> {
+        let board = self.board(index)?;
+
+        if self.result(board).is_some() {
+            return Err(SimulError::Finished(index));
+        }
+
+        if chess::resolve_move(board, mv.from, mv.to, mv.promotion.then_some(mv.promote_to)).as_ref() != Some(mv) {
+            return Err(SimulError::IllegalMove(index, *mv));
+        }
+
+        return self.boards[index].exec_move(mv).map_err(|_| SimulError::IllegalMove(index, *mv));
+    }
+
+    fn waits_for_engine(&self, index: usize) -> bool {
+        let board = &self.boards[index];
+
+        return board.turn() == self.settings.engine_color && self.result(board).is_none();
+    }
+
+    fn result(&self, board: &Board) -> Option<SimulResult> {
+        let winner = match pgn::result(board) {
+            "1-0" => Color::White,
+            "0-1" => Color::Black,
+            "1/2-1/2" => return Some(SimulResult::Draw),
+            _ => return None,
+        };
+
+        return match winner == self.settings.engine_color {
+            true => Some(SimulResult::EngineWon),
+            false => Some(SimulResult::UserWon),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mv(board: &Board, uci: &str) -> Move {
+        return *chess::get_moves(board.turn(), board).iter().find(|mv| mv.to_string() == uci).unwrap();
+    }
+
+    #[test]
+    fn rotates_over_the_waiting_boards() {
+        let settings = SimulSettings {
+            boards: 3,
+            engine_color: Color::White,
+            round_time_ms: 600,
+        };
+
+        assert_eq!(Err(SimulError::BoardCount(0)), Simul::new(SimulSettings { boards: 0, ..settings }).map(|_| ()));
+
+        let mut simul = Simul::new(settings).unwrap();
+        let first = simul.next_turn().unwrap();
+
+        assert_eq!((0, Duration::from_millis(200)), (first.index, first.budget.soft));
+        assert_eq!(Err(SimulError::EngineToMove(0)), simul.play_user_move(0, &mv(&first.board, "e2e4")));
+        assert_eq!(Ok(true), simul.play_engine_move(&first, &mv(&first.board, "e2e4")));
+        assert_eq!(Ok(false), simul.play_engine_move(&first, &mv(&first.board, "d2d4")));
+
+        // the engine moves on to the boards it has not played on yet before it gets back to the first one
+        let second = simul.next_turn().unwrap();
+        assert_eq!((1, Duration::from_millis(300)), (second.index, second.budget.soft));
+
+        let before = simul.board(0).unwrap().clone();
+        let jump = Move::new(chess::Coord::new('d', 8), chess::Coord::new('d', 5));
+        let king_promotion = Move {
+            promotion: true,
+            promote_to: chess::PieceType::King,
+            ..Move::new(chess::Coord::new('e', 7), chess::Coord::new('e', 5))
+        };
+
+        assert_eq!(Err(SimulError::IllegalMove(0, jump)), simul.play_user_move(0, &jump));
+        assert_eq!(Err(SimulError::IllegalMove(0, king_promotion)), simul.play_user_move(0, &king_promotion));
+        assert_eq!(&before, simul.board(0).unwrap());
+
+        simul.play_user_move(0, &mv(simul.board(0).unwrap(), "e7e5")).unwrap();
+
+        assert_eq!(2, simul.next_turn().unwrap().index);
+        assert_eq!(0, simul.next_turn().unwrap().index);
+
+        let progress = simul.progress();
+
+        assert_eq!((3, 0, false), (progress.waiting_for_engine, progress.user_wins, progress.finished));
+        assert_eq!(Some("e7e5".to_string()), progress.boards[0].last_move.map(|mv| mv.to_string()));
+        assert_eq!(Err(SimulError::UnknownBoard(3)), simul.play_user_move(3, &mv(&first.board, "e2e4")));
+    }
+
+    #[test]
+    fn counts_finished_games() {
+        let mut simul = Simul::new(SimulSettings {
+            boards: 1,
+            engine_color: Color::White,
+            round_time_ms: 100,
+        })
+        .unwrap();
+
+        for (engine, user) in [("f2f3", "e7e5"), ("g2g4", "d8h4")] {
+            let turn = simul.next_turn().unwrap();
+            simul.play_engine_move(&turn, &mv(&turn.board, engine)).unwrap();
+            simul.play_user_move(0, &mv(simul.board(0).unwrap(), user)).unwrap();
+        }
+
+        let progress = simul.progress();
+
+        assert!(simul.next_turn().is_none());
+        assert_eq!((1, 0, true), (progress.user_wins, progress.waiting_for_engine, progress.finished));
+        assert_eq!(Some(SimulResult::UserWon), progress.boards[0].result);
+    }
+}
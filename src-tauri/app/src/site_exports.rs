@@ -0,0 +1,302 @@
+use std::io::BufRead;
+
+use chess::pgn::{self, Eval, PgnError, PgnGame, PgnReader, Termination};
+use serde::Deserialize;
+
+const MS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Invalid game in the export: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Pgn(#[from] PgnError),
+}
+
+// one game per line as the lichess api sends it with the ndjson content type, the clocks and the analysis
+// are only there when they were asked for
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LichessGame {
+    id: String,
+    #[serde(default)]
+    rated: bool,
+    variant: String,
+    speed: Option<String>,
+    created_at: Option<i64>,
+    status: String,
+    players: LichessPlayers,
+    winner: Option<String>,
+    opening: Option<LichessOpening>,
+    #[serde(default)]
+    moves: String,
+    clock: Option<LichessClock>,
+    #[serde(default)]
+    clocks: Vec<u64>,
+    #[serde(default)]
+    analysis: Vec<LichessEval>,
+    initial_fen: Option<String>,
+    pgn: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LichessPlayers {
+    white: LichessPlayer,
+    black: LichessPlayer,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LichessPlayer {
+    user: Option<LichessUser>,
+    rating: Option<u32>,
+    ai_level: Option<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LichessUser {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct LichessOpening {
+    eco: String,
+    name: String,
+}
+
+// seconds, the clocks of the moves are in centiseconds
+#[derive(Debug, Deserialize)]
+struct LichessClock {
+    initial: u64,
+    increment: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct LichessEval {
+    eval: Option<i32>,
+    mate: Option<i32>,
+}
+
+// the games of a pgn export from either site, with the termination sentences of chess.com put into the standard values
+pub fn read_pgn_export<R: BufRead>(reader: R) -> impl Iterator<Item = Result<PgnGame, ImportError>> {
+    return PgnReader::new(reader).map(|game| {
+        let mut game = game?;
+
+        for (key, value) in game.tags.iter_mut() {
+            if key == "Termination" {
+                if let Some(termination) = chess_com_termination(value) {
+                    *value = termination.as_str().to_string();
+                }
+            }
+        }
+
+        return Ok(game);
+    });
+}
+
+// an empty line is skipped, a line that is not a game is reported and the next one is read
+pub fn read_lichess_ndjson<R: BufRead>(reader: R) -> impl Iterator<Item = Result<PgnGame, ImportError>> {
+    return reader.lines().filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty())).map(|line| {
+        let game = serde_json::from_str::<LichessGame>(&line.map_err(PgnError::from)?)?;
+
+        return Ok(pgn::read_pgn(&lichess_pgn(&game))?);
+    });
+}
+
+// the game is written the way lichess exports it as pgn, with the evaluation and the clock in the comment of every move
+fn lichess_pgn(game: &LichessGame) -> String {
+    if let Some(pgn) = &game.pgn {
+        return pgn.clone();
+    }
+
+    let result = match (game.winner.as_deref(), game.status.as_str()) {
+        (Some("white"), _) => "1-0",
+        (Some("black"), _) => "0-1",
+        (_, "created" | "started" | "aborted" | "noStart" | "unknownFinish") => "*",
+        _ => "1/2-1/2",
+    };
+
+    let termination = match game.status.as_str() {
+        "resign" => Termination::Resignation,
+        "outoftime" => Termination::TimeForfeit,
+        "timeout" | "noStart" => Termination::Abandonment,
+        "cheat" => Termination::RulesInfraction,
+        _ => Termination::Normal,
+    };
+
+    let mode = if game.rated { "Rated" } else { "Casual" };
+    let mut tags = vec![
+        ("Event".to_string(), format!("{mode} {} game", game.speed.as_deref().unwrap_or("lichess"))),
+        ("Site".to_string(), format!("https://lichess.org/{}", game.id)),
+        ("Date".to_string(), game.created_at.map_or_else(|| "????.??.??".to_string(), date)),
+        ("White".to_string(), player_name(&game.players.white)),
+        ("Black".to_string(), player_name(&game.players.black)),
+        ("Result".to_string(), result.to_string()),
+        ("Variant".to_string(), game.variant.clone()),
+        ("Termination".to_string(), termination.as_str().to_string()),
+    ];
+
+    for (key, player) in [("WhiteElo", &game.players.white), ("BlackElo", &game.players.black)] {
+        if let Some(rating) = player.rating {
+            tags.push((key.to_string(), rating.to_string()));
+        }
+    }
+
+    if let Some(opening) = &game.opening {
+        tags.push(("ECO".to_string(), opening.eco.clone()));
+        tags.push(("Opening".to_string(), opening.name.clone()));
+    }
+
+    if let Some(clock) = &game.clock {
+        tags.push(("TimeControl".to_string(), format!("{}+{}", clock.initial, clock.increment)));
+    }
+
+    if let Some(fen) = &game.initial_fen {
+        tags.push(("SetUp".to_string(), "1".to_string()));
+        tags.push(("FEN".to_string(), fen.clone()));
+    }
+
+    let mut pgn = tags.iter().map(|(key, value)| format!("[{key} \"{}\"]\n", value.replace('\\', "\\\\").replace('"', "\\\""))).collect::<String>();
+    pgn.push('\n');
+
+    // the move numbers are only there for readers of the text, the moves are read by their order
+    let black_starts = game.initial_fen.as_deref().is_some_and(|fen| fen.split_whitespace().nth(1) == Some("b"));
+
+    for (ply, san) in game.moves.split_whitespace().enumerate() {
+        if (ply % 2 == 0) != black_starts {
+            pgn.push_str(&format!("{}. ", ply / 2 + 1));
+        }
+
+        pgn.push_str(san);
+
+        let eval = game.analysis.get(ply).and_then(|eval| match (eval.mate, eval.eval) {
+            (Some(moves), _) => Some(Eval::Mate(moves)),
+            (None, Some(centipawns)) => Some(Eval::Centipawns(centipawns)),
+            (None, None) => None,
+        });
+
+        let comment = [
+            eval.map(|eval| format!("[%eval {eval}]")),
+            game.clocks.get(ply).map(|centiseconds| pgn::with_clock(None, centiseconds * 10)),
+        ];
+
+        let comment = comment.into_iter().flatten().collect::<Vec<_>>();
+
+        if !comment.is_empty() {
+            pgn.push_str(&format!(" {{ {} }}", comment.join(" ")));
+        }
+
+        pgn.push(' ');
+    }
+
+    pgn.push_str(result);
+    pgn.push('\n');
+
+    return pgn;
+}
+
+fn player_name(player: &LichessPlayer) -> String {
+    return match (&player.user, player.ai_level) {
+        (Some(user), _) => user.name.clone(),
+        (None, Some(level)) => format!("lichess AI level {level}"),
+        (None, None) => "Anonymous".to_string(),
+    };
+}
+
+// chess.com writes who won and how as a sentence, like "Alice won by resignation" or "Game drawn by repetition"
+fn chess_com_termination(value: &str) -> Option<Termination> {
+    let value = value.to_lowercase();
+
+    if value.contains("abandoned") {
+        return Some(Termination::Abandonment);
+    }
+
+    if value.contains("on time") || value.contains("by timeout") {
+        return Some(Termination::TimeForfeit);
+    }
+
+    if value.contains("by resignation") {
+        return Some(Termination::Resignation);
+    }
+
+    if value.contains("by agreement") {
+        return Some(Termination::Agreement);
+    }
+
+    if value.contains(" won by ") || value.contains("game drawn by ") {
+        return Some(Termination::Normal);
+    }
+
+    return None;
+}
+
+// the date in utc of a timestamp in milliseconds
+fn date(ms: i64) -> String {
+    let days = ms.div_euclid(MS_PER_DAY);
+
+    // days since 1970 to a civil date, counted in eras of 400 years starting on the first of march
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    return format!("{year}.{month:02}.{day:02}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_lichess_ndjson() {
+        let ndjson = concat!(
+            r#"{"id":"q7ZvsdUF","rated":true,"variant":"standard","speed":"blitz","createdAt":1514505150384,"status":"resign","#,
+            r#""players":{"white":{"user":{"name":"Alice"},"rating":1500},"black":{"aiLevel":3}},"winner":"white","#,
+            r#""opening":{"eco":"B01","name":"Scandinavian Defense"},"moves":"e4 d5 exd5","clock":{"initial":300,"increment":3},"#,
+            r#""clocks":[30003,30003,29850],"analysis":[{"eval":20},{"eval":45},{"mate":-4}]}"#,
+            "\n\n",
+            r#"{"id":"broken","variant":"standard","status":"draw","players":{"white":{},"black":{}},"moves":"e4 e4"}"#,
+            "\n",
+            r#"{"id":"setup","variant":"fromPosition","status":"draw","players":{"white":{},"black":{}},"initialFen":"4k3/8/8/8/8/8/8/4K3 b - - 0 1","moves":"Kd7"}"#,
+        );
+
+        let games = read_lichess_ndjson(ndjson.as_bytes()).collect::<Vec<_>>();
+
+        assert_eq!(3, games.len());
+        assert!(matches!(games[1], Err(ImportError::Pgn(PgnError::IllegalMove(_)))));
+
+        let game = games[0].as_ref().unwrap();
+        let comment = |ply: usize| game.tree.node(game.tree.mainline()[ply]).unwrap().comment.clone().unwrap();
+
+        assert_eq!(Some("2017.12.28"), game.tag("Date"));
+        assert_eq!((Some("Alice"), Some("lichess AI level 3")), (game.tag("White"), game.tag("Black")));
+        assert_eq!((Some("1-0"), Some("resignation")), (game.tag("Result"), game.tag("Termination")));
+        assert_eq!(Some("300+3"), game.tag("TimeControl"));
+        assert_eq!((Some(300_000), Some(Eval::Centipawns(20))), (pgn::parse_clock(&comment(0)), pgn::parse_eval(&comment(0))));
+        assert_eq!((Some(298_000), Some(Eval::Mate(-4))), (pgn::parse_clock(&comment(2)), pgn::parse_eval(&comment(2))));
+
+        let setup = games[2].as_ref().unwrap();
+        assert_eq!("8/3k4/8/8/8/8/8/4K3 w - - 1 2", setup.board.to_fen());
+    }
+
+    #[test]
+    fn reads_chess_com_pgn() {
+        let pgn = "[Event \"Live Chess\"]\n[Site \"Chess.com\"]\n[White \"Alice\"]\n[Black \"Bob\"]\n[Result \"0-1\"]\n\
+                   [Termination \"Bob won on time\"]\n\n1. e4 {[%clk 0:09:58.4]} 1... e5 {[%clk 0:09:57.1]} 0-1\n";
+
+        let game = read_pgn_export(pgn.as_bytes()).next().unwrap().unwrap();
+
+        assert_eq!(Some("time forfeit"), game.tag("Termination"));
+        assert_eq!(Some(597_100), game.tree.node(game.tree.mainline()[1]).unwrap().comment.as_deref().and_then(pgn::parse_clock));
+        assert_eq!(Some(Termination::Normal), chess_com_termination("Game drawn by repetition"));
+        assert_eq!(None, chess_com_termination("normal"));
+        assert_eq!("1970.01.01", date(0));
+        assert_eq!("2000.02.29", date(951_782_400_000));
+    }
+}
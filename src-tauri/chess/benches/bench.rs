@@ -1,73 +1,101 @@
-use chess::{Board, Move, PieceType};
-use criterion::{criterion_group, criterion_main, Criterion};
+use chess::{expand_promotions, get_moves_into, perft::perft, Board, MoveList};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
-fn test_move_count(depth: usize, board: &mut Board, log: bool) -> u128 {
-    if depth == 0 {
-        return 1;
+// to compare a change against the current state, save a baseline first and measure against it afterwards:
+//   cargo bench -p chess -- --save-baseline main
+//   cargo bench -p chess -- --baseline main
+const POSITIONS: [(&str, &str); 5] = [
+    ("start", "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+    ("open", "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"),
+    ("closed", "r1bqk2r/pp1nbppp/2n1p3/2ppP3/3P1P2/2P2N2/PP1N2PP/R1BQKB1R w KQkq - 1 8"),
+    ("checks", "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"),
+    ("promotions", "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1"),
+];
+
+fn boards() -> Vec<(&'static str, Board)> {
+    return POSITIONS.iter().map(|(name, fen)| (*name, Board::from_fen(fen).expect("benchmark position to be valid"))).collect();
+}
+
+fn moves(board: &Board) -> Vec<chess::Move> {
+    let mut moves = MoveList::new();
+    get_moves_into(board.turn(), board, &mut moves);
+
+    return expand_promotions(&moves);
+}
+
+// throughput is reported in generated moves, so positions with more moves are comparable to ones with fewer
+fn movegen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("movegen");
+
+    for (name, board) in boards() {
+        let mut moves = MoveList::new();
+        get_moves_into(board.turn(), &board, &mut moves);
+
+        group.throughput(Throughput::Elements(moves.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board, |b, board| {
+            b.iter(|| {
+                get_moves_into(board.turn(), black_box(board), &mut moves);
+                return moves.len();
+            });
+        });
     }
 
-    let moves = chess::get_moves(board.turn(), &board);
-    let mut count: u128 = 0;
-
-    for mv in moves {
-        if mv.promotion {
-            let to_rook = {
-                let mut mv = mv.clone();
-                mv.promote_to = PieceType::Rook;
-                mv
-            };
-            let to_bishop = {
-                let mut mv = mv.clone();
-                mv.promote_to = PieceType::Bishop;
-                mv
-            };
-            let to_knight = {
-                let mut mv = mv.clone();
-                mv.promote_to = PieceType::Knight;
-                mv
-            };
-            let to_queen = {
-                let mut mv = mv.clone();
-                mv.promote_to = PieceType::Queen;
-                mv
-            };
-
-            test_move_count_iter(&mut count, board, &to_rook, depth, log);
-            test_move_count_iter(&mut count, board, &to_bishop, depth, log);
-            test_move_count_iter(&mut count, board, &to_knight, depth, log);
-            test_move_count_iter(&mut count, board, &to_queen, depth, log);
-        } else {
-            test_move_count_iter(&mut count, board, &mv, depth, log);
-        }
+    group.finish();
+}
+
+fn attack_maps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("attack maps");
+
+    for (name, board) in boards() {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board, |b, board| {
+            let mut board = board.clone();
+            b.iter(|| black_box(&mut board).update_attack_data());
+        });
     }
 
-    return count;
+    group.finish();
 }
 
-fn test_move_count_iter(count: &mut u128, board: &mut Board, mv: &Move, depth: usize, log: bool) {
-    board.exec_move(&mv).unwrap();
+// every move of the position is played and taken back once per iteration
+fn make_unmake(c: &mut Criterion) {
+    let mut group = c.benchmark_group("make unmake");
+
+    for (name, board) in boards() {
+        let moves = moves(&board);
 
-    let c = test_move_count(depth - 1, board, false);
-    *count += c;
+        group.throughput(Throughput::Elements(moves.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &board, |b, board| {
+            let mut board = board.clone();
 
-    if log {
-        println!("{mv}: {c}");
+            b.iter(|| {
+                for mv in &moves {
+                    if board.exec_move(black_box(mv)).is_ok() {
+                        board.undo_move().unwrap();
+                    }
+                }
+            });
+        });
     }
 
-    board.undo_move().unwrap();
+    group.finish();
 }
 
-fn chess_benchmark(c: &mut Criterion) {
-    c.bench_function("perft depth 2", |b| {
-        let mut board = Board::new_game();
-        b.iter(|| test_move_count(2, &mut board, false));
-    });
+// throughput is reported in leaf nodes
+fn perft_nodes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("perft");
+    group.sample_size(10);
+
+    for (name, fen, depth) in [("start", POSITIONS[0].1, 2), ("start", POSITIONS[0].1, 4), ("open", POSITIONS[1].1, 3)] {
+        let mut board = Board::from_fen(fen).expect("benchmark position to be valid");
+
+        group.throughput(Throughput::Elements(perft(&mut board, depth)));
+        group.bench_function(BenchmarkId::new(name, depth), |b| {
+            b.iter(|| perft(black_box(&mut board), depth));
+        });
+    }
 
-    c.bench_function("perft depth 4", |b| {
-        let mut board = Board::new_game();
-        b.iter(|| test_move_count(4, &mut board, false));
-    });
+    group.finish();
 }
 
-criterion_group!(benches, chess_benchmark);
+criterion_group!(benches, movegen, attack_maps, make_unmake, perft_nodes);
 criterion_main!(benches);
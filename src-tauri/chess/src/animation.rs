@@ -0,0 +1,307 @@
+use gif::{Encoder, EncodingError, Frame, Repeat};
+use serde::{Deserialize, Serialize};
+
+use crate::{Board, Color, Coord, PieceType};
+
+const SPRITE_SIZE: usize = 12;
+
+const LIGHT_SQUARE: u8 = 0;
+const DARK_SQUARE: u8 = 1;
+const WHITE_PIECE: u8 = 2;
+const BLACK_PIECE: u8 = 3;
+const OUTLINE: u8 = 4;
+const LIGHT_HIGHLIGHT: u8 = 5;
+const DARK_HIGHLIGHT: u8 = 6;
+
+#[rustfmt::skip]
+const PALETTE: [u8; 21] = [
+    0xf0, 0xd9, 0xb5,
+    0xb5, 0x88, 0x63,
+    0xff, 0xff, 0xff,
+    0x20, 0x20, 0x20,
+    0x10, 0x10, 0x10,
+    0xcd, 0xd2, 0x6a,
+    0xaa, 0xa2, 0x3a,
+];
+
+#[rustfmt::skip]
+const PAWN: [&str; SPRITE_SIZE] = [
+    "............",
+    "............",
+    ".....##.....",
+    "....####....",
+    "....####....",
+    ".....##.....",
+    "....####....",
+    ".....##.....",
+    "....####....",
+    "...######...",
+    "..########..",
+    "............",
+];
+
+#[rustfmt::skip]
+const ROOK: [&str; SPRITE_SIZE] = [
+    "............",
+    "..##.##.##..",
+    "..########..",
+    "...######...",
+    "....####....",
+    "....####....",
+    "....####....",
+    "....####....",
+    "...######...",
+    "..########..",
+    "..########..",
+    "............",
+];
+
+#[rustfmt::skip]
+const KNIGHT: [&str; SPRITE_SIZE] = [
+    "............",
+    ".....##.....",
+    "....####....",
+    "...######...",
+    "..###.####..",
+    ".....#####..",
+    "....#####...",
+    "...#####....",
+    "...######...",
+    "..########..",
+    "..########..",
+    "............",
+];
+
+#[rustfmt::skip]
+const BISHOP: [&str; SPRITE_SIZE] = [
+    "............",
+    ".....##.....",
+    "....####....",
+    "...###.##...",
+    "...##.###...",
+    "...######...",
+    "....####....",
+    ".....##.....",
+    "....####....",
+    "...######...",
+    "..########..",
+    "............",
+];
+
+#[rustfmt::skip]
+const QUEEN: [&str; SPRITE_SIZE] = [
+    "............",
+    "..#..##..#..",
+    "..#.####.#..",
+    "..########..",
+    "...######...",
+    "...######...",
+    "....####....",
+    "....####....",
+    "...######...",
+    "..########..",
+    "..########..",
+    "............",
+];
+
+#[rustfmt::skip]
+const KING: [&str; SPRITE_SIZE] = [
+    ".....##.....",
+    "....####....",
+    ".....##.....",
+    "...######...",
+    "..########..",
+    "..########..",
+    "...######...",
+    "....####....",
+    "...######...",
+    "..########..",
+    "..########..",
+    "............",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnimationError {
+    #[error("The square size must be a multiple of {SPRITE_SIZE} between {SPRITE_SIZE} and 120")]
+    InvalidSquareSize(u16),
+
+    #[error(transparent)]
+    Encoding(#[from] EncodingError),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnimationOptions {
+    pub square_size: u16,
+    pub frame_delay_ms: u16,
+    pub final_delay_ms: u16,
+    pub perspective: Color,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            square_size: 48,
+            frame_delay_ms: 800,
+            final_delay_ms: 3000,
+            perspective: Color::White,
+        }
+    }
+}
+
+// renders every position from the start of the game up to the current one into a looping gif
+pub fn export_gif(board: &Board, options: &AnimationOptions) -> Result<Vec<u8>, AnimationError> {
+    let size = image_size(options)?;
+    let positions = board.history();
+
+    let mut encoder = Encoder::new(Vec::new(), size, size, &PALETTE)?;
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    for (index, position) in positions.iter().enumerate() {
+        let delay = if index + 1 == positions.len() { options.final_delay_ms } else { options.frame_delay_ms };
+
+        let mut frame = Frame::from_indexed_pixels(size, size, render_frame(position, options), None);
+        frame.delay = delay / 10;
+
+        encoder.write_frame(&frame)?;
+    }
+
+    return Ok(encoder.into_inner().map_err(EncodingError::from)?);
+}
+
+// a still image of the current position only, the delays of the options are not used
+pub fn position_gif(board: &Board, options: &AnimationOptions) -> Result<Vec<u8>, AnimationError> {
+    let size = image_size(options)?;
+
+    let mut encoder = Encoder::new(Vec::new(), size, size, &PALETTE)?;
+    encoder.write_frame(&Frame::from_indexed_pixels(size, size, render_frame(board, options), None))?;
+
+    return Ok(encoder.into_inner().map_err(EncodingError::from)?);
+}
+
+fn image_size(options: &AnimationOptions) -> Result<u16, AnimationError> {
+    let square_size = options.square_size;
+
+    if !(SPRITE_SIZE..=120).step_by(SPRITE_SIZE).any(|size| size == square_size as usize) {
+        return Err(AnimationError::InvalidSquareSize(square_size));
+    }
+
+    return Ok(square_size * 8);
+}
+
+fn render_frame(board: &Board, options: &AnimationOptions) -> Vec<u8> {
+    let square_size = options.square_size as usize;
+    let scale = square_size / SPRITE_SIZE;
+    let size = square_size * 8;
+
+    let highlighted = board.last_move().map(|mv| vec![mv.from, mv.to]).unwrap_or_default();
+    let mut pixels = vec![LIGHT_SQUARE; size * size];
+
+    for row in 0..8u8 {
+        for column in 0..8u8 {
+            let coord = Coord::from_xy(column, row);
+
+            let (x, y) = match options.perspective {
+                Color::White => (column as usize, 7 - row as usize),
+                Color::Black => (7 - column as usize, row as usize),
+            };
+
+            let dark = (row + column) % 2 == 0;
+            let background = match (dark, highlighted.contains(&coord)) {
+                (true, true) => DARK_HIGHLIGHT,
+                (false, true) => LIGHT_HIGHLIGHT,
+                (true, false) => DARK_SQUARE,
+                (false, false) => LIGHT_SQUARE,
+            };
+
+            for py in 0..SPRITE_SIZE {
+                for px in 0..SPRITE_SIZE {
+                    let color = match board.piece_at(coord) {
+                        Some(piece) => sprite_pixel(piece.piece_type, piece.color, px, py).unwrap_or(background),
+                        None => background,
+                    };
+
+                    for sy in 0..scale {
+                        let start = (y * square_size + py * scale + sy) * size + x * square_size + px * scale;
+                        pixels[start..start + scale].fill(color);
+                    }
+                }
+            }
+        }
+    }
+
+    return pixels;
+}
+
+fn sprite_pixel(piece_type: PieceType, color: Color, x: usize, y: usize) -> Option<u8> {
+    let sprite = match piece_type {
+        PieceType::Pawn => &PAWN,
+        PieceType::Rook => &ROOK,
+        PieceType::Knight => &KNIGHT,
+        PieceType::Bishop => &BISHOP,
+        PieceType::Queen => &QUEEN,
+        PieceType::King => &KING,
+    };
+
+    let is_set = |x: isize, y: isize| {
+        return x >= 0 && y >= 0 && sprite.get(y as usize).and_then(|row| row.as_bytes().get(x as usize)) == Some(&b'#');
+    };
+
+    let (x, y) = (x as isize, y as isize);
+
+    if is_set(x, y) {
+        return Some(if color == Color::White { WHITE_PIECE } else { BLACK_PIECE });
+    }
+
+    // pixels next to the piece form an outline so white pieces stand out on light squares
+    if is_set(x - 1, y) || is_set(x + 1, y) || is_set(x, y - 1) || is_set(x, y + 1) {
+        return Some(OUTLINE);
+    }
+
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Move;
+
+    #[test]
+    fn one_frame_per_position() {
+        let mut board = Board::new_game();
+        board.exec_move(&Move::new(Coord::new('e', 2), Coord::new('e', 4))).unwrap();
+        board.exec_move(&Move::new(Coord::new('e', 7), Coord::new('e', 5))).unwrap();
+
+        let options = AnimationOptions {
+            square_size: 24,
+            ..AnimationOptions::default()
+        };
+
+        let gif = export_gif(&board, &options).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(gif.as_slice()).unwrap();
+        let mut frames = 0;
+
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((192, 192), (frame.width, frame.height));
+            frames += 1;
+        }
+
+        assert_eq!(3, frames);
+
+        let still = position_gif(&board, &options).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(still.as_slice()).unwrap();
+
+        assert!(decoder.read_next_frame().unwrap().is_some());
+        assert!(decoder.read_next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_square_size() {
+        let options = AnimationOptions {
+            square_size: 50,
+            ..AnimationOptions::default()
+        };
+
+        assert!(matches!(export_gif(&Board::new_game(), &options), Err(AnimationError::InvalidSquareSize(50))));
+    }
+}
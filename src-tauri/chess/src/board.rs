@@ -1,18 +1,38 @@
+mod builder;
+mod diff;
+mod record;
+
 use std::fmt::Display;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     bitboard::BitBoard,
-    fen::{self, FenError},
-    moves, Color, Coord, Move, Piece, PieceType,
+    fen::{self, FenError, FenResult},
+    moves,
+    render::{self, RenderOptions},
+    zobrist, Color, Coord, Move, Odds, Piece, PieceType, Variant,
 };
 
+pub use builder::BoardBuilder;
+pub use diff::{BoardDiff, MovedPiece, StateChange};
+
 const A1: Coord = Coord(0);
 const H1: Coord = Coord(7);
 const A8: Coord = Coord(56);
 const H8: Coord = Coord(63);
 
+const RANK_8: BitBoard = BitBoard(0xff00000000000000);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EnPassantMode {
+    // the square behind every double pawn push, as written in FEN
+    Fen,
+    // only when a pawn of the side to move can capture, so repetitions are not told apart by a square nobody can use
+    Capturable,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum MoveErr {
     #[error("Could not find a piece at {0}")]
@@ -26,6 +46,15 @@ pub enum MoveErr {
 
     #[error("Cannot capture king")]
     CannotCaptureKing,
+
+    #[error("Cannot pass while in check")]
+    NullMoveInCheck,
+
+    #[error("Last move was not a null move")]
+    NotANullMove,
+
+    #[error("There is no pawn to capture en passant on {0}")]
+    InvalidEnPassant(Coord),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -106,8 +135,8 @@ impl BoardSide {
         return &self.king;
     }
 
-    pub fn king_coord(&self) -> Coord {
-        return self.king.into_iter().next().unwrap();
+    pub fn king_coord(&self) -> Option<Coord> {
+        return self.king.into_iter().next();
     }
 
     pub fn checked(&self) -> bool {
@@ -163,9 +192,9 @@ impl BoardSide {
         }
     }
 
-    fn capture(&mut self, coord: Coord) -> Result<PieceType, MoveErr> {
+    fn capture(&mut self, coord: Coord, royal_king: bool) -> Result<PieceType, MoveErr> {
         if let Some(piece_type) = self.lookup[coord.offset()] {
-            if piece_type == PieceType::King {
+            if royal_king && piece_type == PieceType::King {
                 return Err(MoveErr::CannotCaptureKing);
             }
 
@@ -205,15 +234,52 @@ fn is_diagonal(direction: (isize, isize)) -> bool {
 
 #[derive(Debug, Clone, PartialEq)]
 struct LastMove {
-    mv: Move,
+    mv: Option<Move>,
     captured_piece: Option<PieceType>,
     en_passant_square: Option<Coord>,
     white_castling_rights: CastlingRights,
     black_castling_rights: CastlingRights,
+    halfmove_clock: u32,
+    hash: u64,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+pub struct BoardSnapshot {
+    turn: Color,
+
+    all: BitBoard,
+    white: BoardSide,
+    black: BoardSide,
+
+    winner: Option<Color>,
+
+    en_passant_square: Option<Coord>,
+
+    halfmove_clock: u32,
+    fullmove_number: u32,
+
+    hash: u64,
+    ply: usize,
+}
+
+impl BoardSnapshot {
+    pub fn turn(&self) -> Color {
+        return self.turn;
+    }
+
+    pub fn hash(&self) -> u64 {
+        return self.hash;
+    }
+
+    pub fn ply(&self) -> usize {
+        return self.ply;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "record::BoardRecord", try_from = "record::BoardRecord")]
 pub struct Board {
+    variant: Variant,
     turn: Color,
 
     all: BitBoard,
@@ -224,12 +290,18 @@ pub struct Board {
 
     en_passant_square: Option<Coord>,
 
+    halfmove_clock: u32,
+    fullmove_number: u32,
+
+    hash: u64,
+
     last_moves: Vec<LastMove>,
 }
 
 impl Board {
     pub fn empty() -> Board {
         Board {
+            variant: Variant::Standard,
             turn: Color::White,
             winner: None,
 
@@ -239,22 +311,61 @@ impl Board {
 
             en_passant_square: None,
 
+            halfmove_clock: 0,
+            fullmove_number: 1,
+
+            hash: 0,
+
             last_moves: Vec::with_capacity(10),
         }
     }
 
     pub fn from_fen(fen_str: &str) -> Result<Self> {
+        return Self::from_variant_fen(Variant::Standard, fen_str);
+    }
+
+    pub fn from_variant_fen(variant: Variant, fen_str: &str) -> Result<Self> {
         let mut board = Self::empty();
+        board.variant = variant;
         board.apply_fen(fen_str)?;
 
         return Ok(board);
     }
 
     pub fn new_game() -> Board {
-        Self::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").expect("start position to be valid")
+        Self::new_variant_game(Variant::Standard)
+    }
+
+    pub fn new_variant_game(variant: Variant) -> Board {
+        Self::from_variant_fen(variant, variant.start_fen()).expect("start position to be valid")
+    }
+
+    pub fn new_with_odds(odds: Odds) -> Board {
+        Self::from_fen(odds.start_fen()).expect("odds position to be valid")
+    }
+
+    pub fn variant(&self) -> Variant {
+        return self.variant;
+    }
+
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+        self.update_attack_data();
+    }
+
+    pub fn builder() -> BoardBuilder {
+        return BoardBuilder::new();
     }
 
     pub fn apply_fen(&mut self, fen_str: &str) -> Result<(), FenError> {
+        return self.apply_position(fen::parse_fen(fen_str)?);
+    }
+
+    fn apply_position(&mut self, fen: FenResult) -> Result<(), FenError> {
+        fen::validate(&fen, self.variant)?;
+
+        let previous = self.clone();
+
         self.turn = Color::White;
         self.winner = None;
 
@@ -266,8 +377,6 @@ impl Board {
 
         self.last_moves.clear();
 
-        let fen = fen::parse_fen(fen_str)?;
-
         for item in fen.pieces {
             self.set(item);
         }
@@ -284,15 +393,157 @@ impl Board {
         self.turn = fen.turn;
         self.en_passant_square = fen.en_passant_square;
 
+        self.halfmove_clock = fen.halfmove_clock;
+        self.fullmove_number = fen.fullmove_number;
+
         self.white.castling_rights.queenside = fen.castling_rules.white_queenside;
         self.white.castling_rights.kingside = fen.castling_rules.white_kingside;
 
         self.black.castling_rights.queenside = fen.castling_rules.black_queenside;
         self.black.castling_rights.kingside = fen.castling_rules.black_kingside;
 
+        // a right without the king and the rook on their home squares could never be used
+        for color in [Color::White, Color::Black] {
+            let (queenside, kingside) = (self.castling_pieces_home(color, false), self.castling_pieces_home(color, true));
+            let rights = &mut self.side_mut(color).castling_rights;
+
+            rights.queenside &= queenside;
+            rights.kingside &= kingside;
+        }
+
+        if self.variant.has_checks() && self.side(self.turn.invert()).checked() {
+            *self = previous;
+            return Err(FenError::OpponentInCheck);
+        }
+
+        self.hash = zobrist::hash(self);
+
         return Ok(());
     }
 
+    pub fn to_fen(&self) -> String {
+        return fen::to_fen(self);
+    }
+
+    // the changes from this board to the other one, including the state derived from the pieces
+    pub fn diff(&self, other: &Board) -> BoardDiff {
+        return diff::diff(self, other);
+    }
+
+    // panics when the redundant state kept for move generation disagrees with itself, exec_move and undo_move
+    // check this in debug builds so a desync shows up at the move that caused it
+    pub fn assert_valid(&self) {
+        if let Some(inconsistency) = self.find_inconsistency() {
+            panic!("invalid board after {:?}: {}\n{}", self.last_move(), inconsistency, self);
+        }
+    }
+
+    fn find_inconsistency(&self) -> Option<String> {
+        for color in [Color::White, Color::Black] {
+            let side = self.side(color);
+            let mut looked_up = BoardSide::new();
+
+            for (offset, piece_type) in side.lookup.iter().enumerate() {
+                if let Some(piece_type) = piece_type {
+                    looked_up.set(Coord::from_offset(offset), *piece_type);
+                }
+            }
+
+            let pieces = [
+                (PieceType::Pawn, looked_up.pawns, side.pawns),
+                (PieceType::Rook, looked_up.rooks, side.rooks),
+                (PieceType::Knight, looked_up.knights, side.knights),
+                (PieceType::Bishop, looked_up.bishops, side.bishops),
+                (PieceType::Queen, looked_up.queens, side.queens),
+                (PieceType::King, looked_up.king, side.king),
+            ];
+
+            for (piece_type, expected, actual) in pieces {
+                if expected != actual {
+                    return Some(format!("{color} lookup holds {piece_type:?}s on {:016x} but the bitboard {:016x}", expected.0, actual.0));
+                }
+            }
+
+            if looked_up.all != side.all {
+                return Some(format!("{color} lookup holds pieces on {:016x} but all is {:016x}", looked_up.all.0, side.all.0));
+            }
+
+            let kings = side.king.count_ones() as usize;
+
+            if self.variant.king_count(color).is_some_and(|expected| expected != kings) {
+                return Some(format!("{color} has {kings} kings"));
+            }
+
+            for (kingside, right) in [(false, side.castling_rights.queenside), (true, side.castling_rights.kingside)] {
+                if right && !self.castling_pieces_home(color, kingside) {
+                    return Some(format!("{color} may castle {} without its king and rook at home", if kingside { "kingside" } else { "queenside" }));
+                }
+            }
+        }
+
+        if self.all != self.white.all | self.black.all || self.white.all & self.black.all != BitBoard::new(0) {
+            return Some(format!("all {:016x} does not match the sides", self.all.0));
+        }
+
+        let hash = zobrist::hash(self);
+
+        if self.hash != hash {
+            return Some(format!("hash {:016x} does not match the recomputed {hash:016x}", self.hash));
+        }
+
+        return None;
+    }
+
+    fn castling_pieces_home(&self, color: Color, kingside: bool) -> bool {
+        let row = match color {
+            Color::White => 1,
+            Color::Black => 8,
+        };
+
+        let side = self.side(color);
+        let rook = Coord::new(if kingside { 'h' } else { 'a' }, row);
+
+        return side.lookup(Coord::new('e', row)) == Some(PieceType::King) && side.lookup(rook) == Some(PieceType::Rook);
+    }
+
+    pub fn snapshot(&self) -> BoardSnapshot {
+        BoardSnapshot {
+            turn: self.turn,
+
+            all: self.all,
+            white: self.white.clone(),
+            black: self.black.clone(),
+
+            winner: self.winner,
+
+            en_passant_square: self.en_passant_square,
+
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+
+            hash: self.hash,
+            ply: self.last_moves.len(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &BoardSnapshot) {
+        self.turn = snapshot.turn;
+
+        self.all = snapshot.all;
+        self.white.clone_from(&snapshot.white);
+        self.black.clone_from(&snapshot.black);
+
+        self.winner = snapshot.winner;
+
+        self.en_passant_square = snapshot.en_passant_square;
+
+        self.halfmove_clock = snapshot.halfmove_clock;
+        self.fullmove_number = snapshot.fullmove_number;
+
+        self.hash = snapshot.hash;
+        self.last_moves.truncate(snapshot.ply);
+    }
+
     pub fn pieces(&self) -> Vec<Piece> {
         let mut pieces: Vec<Piece> = Vec::new();
 
@@ -327,6 +578,27 @@ impl Board {
         return &self.all;
     }
 
+    pub fn halfmove_clock(&self) -> u32 {
+        return self.halfmove_clock;
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        return self.fullmove_number;
+    }
+
+    // whether the position occurred before since the last capture or pawn move, only positions with the same
+    // side to move are compared
+    pub fn is_repetition(&self) -> bool {
+        return self
+            .last_moves
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .skip(1)
+            .step_by(2)
+            .any(|last_move| last_move.hash == self.hash);
+    }
+
     pub fn white_checked(&self) -> bool {
         return self.white.checked();
     }
@@ -339,10 +611,53 @@ impl Board {
         return self.winner;
     }
 
+    pub fn last_move(&self) -> Option<Move> {
+        return self.last_moves.last().and_then(|last_move| last_move.mv);
+    }
+
+    // every position from the start of the recorded history up to this one
+    pub fn history(&self) -> Vec<Board> {
+        let mut board = self.clone();
+        let mut positions = vec![board.clone()];
+
+        while !board.last_moves.is_empty() && board.undo_move().is_ok() {
+            positions.push(board.clone());
+        }
+
+        positions.reverse();
+
+        return positions;
+    }
+
     pub fn en_passant_square(&self) -> Option<Coord> {
         return self.en_passant_square;
     }
 
+    pub fn en_passant_square_for(&self, mode: EnPassantMode) -> Option<Coord> {
+        let square = self.en_passant_square?;
+
+        if mode == EnPassantMode::Fen {
+            return Some(square);
+        }
+
+        let rank_delta = match self.turn {
+            Color::White => -1,
+            Color::Black => 1,
+        };
+
+        let pawns = self.turning_side().pawns();
+        let capturable = [-1, 1]
+            .into_iter()
+            .filter_map(|file_delta| square.mv(file_delta, rank_delta))
+            .any(|from| pawns.is_set(from));
+
+        return capturable.then_some(square);
+    }
+
+    pub fn hash(&self) -> u64 {
+        return self.hash;
+    }
+
     pub fn lookup(&self, coord: Coord) -> Option<PieceType> {
         if let Some(p) = self.white.lookup(coord) {
             return Some(p);
@@ -355,6 +670,14 @@ impl Board {
         return None;
     }
 
+    pub fn piece_at(&self, coord: Coord) -> Option<Piece> {
+        if let Some(piece_type) = self.white.lookup(coord) {
+            return Some(Piece::new(coord, piece_type, Color::White));
+        }
+
+        return self.black.lookup(coord).map(|piece_type| Piece::new(coord, piece_type, Color::Black));
+    }
+
     pub fn turning_side(&self) -> &BoardSide {
         return self.side(self.turn());
     }
@@ -405,25 +728,38 @@ impl Board {
 
     pub fn exec_move(&mut self, mv: &Move) -> Result<(), MoveErr> {
         let mut last_move = LastMove {
-            mv: mv.clone(),
+            mv: Some(*mv),
             captured_piece: None,
             en_passant_square: self.en_passant_square,
             white_castling_rights: self.side(Color::White).castling_rights.clone(),
             black_castling_rights: self.side(Color::Black).castling_rights.clone(),
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
         };
 
         if !self.all.is_set(mv.from) {
             return Err(MoveErr::NoPieceAt(mv.from));
         }
 
+        let royal_king = self.variant.has_checks();
         let opponent = self.opponent_side_mut();
 
         if opponent.all.is_set(mv.from) {
             return Err(MoveErr::CannotMoveOpponentPiece);
         }
 
+        if mv.en_passant && self.en_passant_victim(mv.to).is_none() {
+            return Err(MoveErr::InvalidEnPassant(mv.to));
+        }
+
+        let mover = self.turn;
+        let mut hash = self.hash ^ zobrist::rights(self) ^ zobrist::black_to_move();
+        let opponent = self.opponent_side_mut();
+
         if opponent.all.is_set(mv.to) {
-            last_move.captured_piece = Some(opponent.capture(mv.to)?);
+            let captured = opponent.capture(mv.to, royal_king)?;
+            hash ^= zobrist::piece(mover.invert(), captured, mv.to);
+            last_move.captured_piece = Some(captured);
         }
 
         let side = self.turning_side_mut();
@@ -437,14 +773,33 @@ impl Board {
         }
 
         let piece_type = self.mv(mv.from, mv.to).ok_or(MoveErr::NoPieceAt(mv.from))?;
+        let placed = if mv.promotion { mv.promote_to } else { piece_type };
+        hash ^= zobrist::piece(mover, piece_type, mv.from) ^ zobrist::piece(mover, placed, mv.to);
+
+        if let Some((from, to)) = self.exec_castling(&mv) {
+            hash ^= zobrist::piece(mover, PieceType::Rook, from) ^ zobrist::piece(mover, PieceType::Rook, to);
+        }
 
-        self.exec_castling(&mv);
         self.set_castling_rights(&mv, piece_type);
 
         self.exec_promotion(&mv);
-        self.exec_en_passant(&mv);
+
+        if let Some(victim) = self.exec_en_passant(&mv) {
+            hash ^= zobrist::piece(mover.invert(), PieceType::Pawn, victim);
+        }
+
         self.set_enpassant_square(piece_type, &mv);
 
+        if piece_type == PieceType::Pawn || last_move.captured_piece.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if self.turn == Color::Black {
+            self.fullmove_number += 1;
+        }
+
         self.set_pin_rays(Color::White);
         self.set_pin_rays(Color::Black);
 
@@ -453,10 +808,16 @@ impl Board {
 
         self.turn = self.turn.invert();
 
+        // finding the winner plays moves on copies of the board, which need the hash of the position already
+        self.hash = hash ^ zobrist::rights(self);
+
         self.set_checkmate();
 
         self.last_moves.push(last_move);
 
+        #[cfg(debug_assertions)]
+        self.assert_valid();
+
         return Ok(());
     }
 
@@ -467,10 +828,23 @@ impl Board {
             en_passant_square,
             white_castling_rights,
             black_castling_rights,
+            halfmove_clock,
+            hash,
         }) = self.last_moves.pop()
         {
             self.winner = None;
             self.turn = self.turn.invert();
+            self.hash = hash;
+            self.halfmove_clock = halfmove_clock;
+
+            let Some(mv) = mv else {
+                self.en_passant_square = en_passant_square;
+                return Ok(());
+            };
+
+            if self.turn == Color::Black {
+                self.fullmove_number -= 1;
+            }
 
             self.mv(mv.to, mv.from).ok_or(MoveErr::NoPieceAt(mv.to))?;
 
@@ -499,7 +873,7 @@ impl Board {
                     Color::White => mv.to.mv(0, -1),
                     Color::Black => mv.to.mv(0, 1),
                 }
-                .expect("victim coord to be valid");
+                .ok_or(MoveErr::InvalidEnPassant(mv.to))?;
 
                 self.all.set(victim_coord);
                 self.opponent_side_mut().set(victim_coord, PieceType::Pawn);
@@ -519,11 +893,47 @@ impl Board {
 
             self.side_mut(Color::White).castling_rights = white_castling_rights;
             self.side_mut(Color::Black).castling_rights = black_castling_rights;
+
+            #[cfg(debug_assertions)]
+            self.assert_valid();
+        }
+
+        return Ok(());
+    }
+
+    pub fn make_null_move(&mut self) -> Result<(), MoveErr> {
+        if self.turning_side().checked() {
+            return Err(MoveErr::NullMoveInCheck);
         }
 
+        self.last_moves.push(LastMove {
+            mv: None,
+            captured_piece: None,
+            en_passant_square: self.en_passant_square,
+            white_castling_rights: self.side(Color::White).castling_rights.clone(),
+            black_castling_rights: self.side(Color::Black).castling_rights.clone(),
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+        });
+
+        let hash = self.hash ^ zobrist::rights(self) ^ zobrist::black_to_move();
+
+        self.en_passant_square = None;
+        self.turn = self.turn.invert();
+        self.hash = hash ^ zobrist::rights(self);
+
+        debug_assert_eq!(zobrist::hash(self), self.hash, "null move hash diverged");
+
         return Ok(());
     }
 
+    pub fn undo_null_move(&mut self) -> Result<(), MoveErr> {
+        match self.last_moves.last() {
+            Some(LastMove { mv: None, .. }) => self.undo_move(),
+            _ => Err(MoveErr::NotANullMove),
+        }
+    }
+
     fn set_castling_rights(&mut self, mv: &Move, piece_type: PieceType) {
         if piece_type == PieceType::King {
             self.turning_side_mut().castling_rights.queenside = false;
@@ -547,9 +957,10 @@ impl Board {
         }
     }
 
-    fn exec_castling(&mut self, mv: &Move) {
+    // the rook's squares are returned so the hash can follow it
+    fn exec_castling(&mut self, mv: &Move) -> Option<(Coord, Coord)> {
         if !mv.castling {
-            return;
+            return None;
         }
 
         let is_left = mv.to.column() == 'c';
@@ -562,7 +973,7 @@ impl Board {
         let from = Coord::new(from_col, row);
         let to = Coord::new(to_col, row);
 
-        self.mv(from, to);
+        return self.mv(from, to).map(|_| (from, to));
     }
 
     fn set_enpassant_square(&mut self, piece_type: PieceType, mv: &Move) {
@@ -591,20 +1002,26 @@ impl Board {
         side.set(mv.to, mv.promote_to);
     }
 
-    fn exec_en_passant(&mut self, mv: &Move) {
+    fn exec_en_passant(&mut self, mv: &Move) -> Option<Coord> {
         if !mv.en_passant {
-            return;
+            return None;
         }
 
+        let victim = self.en_passant_victim(mv.to)?;
+
+        self.all.unset(victim);
+        self.opponent_side_mut().unset(victim);
+
+        return Some(victim);
+    }
+
+    fn en_passant_victim(&self, to: Coord) -> Option<Coord> {
         let victim = match self.turn() {
-            Color::White => mv.to.mv(0, -1),
-            Color::Black => mv.to.mv(0, 1),
-        };
+            Color::White => to.mv(0, -1),
+            Color::Black => to.mv(0, 1),
+        }?;
 
-        if let Some(victim) = victim {
-            self.all.unset(victim);
-            self.opponent_side_mut().unset(victim);
-        }
+        return self.opponent_side().pawns().is_set(victim).then_some(victim);
     }
 
     fn set_check(&mut self, color: Color) {
@@ -612,18 +1029,18 @@ impl Board {
         let side = self.side(color);
         let opponent_side = self.side(color.invert());
 
-        let is_checked = side.attacked_squares() & opponent_side.king() != 0.into();
+        let is_checked = self.variant.has_checks() && side.attacked_squares() & opponent_side.king() != 0.into();
 
-        if !is_checked {
+        let Some(king_coord) = opponent_side.king_coord().filter(|_| is_checked) else {
             self.side_mut(opponent_color).check_targets = 0.into();
             return;
-        }
+        };
 
         let mut check_targets = BitBoard::new(0);
 
         for direction in moves::KING_MOVES {
             let mut ray = BitBoard::new(0);
-            let mut coord = opponent_side.king_coord().clone();
+            let mut coord = king_coord;
 
             let is_orthogonal = is_orthogonal(direction);
             let is_diagonal = is_diagonal(direction);
@@ -658,8 +1075,9 @@ impl Board {
 
         let king = *opponent_side.king();
 
+        // a knight checks even when it is pinned or its own king is in check, so its moves must not be filtered
         for knight in side.knights() {
-            if moves::get_move_mask_from(color, knight, self) & king == king {
+            if moves::KNIGHT_MOVE_MAP[knight.offset()] & king == king {
                 check_targets.set(knight);
             }
         }
@@ -670,27 +1088,77 @@ impl Board {
             }
         }
 
-        if moves::get_move_mask_from(color, side.king_coord(), self) & king == king {
-            check_targets |= *side.king();
+        for own_king in side.king() {
+            if moves::get_move_mask_from(color, own_king, self) & king == king {
+                check_targets |= *side.king();
+            }
         }
 
         self.side_mut(opponent_color).check_targets = check_targets;
     }
 
     fn set_checkmate(&mut self) {
+        self.winner = match self.variant {
+            Variant::Standard | Variant::Horde => self.checkmate_winner(),
+            Variant::Antichess => self.antichess_winner(),
+            Variant::RacingKings => self.racing_kings_winner(),
+        };
+    }
+
+    fn checkmate_winner(&self) -> Option<Color> {
+        // in horde white has no king and loses once all of its pieces are captured
+        if *self.turning_side().all() == 0.into() {
+            return Some(self.turn().invert());
+        }
+
         if !self.turning_side().checked() {
-            self.winner = None;
-            return;
+            return None;
         }
 
-        let moves = moves::get_move_mask(self.turn(), self);
+        if moves::get_move_mask(self.turn(), self) != 0.into() {
+            return None;
+        }
 
-        if moves != 0.into() {
-            self.winner = None;
-            return;
+        return Some(self.turn().invert());
+    }
+
+    fn antichess_winner(&self) -> Option<Color> {
+        if moves::get_move_mask(self.turn(), self) != 0.into() {
+            return None;
         }
 
-        self.winner = Some(self.turn().invert());
+        return Some(self.turn());
+    }
+
+    fn racing_kings_winner(&self) -> Option<Color> {
+        let white_home = self.white.king & RANK_8 != 0.into();
+        let black_home = self.black.king & RANK_8 != 0.into();
+
+        return match (white_home, black_home) {
+            (true, true) | (false, false) => None,
+            (false, true) => Some(Color::Black),
+            (true, false) if self.turn() == Color::White => Some(Color::White),
+            (true, false) => {
+                // black gets one more move to draw by also reaching the last rank
+                let can_draw = moves::get_moves(Color::Black, self)
+                    .iter()
+                    .any(|mv| self.black.king.is_set(mv.from) && RANK_8.is_set(mv.to));
+
+                if can_draw {
+                    None
+                } else {
+                    Some(Color::White)
+                }
+            }
+        };
+    }
+
+    pub fn is_racing_kings_finished(&self) -> bool {
+        if self.variant != Variant::RacingKings {
+            return false;
+        }
+
+        return self.winner.is_some() || (self.turn() == Color::White && self.white.king & RANK_8 != 0.into());
     }
 
     fn set_attacked_squares(&mut self, color: Color) {
@@ -698,12 +1166,17 @@ impl Board {
     }
 
     fn set_pin_rays(&mut self, color: Color) {
+        let has_checks = self.variant.has_checks();
         let (side, opponent_side) = match color {
             Color::White => (&mut self.white, &self.black),
             Color::Black => (&mut self.black, &self.white),
         };
 
-        let king = side.king_coord();
+        let Some(king) = side.king.into_iter().next().filter(|_| has_checks) else {
+            side.pin_rays_count = 0;
+            return;
+        };
+
         let mut i = 0;
 
         for queen in opponent_side.queens() {
@@ -759,50 +1232,12 @@ impl Board {
     }
 }
 
+// `{:#}` renders unicode glyphs, which reads better in debug logs
 impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("  ")?;
+        let options = if f.alternate() { RenderOptions::unicode() } else { RenderOptions::new() };
 
-        for c in 'A'..='H' {
-            write!(f, " {}", c)?;
-        }
-
-        f.write_str("\n\n")?;
-
-        let pieces = self.pieces();
-
-        for row in (0..=7).rev() {
-            write!(f, "{} ", row + 1)?;
-
-            for column in 0..=7 {
-                let coord = Coord::from_xy(column, row);
-
-                if let Some(piece) = pieces.iter().find(|p| p.coord == coord) {
-                    let c = match (piece.piece_type, piece.color) {
-                        (PieceType::Pawn, Color::White) => "P",
-                        (PieceType::Rook, Color::White) => "R",
-                        (PieceType::Knight, Color::White) => "N",
-                        (PieceType::Bishop, Color::White) => "B",
-                        (PieceType::Queen, Color::White) => "Q",
-                        (PieceType::King, Color::White) => "K",
-                        (PieceType::Pawn, Color::Black) => "p",
-                        (PieceType::Rook, Color::Black) => "r",
-                        (PieceType::Knight, Color::Black) => "n",
-                        (PieceType::Bishop, Color::Black) => "b",
-                        (PieceType::Queen, Color::Black) => "q",
-                        (PieceType::King, Color::Black) => "k",
-                    };
-
-                    write!(f, " {}", c)?;
-                } else {
-                    f.write_str(" .")?;
-                }
-            }
-
-            f.write_str("\n")?;
-        }
-
-        return Ok(());
+        return f.write_str(&render::render(self, &options));
     }
 }
 
@@ -810,8 +1245,39 @@ impl Display for Board {
 mod tests {
 
     use super::*;
+    use crate::{pgn::read_pgn, MoveList};
+    use proptest::{prelude::*, test_runner::TestCaseError};
     use rayon::prelude::*;
 
+    #[test]
+    fn round_trips_through_json() {
+        let board = read_pgn("1. e4 d5 2. exd5 Qxd5 3. Nc3").unwrap().board;
+        let json = serde_json::to_string(&board).unwrap();
+        let mut restored: Board = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(board, restored);
+        assert_eq!(6, restored.history().len());
+
+        restored.undo_move().unwrap();
+        assert_eq!(board.history()[4], restored);
+
+        let bare = json.replace(&board.history()[0].to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(serde_json::from_str::<Board>(&bare).is_err());
+
+        // moves exec_move would carry out but no game can contain
+        let jump = Move::new(Coord::new('c', 1), Coord::new('h', 6));
+        let king_promotion = Move {
+            promotion: true,
+            promote_to: PieceType::King,
+            ..Move::new(Coord::new('e', 2), Coord::new('e', 4))
+        };
+
+        for mv in [jump, king_promotion] {
+            let record = serde_json::json!({ "startFen": Board::new_game().to_fen(), "moves": [mv] });
+            assert!(serde_json::from_value::<Board>(record).is_err());
+        }
+    }
+
     #[test]
     fn move_count_depth_1() {
         test_move_count_board(&mut Board::new_game(), 1, 20)
@@ -912,6 +1378,336 @@ mod tests {
         test_move_count_new_game_moves(vec![("d2", "d4"), ("e7", "e5"), ("d4", "d5"), ("e8", "e7")], 2, 603);
     }
 
+    #[test]
+    fn restore_snapshot() {
+        let mut board = Board::from_fen(CPW_POSITION_2).unwrap();
+        let start = board.clone();
+        let snapshot = board.snapshot();
+
+        for (from, to) in [("e5", "f7"), ("e8", "f7"), ("e1", "g1")] {
+            let mv = Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap());
+            board.exec_move(&mv).unwrap();
+        }
+
+        board.restore(&snapshot);
+
+        assert_eq!(start, board);
+    }
+
+    #[test]
+    fn restore_snapshot_keeps_earlier_history() {
+        let mut board = Board::new_game();
+        board.exec_move(&Move::new(Coord::new('e', 2), Coord::new('e', 4))).unwrap();
+
+        let snapshot = board.snapshot();
+
+        board.exec_move(&Move::new(Coord::new('e', 7), Coord::new('e', 5))).unwrap();
+        board.restore(&snapshot);
+        board.undo_move().unwrap();
+
+        assert_eq!(Board::new_game(), board);
+    }
+
+    #[test]
+    fn pinned_knight_gives_check() {
+        assert!(Board::from_fen("k7/b7/1N6/8/3K4/8/8/8 b - -").unwrap().black_checked());
+
+        // fxg6 discovers a check on the white king, undoing it has to restore the knight check on black
+        let mut board = Board::from_fen("1n2brnk/r1q2pp1/6NB/pp2p3/2B1P3/PP1P4/2P2KPP/RRQ5 b - -").unwrap();
+        let before = board.clone();
+
+        board.exec_move(&Move::new(Coord::new('f', 7), Coord::new('g', 6))).unwrap();
+        assert!(board.white_checked());
+
+        board.undo_move().unwrap();
+        assert_eq!(before.side(Color::Black).check_targets(), board.side(Color::Black).check_targets());
+    }
+
+    #[test]
+    fn detects_repetitions() {
+        let mut board = Board::new_game();
+        let shuffle = [("g1", "f3"), ("g8", "f6"), ("f3", "g1"), ("f6", "g8")];
+
+        for (i, (from, to)) in shuffle.iter().enumerate() {
+            assert!(!board.is_repetition(), "no repetition before move {i}");
+            board.exec_move(&Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap())).unwrap();
+        }
+
+        assert!(board.is_repetition());
+
+        board.exec_move(&Move::new(Coord::new('e', 2), Coord::new('e', 4))).unwrap();
+
+        assert!(!board.is_repetition());
+    }
+
+    #[test]
+    fn diff_between_boards() {
+        let start = Board::new_game();
+        let mut board = start.clone();
+
+        assert!(start.diff(&board).is_empty());
+
+        for (from, to) in [("e2", "e4"), ("d7", "d5"), ("e4", "d5"), ("e8", "d7")] {
+            board.exec_move(&Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap())).unwrap();
+        }
+
+        let diff = start.diff(&board);
+        let moved = diff.moved.iter().map(|moved| (moved.from.to_string(), moved.piece.coord.to_string())).collect::<Vec<_>>();
+
+        assert_eq!(vec![("e2".to_string(), "d5".to_string()), ("e8".to_string(), "d7".to_string())], moved);
+        assert_eq!(vec!["p d7".to_string()], diff.removed.iter().map(|piece| format!("{} {}", piece.to_fen_char(), piece.coord)).collect::<Vec<_>>());
+        assert!(diff.added.is_empty());
+        assert!(diff.changes.iter().any(|change| change.field == "castling" && change.before == "KQkq" && change.after == "KQ"));
+
+        let rendered = diff.to_string();
+
+        assert!(rendered.contains("5  .  .  . [P] .  .  .  . "));
+        assert!(rendered.contains("~ k e8 -> d7"));
+    }
+
+    #[test]
+    #[should_panic(expected = "White lookup holds Pawns")]
+    fn assert_valid_catches_desync() {
+        let mut board = Board::new_game();
+        board.white.pawns.set(Coord::new('e', 4));
+        board.white.all.set(Coord::new('e', 4));
+
+        board.assert_valid();
+    }
+
+    #[test]
+    fn drops_unusable_castling_rights() {
+        let board = Board::from_fen("r3k3/8/8/8/8/8/8/4K2R w KQkq - 0 1").unwrap();
+
+        assert_eq!("r3k3/8/8/8/8/8/8/4K2R w Kq - 0 1", board.to_fen());
+        board.assert_valid();
+    }
+
+    #[test]
+    fn null_move_round_trip() {
+        let mut board = Board::from_fen("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3").unwrap();
+        let start = board.clone();
+
+        board.make_null_move().unwrap();
+
+        assert_eq!(Color::White, board.turn());
+        assert_eq!(None, board.en_passant_square());
+
+        board.undo_null_move().unwrap();
+
+        assert_eq!(start, board);
+    }
+
+    #[test]
+    fn hash_is_independent_of_move_order() {
+        let mut a = Board::new_game();
+        let mut b = Board::new_game();
+
+        for (from, to) in [("g1", "f3"), ("g8", "f6"), ("b1", "c3")] {
+            a.exec_move(&Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap())).unwrap();
+        }
+
+        for (from, to) in [("b1", "c3"), ("g8", "f6"), ("g1", "f3")] {
+            b.exec_move(&Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap())).unwrap();
+        }
+
+        assert_eq!(a.hash(), b.hash());
+        assert_ne!(Board::new_game().hash(), a.hash());
+    }
+
+    #[test]
+    fn hash_restored_on_undo() {
+        let mut board = Board::from_fen(CPW_POSITION_2).unwrap();
+        let hash = board.hash();
+
+        board.exec_move(&Move::castling(Coord::new('e', 1), Coord::new('g', 1))).unwrap();
+        board.make_null_move().unwrap();
+
+        assert_ne!(hash, board.hash());
+
+        board.undo_null_move().unwrap();
+        board.undo_move().unwrap();
+
+        assert_eq!(hash, board.hash());
+    }
+
+    #[test]
+    fn antichess_move_count_depth_3() {
+        test_move_count_board(&mut Board::new_variant_game(Variant::Antichess), 3, 8067);
+    }
+
+    #[test]
+    fn antichess_captures_are_forced() {
+        let mut board = Board::new_variant_game(Variant::Antichess);
+
+        board.exec_move(&Move::new(Coord::new('e', 2), Coord::new('e', 4))).unwrap();
+        board.exec_move(&Move::new(Coord::new('d', 7), Coord::new('d', 5))).unwrap();
+
+        let moves = moves::get_moves(board.turn(), &board);
+
+        assert_eq!(vec![Move::new(Coord::new('e', 4), Coord::new('d', 5))], moves);
+    }
+
+    #[test]
+    fn antichess_king_can_be_captured() {
+        let mut board = Board::from_variant_fen(Variant::Antichess, "8/8/8/8/8/8/1k6/K7 w - -").unwrap();
+
+        assert!(!board.white_checked());
+
+        board.exec_move(&Move::new(Coord::new('a', 1), Coord::new('b', 2))).unwrap();
+
+        assert_eq!(Some(Color::Black), board.winner());
+    }
+
+    #[test]
+    fn horde_move_count_depth_4() {
+        test_move_count_board(&mut Board::new_variant_game(Variant::Horde), 4, 23310);
+    }
+
+    #[test]
+    fn horde_first_rank_pawns_double_step() {
+        let board = Board::new_variant_game(Variant::Horde);
+        let moves = moves::get_moves(Color::White, &board);
+
+        assert_eq!(8, moves.len());
+        assert!(moves.contains(&Move::new(Coord::new('b', 5), Coord::new('b', 6))));
+    }
+
+    #[test]
+    fn horde_black_wins_by_capturing_all_pawns() {
+        let mut board = Board::from_variant_fen(Variant::Horde, "4k3/8/8/8/8/8/3P4/2q5 b - -").unwrap();
+
+        board.exec_move(&Move::new(Coord::new('c', 1), Coord::new('d', 2))).unwrap();
+
+        assert_eq!(Some(Color::Black), board.winner());
+    }
+
+    #[test]
+    fn horde_pawn_on_first_rank() {
+        let board = Board::from_variant_fen(Variant::Horde, "4k3/8/8/8/8/8/8/P7 w - -").unwrap();
+
+        let moves = moves::get_moves(Color::White, &board);
+
+        assert_eq!(
+            vec![Move::new(Coord::new('a', 1), Coord::new('a', 2)), Move::new(Coord::new('a', 1), Coord::new('a', 3))],
+            moves
+        );
+    }
+
+    #[test]
+    fn racing_kings_move_count_depth_3() {
+        test_move_count_board(&mut Board::new_variant_game(Variant::RacingKings), 3, 11264);
+    }
+
+    #[test]
+    fn racing_kings_forbids_checks() {
+        let board = Board::new_variant_game(Variant::RacingKings);
+
+        for mv in moves::get_moves(Color::White, &board) {
+            let mut board = board.clone();
+            board.exec_move(&mv).unwrap();
+
+            assert!(!board.black_checked(), "{mv} gives check");
+        }
+    }
+
+    #[test]
+    fn racing_kings_win_and_draw() {
+        let mut board = Board::from_fen("8/6K1/8/8/k7/8/8/8 w - -").unwrap();
+        board.set_variant(Variant::RacingKings);
+        board.exec_move(&Move::new(Coord::new('g', 7), Coord::new('g', 8))).unwrap();
+
+        assert_eq!(Some(Color::White), board.winner());
+        assert!(moves::get_moves(Color::Black, &board).is_empty());
+
+        let mut board = Board::from_fen("8/k5K1/8/8/8/8/8/8 w - -").unwrap();
+        board.set_variant(Variant::RacingKings);
+        board.exec_move(&Move::new(Coord::new('g', 7), Coord::new('g', 8))).unwrap();
+
+        assert_eq!(None, board.winner());
+
+        board.exec_move(&Move::new(Coord::new('a', 7), Coord::new('a', 8))).unwrap();
+
+        assert_eq!(None, board.winner());
+        assert!(moves::get_moves(Color::White, &board).is_empty());
+    }
+
+    #[test]
+    fn odds_positions() {
+        let pawn_and_move = Board::new_with_odds(Odds::PawnAndMove);
+        let knight = Board::new_with_odds(Odds::Knight);
+        let queen = Board::new_with_odds(Odds::Queen);
+
+        assert_eq!(None, pawn_and_move.lookup(Coord::new('f', 7)));
+        assert_eq!(Color::White, pawn_and_move.turn());
+        assert_eq!(None, knight.lookup(Coord::new('b', 1)));
+        assert_eq!(None, queen.lookup(Coord::new('d', 1)));
+        assert_eq!(20, moves::get_moves(Color::White, &pawn_and_move).len());
+    }
+
+    #[test]
+    fn move_counters() {
+        let mut board = Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3 7").unwrap();
+
+        board.exec_move(&Move::new(Coord::new('g', 1), Coord::new('f', 3))).unwrap();
+        assert_eq!((4, 7), (board.halfmove_clock(), board.fullmove_number()));
+
+        board.exec_move(&Move::new(Coord::new('e', 7), Coord::new('e', 5))).unwrap();
+        assert_eq!((0, 8), (board.halfmove_clock(), board.fullmove_number()));
+
+        board.undo_move().unwrap();
+        board.undo_move().unwrap();
+        assert_eq!((3, 7), (board.halfmove_clock(), board.fullmove_number()));
+    }
+
+    #[test]
+    fn opponent_in_check_is_rejected() {
+        let mut board = Board::new_game();
+
+        assert!(matches!(board.apply_fen("4k3/8/8/8/8/8/8/4K2r w - -"), Ok(())));
+        assert!(matches!(board.apply_fen("4k3/8/8/8/8/8/8/4K2r b - -"), Err(FenError::OpponentInCheck)));
+        assert_eq!(Board::from_fen("4k3/8/8/8/8/8/8/4K2r w - -").unwrap(), board);
+    }
+
+    #[test]
+    fn en_passant_square_modes() {
+        let mut board = Board::new_game();
+        board.exec_move(&Move::new(Coord::new('e', 2), Coord::new('e', 4))).unwrap();
+
+        assert_eq!(Some(Coord::new('e', 3)), board.en_passant_square_for(EnPassantMode::Fen));
+        assert_eq!(None, board.en_passant_square_for(EnPassantMode::Capturable));
+        assert_eq!(Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq -").unwrap().hash(), board.hash());
+
+        let board = Board::from_fen("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3").unwrap();
+
+        assert_eq!(Some(Coord::new('e', 3)), board.en_passant_square_for(EnPassantMode::Capturable));
+        assert_ne!(Board::from_fen("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq -").unwrap().hash(), board.hash());
+    }
+
+    #[test]
+    fn invalid_en_passant_is_rejected() {
+        let mut board = Board::new_game();
+        let mv = Move::en_passant(Coord::new('e', 2), Coord::new('d', 3));
+
+        assert!(matches!(board.exec_move(&mv), Err(MoveErr::InvalidEnPassant(_))));
+        assert_eq!(Board::new_game(), board);
+    }
+
+    #[test]
+    fn null_move_in_check() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K2r w - -").unwrap();
+
+        assert!(matches!(board.make_null_move(), Err(MoveErr::NullMoveInCheck)));
+    }
+
+    #[test]
+    fn undo_null_move_after_regular_move() {
+        let mut board = Board::new_game();
+        board.exec_move(&Move::new(Coord::new('e', 2), Coord::new('e', 4))).unwrap();
+
+        assert!(matches!(board.undo_null_move(), Err(MoveErr::NotANullMove)));
+    }
+
     const CPW_POSITION_2: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -";
     const CPW_POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -";
     const CPW_POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
@@ -1112,8 +1908,7 @@ mod tests {
 
         if start != *board {
             eprintln!("expected board to return to its original state");
-            eprintln!("start: {start:#?}");
-            eprintln!("end: {:#?}", *board);
+            eprint!("{}", start.diff(board));
         }
 
         assert_eq!(expected_move_count, count, "expected {expected_move_count}, got {count} moves");
@@ -1129,30 +1924,31 @@ mod tests {
             return 1;
         }
 
-        let moves = super::moves::get_moves(board.turn(), &board);
+        let mut moves = MoveList::new();
+        super::moves::get_moves_into(board.turn(), board, &mut moves);
 
         return moves
-            .into_par_iter()
+            .par_iter()
             .map(|mv| {
                 if mv.promotion {
                     return [
                         {
-                            let mut mv = mv.clone();
+                            let mut mv = *mv;
                             mv.promote_to = PieceType::Rook;
                             mv
                         },
                         {
-                            let mut mv = mv.clone();
+                            let mut mv = *mv;
                             mv.promote_to = PieceType::Bishop;
                             mv
                         },
                         {
-                            let mut mv = mv.clone();
+                            let mut mv = *mv;
                             mv.promote_to = PieceType::Knight;
                             mv
                         },
                         {
-                            let mut mv = mv.clone();
+                            let mut mv = *mv;
                             mv.promote_to = PieceType::Queen;
                             mv
                         },
@@ -1163,7 +1959,7 @@ mod tests {
                     })
                     .sum();
                 } else {
-                    return test_move_count_iter(&mut board.clone(), &mv, depth, log);
+                    return test_move_count_iter(&mut board.clone(), mv, depth, log);
                 }
             })
             .sum();
@@ -1181,4 +1977,90 @@ mod tests {
         board.undo_move().unwrap();
         return c;
     }
+
+    // besides the redundant piece state, the attack data and hash kept incrementally must match a recompute,
+    // the squares attacked by the side to move are only refreshed once it moves and are left out
+    fn check_invariants(board: &Board) -> Result<(), String> {
+        if let Some(inconsistency) = board.find_inconsistency() {
+            return Err(inconsistency);
+        }
+
+        if board.hash != zobrist::hash(board) {
+            return Err(format!("hash {:016x} does not match the recomputed {:016x}", board.hash, zobrist::hash(board)));
+        }
+
+        let mut recomputed = board.clone();
+        recomputed.update_attack_data();
+
+        for color in [Color::White, Color::Black] {
+            let (side, expected) = (board.side(color), recomputed.side(color));
+
+            let stale_attacks = color != board.turn && side.attacked_squares != expected.attacked_squares;
+
+            if stale_attacks || side.check_targets != expected.check_targets || side.pin_rays() != expected.pin_rays() {
+                return Err(format!("stale attack data for {color}\n{}", board.diff(&recomputed)));
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn check_restored(before: &Board, after: &Board) -> Result<(), String> {
+        let unmaintained = format!("{} attacked squares", after.turn.to_string().to_lowercase());
+        let mut diff = before.diff(after);
+
+        diff.changes.retain(|change| change.field != unmaintained);
+
+        return if diff.is_empty() { Ok(()) } else { Err(diff.to_string()) };
+    }
+
+    const PLAYOUT_POSITIONS: [&str; 6] = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+        CPW_POSITION_2,
+        CPW_POSITION_3,
+        CPW_POSITION_4,
+        CPW_POSITION_5,
+        CPW_POSITION_6,
+    ];
+
+    proptest! {
+        // random legal games, every move is checked on its own and undone and replayed before the game goes on
+        #[test]
+        fn make_unmake_invariants(position in 0..PLAYOUT_POSITIONS.len(), choices in prop::collection::vec(any::<u16>(), 1..80)) {
+            let mut board = Board::from_fen(PLAYOUT_POSITIONS[position]).unwrap();
+            let start = board.clone();
+
+            check_invariants(&board).map_err(TestCaseError::fail)?;
+
+            for choice in &choices {
+                let moves = moves::get_moves(board.turn(), &board);
+
+                if moves.is_empty() {
+                    break;
+                }
+
+                let mut mv = moves[*choice as usize % moves.len()];
+
+                if mv.promotion {
+                    mv.promote_to = [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight][*choice as usize % 4];
+                }
+
+                let before = board.clone();
+
+                board.exec_move(&mv).unwrap();
+                check_invariants(&board).map_err(|err| TestCaseError::fail(format!("after {mv}: {err}")))?;
+
+                board.undo_move().unwrap();
+                check_restored(&before, &board).map_err(|err| TestCaseError::fail(format!("undoing {mv} changed the board\n{err}")))?;
+
+                board.exec_move(&mv).unwrap();
+            }
+
+            while !board.last_moves.is_empty() {
+                board.undo_move().unwrap();
+            }
+
+            check_restored(&start, &board).map_err(|err| TestCaseError::fail(format!("undoing the game changed the board\n{err}")))?;
+        }
+    }
 }
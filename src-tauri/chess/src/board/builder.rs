@@ -0,0 +1,173 @@
+use crate::{
+    fen::{self, CastlingRules, FenError, FenResult},
+    Color, Coord, Piece, PieceType, Variant,
+};
+
+use super::Board;
+
+// sets up a position piece by piece, build() checks it the same way a FEN string is checked,
+// castling rights without the king and the rook on their home squares are dropped like they are there
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    variant: Variant,
+    squares: [Option<(Color, PieceType)>; 64],
+    turn: Color,
+    castling: [(bool, bool); 2],
+    en_passant_square: Option<Coord>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
+impl BoardBuilder {
+    // an empty board with white to move and no castling rights
+    pub fn new() -> Self {
+        BoardBuilder {
+            variant: Variant::Standard,
+            squares: [None; 64],
+            turn: Color::White,
+            castling: [(false, false); 2],
+            en_passant_square: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    // starts from an existing position, the moves that led to it are not kept
+    pub fn from_board(board: &Board) -> Self {
+        let mut builder = Self::new().variant(board.variant()).turn(board.turn()).en_passant(board.en_passant_square());
+
+        for piece in board.pieces() {
+            builder = builder.piece(piece.coord, piece.piece_type, piece.color);
+        }
+
+        for color in [Color::White, Color::Black] {
+            let side = board.side(color);
+            builder = builder.castling(color, side.can_castle_queenside(), side.can_castle_kingside());
+        }
+
+        return builder.clocks(board.halfmove_clock(), board.fullmove_number());
+    }
+
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        return self;
+    }
+
+    // a piece on an occupied square replaces the one that was there
+    pub fn piece(mut self, coord: Coord, piece_type: PieceType, color: Color) -> Self {
+        self.squares[coord.offset()] = Some((color, piece_type));
+        return self;
+    }
+
+    pub fn remove(mut self, coord: Coord) -> Self {
+        self.squares[coord.offset()] = None;
+        return self;
+    }
+
+    pub fn turn(mut self, turn: Color) -> Self {
+        self.turn = turn;
+        return self;
+    }
+
+    pub fn castling(mut self, color: Color, queenside: bool, kingside: bool) -> Self {
+        self.castling[color as usize] = (queenside, kingside);
+        return self;
+    }
+
+    pub fn en_passant(mut self, square: Option<Coord>) -> Self {
+        self.en_passant_square = square;
+        return self;
+    }
+
+    pub fn clocks(mut self, halfmove_clock: u32, fullmove_number: u32) -> Self {
+        self.halfmove_clock = halfmove_clock;
+        self.fullmove_number = fullmove_number;
+        return self;
+    }
+
+    pub fn build(&self) -> Result<Board, FenError> {
+        if self.fullmove_number == 0 {
+            return Err(FenError::InvalidMoveCounter(self.fullmove_number.to_string()));
+        }
+
+        let pieces = self
+            .squares
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, square)| square.map(|(color, piece_type)| Piece::new(Coord::from_offset(offset), piece_type, color)))
+            .collect::<Vec<_>>();
+
+        if let Some(square) = self.en_passant_square.filter(|square| !fen::is_en_passant_square(*square, self.turn, &pieces)) {
+            return Err(FenError::InvalidEnPassantSquare(square.to_string()));
+        }
+
+        let (white, black) = (self.castling[Color::White as usize], self.castling[Color::Black as usize]);
+
+        let mut board = Board::empty();
+        board.variant = self.variant;
+        board.apply_position(FenResult {
+            pieces,
+            turn: self.turn,
+            castling_rules: CastlingRules {
+                white_queenside: white.0,
+                white_kingside: white.1,
+                black_queenside: black.0,
+                black_kingside: black.1,
+            },
+            en_passant_square: self.en_passant_square,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+        })?;
+
+        return Ok(board);
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coord(square: &str) -> Coord {
+        return Coord::from_str(square).unwrap();
+    }
+
+    #[test]
+    fn builds_and_validates() {
+        let board = Board::builder()
+            .piece(coord("e1"), PieceType::King, Color::White)
+            .piece(coord("h1"), PieceType::Rook, Color::White)
+            .piece(coord("a1"), PieceType::Rook, Color::White)
+            .piece(coord("e8"), PieceType::King, Color::Black)
+            .piece(coord("d4"), PieceType::Pawn, Color::Black)
+            .piece(coord("e4"), PieceType::Pawn, Color::White)
+            .turn(Color::Black)
+            .castling(Color::White, true, true)
+            .castling(Color::Black, true, true)
+            .en_passant(Some(coord("e3")))
+            .clocks(0, 12)
+            .build()
+            .unwrap();
+
+        // black has no rooks, so its rights are dropped
+        assert_eq!("4k3/8/8/8/3pP3/8/8/R3K2R b KQ e3 0 12", board.to_fen());
+        assert_eq!(board.to_fen(), BoardBuilder::from_board(&board).build().unwrap().to_fen());
+
+        let start = Board::new_game();
+        assert_eq!(start.to_fen(), BoardBuilder::from_board(&start).build().unwrap().to_fen());
+
+        let kings = Board::builder().piece(coord("e1"), PieceType::King, Color::White).piece(coord("e8"), PieceType::King, Color::Black);
+
+        assert!(matches!(kings.clone().piece(coord("e8"), PieceType::Queen, Color::Black).build(), Err(FenError::InvalidKingCount { .. })));
+        assert!(matches!(kings.clone().piece(coord("a8"), PieceType::Pawn, Color::White).build(), Err(FenError::PawnOnBackRank(_))));
+        assert!(kings.clone().piece(coord("e2"), PieceType::Rook, Color::White).turn(Color::Black).build().is_ok());
+        assert!(matches!(kings.clone().piece(coord("e2"), PieceType::Rook, Color::White).build(), Err(FenError::OpponentInCheck)));
+        assert!(matches!(kings.clone().en_passant(Some(coord("d6"))).build(), Err(FenError::InvalidEnPassantSquare(_))));
+        assert!(matches!(kings.clocks(0, 0).build(), Err(FenError::InvalidMoveCounter(_))));
+    }
+}
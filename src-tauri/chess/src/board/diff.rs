@@ -0,0 +1,189 @@
+use std::fmt::Display;
+
+use crate::{bitboard::BitBoard, Color, Coord, Piece, PieceType};
+
+use super::{Board, BoardSide};
+
+#[derive(Debug, Clone)]
+pub struct MovedPiece {
+    pub piece: Piece,
+    pub from: Coord,
+}
+
+// a difference in the state around the pieces, like the castling rights or the attack data kept per side
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+// what changed from one board to another, pieces that vanished from one square and appeared on another
+// of the same kind are reported as moved
+#[derive(Debug, Clone)]
+pub struct BoardDiff {
+    pub added: Vec<Piece>,
+    pub removed: Vec<Piece>,
+    pub moved: Vec<MovedPiece>,
+    pub changes: Vec<StateChange>,
+
+    squares: [Option<(Color, PieceType)>; 64],
+    changed: BitBoard,
+}
+
+impl BoardDiff {
+    pub fn is_empty(&self) -> bool {
+        return self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty() && self.changes.is_empty();
+    }
+}
+
+pub(super) fn diff(before: &Board, after: &Board) -> BoardDiff {
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = BitBoard::new(0);
+
+    for offset in 0..64 {
+        let (old, new) = (square(before, offset), square(after, offset));
+
+        if old == new {
+            continue;
+        }
+
+        let coord = Coord::from_offset(offset);
+        changed.set(coord);
+
+        if let Some((color, piece_type)) = old {
+            removed.push(Piece::new(coord, piece_type, color));
+        }
+
+        if let Some((color, piece_type)) = new {
+            added.push(Piece::new(coord, piece_type, color));
+        }
+    }
+
+    let mut moved = Vec::new();
+
+    removed.retain(|from: &Piece| {
+        let Some(index) = added.iter().position(|to| to.color == from.color && to.piece_type == from.piece_type) else {
+            return true;
+        };
+
+        moved.push(MovedPiece {
+            piece: added.remove(index),
+            from: from.coord,
+        });
+
+        return false;
+    });
+
+    let mut changes = Vec::new();
+    let mut compare = |field: &str, before: String, after: String| {
+        if before != after {
+            changes.push(StateChange { field: field.to_string(), before, after });
+        }
+    };
+
+    // the fen fields after the placement cover the turn, castling rights, en passant square and move counters
+    let fields = ["turn", "castling", "en passant", "halfmove clock", "fullmove number"];
+
+    for (field, (old, new)) in fields.iter().zip(before.to_fen().split(' ').skip(1).zip(after.to_fen().split(' ').skip(1))) {
+        compare(field, old.to_string(), new.to_string());
+    }
+
+    compare("hash", format!("{:016x}", before.hash), format!("{:016x}", after.hash));
+    compare("history", before.last_moves.len().to_string(), after.last_moves.len().to_string());
+    compare("winner", format!("{:?}", before.winner), format!("{:?}", after.winner));
+    compare("all", bits(&before.all), bits(&after.all));
+
+    for color in [Color::White, Color::Black] {
+        let (old, new) = (before.side(color), after.side(color));
+
+        for (name, get) in SIDE_FIELDS {
+            compare(&format!("{} {}", color.to_string().to_lowercase(), name), get(old), get(new));
+        }
+    }
+
+    return BoardDiff {
+        added,
+        removed,
+        moved,
+        changes,
+        squares: std::array::from_fn(|offset| square(after, offset)),
+        changed,
+    };
+}
+
+type SideField = (&'static str, fn(&BoardSide) -> String);
+
+const SIDE_FIELDS: [SideField; 10] = [
+    ("all", |side| bits(&side.all)),
+    ("pawns", |side| bits(&side.pawns)),
+    ("rooks", |side| bits(&side.rooks)),
+    ("knights", |side| bits(&side.knights)),
+    ("bishops", |side| bits(&side.bishops)),
+    ("queens", |side| bits(&side.queens)),
+    ("king", |side| bits(&side.king)),
+    ("attacked squares", |side| bits(&side.attacked_squares)),
+    ("check targets", |side| bits(&side.check_targets)),
+    ("pin rays", |side| side.pin_rays().iter().map(bits).collect::<Vec<_>>().join(" ")),
+];
+
+fn square(board: &Board, offset: usize) -> Option<(Color, PieceType)> {
+    return match (board.white.lookup[offset], board.black.lookup[offset]) {
+        (Some(piece_type), _) => Some((Color::White, piece_type)),
+        (None, Some(piece_type)) => Some((Color::Black, piece_type)),
+        (None, None) => None,
+    };
+}
+
+fn bits(bitboard: &BitBoard) -> String {
+    return format!("{:016x}", bitboard.0);
+}
+
+// the position after the change with every changed square in brackets, followed by a line per difference
+impl Display for BoardDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+
+        writeln!(f, "   a  b  c  d  e  f  g  h")?;
+
+        for row in (0..8).rev() {
+            write!(f, "{} ", row + 1)?;
+
+            for column in 0..8 {
+                let coord = Coord::from_xy(column, row);
+                let symbol = match self.squares[coord.offset()] {
+                    Some((color, piece_type)) => Piece::new(coord, piece_type, color).to_fen_char(),
+                    None => '.',
+                };
+
+                match self.changed.is_set(coord) {
+                    true => write!(f, "[{}]", symbol)?,
+                    false => write!(f, " {} ", symbol)?,
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        for piece in &self.added {
+            writeln!(f, "+ {} {}", piece.to_fen_char(), piece.coord)?;
+        }
+
+        for piece in &self.removed {
+            writeln!(f, "- {} {}", piece.to_fen_char(), piece.coord)?;
+        }
+
+        for moved in &self.moved {
+            writeln!(f, "~ {} {} -> {}", moved.piece.to_fen_char(), moved.from, moved.piece.coord)?;
+        }
+
+        for change in &self.changes {
+            writeln!(f, "{}: {} -> {}", change.field, change.before, change.after)?;
+        }
+
+        return Ok(());
+    }
+}
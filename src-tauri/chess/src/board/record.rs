@@ -0,0 +1,49 @@
+use anyhow::{anyhow, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::{moves, Move, Variant};
+
+use super::Board;
+
+// a board is stored as its start position and the moves played since, so undoing moves still works after loading it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct BoardRecord {
+    #[serde(default)]
+    variant: Variant,
+    start_fen: String,
+    moves: Vec<Move>,
+}
+
+impl From<Board> for BoardRecord {
+    fn from(board: Board) -> Self {
+        let positions = board.history();
+
+        BoardRecord {
+            variant: board.variant(),
+            start_fen: positions[0].to_fen(),
+            moves: positions.iter().skip(1).filter_map(Board::last_move).collect(),
+        }
+    }
+}
+
+impl TryFrom<BoardRecord> for Board {
+    type Error = Error;
+
+    fn try_from(record: BoardRecord) -> Result<Self, Self::Error> {
+        let mut board = Board::from_variant_fen(record.variant, &record.start_fen)?;
+
+        // a stored move is only replayed as the legal move it names, flags and promotion included
+        for mv in record.moves {
+            let legal = moves::resolve_move(&board, mv.from, mv.to, mv.promotion.then_some(mv.promote_to));
+
+            if legal != Some(mv) {
+                return Err(anyhow!("The move {mv} is illegal in the stored game"));
+            }
+
+            board.exec_move(&mv)?;
+        }
+
+        return Ok(board);
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+
+use crate::{expand_promotions, get_moves, Board, Color, Coord, EnPassantMode, Move, PieceType, Variant};
+
+use self::keys::RANDOM;
+
+mod keys;
+
+const ENTRY_SIZE: usize = 16;
+const CASTLING_KEYS: usize = 768;
+const EN_PASSANT_KEYS: usize = 772;
+const TURN_KEY: usize = 780;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum BookError {
+    #[error("A book of {0} bytes does not consist of 16 byte entries")]
+    InvalidLength(usize),
+}
+
+// one move of a polyglot book, the move is stored in the polyglot encoding and not as our Move::to_u16
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BookEntry {
+    pub key: u64,
+    pub mv: u16,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+// the entries are kept sorted by key, the way polyglot expects them on disk
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Book {
+    entries: Vec<BookEntry>,
+}
+
+impl Book {
+    pub fn new(mut entries: Vec<BookEntry>) -> Self {
+        entries.sort_by(|a, b| a.key.cmp(&b.key).then(b.weight.cmp(&a.weight)).then(a.mv.cmp(&b.mv)));
+        return Book { entries };
+    }
+
+    // every entry is 16 big-endian bytes: key, move, weight and learn
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BookError> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(BookError::InvalidLength(bytes.len()));
+        }
+
+        let entries = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|chunk| BookEntry {
+                key: u64::from_be_bytes(chunk[0..8].try_into().unwrap()),
+                mv: u16::from_be_bytes(chunk[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(chunk[10..12].try_into().unwrap()),
+                learn: u32::from_be_bytes(chunk[12..16].try_into().unwrap()),
+            })
+            .collect();
+
+        return Ok(Self::new(entries));
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.entries.len() * ENTRY_SIZE);
+
+        for entry in &self.entries {
+            bytes.extend_from_slice(&entry.key.to_be_bytes());
+            bytes.extend_from_slice(&entry.mv.to_be_bytes());
+            bytes.extend_from_slice(&entry.weight.to_be_bytes());
+            bytes.extend_from_slice(&entry.learn.to_be_bytes());
+        }
+
+        return bytes;
+    }
+
+    pub fn entries(&self) -> &[BookEntry] {
+        return &self.entries;
+    }
+
+    // the book moves that are legal in the position with their weights, heaviest first
+    pub fn moves(&self, board: &Board) -> Vec<(Move, u16)> {
+        if board.variant() != Variant::Standard {
+            return Vec::new();
+        }
+
+        let key = polyglot_key(board);
+        let start = self.entries.partition_point(|entry| entry.key < key);
+        let legal = expand_promotions(&get_moves(board.turn(), board));
+
+        return self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| legal.iter().find(|mv| encode_move(mv) == entry.mv).map(|mv| (*mv, entry.weight)))
+            .collect();
+    }
+
+    // a weighted random choice, the seed decides which move is taken
+    pub fn pick(&self, board: &Board, seed: u64) -> Option<Move> {
+        let moves = self.moves(board);
+        let total = moves.iter().map(|(_, weight)| *weight as u64).sum::<u64>();
+
+        if total == 0 {
+            return None;
+        }
+
+        let mut state = seed | 1;
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let mut target = state % total;
+
+        for (mv, weight) in moves {
+            if target < weight as u64 {
+                return Some(mv);
+            }
+
+            target -= weight as u64;
+        }
+
+        return None;
+    }
+}
+
+// moves are weighted by how they scored for the side that played them, two points for a win and one for a draw
+#[derive(Debug, Clone)]
+pub struct BookBuilder {
+    max_plies: usize,
+    scores: HashMap<(u64, u16), u64>,
+}
+
+impl BookBuilder {
+    pub fn new(max_plies: usize) -> Self {
+        BookBuilder { max_plies, scores: HashMap::new() }
+    }
+
+    // with a player color only the moves of that side are added, so the book holds one side of a repertoire,
+    // unfinished games and variants are skipped
+    pub fn add_game(&mut self, board: &Board, result: &str, player: Option<Color>) {
+        let winner = match result {
+            "1-0" => Some(Color::White),
+            "0-1" => Some(Color::Black),
+            "1/2-1/2" => None,
+            _ => return,
+        };
+
+        if board.variant() != Variant::Standard {
+            return;
+        }
+
+        let positions = board.history();
+
+        for pair in positions.windows(2).take(self.max_plies) {
+            let (before, after) = (&pair[0], &pair[1]);
+            let Some(mv) = after.last_move() else {
+                break;
+            };
+
+            if player.is_some_and(|player| player != before.turn()) {
+                continue;
+            }
+
+            let score = match winner {
+                Some(winner) if winner == before.turn() => 2,
+                Some(_) => 0,
+                None => 1,
+            };
+
+            *self.scores.entry((polyglot_key(before), encode_move(&mv))).or_default() += score;
+        }
+    }
+
+    // moves that never scored are left out, weights are scaled down when they do not fit into 16 bits
+    pub fn build(&self) -> Book {
+        let max = self.scores.values().copied().max().unwrap_or(0);
+        let scale = |score: u64| match max > u16::MAX as u64 {
+            true => (score * u16::MAX as u64 / max).max(1) as u16,
+            false => score as u16,
+        };
+
+        let entries = self
+            .scores
+            .iter()
+            .filter(|(_, score)| **score > 0)
+            .map(|(&(key, mv), &score)| BookEntry {
+                key,
+                mv,
+                weight: scale(score),
+                learn: 0,
+            })
+            .collect();
+
+        return Book::new(entries);
+    }
+}
+
+// the polyglot hash, which differs from Board::hash in its keys and in only counting the en passant square when
+// a pawn can take
+pub fn polyglot_key(board: &Board) -> u64 {
+    let mut key = 0;
+
+    for piece in board.pieces() {
+        let kind = 2 * piece_index(piece.piece_type) + (piece.color == Color::White) as usize;
+        key ^= RANDOM[64 * kind + piece.coord.offset()];
+    }
+
+    let rights = [
+        board.side(Color::White).can_castle_kingside(),
+        board.side(Color::White).can_castle_queenside(),
+        board.side(Color::Black).can_castle_kingside(),
+        board.side(Color::Black).can_castle_queenside(),
+    ];
+
+    for (index, right) in rights.into_iter().enumerate() {
+        if right {
+            key ^= RANDOM[CASTLING_KEYS + index];
+        }
+    }
+
+    if let Some(square) = board.en_passant_square_for(EnPassantMode::Capturable) {
+        key ^= RANDOM[EN_PASSANT_KEYS + square.column_index() as usize];
+    }
+
+    if board.turn() == Color::White {
+        key ^= RANDOM[TURN_KEY];
+    }
+
+    return key;
+}
+
+// the target in the low six bits and the origin above, castling is written as the king taking its own rook
+fn encode_move(mv: &Move) -> u16 {
+    let to = match mv.castling {
+        true if mv.to.column_index() > mv.from.column_index() => Coord::from_xy(7, mv.from.row_index()),
+        true => Coord::from_xy(0, mv.from.row_index()),
+        false => mv.to,
+    };
+
+    let promotion = match (mv.promotion, mv.promote_to) {
+        (false, _) => 0,
+        (true, PieceType::Knight) => 1,
+        (true, PieceType::Bishop) => 2,
+        (true, PieceType::Rook) => 3,
+        (true, _) => 4,
+    };
+
+    return to.offset() as u16 | (mv.from.offset() as u16) << 6 | promotion << 12;
+}
+
+fn piece_index(piece_type: PieceType) -> usize {
+    match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::read_pgn;
+
+    #[test]
+    fn polyglot_reference_keys() {
+        let references = [
+            ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b_9618_1691_fc9c),
+            ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c_9b50_fd11_4196),
+            ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a4_8b5a_8e47_ff78),
+            ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 1 3", 0x652a_607c_a3f2_42c1),
+            ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 2 4", 0x00fd_d303_c946_bdd9),
+            ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c81_23ea_7b06_7637),
+        ];
+
+        for (fen, key) in references {
+            assert_eq!(key, polyglot_key(&Board::from_fen(fen).unwrap()), "{fen}");
+        }
+    }
+
+    #[test]
+    fn builds_weighted_books() {
+        let games = [
+            ("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. O-O", "1-0"),
+            ("1. e4 c5", "0-1"),
+            ("1. d4 d5", "1/2-1/2"),
+            ("1. c4", "*"),
+        ];
+
+        let mut builder = BookBuilder::new(8);
+
+        for (moves, result) in games {
+            builder.add_game(&read_pgn(moves).unwrap().board, result, None);
+        }
+
+        let book = Book::from_bytes(&builder.build().to_bytes()).unwrap();
+        let start = Board::new_game();
+        let e4 = Move::new(Coord::new('e', 2), Coord::new('e', 4));
+
+        assert_eq!(vec![(e4, 2), (Move::new(Coord::new('d', 2), Coord::new('d', 4)), 1)], book.moves(&start));
+        assert!(book.pick(&start, 7).is_some());
+
+        // black lost with e5, so only c5 is left after 1. e4
+        let mut after_e4 = start.clone();
+        after_e4.exec_move(&e4).unwrap();
+
+        assert_eq!(
+            vec![Move::new(Coord::new('c', 7), Coord::new('c', 5))],
+            book.moves(&after_e4).into_iter().map(|(mv, _)| mv).collect::<Vec<_>>()
+        );
+
+        let before_castling = read_pgn("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5").unwrap().board;
+        let castling = Move::castling(Coord::new('e', 1), Coord::new('g', 1));
+
+        assert_eq!(vec![(castling, 2)], book.moves(&before_castling));
+        assert!(book.entries().iter().any(|entry| entry.mv == 7 | 4 << 6));
+
+        let mut white_only = BookBuilder::new(8);
+        white_only.add_game(&read_pgn("1. e4 c5").unwrap().board, "1/2-1/2", Some(Color::White));
+
+        assert_eq!(1, white_only.build().entries().len());
+        assert_eq!(Err(BookError::InvalidLength(15)), Book::from_bytes(&[0; 15]));
+    }
+}
@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{clock::ClockSnapshot, engine::piece_value, expand_promotions, get_moves, Board, Color, Move};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum BotError {
+    #[error("There is no bot called '{0}'")]
+    UnknownBot(String),
+}
+
+// a scripted opponent, it sees the position and the clock when the game has one,
+// only positions without a legal move are left without an answer
+pub trait Bot: Send {
+    fn choose_move(&mut self, board: &Board, clock: Option<&ClockSnapshot>) -> Option<Move>;
+}
+
+pub type BotFactory = Box<dyn Fn() -> Box<dyn Bot> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BotInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+// the bots that can be picked as opponents, each game gets a fresh bot from its factory
+#[derive(Default)]
+pub struct BotRegistry {
+    bots: Vec<(BotInfo, BotFactory)>,
+}
+
+impl BotRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // a bot registered under an existing id replaces the old one
+    pub fn register(&mut self, info: BotInfo, factory: BotFactory) {
+        self.bots.retain(|(existing, _)| existing.id != info.id);
+        self.bots.push((info, factory));
+    }
+
+    pub fn list(&self) -> Vec<BotInfo> {
+        return self.bots.iter().map(|(info, _)| info.clone()).collect();
+    }
+
+    pub fn create(&self, id: &str) -> Result<Box<dyn Bot>, BotError> {
+        return self
+            .bots
+            .iter()
+            .find(|(info, _)| info.id == id)
+            .map(|(_, factory)| factory())
+            .ok_or_else(|| BotError::UnknownBot(id.to_string()));
+    }
+}
+
+// the reference opponents, the weakest players there are, which makes them a baseline for everything else
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BotKind {
+    Random,
+    Greedy,
+}
+
+impl BotKind {
+    pub const ALL: [BotKind; 2] = [BotKind::Random, BotKind::Greedy];
+
+    pub fn info(self) -> BotInfo {
+        let (id, name, description) = match self {
+            BotKind::Random => ("random", "Random mover", "Plays any legal move"),
+            BotKind::Greedy => ("greedy", "Greedy capturer", "Takes the most material it can get right now"),
+        };
+
+        return BotInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+        };
+    }
+
+    pub fn create(self, seed: u64) -> Box<dyn Bot> {
+        return match self {
+            BotKind::Random => Box::new(RandomMover::new(seed)),
+            BotKind::Greedy => Box::new(GreedyCapturer::new(seed)),
+        };
+    }
+}
+
+// every legal move is equally likely
+pub struct RandomMover {
+    rng: Rng,
+}
+
+impl RandomMover {
+    pub fn new(seed: u64) -> Self {
+        RandomMover { rng: Rng::new(seed) }
+    }
+}
+
+impl Bot for RandomMover {
+    fn choose_move(&mut self, board: &Board, _clock: Option<&ClockSnapshot>) -> Option<Move> {
+        let moves = expand_promotions(&get_moves(board.turn(), board));
+        return self.rng.pick(&moves).copied();
+    }
+}
+
+// looks one ply ahead and plays a move that leaves it with the most material, a random one among equals
+pub struct GreedyCapturer {
+    rng: Rng,
+}
+
+impl GreedyCapturer {
+    pub fn new(seed: u64) -> Self {
+        GreedyCapturer { rng: Rng::new(seed) }
+    }
+}
+
+impl Bot for GreedyCapturer {
+    fn choose_move(&mut self, board: &Board, _clock: Option<&ClockSnapshot>) -> Option<Move> {
+        let color = board.turn();
+        let mut best = Vec::new();
+        let mut best_score = i32::MIN;
+
+        for mv in expand_promotions(&get_moves(color, board)) {
+            let mut after = board.clone();
+
+            if after.exec_move(&mv).is_err() {
+                continue;
+            }
+
+            let score = material(&after, color);
+
+            if score > best_score {
+                best_score = score;
+                best.clear();
+            }
+
+            if score == best_score {
+                best.push(mv);
+            }
+        }
+
+        return self.rng.pick(&best).copied();
+    }
+}
+
+// the material of the given color minus that of the other one
+pub fn material(board: &Board, color: Color) -> i32 {
+    return board
+        .pieces()
+        .iter()
+        .map(|piece| if piece.color == color { piece_value(piece.piece_type) } else { -piece_value(piece.piece_type) })
+        .sum();
+}
+
+// xorshift, the seed decides the moves so games can be replayed
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng { state: seed | 1 }
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        return items.get(self.state as usize % items.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // plays the first move it finds
+    struct FirstMover;
+
+    impl Bot for FirstMover {
+        fn choose_move(&mut self, board: &Board, _clock: Option<&ClockSnapshot>) -> Option<Move> {
+            return expand_promotions(&get_moves(board.turn(), board)).first().copied();
+        }
+    }
+
+    #[test]
+    fn registers_and_creates_bots() {
+        let info = |name: &str| BotInfo {
+            id: "first".to_string(),
+            name: name.to_string(),
+            description: "Plays the first legal move".to_string(),
+        };
+
+        let mut registry = BotRegistry::new();
+        registry.register(info("First"), Box::new(|| Box::new(FirstMover)));
+        registry.register(info("First mover"), Box::new(|| Box::new(FirstMover)));
+
+        assert_eq!(vec![info("First mover")], registry.list());
+        assert!(matches!(registry.create("greedy"), Err(BotError::UnknownBot(id)) if id == "greedy"));
+
+        let mut board = Board::new_game();
+        let mut bot = registry.create("first").unwrap();
+        let mv = bot.choose_move(&board, None).unwrap();
+
+        assert!(board.exec_move(&mv).is_ok());
+        assert_eq!(None, bot.choose_move(&Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - -").unwrap(), None));
+    }
+
+    #[test]
+    fn reference_bots() {
+        // the queen on d5 is hanging, a rook and a pawn could be taken as well
+        let board = Board::from_fen("4k3/8/8/3q4/2P5/8/r7/R3K3 w - -").unwrap();
+        let mut greedy = BotKind::Greedy.create(7);
+
+        for _ in 0..10 {
+            assert_eq!("c4d5", greedy.choose_move(&board, None).unwrap().to_string());
+        }
+
+        let mut random = BotKind::Random.create(7);
+        let moves = (0..50).map(|_| random.choose_move(&board, None).unwrap()).collect::<Vec<_>>();
+
+        assert!(moves.iter().all(|mv| board.clone().exec_move(mv).is_ok()));
+        assert!(moves.iter().any(|mv| *mv != moves[0]));
+    }
+}
@@ -0,0 +1,645 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Color;
+
+// fischer adds the full increment after every move, bronstein gives back at most the time spent on the move,
+// a simple delay only starts the clock once the delay has passed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IncrementMode {
+    #[default]
+    Fischer,
+    Bronstein,
+    Delay,
+}
+
+impl IncrementMode {
+    fn separator(&self) -> char {
+        match self {
+            IncrementMode::Fischer => '+',
+            IncrementMode::Bronstein => 'b',
+            IncrementMode::Delay => 'd',
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeControl {
+    pub base_ms: u64,
+    pub increment_ms: u64,
+    #[serde(default)]
+    pub mode: IncrementMode,
+}
+
+impl TimeControl {
+    pub fn new(base_ms: u64, increment_ms: u64) -> Self {
+        TimeControl {
+            base_ms,
+            increment_ms,
+            mode: IncrementMode::Fischer,
+        }
+    }
+
+    pub fn with_mode(base_ms: u64, increment_ms: u64, mode: IncrementMode) -> Self {
+        TimeControl { base_ms, increment_ms, mode }
+    }
+
+    // pgn notation in seconds, like 300+2, with 300b2 for bronstein and 300d2 for a simple delay
+    pub fn to_pgn(&self) -> String {
+        if self.increment_ms == 0 {
+            return format_seconds(self.base_ms);
+        }
+
+        return format!("{}{}{}", format_seconds(self.base_ms), self.mode.separator(), format_seconds(self.increment_ms));
+    }
+
+    pub fn from_pgn(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        for mode in [IncrementMode::Fischer, IncrementMode::Bronstein, IncrementMode::Delay] {
+            if let Some((base, increment)) = value.split_once(mode.separator()) {
+                return Some(TimeControl::with_mode(parse_seconds(base)?, parse_seconds(increment)?, mode));
+            }
+        }
+
+        return Some(TimeControl::new(parse_seconds(value)?, 0));
+    }
+
+    // the part of the time spent on a move that is taken from the clock
+    fn charged(&self, spent: Duration) -> Duration {
+        match self.mode {
+            IncrementMode::Delay => spent.saturating_sub(Duration::from_millis(self.increment_ms)),
+            _ => spent,
+        }
+    }
+
+    // the time given back once the move is made
+    fn bonus(&self, spent: Duration) -> Duration {
+        let increment = Duration::from_millis(self.increment_ms);
+
+        match self.mode {
+            IncrementMode::Fischer => increment,
+            IncrementMode::Bronstein => increment.min(spent),
+            IncrementMode::Delay => Duration::ZERO,
+        }
+    }
+}
+
+// each side has its own time control, so time odds are an asymmetric pair
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSettings {
+    pub white: TimeControl,
+    pub black: TimeControl,
+}
+
+impl ClockSettings {
+    pub fn new(white: TimeControl, black: TimeControl) -> Self {
+        ClockSettings { white, black }
+    }
+
+    pub fn symmetric(time_control: TimeControl) -> Self {
+        ClockSettings::new(time_control, time_control)
+    }
+
+    // white gets more time to make up for black's draw odds
+    pub fn armageddon() -> Self {
+        ClockSettings::new(TimeControl::new(300_000, 0), TimeControl::new(240_000, 0))
+    }
+
+    pub fn side(&self, color: Color) -> TimeControl {
+        match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+        }
+    }
+
+    // equal time controls use the standard TimeControl tag, time odds are written as one tag per side
+    pub fn pgn_tags(&self) -> Vec<(String, String)> {
+        if self.white == self.black {
+            return vec![("TimeControl".to_string(), self.white.to_pgn())];
+        }
+
+        return vec![
+            ("WhiteTimeControl".to_string(), self.white.to_pgn()),
+            ("BlackTimeControl".to_string(), self.black.to_pgn()),
+        ];
+    }
+
+    pub fn from_pgn_tags(tags: &[(String, String)]) -> Option<Self> {
+        let tag = |name: &str| tags.iter().find(|(key, _)| key == name).and_then(|(_, value)| TimeControl::from_pgn(value));
+
+        if let (Some(white), Some(black)) = (tag("WhiteTimeControl"), tag("BlackTimeControl")) {
+            return Some(ClockSettings::new(white, black));
+        }
+
+        return tag("TimeControl").map(ClockSettings::symmetric);
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSnapshot {
+    pub settings: ClockSettings,
+    pub white_ms: u64,
+    pub black_ms: u64,
+    pub turn: Color,
+    pub running: bool,
+    pub flagged: Option<Color>,
+}
+
+// the state of the authoritative clock as of sending, replicas order them by ply
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockSync {
+    pub ply: usize,
+    pub white_ms: u64,
+    pub black_ms: u64,
+    pub turn: Color,
+    pub running: bool,
+    pub flagged: Option<Color>,
+}
+
+// the time a side spent on one of its moves and what it had left after the increment
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveTime {
+    pub color: Color,
+    pub spent_ms: u64,
+    pub remaining_ms: u64,
+}
+
+// the longest think is numbered among the side's own moves, starting at one
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeUsage {
+    pub moves: usize,
+    pub total_ms: u64,
+    pub average_ms: u64,
+    pub longest_ms: u64,
+    pub longest_move: Option<usize>,
+}
+
+impl TimeUsage {
+    pub fn of(times: &[MoveTime], color: Color) -> Self {
+        let spent = times.iter().filter(|time| time.color == color).map(|time| time.spent_ms).collect::<Vec<_>>();
+        let total_ms = spent.iter().sum::<u64>();
+        let longest = spent.iter().enumerate().max_by_key(|(index, spent)| (**spent, std::cmp::Reverse(*index)));
+
+        return TimeUsage {
+            moves: spent.len(),
+            total_ms,
+            average_ms: total_ms.checked_div(spent.len() as u64).unwrap_or(0),
+            longest_ms: longest.map_or(0, |(_, spent)| *spent),
+            longest_move: longest.map(|(index, _)| index + 1),
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeReport {
+    pub moves: Vec<MoveTime>,
+    pub white: TimeUsage,
+    pub black: TimeUsage,
+}
+
+// the clock is driven by the caller's timestamps, so the accounting does not depend on when it is polled,
+// in networked games one side keeps the authoritative clock and the other follows it with a replica
+#[derive(Debug, Clone)]
+pub struct GameClock {
+    settings: ClockSettings,
+    white: Duration,
+    black: Duration,
+    turn: Color,
+    spent: Duration,
+    running_since: Option<Instant>,
+    flagged: Option<Color>,
+    moves: Vec<MoveTime>,
+    start_ply: usize,
+}
+
+impl GameClock {
+    // the clock of the side to move in the position the game starts from runs first,
+    // a game started from a position further into a game has that many plies without times
+    pub fn new(settings: ClockSettings, turn: Color, start_ply: usize) -> Self {
+        GameClock {
+            settings,
+            white: Duration::from_millis(settings.white.base_ms),
+            black: Duration::from_millis(settings.black.base_ms),
+            turn,
+            spent: Duration::ZERO,
+            running_since: None,
+            flagged: None,
+            moves: Vec::new(),
+            start_ply,
+        }
+    }
+
+    pub fn settings(&self) -> ClockSettings {
+        return self.settings;
+    }
+
+    pub fn turn(&self) -> Color {
+        return self.turn;
+    }
+
+    pub fn flagged(&self) -> Option<Color> {
+        return self.flagged;
+    }
+
+    pub fn start_ply(&self) -> usize {
+        return self.start_ply;
+    }
+
+    pub fn start(&mut self, now: Instant) {
+        if self.flagged.is_none() {
+            self.running_since.get_or_insert(now);
+        }
+    }
+
+    // the time spent on the current move is kept, so a delay is not granted again when the clock is restarted
+    pub fn pause(&mut self, now: Instant) {
+        self.spent = self.spent_on_move(now);
+        self.running_since = None;
+        self.check_flag(now);
+    }
+
+    pub fn remaining(&self, color: Color, now: Instant) -> Duration {
+        let stored = self.time(color);
+
+        if color != self.turn {
+            return stored;
+        }
+
+        return stored.saturating_sub(self.settings.side(color).charged(self.spent_on_move(now)));
+    }
+
+    // ends the turn of the side to move and adds its increment, a side whose time ran out keeps its flag
+    pub fn press(&mut self, now: Instant) -> Option<Color> {
+        if self.check_flag(now) {
+            return self.flagged;
+        }
+
+        let spent = self.spent_on_move(now);
+        let time_control = self.settings.side(self.turn);
+        let remaining = self.remaining(self.turn, now) + time_control.bonus(spent);
+
+        *self.time_mut(self.turn) = remaining;
+
+        self.moves.push(MoveTime {
+            color: self.turn,
+            spent_ms: spent.as_millis() as u64,
+            remaining_ms: remaining.as_millis() as u64,
+        });
+
+        self.turn = self.turn.invert();
+        self.spent = Duration::ZERO;
+
+        if self.running_since.is_some() {
+            self.running_since = Some(now);
+        }
+
+        return None;
+    }
+
+    // the side to move changed without a move, like after an undo or a new position, the time spent so far is charged
+    // to the side that was to move but earns no increment, the times of moves past the board's plies were taken back
+    pub fn set_turn(&mut self, turn: Color, plies: usize, now: Instant) {
+        self.moves.truncate(plies.saturating_sub(self.start_ply));
+
+        if self.turn == turn || self.check_flag(now) {
+            return;
+        }
+
+        *self.time_mut(self.turn) = self.remaining(self.turn, now);
+
+        self.turn = turn;
+        self.spent = Duration::ZERO;
+
+        if self.running_since.is_some() {
+            self.running_since = Some(now);
+        }
+    }
+
+    // a move that reached the authoritative clock late is charged as if it arrived when it was sent, for at most
+    // the grace window, the opponent's time starts running when the move is received
+    pub fn press_with_lag(&mut self, now: Instant, lag: Duration, grace: Duration) -> Option<Color> {
+        let sent = now.checked_sub(lag.min(grace)).unwrap_or(now);
+        let flagged = self.press(sent);
+
+        if flagged.is_none() && self.running_since.is_some() {
+            self.running_since = Some(now);
+        }
+
+        return flagged;
+    }
+
+    // one entry for every press that was not too late, in the order of the moves
+    pub fn move_times(&self) -> &[MoveTime] {
+        return &self.moves;
+    }
+
+    pub fn time_report(&self) -> TimeReport {
+        return TimeReport {
+            moves: self.moves.clone(),
+            white: TimeUsage::of(&self.moves, Color::White),
+            black: TimeUsage::of(&self.moves, Color::Black),
+        };
+    }
+
+    pub fn sync(&self, ply: usize, now: Instant) -> ClockSync {
+        let snapshot = self.snapshot(now);
+
+        return ClockSync {
+            ply,
+            white_ms: snapshot.white_ms,
+            black_ms: snapshot.black_ms,
+            turn: snapshot.turn,
+            running: snapshot.running,
+            flagged: snapshot.flagged,
+        };
+    }
+
+    pub fn snapshot(&self, now: Instant) -> ClockSnapshot {
+        return ClockSnapshot {
+            settings: self.settings,
+            white_ms: self.remaining(Color::White, now).as_millis() as u64,
+            black_ms: self.remaining(Color::Black, now).as_millis() as u64,
+            turn: self.turn,
+            running: self.running_since.is_some(),
+            flagged: self.flagged,
+        };
+    }
+
+    fn spent_on_move(&self, now: Instant) -> Duration {
+        return match self.running_since {
+            Some(since) => self.spent + now.saturating_duration_since(since),
+            None => self.spent,
+        };
+    }
+
+    fn check_flag(&mut self, now: Instant) -> bool {
+        if self.flagged.is_none() && self.remaining(self.turn, now).is_zero() {
+            *self.time_mut(self.turn) = Duration::ZERO;
+            self.flagged = Some(self.turn);
+            self.running_since = None;
+        }
+
+        return self.flagged.is_some();
+    }
+
+    fn time(&self, color: Color) -> Duration {
+        match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+        }
+    }
+
+    fn time_mut(&mut self, color: Color) -> &mut Duration {
+        match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        }
+    }
+}
+
+// a remote player's view of the authoritative clock, between syncs the side to move runs down locally
+#[derive(Debug, Clone)]
+pub struct ClockReplica {
+    settings: ClockSettings,
+    last: Option<(ClockSync, Instant)>,
+}
+
+impl ClockReplica {
+    pub fn new(settings: ClockSettings) -> Self {
+        ClockReplica { settings, last: None }
+    }
+
+    // the lag is the one-way delay measured by the connection, a sync older than the last one is ignored
+    pub fn apply(&mut self, sync: ClockSync, received: Instant, lag: Duration) -> bool {
+        if self.last.is_some_and(|(last, _)| sync.ply < last.ply) {
+            return false;
+        }
+
+        self.last = Some((sync, received.checked_sub(lag).unwrap_or(received)));
+
+        return true;
+    }
+
+    pub fn snapshot(&self, now: Instant) -> ClockSnapshot {
+        let Some((sync, sent)) = self.last else {
+            return GameClock::new(self.settings, Color::White, 0).snapshot(now);
+        };
+
+        let elapsed = match sync.running && sync.flagged.is_none() {
+            true => now.saturating_duration_since(sent).as_millis() as u64,
+            false => 0,
+        };
+
+        let run_down = |color: Color, ms: u64| if color == sync.turn { ms.saturating_sub(elapsed) } else { ms };
+
+        return ClockSnapshot {
+            settings: self.settings,
+            white_ms: run_down(Color::White, sync.white_ms),
+            black_ms: run_down(Color::Black, sync.black_ms),
+            turn: sync.turn,
+            running: sync.running,
+            flagged: sync.flagged,
+        };
+    }
+}
+
+fn format_seconds(ms: u64) -> String {
+    if ms.is_multiple_of(1000) {
+        return (ms / 1000).to_string();
+    }
+
+    return format!("{}", ms as f64 / 1000.0);
+}
+
+fn parse_seconds(value: &str) -> Option<u64> {
+    let seconds = value.trim().parse::<f64>().ok().filter(|seconds| *seconds >= 0.0)?;
+    return Some((seconds * 1000.0).round() as u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_odds_accounting() {
+        let settings = ClockSettings::new(TimeControl::new(300_000, 2_000), TimeControl::new(60_000, 0));
+        let mut clock = GameClock::new(settings, Color::White, 0);
+        let start = Instant::now();
+
+        clock.start(start);
+
+        assert_eq!(None, clock.press(start + Duration::from_secs(10)));
+        assert_eq!(None, clock.press(start + Duration::from_secs(15)));
+
+        let snapshot = clock.snapshot(start + Duration::from_secs(15));
+
+        assert_eq!((292_000, 55_000, Color::White), (snapshot.white_ms, snapshot.black_ms, snapshot.turn));
+
+        clock.press(start + Duration::from_secs(16));
+
+        assert_eq!(Some(Color::Black), clock.press(start + Duration::from_secs(80)));
+        assert_eq!(0, clock.snapshot(start + Duration::from_secs(90)).black_ms);
+
+        let report = clock.time_report();
+
+        assert_eq!(3, report.moves.len());
+        assert_eq!((Color::Black, 5_000, 55_000), (report.moves[1].color, report.moves[1].spent_ms, report.moves[1].remaining_ms));
+        let white = report.white;
+
+        assert_eq!((2, 11_000, 5_500), (white.moves, white.total_ms, white.average_ms));
+        assert_eq!((10_000, Some(1)), (white.longest_ms, white.longest_move));
+        assert_eq!(TimeUsage::default(), TimeUsage::of(&[], Color::Black));
+    }
+
+    #[test]
+    fn follows_the_side_to_move() {
+        let settings = ClockSettings::symmetric(TimeControl::new(60_000, 1_000));
+        let mut clock = GameClock::new(settings, Color::Black, 0);
+        let start = Instant::now();
+
+        clock.start(start);
+        clock.press(start + Duration::from_secs(5));
+
+        assert_eq!((Color::White, 56_000), (clock.turn(), clock.snapshot(start + Duration::from_secs(5)).black_ms));
+
+        // taking the move back hands the turn back without an increment for the time white thought
+        clock.set_turn(Color::Black, 0, start + Duration::from_secs(8));
+        let snapshot = clock.snapshot(start + Duration::from_secs(10));
+
+        assert_eq!((Color::Black, 57_000, 54_000), (snapshot.turn, snapshot.white_ms, snapshot.black_ms));
+        assert!(clock.time_report().moves.is_empty());
+    }
+
+    #[test]
+    fn taken_back_moves_leave_the_report() {
+        let settings = ClockSettings::symmetric(TimeControl::new(60_000, 0));
+        let mut clock = GameClock::new(settings, Color::White, 0);
+        let start = Instant::now();
+
+        clock.start(start);
+        clock.press(start + Duration::from_secs(2));
+        clock.press(start + Duration::from_secs(5));
+        clock.press(start + Duration::from_secs(25));
+
+        assert_eq!((20_000, Some(2)), (clock.time_report().white.longest_ms, clock.time_report().white.longest_move));
+
+        // two plies taken back leave white to move again with only the first move of each side timed
+        clock.set_turn(Color::White, 2, start + Duration::from_secs(26));
+        clock.press(start + Duration::from_secs(30));
+
+        let report = clock.time_report();
+
+        assert_eq!(vec![2_000, 3_000, 4_000], report.moves.iter().map(|time| time.spent_ms).collect::<Vec<_>>());
+        assert_eq!((2, 6_000, 4_000), (report.white.moves, report.white.total_ms, report.white.longest_ms));
+        assert_eq!((1, 3_000), (report.black.moves, report.black.total_ms));
+    }
+
+    #[test]
+    fn forked_games_count_plies_from_the_fork() {
+        let settings = ClockSettings::symmetric(TimeControl::new(60_000, 0));
+        let mut clock = GameClock::new(settings, Color::Black, 9);
+        let start = Instant::now();
+
+        clock.start(start);
+        clock.press(start + Duration::from_secs(3));
+        clock.press(start + Duration::from_secs(7));
+
+        // taking back white's reply leaves the board at ply 10, with black's first move of the fork still timed
+        clock.set_turn(Color::White, 10, start + Duration::from_secs(8));
+
+        assert_eq!(vec![3_000], clock.time_report().moves.iter().map(|time| time.spent_ms).collect::<Vec<_>>());
+        assert_eq!(9, clock.start_ply());
+    }
+
+    #[test]
+    fn bronstein_and_delay_accounting() {
+        let settings = ClockSettings::new(
+            TimeControl::with_mode(60_000, 3_000, IncrementMode::Bronstein),
+            TimeControl::with_mode(60_000, 3_000, IncrementMode::Delay),
+        );
+        let mut clock = GameClock::new(settings, Color::White, 0);
+        let start = Instant::now();
+
+        clock.start(start);
+
+        // bronstein gives back the full increment for a slow move and only the time spent for a fast one
+        clock.press(start + Duration::from_secs(5));
+        assert_eq!(58_000, clock.snapshot(start + Duration::from_secs(5)).white_ms);
+
+        // a move within the delay costs nothing
+        clock.press(start + Duration::from_secs(7));
+        assert_eq!(60_000, clock.snapshot(start + Duration::from_secs(7)).black_ms);
+
+        clock.press(start + Duration::from_millis(8_500));
+        assert_eq!(58_000, clock.snapshot(start + Duration::from_millis(8_500)).white_ms);
+
+        // the delay is not granted again after a pause
+        clock.pause(start + Duration::from_millis(10_500));
+        clock.start(start + Duration::from_secs(20));
+        clock.press(start + Duration::from_secs(25));
+
+        assert_eq!(56_000, clock.snapshot(start + Duration::from_secs(25)).black_ms);
+    }
+
+    #[test]
+    fn lag_compensation_and_replica() {
+        let settings = ClockSettings::symmetric(TimeControl::new(60_000, 0));
+        let mut clock = GameClock::new(settings, Color::White, 0);
+        let start = Instant::now();
+        let grace = Duration::from_millis(500);
+
+        clock.start(start);
+
+        // 300ms of the 10s came from the connection, of a 2s lag only the grace window is given back
+        clock.press_with_lag(start + Duration::from_secs(10), Duration::from_millis(300), grace);
+        assert_eq!(50_300, clock.snapshot(start + Duration::from_secs(10)).white_ms);
+
+        clock.press_with_lag(start + Duration::from_secs(15), Duration::from_secs(2), grace);
+        assert_eq!(55_500, clock.snapshot(start + Duration::from_secs(15)).black_ms);
+
+        let mut replica = ClockReplica::new(settings);
+        let received = start + Duration::from_millis(15_100);
+
+        assert!(replica.apply(clock.sync(2, start + Duration::from_secs(15)), received, Duration::from_millis(100)));
+        assert!(!replica.apply(clock.sync(1, start + Duration::from_secs(15)), received, Duration::ZERO));
+
+        let snapshot = replica.snapshot(start + Duration::from_secs(20));
+
+        assert_eq!((45_300, 55_500, Color::White), (snapshot.white_ms, snapshot.black_ms, snapshot.turn));
+        assert_eq!(clock.snapshot(start + Duration::from_secs(20)), snapshot);
+    }
+
+    #[test]
+    fn pgn_tags() {
+        let odds = ClockSettings::new(TimeControl::new(300_000, 2_000), TimeControl::new(90_500, 0));
+        let tags = odds.pgn_tags();
+
+        assert_eq!(vec![("WhiteTimeControl".to_string(), "300+2".to_string()), ("BlackTimeControl".to_string(), "90.5".to_string())], tags);
+        assert_eq!(Some(odds), ClockSettings::from_pgn_tags(&tags));
+
+        let blitz = ClockSettings::symmetric(TimeControl::new(180_000, 0));
+
+        assert_eq!(vec![("TimeControl".to_string(), "180".to_string())], blitz.pgn_tags());
+        assert_eq!(Some(blitz), ClockSettings::from_pgn_tags(&blitz.pgn_tags()));
+        assert_eq!(
+            vec![("WhiteTimeControl".to_string(), "300".to_string()), ("BlackTimeControl".to_string(), "240".to_string())],
+            ClockSettings::armageddon().pgn_tags()
+        );
+
+        let delay = TimeControl::with_mode(300_000, 5_000, IncrementMode::Delay);
+
+        assert_eq!("300d5", delay.to_pgn());
+        assert_eq!(Some(delay), TimeControl::from_pgn("300d5"));
+        assert_eq!(Some(TimeControl::with_mode(300_000, 2_000, IncrementMode::Bronstein)), TimeControl::from_pgn("300b2"));
+    }
+}
@@ -0,0 +1,118 @@
+use serde::Serialize;
+
+use crate::{moves, Board, Move};
+
+const FIFTY_MOVE_PLIES: u32 = 100;
+const CLAIMABLE_REPETITIONS: usize = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DrawReason {
+    FiftyMoves,
+    Repetition,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ClaimError {
+    #[error("The game is already over")]
+    GameOver,
+
+    #[error("The intended move {0} is illegal")]
+    IllegalMove(Move),
+
+    #[error("Neither the fifty-move rule nor threefold repetition applies")]
+    NotClaimable,
+}
+
+// how often the position occurred so far, positions are equal when the pieces, side to move,
+// castling rights and capturable en passant square match
+pub fn repetitions(board: &Board) -> usize {
+    return board.history().iter().filter(|position| position.hash() == board.hash()).count();
+}
+
+// a claim holds for the current position or, per FIDE 9.2 and 9.3, for the position after the intended move,
+// the caller plays that move when the claim succeeds
+pub fn check_draw_claim(board: &Board, intended: Option<&Move>) -> Result<DrawReason, ClaimError> {
+    if board.winner().is_some() || moves::get_moves(board.turn(), board).is_empty() {
+        return Err(ClaimError::GameOver);
+    }
+
+    let position = match intended {
+        Some(mv) => {
+            let legal = moves::resolve_move(board, mv.from, mv.to, mv.promotion.then_some(mv.promote_to));
+
+            if legal.as_ref() != Some(mv) {
+                return Err(ClaimError::IllegalMove(*mv));
+            }
+
+            let mut position = board.clone();
+            position.exec_move(mv).map_err(|_| ClaimError::IllegalMove(*mv))?;
+
+            // a move that ends the game cannot be combined with a claim
+            if position.winner().is_some() {
+                return Err(ClaimError::NotClaimable);
+            }
+
+            position
+        }
+        None => board.clone(),
+    };
+
+    if repetitions(&position) >= CLAIMABLE_REPETITIONS {
+        return Ok(DrawReason::Repetition);
+    }
+
+    if position.halfmove_clock() >= FIFTY_MOVE_PLIES {
+        return Ok(DrawReason::FiftyMoves);
+    }
+
+    return Err(ClaimError::NotClaimable);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coord;
+
+    fn play(board: &mut Board, moves: &[(&str, &str)]) {
+        for (from, to) in moves {
+            board.exec_move(&Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap())).unwrap();
+        }
+    }
+
+    #[test]
+    fn repetition_claims() {
+        let mut board = Board::new_game();
+        let shuffle = [("g1", "f3"), ("g8", "f6"), ("f3", "g1"), ("f6", "g8")];
+
+        play(&mut board, &shuffle);
+        play(&mut board, &shuffle[..3]);
+
+        let knight_back = Move::new(Coord::new('f', 6), Coord::new('g', 8));
+
+        assert_eq!(Err(ClaimError::NotClaimable), check_draw_claim(&board, None));
+        assert_eq!(Ok(DrawReason::Repetition), check_draw_claim(&board, Some(&knight_back)));
+
+        let illegal = Move::new(Coord::new('f', 6), Coord::new('f', 3));
+
+        assert_eq!(Err(ClaimError::IllegalMove(illegal)), check_draw_claim(&board, Some(&illegal)));
+
+        play(&mut board, &shuffle[3..]);
+
+        assert_eq!(3, repetitions(&board));
+        assert_eq!(Ok(DrawReason::Repetition), check_draw_claim(&board, None));
+    }
+
+    #[test]
+    fn fifty_move_claims() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K2R w - - 99 80").unwrap();
+
+        assert_eq!(Err(ClaimError::NotClaimable), check_draw_claim(&board, None));
+        assert_eq!(Ok(DrawReason::FiftyMoves), check_draw_claim(&board, Some(&Move::new(Coord::new('h', 1), Coord::new('h', 2)))));
+        assert_eq!(Err(ClaimError::NotClaimable), check_draw_claim(&board, Some(&Move::new(Coord::new('e', 2), Coord::new('e', 3)))));
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K2R w - - 100 80").unwrap();
+
+        assert_eq!(Ok(DrawReason::FiftyMoves), check_draw_claim(&board, None));
+    }
+}
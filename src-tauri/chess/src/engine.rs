@@ -0,0 +1,38 @@
+mod autoplay;
+mod bench;
+mod conduct;
+mod eval;
+mod host;
+mod mcts;
+mod memory;
+mod options;
+mod ordering;
+mod ponder;
+mod search;
+mod stats;
+mod trace;
+mod tournament;
+mod time;
+mod tt;
+
+pub use autoplay::{Adjudication, AutoPlay, AutoPlayStep, BotConfig, EngineConfig, GameOutcome, PlayerConfig};
+pub use bench::{BenchResult, BENCH_DEPTH};
+pub use conduct::{Conduct, ConductDecision, ConductSettings};
+pub use eval::evaluate;
+pub use eval::piece_value;
+pub use eval::explain;
+pub use eval::{EvalBreakdown, PieceScore};
+pub use host::{ExternalEngine, PlayError, UciEngine, UciError};
+pub use memory::{memory_usage, MemoryUsage};
+pub use options::{uci_options, EngineOptions, OptionError, OptionKind, OptionSpec, OPTIONS};
+pub use ordering::MoveOrderer;
+pub use ordering::MAX_PLY;
+pub use ponder::Ponder;
+pub use search::Engine;
+pub use search::{Pruning, PvLine, SearchResult, MAX_SKILL_LEVEL};
+pub use search::MATE_SCORE;
+pub use stats::{IterationInfo, SearchListener, SearchStats};
+pub use time::{allocate, TimeBudget, TimeLeft};
+pub use trace::{NodeType, SearchTrace, TraceLimits, TraceNode};
+pub use tournament::{MatchStats, Participant, RoundRobin, RoundRobinResults, Sprt, SprtDecision, Standing, Tournament, TournamentGame};
+pub use tt::{Bound, TranspositionTable, TtEntry, DEFAULT_HASH_SIZE_MB};
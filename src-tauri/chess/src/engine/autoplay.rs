@@ -0,0 +1,319 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bot::{Bot, BotKind},
+    get_moves_into, Board, Color, Move, MoveList,
+};
+
+use super::{
+    evaluate,
+    host::{ExternalEngine, PlayError, UciEngine},
+    Engine, Pruning, SearchResult, SearchStats,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineConfig {
+    pub depth: u8,
+    pub threads: usize,
+    #[serde(default)]
+    pub pruning: Pruning,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig {
+            depth: 4,
+            threads: 1,
+            pruning: Pruning::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PlayerConfig {
+    Builtin(EngineConfig),
+    External(ExternalEngine),
+    Bot(BotConfig),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BotConfig {
+    pub kind: BotKind,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Adjudication {
+    pub resign_score: i32,
+    pub resign_moves: usize,
+    pub draw_score: i32,
+    pub draw_moves: usize,
+    pub max_moves: usize,
+}
+
+impl Default for Adjudication {
+    fn default() -> Self {
+        Adjudication {
+            resign_score: 900,
+            resign_moves: 5,
+            draw_score: 10,
+            draw_moves: 40,
+            max_moves: 300,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "winner", rename_all = "camelCase")]
+pub enum GameOutcome {
+    Checkmate(Color),
+    Resignation(Color),
+    Stalemate,
+    DrawAdjudicated,
+    MoveLimit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AutoPlayStep {
+    Moved(Move, Box<SearchResult>),
+    Finished(GameOutcome),
+}
+
+enum Player {
+    Builtin(Box<Engine>, u8),
+    External(UciEngine, u8),
+    Bot(Box<dyn Bot>),
+}
+
+impl Player {
+    fn start(config: &PlayerConfig) -> Result<Self, PlayError> {
+        return match config {
+            PlayerConfig::Builtin(config) => {
+                let mut engine = Engine::new();
+                engine.set_threads(config.threads);
+                engine.set_pruning(config.pruning);
+
+                Ok(Player::Builtin(Box::new(engine), config.depth))
+            }
+            PlayerConfig::External(engine) => Ok(Player::External(UciEngine::start(engine)?, engine.depth)),
+            PlayerConfig::Bot(config) => Ok(Player::Bot(config.kind.create(config.seed))),
+        };
+    }
+
+    fn search(&mut self, board: &Board) -> Result<SearchResult, PlayError> {
+        return match self {
+            Player::Builtin(engine, depth) => Ok(engine.search(board, *depth)),
+            Player::External(engine, depth) => Ok(engine.search(board, *depth)?),
+            Player::Bot(bot) => bot_search(bot.as_mut(), board),
+        };
+    }
+}
+
+// bots do not search, the static eval of the position they leave behind stands in for the score,
+// so their games are adjudicated like any other
+fn bot_search(bot: &mut dyn Bot, board: &Board) -> Result<SearchResult, PlayError> {
+    let best_move = bot.choose_move(board, None);
+    let score = match best_move {
+        Some(mv) => {
+            let mut after = board.clone();
+            after.exec_move(&mv)?;
+            -evaluate(&after)
+        }
+        None => 0,
+    };
+
+    return Ok(SearchResult {
+        best_move,
+        score,
+        depth: 1,
+        nodes: 1,
+        pv: best_move.into_iter().collect(),
+        lines: Vec::new(),
+        stats: SearchStats::new(),
+    });
+}
+
+pub struct AutoPlay {
+    board: Board,
+    players: [Player; 2],
+    adjudication: Adjudication,
+    resign_counts: [usize; 2],
+    draw_count: usize,
+    plies: usize,
+    outcome: Option<GameOutcome>,
+}
+
+impl AutoPlay {
+    pub fn new(board: &Board, white: EngineConfig, black: EngineConfig, adjudication: Adjudication) -> Self {
+        let start = |config: EngineConfig| Player::start(&PlayerConfig::Builtin(config)).expect("built-in engine to start");
+        return Self::from_players(board, [start(white), start(black)], adjudication);
+    }
+
+    // external engines are started here, so a missing or broken engine is reported before the first move
+    pub fn with_players(board: &Board, white: &PlayerConfig, black: &PlayerConfig, adjudication: Adjudication) -> Result<Self, PlayError> {
+        return Ok(Self::from_players(board, [Player::start(white)?, Player::start(black)?], adjudication));
+    }
+
+    fn from_players(board: &Board, players: [Player; 2], adjudication: Adjudication) -> Self {
+        AutoPlay {
+            board: board.clone(),
+            players,
+            adjudication,
+            resign_counts: [0; 2],
+            draw_count: 0,
+            plies: 0,
+            outcome: None,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        return &self.board;
+    }
+
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        return self.outcome;
+    }
+
+    pub fn step(&mut self) -> Result<AutoPlayStep, PlayError> {
+        if let Some(outcome) = self.outcome.or_else(|| self.board_outcome()) {
+            self.outcome = Some(outcome);
+            return Ok(AutoPlayStep::Finished(outcome));
+        }
+
+        let color = self.board.turn();
+        let index = color_index(color);
+        let result = self.players[index].search(&self.board)?;
+
+        let Some(best_move) = result.best_move else {
+            let outcome = self.board_outcome().unwrap_or(GameOutcome::Stalemate);
+            self.outcome = Some(outcome);
+            return Ok(AutoPlayStep::Finished(outcome));
+        };
+
+        self.board.exec_move(&best_move)?;
+        self.plies += 1;
+        self.adjudicate(color, result.score);
+
+        return Ok(AutoPlayStep::Moved(best_move, Box::new(result)));
+    }
+
+    pub fn play(&mut self) -> Result<GameOutcome, PlayError> {
+        loop {
+            if let AutoPlayStep::Finished(outcome) = self.step()? {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    fn board_outcome(&self) -> Option<GameOutcome> {
+        if let Some(winner) = self.board.winner() {
+            return Some(GameOutcome::Checkmate(winner));
+        }
+
+        let mut moves = MoveList::new();
+        get_moves_into(self.board.turn(), &self.board, &mut moves);
+
+        if moves.is_empty() {
+            return Some(GameOutcome::Stalemate);
+        }
+
+        return None;
+    }
+
+    fn adjudicate(&mut self, color: Color, score: i32) {
+        let index = color_index(color);
+
+        if score < -self.adjudication.resign_score {
+            self.resign_counts[index] += 1;
+        } else {
+            self.resign_counts[index] = 0;
+        }
+
+        if score.abs() < self.adjudication.draw_score {
+            self.draw_count += 1;
+        } else {
+            self.draw_count = 0;
+        }
+
+        self.outcome = if self.resign_counts[index] >= self.adjudication.resign_moves {
+            Some(GameOutcome::Resignation(color.invert()))
+        } else if self.draw_count >= self.adjudication.draw_moves * 2 {
+            Some(GameOutcome::DrawAdjudicated)
+        } else if self.plies >= self.adjudication.max_moves * 2 {
+            Some(GameOutcome::MoveLimit)
+        } else {
+            self.board_outcome()
+        };
+    }
+}
+
+fn color_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(depth: u8) -> EngineConfig {
+        EngineConfig { depth, ..EngineConfig::default() }
+    }
+
+    #[test]
+    fn plays_until_checkmate() {
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -").unwrap();
+        let mut autoplay = AutoPlay::new(&board, config(3), config(3), Adjudication::default());
+
+        assert_eq!(GameOutcome::Checkmate(Color::White), autoplay.play().unwrap());
+        assert_eq!(Some(Color::White), autoplay.board().winner());
+    }
+
+    #[test]
+    fn losing_side_resigns() {
+        let board = Board::from_fen("3qk3/8/8/8/8/8/8/4K3 w - -").unwrap();
+        let adjudication = Adjudication {
+            resign_moves: 2,
+            ..Adjudication::default()
+        };
+
+        let mut autoplay = AutoPlay::new(&board, config(2), config(2), adjudication);
+
+        assert_eq!(GameOutcome::Resignation(Color::Black), autoplay.play().unwrap());
+    }
+
+    #[test]
+    fn quiet_game_is_drawn() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap();
+        let adjudication = Adjudication {
+            draw_score: 100,
+            draw_moves: 3,
+            ..Adjudication::default()
+        };
+
+        let mut autoplay = AutoPlay::new(&board, config(1), config(1), adjudication);
+
+        assert_eq!(GameOutcome::DrawAdjudicated, autoplay.play().unwrap());
+    }
+
+    #[test]
+    fn bots_take_part() {
+        let board = Board::from_fen("4k3/8/8/3q4/2P5/8/8/4K3 w - -").unwrap();
+        let greedy = PlayerConfig::Bot(BotConfig { kind: BotKind::Greedy, seed: 1 });
+        let mut autoplay = AutoPlay::with_players(&board, &greedy, &PlayerConfig::Builtin(config(1)), Adjudication::default()).unwrap();
+
+        let AutoPlayStep::Moved(mv, result) = autoplay.step().unwrap() else {
+            panic!("the bot to move");
+        };
+
+        assert_eq!("c4d5", mv.to_string());
+        assert!(result.score > 0);
+    }
+}
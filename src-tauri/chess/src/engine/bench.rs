@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use crate::Board;
+
+use super::Engine;
+
+pub const BENCH_DEPTH: u8 = 5;
+
+const BENCH_POSITIONS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq -",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ -",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - -",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq -",
+    "8/8/4k3/8/2p5/8/B2K4/8 w - -",
+];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BenchResult {
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    pub fn nps(&self) -> u64 {
+        let seconds = self.elapsed.as_secs_f64();
+
+        if seconds == 0.0 {
+            return 0;
+        }
+
+        return (self.nodes as f64 / seconds) as u64;
+    }
+}
+
+impl Engine {
+    // searches a fixed set of positions on a single thread with cleared tables so the node count is reproducible
+    pub fn bench(&mut self, depth: u8) -> BenchResult {
+        let threads = self.threads();
+        self.set_threads(1);
+
+        let start = Instant::now();
+        let mut nodes = 0;
+
+        for fen in BENCH_POSITIONS {
+            let board = Board::from_fen(fen).expect("bench positions to be valid");
+
+            self.new_game();
+            nodes += self.search(&board, depth).nodes;
+        }
+
+        self.set_threads(threads);
+
+        return BenchResult {
+            nodes,
+            elapsed: start.elapsed(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bench_is_deterministic() {
+        let mut engine = Engine::new();
+        engine.set_threads(2);
+
+        let first = engine.bench(3);
+        let second = engine.bench(3);
+
+        assert!(first.nodes > 0);
+        assert_eq!(first.nodes, second.nodes);
+        assert_eq!(2, engine.threads());
+    }
+}
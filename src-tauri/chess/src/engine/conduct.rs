@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{draw, Board};
+
+// the engine resigns when its score stays below -resign_score for resign_moves of its moves in a row,
+// it offers and accepts draws once its score stayed within draw_score for draw_moves of its moves,
+// offers are only made in repeated positions and never before min_draw_ply
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConductSettings {
+    pub resign: bool,
+    pub resign_score: i32,
+    pub resign_moves: usize,
+    pub draws: bool,
+    pub draw_score: i32,
+    pub draw_moves: usize,
+    pub min_draw_ply: usize,
+}
+
+impl Default for ConductSettings {
+    fn default() -> Self {
+        ConductSettings {
+            resign: true,
+            resign_score: 900,
+            resign_moves: 3,
+            draws: true,
+            draw_score: 15,
+            draw_moves: 6,
+            min_draw_ply: 60,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConductDecision {
+    Resign,
+    OfferDraw,
+}
+
+// follows the scores of the engine's moves over one game, scores are from the engine's side
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conduct {
+    settings: ConductSettings,
+    scores: Vec<i32>,
+}
+
+impl Conduct {
+    pub fn new(settings: ConductSettings) -> Self {
+        Conduct { settings, scores: Vec::new() }
+    }
+
+    pub fn settings(&self) -> ConductSettings {
+        return self.settings;
+    }
+
+    // called with the position and the score of every search, before the engine's move is played,
+    // a resigning engine does not play its move at all
+    pub fn after_search(&mut self, board: &Board, score: i32) -> Option<ConductDecision> {
+        self.scores.push(score);
+
+        let hopeless = self
+            .last_scores(self.settings.resign_moves)
+            .is_some_and(|scores| scores.iter().all(|score| *score < -self.settings.resign_score));
+
+        if self.settings.resign && hopeless {
+            return Some(ConductDecision::Resign);
+        }
+
+        if self.accepts_draw(board) && draw::repetitions(board) > 1 {
+            return Some(ConductDecision::OfferDraw);
+        }
+
+        return None;
+    }
+
+    pub fn accepts_draw(&self, board: &Board) -> bool {
+        if !self.settings.draws || board.history().len() <= self.settings.min_draw_ply {
+            return false;
+        }
+
+        return self
+            .last_scores(self.settings.draw_moves)
+            .is_some_and(|scores| scores.iter().all(|score| score.abs() <= self.settings.draw_score));
+    }
+
+    fn last_scores(&self, count: usize) -> Option<&[i32]> {
+        if count == 0 || self.scores.len() < count {
+            return None;
+        }
+
+        return Some(&self.scores[self.scores.len() - count..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::read_pgn;
+
+    #[test]
+    fn resigns_and_offers_draws() {
+        let board = Board::new_game();
+        let mut conduct = Conduct::new(ConductSettings::default());
+
+        assert_eq!(None, conduct.after_search(&board, -1000));
+        assert_eq!(None, conduct.after_search(&board, -1000));
+        assert_eq!(Some(ConductDecision::Resign), conduct.after_search(&board, -1000));
+
+        // the knights go back and forth, which repeats the position long enough for a draw
+        let shuffle = "Nf3 Nf6 Ng1 Ng8 ".repeat(16);
+        let repeated = read_pgn(&shuffle).unwrap().board;
+        let settings = ConductSettings { resign: false, ..ConductSettings::default() };
+        let mut conduct = Conduct::new(settings);
+
+        for _ in 0..settings.draw_moves - 1 {
+            assert_eq!(None, conduct.after_search(&repeated, 5));
+        }
+
+        assert!(!conduct.accepts_draw(&repeated));
+        assert_eq!(Some(ConductDecision::OfferDraw), conduct.after_search(&repeated, -5));
+        assert!(conduct.accepts_draw(&repeated));
+        assert!(!conduct.accepts_draw(&board));
+
+        conduct.after_search(&repeated, 80);
+
+        assert!(!conduct.accepts_draw(&repeated));
+    }
+}
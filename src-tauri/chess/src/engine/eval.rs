@@ -0,0 +1,366 @@
+use serde::Serialize;
+
+use crate::{get_move_mask_from, Board, Color, Coord, Piece, PieceType};
+
+pub const PAWN_VALUE: i32 = 100;
+pub const KNIGHT_VALUE: i32 = 320;
+pub const BISHOP_VALUE: i32 = 330;
+pub const ROOK_VALUE: i32 = 500;
+pub const QUEEN_VALUE: i32 = 900;
+
+const DOUBLED_PAWN_PENALTY: i32 = 10;
+const ISOLATED_PAWN_PENALTY: i32 = 15;
+const PASSED_PAWN_BONUS: [i32; 8] = [0, 5, 10, 20, 35, 60, 100, 0];
+const PAWN_SHIELD_BONUS: i32 = 10;
+
+const KNIGHT_MOBILITY: i32 = 4;
+const BISHOP_MOBILITY: i32 = 4;
+const ROOK_MOBILITY: i32 = 2;
+const QUEEN_MOBILITY: i32 = 1;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PieceScore {
+    pub piece: Piece,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalBreakdown {
+    pub material: i32,
+    pub piece_squares: i32,
+    pub pawn_structure: i32,
+    pub king_safety: i32,
+    pub mobility: i32,
+    pub total: i32,
+    pub pieces: Vec<PieceScore>,
+}
+
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+pub fn piece_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => PAWN_VALUE,
+        PieceType::Knight => KNIGHT_VALUE,
+        PieceType::Bishop => BISHOP_VALUE,
+        PieceType::Rook => ROOK_VALUE,
+        PieceType::Queen => QUEEN_VALUE,
+        PieceType::King => 0,
+    }
+}
+
+pub fn square_value(piece_type: PieceType, color: Color, coord: Coord) -> i32 {
+    let table = match piece_type {
+        PieceType::Pawn => &PAWN_TABLE,
+        PieceType::Knight => &KNIGHT_TABLE,
+        PieceType::Bishop => &BISHOP_TABLE,
+        PieceType::Rook => &ROOK_TABLE,
+        PieceType::Queen => &QUEEN_TABLE,
+        PieceType::King => &KING_TABLE,
+    };
+
+    // the tables are laid out with the 8th rank first, as seen from white
+    let row = match color {
+        Color::White => 7 - coord.row_index() as usize,
+        Color::Black => coord.row_index() as usize,
+    };
+
+    return table[row * 8 + coord.column_index() as usize];
+}
+
+pub fn evaluate(board: &Board) -> i32 {
+    let (material, piece_squares) = material_and_squares(board);
+    let score = material + piece_squares + pawn_structure(board) + king_safety(board) + mobility(board);
+
+    return match board.turn() {
+        Color::White => score,
+        Color::Black => -score,
+    };
+}
+
+pub fn explain(board: &Board) -> EvalBreakdown {
+    let (material, piece_squares) = material_and_squares(board);
+    let pawn_structure = pawn_structure(board);
+    let king_safety = king_safety(board);
+    let mobility = mobility(board);
+
+    let pieces = board
+        .pieces()
+        .into_iter()
+        .map(|piece| PieceScore {
+            score: piece_value(piece.piece_type) + square_value(piece.piece_type, piece.color, piece.coord),
+            piece,
+        })
+        .collect();
+
+    return EvalBreakdown {
+        material,
+        piece_squares,
+        pawn_structure,
+        king_safety,
+        mobility,
+        total: material + piece_squares + pawn_structure + king_safety + mobility,
+        pieces,
+    };
+}
+
+fn material_and_squares(board: &Board) -> (i32, i32) {
+    let mut material = 0;
+    let mut piece_squares = 0;
+
+    for color in [Color::White, Color::Black] {
+        let side = board.side(color);
+        let sign = sign(color);
+
+        for coord in side.all() {
+            if let Some(piece_type) = side.lookup(coord) {
+                material += sign * piece_value(piece_type);
+                piece_squares += sign * square_value(piece_type, color, coord);
+            }
+        }
+    }
+
+    return (material, piece_squares);
+}
+
+fn pawn_structure(board: &Board) -> i32 {
+    let mut score = 0;
+
+    for color in [Color::White, Color::Black] {
+        let pawns = board.side(color).pawns();
+        let enemy_pawns = board.side(color.invert()).pawns();
+        let mut files = [0; 8];
+
+        for pawn in pawns {
+            files[pawn.column_index() as usize] += 1;
+        }
+
+        let mut side_score = 0;
+
+        for count in files.iter().filter(|count| **count > 1) {
+            side_score -= DOUBLED_PAWN_PENALTY * (count - 1);
+        }
+
+        for pawn in pawns {
+            let file = pawn.column_index() as usize;
+            let has_neighbour = (file > 0 && files[file - 1] > 0) || (file < 7 && files[file + 1] > 0);
+
+            if !has_neighbour {
+                side_score -= ISOLATED_PAWN_PENALTY;
+            }
+
+            let is_passed = enemy_pawns
+                .into_iter()
+                .all(|enemy| (enemy.column_index() as i8 - pawn.column_index() as i8).abs() > 1 || !is_ahead(color, pawn, enemy));
+
+            if is_passed {
+                side_score += PASSED_PAWN_BONUS[relative_rank(color, pawn)];
+            }
+        }
+
+        score += sign(color) * side_score;
+    }
+
+    return score;
+}
+
+fn king_safety(board: &Board) -> i32 {
+    let mut score = 0;
+
+    for color in [Color::White, Color::Black] {
+        let side = board.side(color);
+
+        let Some(king) = side.king().into_iter().next() else {
+            continue;
+        };
+
+        if relative_rank(color, king) > 1 {
+            continue;
+        }
+
+        let forward = match color {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+
+        let mut shield = 0;
+
+        for file in -1..=1 {
+            for rank in 1..=2 {
+                if let Some(coord) = king.mv(file, rank * forward) {
+                    if side.pawns().is_set(coord) {
+                        shield += 1;
+                        break;
+                    }
+                }
+            }
+        }
+
+        score += sign(color) * shield * PAWN_SHIELD_BONUS;
+    }
+
+    return score;
+}
+
+fn mobility(board: &Board) -> i32 {
+    let mut score = 0;
+
+    for color in [Color::White, Color::Black] {
+        let side = board.side(color);
+        let mut side_score = 0;
+
+        for (pieces, weight) in [
+            (side.knights(), KNIGHT_MOBILITY),
+            (side.bishops(), BISHOP_MOBILITY),
+            (side.rooks(), ROOK_MOBILITY),
+            (side.queens(), QUEEN_MOBILITY),
+        ] {
+            for coord in pieces {
+                side_score += get_move_mask_from(color, coord, board).count_ones() as i32 * weight;
+            }
+        }
+
+        score += sign(color) * side_score;
+    }
+
+    return score;
+}
+
+fn is_ahead(color: Color, pawn: Coord, other: Coord) -> bool {
+    return match color {
+        Color::White => other.row_index() > pawn.row_index(),
+        Color::Black => other.row_index() < pawn.row_index(),
+    };
+}
+
+fn relative_rank(color: Color, coord: Coord) -> usize {
+    return match color {
+        Color::White => coord.row_index() as usize,
+        Color::Black => 7 - coord.row_index() as usize,
+    };
+}
+
+fn sign(color: Color) -> i32 {
+    match color {
+        Color::White => 1,
+        Color::Black => -1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_position_is_balanced() {
+        assert_eq!(0, evaluate(&Board::new_game()));
+    }
+
+    #[test]
+    fn relative_to_side_to_move() {
+        let white = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 w - -").unwrap();
+        let black = Board::from_fen("4k3/8/8/8/8/8/8/3QK3 b - -").unwrap();
+
+        assert!(evaluate(&white) > 0);
+        assert_eq!(evaluate(&white), -evaluate(&black));
+    }
+
+    #[test]
+    fn explain_adds_up_to_evaluate() {
+        let board = Board::from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq -").unwrap();
+        let breakdown = explain(&board);
+
+        assert_eq!(-evaluate(&board), breakdown.total);
+        assert_eq!(32, breakdown.pieces.len());
+    }
+
+    #[test]
+    fn pawn_structure_terms() {
+        let doubled = Board::from_fen("4k3/8/8/8/8/4P3/4P3/4K3 w - -").unwrap();
+        let passed = Board::from_fen("4k3/8/1P6/8/8/8/8/4K3 w - -").unwrap();
+        let blocked = Board::from_fen("4k3/p7/1P6/8/8/8/8/4K3 w - -").unwrap();
+
+        assert!(explain(&doubled).pawn_structure < 0);
+        assert!(explain(&passed).pawn_structure > explain(&blocked).pawn_structure + PASSED_PAWN_BONUS[5] / 2);
+    }
+
+    #[test]
+    fn king_safety_rewards_pawn_shield() {
+        let sheltered = Board::from_fen("4k3/8/8/8/8/8/5PPP/6K1 w - -").unwrap();
+        let exposed = Board::from_fen("4k3/8/8/8/8/5PPP/8/6K1 w - -").unwrap();
+
+        assert!(explain(&sheltered).king_safety > 0);
+        assert!(explain(&sheltered).king_safety >= explain(&exposed).king_safety);
+    }
+}
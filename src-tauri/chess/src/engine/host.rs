@@ -0,0 +1,273 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{san::parse_san, Board, MoveErr};
+
+use super::{SearchResult, SearchStats, MATE_SCORE};
+
+#[derive(Debug, thiserror::Error)]
+pub enum UciError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("The engine closed its output")]
+    Disconnected,
+
+    #[error("The engine played the illegal move '{0}'")]
+    IllegalMove(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PlayError {
+    #[error(transparent)]
+    Move(#[from] MoveErr),
+
+    #[error(transparent)]
+    Uci(#[from] UciError),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalEngine {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub depth: u8,
+    #[serde(default)]
+    pub options: Vec<(String, String)>,
+}
+
+// talks to an engine process over the uci protocol, the process is asked to quit when the host is dropped
+pub struct UciEngine {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl UciEngine {
+    pub fn start(engine: &ExternalEngine) -> Result<Self, UciError> {
+        let mut child = Command::new(&engine.path)
+            .args(&engine.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdin = child.stdin.take().ok_or(UciError::Disconnected)?;
+        let stdout = BufReader::new(child.stdout.take().ok_or(UciError::Disconnected)?);
+
+        let mut host = UciEngine {
+            name: engine.path.display().to_string(),
+            child,
+            stdin,
+            stdout,
+        };
+
+        host.send("uci")?;
+
+        loop {
+            let line = host.read_line()?;
+
+            if let Some(name) = line.strip_prefix("id name ") {
+                host.name = name.to_string();
+            } else if line == "uciok" {
+                break;
+            }
+        }
+
+        for (name, value) in &engine.options {
+            host.send(&format!("setoption name {} value {}", name, value))?;
+        }
+
+        host.new_game()?;
+
+        return Ok(host);
+    }
+
+    pub fn name(&self) -> &str {
+        return &self.name;
+    }
+
+    pub fn new_game(&mut self) -> Result<(), UciError> {
+        self.send("ucinewgame")?;
+        return self.sync();
+    }
+
+    // the position is sent as the starting fen plus the moves played since, so engines can detect repetitions
+    pub fn search(&mut self, board: &Board, depth: u8) -> Result<SearchResult, UciError> {
+        let history = board.history();
+        let moves = history.iter().filter_map(Board::last_move).map(|mv| mv.to_string()).collect::<Vec<_>>();
+
+        if moves.is_empty() {
+            self.send(&format!("position fen {}", history[0].to_fen()))?;
+        } else {
+            self.send(&format!("position fen {} moves {}", history[0].to_fen(), moves.join(" ")))?;
+        }
+
+        self.send(&format!("go depth {}", depth))?;
+
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+            lines: Vec::new(),
+            stats: SearchStats::new(),
+        };
+
+        loop {
+            let line = self.read_line()?;
+            let mut tokens = line.split_whitespace();
+
+            match tokens.next() {
+                Some("info") => parse_info(board, tokens.collect(), &mut result),
+                Some("bestmove") => {
+                    result.best_move = match tokens.next() {
+                        None | Some("(none)") | Some("0000") => None,
+                        Some(mv) => Some(parse_san(board, mv).ok_or_else(|| UciError::IllegalMove(mv.to_string()))?),
+                    };
+
+                    return Ok(result);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // "go perft" is not part of the protocol but most engines answer it with a "move: nodes" line per root move
+    pub fn perft(&mut self, board: &Board, depth: usize) -> Result<BTreeMap<String, u64>, UciError> {
+        self.send(&format!("position fen {}", board.to_fen()))?;
+        self.send(&format!("go perft {}", depth))?;
+
+        let mut divide = BTreeMap::new();
+
+        loop {
+            let line = self.read_line()?;
+
+            if line.starts_with("Nodes searched") {
+                return Ok(divide);
+            }
+
+            if let Some((mv, nodes)) = line.split_once(':') {
+                if let Ok(nodes) = nodes.trim().parse() {
+                    divide.insert(mv.trim().to_string(), nodes);
+                }
+            }
+        }
+    }
+
+    fn sync(&mut self) -> Result<(), UciError> {
+        self.send("isready")?;
+
+        while self.read_line()? != "readyok" {}
+
+        return Ok(());
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), UciError> {
+        tracing::trace!(engine = %self.name, command, "uci send");
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()?;
+
+        return Ok(());
+    }
+
+    fn read_line(&mut self) -> Result<String, UciError> {
+        let mut line = String::new();
+
+        if self.stdout.read_line(&mut line)? == 0 {
+            tracing::warn!(engine = %self.name, "uci engine disconnected");
+            return Err(UciError::Disconnected);
+        }
+
+        tracing::trace!(engine = %self.name, line = line.trim_end(), "uci receive");
+
+        return Ok(line.trim().to_string());
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.wait();
+    }
+}
+
+// mate scores are converted to the built-in engine's scale, where a mate in n moves is MATE_SCORE minus the plies
+fn parse_info(board: &Board, tokens: Vec<&str>, result: &mut SearchResult) {
+    let value = |name: &str| tokens.iter().position(|token| *token == name).and_then(|index| tokens.get(index + 1));
+
+    if let Some(depth) = value("depth").and_then(|depth| depth.parse().ok()) {
+        result.depth = depth;
+    }
+
+    if let Some(nodes) = value("nodes").and_then(|nodes| nodes.parse().ok()) {
+        result.nodes = nodes;
+    }
+
+    match (value("cp"), value("mate").and_then(|mate| mate.parse::<i32>().ok())) {
+        (Some(cp), _) => result.score = cp.parse().unwrap_or(result.score),
+        (_, Some(mate)) if mate > 0 => result.score = MATE_SCORE - (mate * 2 - 1),
+        (_, Some(mate)) => result.score = -MATE_SCORE - mate * 2,
+        _ => {}
+    }
+
+    if let Some(start) = tokens.iter().position(|token| *token == "pv") {
+        let mut position = board.clone();
+        result.pv.clear();
+
+        for token in &tokens[start + 1..] {
+            let Some(mv) = parse_san(&position, token) else {
+                break;
+            };
+
+            if position.exec_move(&mv).is_err() {
+                break;
+            }
+
+            result.pv.push(mv);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{Coord, Move};
+
+    const FAKE_ENGINE: &str = r#"
+        while read line; do
+            case "$line" in
+                uci) echo "id name Fake"; echo "uciok";;
+                isready) echo "readyok";;
+                go*) echo "info depth 3 score mate 2 nodes 120 pv e2e4 e7e5"; echo "bestmove e2e4";;
+                quit) exit 0;;
+            esac
+        done
+    "#;
+
+    #[test]
+    fn plays_through_uci() {
+        let engine = ExternalEngine {
+            path: PathBuf::from("sh"),
+            args: vec!["-c".to_string(), FAKE_ENGINE.to_string()],
+            depth: 3,
+            options: vec![("Hash".to_string(), "16".to_string())],
+        };
+
+        let mut host = UciEngine::start(&engine).unwrap();
+        let result = host.search(&Board::new_game(), 3).unwrap();
+
+        assert_eq!("Fake", host.name());
+        assert_eq!(Some(Move::new(Coord::new('e', 2), Coord::new('e', 4))), result.best_move);
+        assert_eq!((3, 120, MATE_SCORE - 3, 2), (result.depth, result.nodes, result.score, result.pv.len()));
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use crate::{expand_promotions, get_moves, Board, Move};
+
+use super::{evaluate, PvLine, SearchResult, SearchStats, MATE_SCORE};
+
+const EXPLORATION: f64 = 1.4;
+
+// centipawns are turned into an expected score between 0 and 1 and back, a lead of this size is worth about 73%
+const SCORE_SCALE: f64 = 400.0;
+
+// the depth asked for is turned into a number of iterations, each level doubles them
+const BASE_ITERATIONS: u32 = 250;
+const MAX_DEPTH_DOUBLINGS: u8 = 12;
+
+struct Node {
+    mv: Option<Move>,
+    parent: usize,
+    children: Vec<usize>,
+    untried: Vec<Move>,
+    visits: u32,
+    // the sum of the results for the side that played the move leading here
+    value: f64,
+}
+
+impl Node {
+    fn new(mv: Option<Move>, parent: usize, board: &Board) -> Self {
+        Node {
+            mv,
+            parent,
+            children: Vec::new(),
+            untried: expand_promotions(&get_moves(board.turn(), board)),
+            visits: 0,
+            value: 0.0,
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        return self.value / self.visits.max(1) as f64;
+    }
+}
+
+pub fn iterations(depth: u8) -> u32 {
+    return BASE_ITERATIONS << depth.min(MAX_DEPTH_DOUBLINGS);
+}
+
+// uct without random playouts, a new leaf is valued by the static eval, which is far cheaper than playing the game out
+// and good enough to steer the tree, draws by repetition or the fifty move rule are not seen,
+// with a deadline the search runs until then but always expands the root moves once
+pub fn search(board: &Board, iterations: u32, stop: &AtomicBool, deadline: Option<Instant>) -> SearchResult {
+    let _search = tracing::debug_span!("mcts", fen = %board.to_fen(), iterations).entered();
+    let mut nodes = vec![Node::new(None, 0, board)];
+    let mut completed = 0;
+
+    while completed < iterations && !stop.load(Ordering::Relaxed) {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) && nodes[0].untried.is_empty() {
+            break;
+        }
+
+        let mut current = board.clone();
+        let mut index = 0;
+
+        // selection, down through fully expanded nodes
+        while nodes[index].untried.is_empty() && !nodes[index].children.is_empty() {
+            index = select_child(&nodes, index);
+            current.exec_move(&nodes[index].mv.expect("child node to have a move")).expect("tree moves to be legal");
+        }
+
+        // expansion, one untried move at a time, illegal ones are dropped
+        while let Some(mv) = nodes[index].untried.pop() {
+            if current.exec_move(&mv).is_ok() {
+                let child = nodes.len();
+                nodes.push(Node::new(Some(mv), index, &current));
+                nodes[index].children.push(child);
+                index = child;
+                break;
+            }
+        }
+
+        let mut value = 1.0 - leaf_value(&current, &nodes[index]);
+
+        // backpropagation, every level up the result flips sides
+        loop {
+            nodes[index].visits += 1;
+            nodes[index].value += value;
+
+            if index == 0 {
+                break;
+            }
+
+            index = nodes[index].parent;
+            value = 1.0 - value;
+        }
+
+        completed += 1;
+    }
+
+    let pv = principal_variation(&nodes);
+    let score = pv.first().map_or(0, |_| to_centipawns(nodes[most_visited(&nodes, 0)].mean()));
+    let mut stats = SearchStats::new();
+    stats.nodes = nodes.len() as u64;
+
+    tracing::debug!(best_move = ?pv.first(), score, iterations = completed, "mcts finished");
+
+    return SearchResult {
+        best_move: pv.first().copied(),
+        score,
+        depth: pv.len() as u8,
+        nodes: stats.nodes,
+        lines: vec![PvLine { score, pv: pv.clone() }],
+        pv,
+        stats,
+    };
+}
+
+// the expected score for the side to move, a position without moves is over
+fn leaf_value(board: &Board, node: &Node) -> f64 {
+    if node.children.is_empty() && node.untried.is_empty() {
+        return match board.winner() {
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+    }
+
+    return 1.0 / (1.0 + (-evaluate(board) as f64 / SCORE_SCALE).exp());
+}
+
+fn select_child(nodes: &[Node], parent: usize) -> usize {
+    let log_visits = (nodes[parent].visits.max(1) as f64).ln();
+    let uct = |child: &usize| {
+        let node = &nodes[*child];
+        return node.mean() + EXPLORATION * (log_visits / node.visits.max(1) as f64).sqrt();
+    };
+
+    return *nodes[parent]
+        .children
+        .iter()
+        .max_by(|a, b| uct(a).total_cmp(&uct(b)))
+        .expect("node to have children");
+}
+
+fn most_visited(nodes: &[Node], parent: usize) -> usize {
+    return *nodes[parent].children.iter().max_by_key(|child| nodes[**child].visits).unwrap_or(&parent);
+}
+
+fn principal_variation(nodes: &[Node]) -> Vec<Move> {
+    let mut pv = Vec::new();
+    let mut index = 0;
+
+    while !nodes[index].children.is_empty() {
+        index = most_visited(nodes, index);
+        pv.push(nodes[index].mv.expect("child node to have a move"));
+    }
+
+    return pv;
+}
+
+fn to_centipawns(expected: f64) -> i32 {
+    let expected = expected.clamp(1e-6, 1.0 - 1e-6);
+    return (SCORE_SCALE * (expected / (1.0 - expected)).ln()).round().clamp(-MATE_SCORE as f64, MATE_SCORE as f64) as i32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+
+    fn best_move(fen: &str) -> SearchResult {
+        let mut engine = Engine::new();
+        engine.set_mcts(true);
+
+        return engine.search(&Board::from_fen(fen).unwrap(), 3);
+    }
+
+    #[test]
+    fn finds_mate_and_material() {
+        let mate = best_move("6k1/5ppp/8/8/8/8/8/R5K1 w - -");
+        assert_eq!("a1a8", mate.best_move.unwrap().to_string());
+        assert!(mate.score > 0);
+
+        let capture = best_move("4k3/8/8/3q4/2P5/8/8/4K3 w - -");
+        assert_eq!("c4d5", capture.best_move.unwrap().to_string());
+        assert_eq!(capture.best_move, capture.pv.first().copied());
+    }
+
+    #[test]
+    fn stops_when_asked() {
+        let stop = AtomicBool::new(true);
+        let result = search(&Board::new_game(), 1000, &stop, None);
+
+        assert_eq!(None, result.best_move);
+        assert_eq!(1, result.nodes);
+    }
+}
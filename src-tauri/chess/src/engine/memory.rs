@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+use crate::{moves, tablebase, zobrist};
+
+use super::{search::searcher_bytes, TranspositionTable};
+
+// in bytes, the lookup tables are shared by every engine of the process
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    pub sliding_tables: usize,
+    pub zobrist_keys: usize,
+    pub tablebases: usize,
+    pub transposition_table: usize,
+    // the move ordering and principal variation tables, one set for every search thread
+    pub search_tables: usize,
+    pub total: usize,
+}
+
+// what an engine with this hash size and number of threads takes, the tablebases only count once a probe built them
+pub fn memory_usage(hash_mb: usize, threads: usize) -> MemoryUsage {
+    let mut usage = MemoryUsage {
+        sliding_tables: moves::sliding_table_bytes(),
+        zobrist_keys: zobrist::table_bytes(),
+        tablebases: tablebase::loaded_bytes(),
+        transposition_table: TranspositionTable::bytes_for(hash_mb),
+        search_tables: searcher_bytes() * threads.max(1),
+        total: 0,
+    };
+
+    usage.total = usage.sliding_tables + usage.zobrist_keys + usage.tablebases + usage.transposition_table + usage.search_tables;
+
+    return usage;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+
+    #[test]
+    fn counts_tables() {
+        let mut engine = Engine::new();
+        engine.set_hash_size(4);
+        engine.set_threads(2);
+
+        let usage = engine.memory_usage();
+
+        assert_eq!(4 * 1024 * 1024, usage.transposition_table);
+        assert_eq!(2 * memory_usage(4, 1).search_tables, usage.search_tables);
+        assert!(usage.sliding_tables > 1024 * 1024);
+        assert!(usage.total > usage.transposition_table + usage.sliding_tables);
+        assert!(memory_usage(64, 2).total > usage.total);
+    }
+}
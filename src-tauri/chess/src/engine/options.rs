@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Engine, Pruning, DEFAULT_HASH_SIZE_MB, MAX_SKILL_LEVEL};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum OptionError {
+    #[error("Unknown engine option '{0}'")]
+    UnknownOption(String),
+
+    #[error("Invalid value '{value}' for engine option '{name}'")]
+    InvalidValue { name: String, value: String },
+
+    #[error("Invalid setoption command '{0}'")]
+    InvalidCommand(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OptionKind {
+    Spin { default: i64, min: i64, max: i64 },
+    Check { default: bool },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OptionSpec {
+    pub name: &'static str,
+    pub kind: OptionKind,
+}
+
+// names follow the conventions of other uci engines so guis recognise them
+pub const OPTIONS: [OptionSpec; 11] = [
+    OptionSpec {
+        name: "Hash",
+        kind: OptionKind::Spin {
+            default: DEFAULT_HASH_SIZE_MB as i64,
+            min: 1,
+            max: 1024,
+        },
+    },
+    OptionSpec {
+        name: "Threads",
+        kind: OptionKind::Spin { default: 1, min: 1, max: 64 },
+    },
+    OptionSpec {
+        name: "Contempt",
+        kind: OptionKind::Spin { default: 0, min: -100, max: 100 },
+    },
+    OptionSpec {
+        name: "MultiPV",
+        kind: OptionKind::Spin { default: 1, min: 1, max: 16 },
+    },
+    OptionSpec {
+        name: "Skill Level",
+        kind: OptionKind::Spin {
+            default: MAX_SKILL_LEVEL as i64,
+            min: 0,
+            max: MAX_SKILL_LEVEL as i64,
+        },
+    },
+    OptionSpec {
+        name: "Ponder",
+        kind: OptionKind::Check { default: false },
+    },
+    OptionSpec {
+        name: "NullMove",
+        kind: OptionKind::Check { default: true },
+    },
+    OptionSpec {
+        name: "LMR",
+        kind: OptionKind::Check { default: true },
+    },
+    OptionSpec {
+        name: "FutilityPruning",
+        kind: OptionKind::Check { default: true },
+    },
+    OptionSpec {
+        name: "DeltaPruning",
+        kind: OptionKind::Check { default: true },
+    },
+    OptionSpec {
+        name: "MCTS",
+        kind: OptionKind::Check { default: false },
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EngineOptions {
+    pub hash_mb: usize,
+    pub threads: usize,
+    pub contempt: i32,
+    pub multi_pv: usize,
+    pub skill_level: u8,
+    pub ponder: bool,
+    pub null_move: bool,
+    pub late_move_reductions: bool,
+    pub futility_pruning: bool,
+    pub delta_pruning: bool,
+    pub mcts: bool,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        EngineOptions {
+            hash_mb: DEFAULT_HASH_SIZE_MB,
+            threads: 1,
+            contempt: 0,
+            multi_pv: 1,
+            skill_level: MAX_SKILL_LEVEL,
+            ponder: false,
+            null_move: true,
+            late_move_reductions: true,
+            futility_pruning: true,
+            delta_pruning: true,
+            mcts: false,
+        }
+    }
+}
+
+impl EngineOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // option names are matched case-insensitively, values are checked against the registered range
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), OptionError> {
+        let spec = OPTIONS
+            .iter()
+            .find(|spec| spec.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| OptionError::UnknownOption(name.to_string()))?;
+
+        let invalid = || OptionError::InvalidValue {
+            name: spec.name.to_string(),
+            value: value.to_string(),
+        };
+
+        match spec.kind {
+            OptionKind::Spin { min, max, .. } => {
+                let number = value.trim().parse::<i64>().map_err(|_| invalid())?;
+
+                if !(min..=max).contains(&number) {
+                    return Err(invalid());
+                }
+
+                match spec.name {
+                    "Hash" => self.hash_mb = number as usize,
+                    "Threads" => self.threads = number as usize,
+                    "Contempt" => self.contempt = number as i32,
+                    "MultiPV" => self.multi_pv = number as usize,
+                    _ => self.skill_level = number as u8,
+                }
+            }
+            OptionKind::Check { .. } => {
+                let enabled = match value.trim().to_lowercase().as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(invalid()),
+                };
+
+                match spec.name {
+                    "NullMove" => self.null_move = enabled,
+                    "LMR" => self.late_move_reductions = enabled,
+                    "FutilityPruning" => self.futility_pruning = enabled,
+                    "DeltaPruning" => self.delta_pruning = enabled,
+                    "MCTS" => self.mcts = enabled,
+                    _ => self.ponder = enabled,
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    // parses a uci command like "setoption name Skill Level value 10"
+    pub fn set_uci(&mut self, command: &str) -> Result<(), OptionError> {
+        let invalid = || OptionError::InvalidCommand(command.to_string());
+
+        let rest = command.trim().strip_prefix("setoption").ok_or_else(invalid)?;
+        let rest = rest.trim_start().strip_prefix("name ").ok_or_else(invalid)?;
+        let (name, value) = rest.split_once(" value ").ok_or_else(invalid)?;
+
+        return self.set(name, value);
+    }
+
+    pub fn pruning(&self) -> Pruning {
+        return Pruning {
+            null_move: self.null_move,
+            late_move_reductions: self.late_move_reductions,
+            futility: self.futility_pruning,
+            delta: self.delta_pruning,
+        };
+    }
+
+    // the hash table is only reallocated when its size changes, so repeated applies keep its contents
+    pub fn apply(&self, engine: &mut Engine) {
+        if engine.hash_size() != self.hash_mb {
+            engine.set_hash_size(self.hash_mb);
+        }
+
+        engine.set_threads(self.threads);
+        engine.set_contempt(self.contempt);
+        engine.set_pruning(self.pruning());
+        engine.set_multi_pv(self.multi_pv);
+        engine.set_skill_level(self.skill_level);
+        engine.set_mcts(self.mcts);
+    }
+}
+
+// the option lines an engine sends in reply to the uci command
+pub fn uci_options() -> Vec<String> {
+    return OPTIONS
+        .iter()
+        .map(|spec| match spec.kind {
+            OptionKind::Spin { default, min, max } => format!("option name {} type spin default {} min {} max {}", spec.name, default, min, max),
+            OptionKind::Check { default } => format!("option name {} type check default {}", spec.name, default),
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_validate() {
+        let mut options = EngineOptions::new();
+
+        options.set("threads", "4").unwrap();
+        options.set("Ponder", "true").unwrap();
+
+        options.set("lmr", "false").unwrap();
+        options.set("mcts", "true").unwrap();
+
+        assert_eq!(4, options.threads);
+        assert!(options.ponder && options.mcts);
+        assert!(!options.pruning().late_move_reductions && options.pruning().null_move);
+        assert_eq!(Err(OptionError::UnknownOption("Style".to_string())), options.set("Style", "1"));
+        assert!(matches!(options.set("MultiPV", "0"), Err(OptionError::InvalidValue { .. })));
+        assert!(matches!(options.set("Contempt", "high"), Err(OptionError::InvalidValue { .. })));
+    }
+
+    #[test]
+    fn uci_mapping() {
+        let mut options = EngineOptions::new();
+
+        options.set_uci("setoption name Skill Level value 10").unwrap();
+        options.set_uci("setoption name Hash value 64").unwrap();
+
+        assert_eq!(10, options.skill_level);
+        assert_eq!(64, options.hash_mb);
+        assert!(options.set_uci("setoption Hash 64").is_err());
+        assert!(uci_options().contains(&"option name Skill Level type spin default 20 min 0 max 20".to_string()));
+    }
+}
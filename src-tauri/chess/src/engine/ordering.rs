@@ -0,0 +1,183 @@
+use std::cmp::Reverse;
+
+use crate::{Board, Color, Move, MoveList, PieceType};
+
+use super::eval::piece_value;
+
+pub const MAX_PLY: usize = 128;
+
+const HASH_MOVE_SCORE: i32 = 1_000_000;
+const CAPTURE_SCORE: i32 = 100_000;
+const PROMOTION_SCORE: i32 = 90_000;
+const FIRST_KILLER_SCORE: i32 = 80_000;
+const SECOND_KILLER_SCORE: i32 = 79_000;
+const MAX_HISTORY: i32 = 50_000;
+
+#[derive(Clone)]
+pub struct MoveOrderer {
+    killers: [[Option<Move>; 2]; MAX_PLY],
+    history: [[[i32; 64]; 64]; 2],
+}
+
+impl MoveOrderer {
+    pub fn new() -> Self {
+        MoveOrderer {
+            killers: [[None; 2]; MAX_PLY],
+            history: [[[0; 64]; 64]; 2],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.killers = [[None; 2]; MAX_PLY];
+        self.history = [[[0; 64]; 64]; 2];
+    }
+
+    pub fn order(&self, board: &Board, moves: &mut MoveList, hash_move: Option<&Move>, ply: usize) {
+        moves.sort_by_key(|mv| Reverse(self.score(board, mv, hash_move, ply)));
+    }
+
+    pub fn score(&self, board: &Board, mv: &Move, hash_move: Option<&Move>, ply: usize) -> i32 {
+        if hash_move == Some(mv) {
+            return HASH_MOVE_SCORE;
+        }
+
+        if let Some(victim) = captured_piece(board, mv) {
+            let attacker = board.lookup(mv.from).unwrap_or(PieceType::Pawn);
+            return CAPTURE_SCORE + mvv_lva(victim, attacker);
+        }
+
+        if mv.promotion {
+            return PROMOTION_SCORE + piece_value(mv.promote_to);
+        }
+
+        if let Some(killers) = self.killers.get(ply) {
+            if killers[0].as_ref() == Some(mv) {
+                return FIRST_KILLER_SCORE;
+            }
+
+            if killers[1].as_ref() == Some(mv) {
+                return SECOND_KILLER_SCORE;
+            }
+        }
+
+        return self.history[color_index(board.turn())][mv.from.offset()][mv.to.offset()];
+    }
+
+    pub fn add_killer(&mut self, mv: &Move, ply: usize) {
+        if let Some(killers) = self.killers.get_mut(ply) {
+            if killers[0].as_ref() == Some(mv) {
+                return;
+            }
+
+            killers[1] = killers[0];
+            killers[0] = Some(*mv);
+        }
+    }
+
+    pub fn add_history(&mut self, color: Color, mv: &Move, depth: u8) {
+        let entry = &mut self.history[color_index(color)][mv.from.offset()][mv.to.offset()];
+        *entry += depth as i32 * depth as i32;
+
+        if *entry > MAX_HISTORY {
+            for from in self.history.iter_mut().flatten() {
+                for value in from.iter_mut() {
+                    *value /= 2;
+                }
+            }
+        }
+    }
+}
+
+impl Default for MoveOrderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn captured_piece(board: &Board, mv: &Move) -> Option<PieceType> {
+    if mv.en_passant {
+        return Some(PieceType::Pawn);
+    }
+
+    return board.opponent_side().lookup(mv.to);
+}
+
+pub fn is_quiet(board: &Board, mv: &Move) -> bool {
+    return !mv.promotion && captured_piece(board, mv).is_none();
+}
+
+fn mvv_lva(victim: PieceType, attacker: PieceType) -> i32 {
+    return piece_value(victim) * 10 - piece_value(attacker) / 10;
+}
+
+fn color_index(color: Color) -> usize {
+    match color {
+        Color::White => 0,
+        Color::Black => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_moves_into, Coord};
+
+    fn ordered(fen: &str, orderer: &MoveOrderer, hash_move: Option<&Move>) -> MoveList {
+        let board = Board::from_fen(fen).unwrap();
+        let mut moves = MoveList::new();
+
+        get_moves_into(board.turn(), &board, &mut moves);
+        orderer.order(&board, &mut moves, hash_move, 0);
+
+        return moves;
+    }
+
+    fn mv(from: &str, to: &str) -> Move {
+        Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap())
+    }
+
+    #[test]
+    fn hash_move_first() {
+        let hash_move = mv("g1", "f3");
+        let moves = ordered("rnbqkbnr/pppp1ppp/8/4p3/3P4/8/PPP1PPPP/RNBQKBNR w KQkq -", &MoveOrderer::new(), Some(&hash_move));
+
+        assert_eq!(hash_move, moves[0]);
+    }
+
+    #[test]
+    fn captures_before_quiet_moves() {
+        let moves = ordered("rnbqkbnr/pppp1ppp/8/4p3/3P4/8/PPP1PPPP/RNBQKBNR w KQkq -", &MoveOrderer::new(), None);
+
+        assert_eq!(mv("d4", "e5"), moves[0]);
+    }
+
+    #[test]
+    fn most_valuable_victim_first() {
+        let moves = ordered("4k3/8/8/2q1r3/3P4/8/8/7K w - -", &MoveOrderer::new(), None);
+
+        assert_eq!(mv("d4", "c5"), moves[0]);
+        assert_eq!(mv("d4", "e5"), moves[1]);
+    }
+
+    #[test]
+    fn least_valuable_attacker_first() {
+        let moves = ordered("4k3/8/8/3r4/2P5/8/8/3RK3 w - -", &MoveOrderer::new(), None);
+
+        assert_eq!(mv("c4", "d5"), moves[0]);
+        assert_eq!(mv("d1", "d5"), moves[1]);
+    }
+
+    #[test]
+    fn killers_before_history() {
+        let mut orderer = MoveOrderer::new();
+        orderer.add_history(Color::White, &mv("a2", "a3"), 10);
+        orderer.add_killer(&mv("h2", "h3"), 0);
+        orderer.add_killer(&mv("g2", "g3"), 0);
+
+        let moves = ordered("4k3/8/8/8/8/8/PPPPPPPP/4K3 w - -", &orderer, None);
+
+        assert_eq!(mv("g2", "g3"), moves[0]);
+        assert_eq!(mv("h2", "h3"), moves[1]);
+        assert_eq!(mv("a2", "a3"), moves[2]);
+    }
+}
@@ -0,0 +1,111 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{Board, Move, MoveErr};
+
+use super::{Engine, SearchResult};
+
+pub struct Ponder {
+    board: Board,
+    expected: Move,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<(Engine, SearchResult)>,
+}
+
+impl Ponder {
+    pub fn start(mut engine: Engine, board: &Board, expected: &Move, depth: u8) -> Result<Self, MoveErr> {
+        let mut board = board.clone();
+        board.exec_move(expected)?;
+
+        let stop = engine.stop_handle();
+        let search_board = board.clone();
+
+        let handle = thread::spawn(move || {
+            let result = engine.search(&search_board, depth);
+            return (engine, result);
+        });
+
+        return Ok(Ponder {
+            board,
+            expected: *expected,
+            stop,
+            handle,
+        });
+    }
+
+    pub fn expected(&self) -> &Move {
+        return &self.expected;
+    }
+
+    pub fn board(&self) -> &Board {
+        return &self.board;
+    }
+
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        return self.stop.clone();
+    }
+
+    pub fn is_hit(&self, board: &Board) -> bool {
+        return self.board == *board;
+    }
+
+    pub fn ponderhit(self) -> (Engine, SearchResult) {
+        return self.handle.join().expect("ponder thread to not panic");
+    }
+
+    pub fn stop(self) -> Engine {
+        self.stop.store(true, Ordering::Relaxed);
+
+        let (engine, _) = self.handle.join().expect("ponder thread to not panic");
+        engine.stop_handle().store(false, Ordering::Relaxed);
+
+        return engine;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coord;
+
+    #[test]
+    fn ponderhit_searches_expected_position() {
+        let board = Board::new_game();
+        let expected = Move::new(Coord::new('e', 2), Coord::new('e', 4));
+
+        let ponder = Ponder::start(Engine::new(), &board, &expected, 3).unwrap();
+
+        let mut played = board.clone();
+        played.exec_move(&expected).unwrap();
+
+        assert!(ponder.is_hit(&played));
+
+        let (_, result) = ponder.ponderhit();
+
+        assert_eq!(3, result.depth);
+        assert!(played.turning_side().lookup(result.best_move.unwrap().from).is_some());
+    }
+
+    #[test]
+    fn miss_stops_search() {
+        let board = Board::new_game();
+        let expected = Move::new(Coord::new('e', 2), Coord::new('e', 4));
+
+        let ponder = Ponder::start(Engine::new(), &board, &expected, 64).unwrap();
+
+        let mut played = board.clone();
+        played.exec_move(&Move::new(Coord::new('d', 2), Coord::new('d', 4))).unwrap();
+
+        assert!(!ponder.is_hit(&played));
+
+        let mut engine = ponder.stop();
+        let result = engine.search(&played, 1);
+
+        assert_eq!(1, result.depth);
+    }
+}
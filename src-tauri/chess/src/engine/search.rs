@@ -0,0 +1,991 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{get_moves_into, Board, Color, Move, MoveList, Variant};
+
+use super::{
+    eval::{evaluate, piece_value, PAWN_VALUE},
+    mcts,
+    memory::{memory_usage, MemoryUsage},
+    ordering::{is_quiet, MoveOrderer, MAX_PLY},
+    stats::{IterationInfo, SearchListener, SearchStats},
+    time::TimeBudget,
+    trace::{SearchTrace, TraceLimits, TraceRecorder},
+    tt::{Bound, TranspositionTable, TtEntry, DEFAULT_HASH_SIZE_MB},
+};
+
+pub const INFINITY: i32 = 1_000_000;
+pub const MATE_SCORE: i32 = 100_000;
+
+pub const MAX_SKILL_LEVEL: u8 = 20;
+
+const STOP_CHECK_INTERVAL: u64 = 1024;
+const FIFTY_MOVE_PLIES: u32 = 100;
+
+const NULL_MOVE_MIN_DEPTH: u8 = 3;
+const NULL_MOVE_REDUCTION: u8 = 2;
+const FUTILITY_MARGINS: [i32; 3] = [0, 200, 400];
+const LMR_MIN_DEPTH: u8 = 3;
+const LMR_MIN_MOVES: usize = 3;
+const DELTA_MARGIN: i32 = 200;
+
+// lines considered when a reduced skill level picks a weaker move
+const SKILL_CANDIDATES: usize = 4;
+const SKILL_MARGIN_PER_LEVEL: i32 = 25;
+
+// every technique can be switched off on its own, so its effect can be measured in engine matches
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Pruning {
+    pub null_move: bool,
+    pub late_move_reductions: bool,
+    pub futility: bool,
+    pub delta: bool,
+}
+
+impl Default for Pruning {
+    fn default() -> Self {
+        Pruning {
+            null_move: true,
+            late_move_reductions: true,
+            futility: true,
+            delta: true,
+        }
+    }
+}
+
+impl Pruning {
+    pub fn none() -> Self {
+        Pruning {
+            null_move: false,
+            late_move_reductions: false,
+            futility: false,
+            delta: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PvLine {
+    pub score: i32,
+    pub pv: Vec<Move>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub depth: u8,
+    pub nodes: u64,
+    pub pv: Vec<Move>,
+    pub lines: Vec<PvLine>,
+    pub stats: SearchStats,
+}
+
+pub struct Engine {
+    tt: Arc<TranspositionTable>,
+    stop: Arc<AtomicBool>,
+    threads: usize,
+    hash_size_mb: usize,
+    contempt: i32,
+    pruning: Pruning,
+    multi_pv: usize,
+    skill_level: u8,
+    mcts: bool,
+    deadline: Option<Deadline>,
+    main: Searcher,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Deadline {
+    soft: Instant,
+    hard: Instant,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        let tt = Arc::new(TranspositionTable::new(DEFAULT_HASH_SIZE_MB));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        Engine {
+            main: Searcher::new(tt.clone(), stop.clone()),
+            tt,
+            stop,
+            threads: 1,
+            hash_size_mb: DEFAULT_HASH_SIZE_MB,
+            contempt: 0,
+            pruning: Pruning::default(),
+            multi_pv: 1,
+            skill_level: MAX_SKILL_LEVEL,
+            mcts: false,
+            deadline: None,
+        }
+    }
+
+    pub fn stop_handle(&self) -> Arc<AtomicBool> {
+        return self.stop.clone();
+    }
+
+    pub fn threads(&self) -> usize {
+        return self.threads;
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn hash_size(&self) -> usize {
+        return self.hash_size_mb;
+    }
+
+    pub fn set_hash_size(&mut self, size_mb: usize) {
+        let listener = self.main.listener.take();
+
+        self.hash_size_mb = size_mb;
+        self.tt = Arc::new(TranspositionTable::new(size_mb));
+        self.main = Searcher::new(self.tt.clone(), self.stop.clone());
+        self.main.listener = listener;
+    }
+
+    // the contempt in centipawns is subtracted from draw scores for the side the engine plays
+    pub fn set_contempt(&mut self, contempt: i32) {
+        self.contempt = contempt;
+    }
+
+    pub fn set_pruning(&mut self, pruning: Pruning) {
+        self.pruning = pruning;
+    }
+
+    pub fn set_multi_pv(&mut self, multi_pv: usize) {
+        self.multi_pv = multi_pv.max(1);
+    }
+
+    pub fn set_skill_level(&mut self, skill_level: u8) {
+        self.skill_level = skill_level.min(MAX_SKILL_LEVEL);
+    }
+
+    // monte carlo tree search instead of alpha-beta, the depth then only decides how many iterations are run
+    pub fn set_mcts(&mut self, mcts: bool) {
+        self.mcts = mcts;
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        return memory_usage(self.hash_size_mb, self.threads);
+    }
+
+    pub fn set_listener(&mut self, listener: Option<SearchListener>) {
+        self.main.listener = listener;
+    }
+
+    pub fn new_game(&mut self) {
+        self.tt.clear();
+    }
+
+    // additional lines are found by searching the root again without the moves of the previous lines
+    pub fn search(&mut self, board: &Board, depth: u8) -> SearchResult {
+        if self.mcts {
+            let iterations = if self.deadline.is_some() { u32::MAX } else { mcts::iterations(depth) };
+            let result = mcts::search(board, iterations, &self.stop, self.deadline.map(|deadline| deadline.soft));
+            self.stop.store(false, Ordering::Relaxed);
+
+            return result;
+        }
+
+        let _search = tracing::debug_span!("search", fen = %board.to_fen(), depth, threads = self.threads).entered();
+        let mut line_count = self.multi_pv;
+
+        if self.skill_level < MAX_SKILL_LEVEL {
+            line_count = line_count.max(SKILL_CANDIDATES);
+        }
+
+        let mut result = self.search_excluding(board, depth, &[]);
+        let mut excluded = result.best_move.into_iter().collect::<Vec<_>>();
+
+        result.lines = vec![PvLine {
+            score: result.score,
+            pv: result.pv.clone(),
+        }];
+
+        while result.lines.len() < line_count && !excluded.is_empty() && !self.stop.load(Ordering::Relaxed) {
+            let line = self.search_excluding(board, depth, &excluded);
+            result.stats.merge(&line.stats);
+
+            let Some(best_move) = line.best_move else {
+                break;
+            };
+
+            excluded.push(best_move);
+            result.lines.push(PvLine {
+                score: line.score,
+                pv: line.pv,
+            });
+        }
+
+        if let Some(line) = self.pick_by_skill(&result.lines) {
+            result.score = line.score;
+            result.pv = line.pv.clone();
+            result.best_move = result.pv.first().copied();
+        }
+
+        result.lines.truncate(self.multi_pv);
+        result.nodes = result.stats.total_nodes();
+
+        self.stop.store(false, Ordering::Relaxed);
+
+        tracing::debug!(best_move = ?result.best_move, score = result.score, depth = result.depth, nodes = result.nodes, "search finished");
+
+        return result;
+    }
+
+    // deepens until the time is up instead of to a fixed depth, the first iteration always completes
+    pub fn search_timed(&mut self, board: &Board, budget: TimeBudget) -> SearchResult {
+        let start = Instant::now();
+
+        self.deadline = Some(Deadline {
+            soft: start + budget.soft,
+            hard: start + budget.hard,
+        });
+
+        let result = self.search(board, (MAX_PLY - 1) as u8);
+        self.deadline = None;
+
+        return result;
+    }
+
+    // a single searcher with a table of its own, so the recorded tree does not depend on earlier searches
+    pub fn trace_search(&mut self, board: &Board, depth: u8, limits: TraceLimits) -> (SearchResult, SearchTrace) {
+        let mut searcher = Searcher::new(Arc::new(TranspositionTable::new(self.hash_size_mb)), self.stop.clone());
+        searcher.configure(self.contempt, self.pruning, board.turn(), &[]);
+        searcher.trace = Some(Box::new(TraceRecorder::new(limits)));
+
+        let result = searcher.search(board, 1, depth);
+        let trace = searcher.trace.take().expect("trace to be recorded").finish(board, result.depth);
+
+        self.stop.store(false, Ordering::Relaxed);
+
+        return (result, trace);
+    }
+
+    // unrelated positions are spread over the rayon pool instead of helper threads, every worker keeps its own
+    // searcher but they all share the transposition table, so positions from the same game help each other
+    pub fn evaluate_batch(&self, boards: &[Board], depth: u8) -> Vec<SearchResult> {
+        let _batch = tracing::debug_span!("evaluate_batch", positions = boards.len(), depth).entered();
+        let (tt, stop, contempt, pruning) = (&self.tt, &self.stop, self.contempt, self.pruning);
+
+        let results = boards
+            .par_iter()
+            .map_init(
+                || Searcher::new(tt.clone(), stop.clone()),
+                |searcher, board| {
+                    searcher.configure(contempt, pruning, board.turn(), &[]);
+
+                    let mut result = searcher.search(board, 1, depth);
+                    result.lines = vec![PvLine {
+                        score: result.score,
+                        pv: result.pv.clone(),
+                    }];
+
+                    return result;
+                },
+            )
+            .collect::<Vec<_>>();
+
+        self.stop.store(false, Ordering::Relaxed);
+
+        return results;
+    }
+
+    fn search_excluding(&mut self, board: &Board, depth: u8, excluded: &[Move]) -> SearchResult {
+        let helper_stop = Arc::new(AtomicBool::new(false));
+        let (contempt, pruning, root) = (self.contempt, self.pruning, board.turn());
+        let span = tracing::Span::current();
+
+        self.main.configure(contempt, pruning, root, excluded);
+        self.main.deadline = self.deadline;
+
+        return thread::scope(|scope| {
+            let helpers = (1..self.threads)
+                .map(|i| {
+                    let mut helper = Searcher::new(self.tt.clone(), helper_stop.clone());
+                    helper.configure(contempt, pruning, root, excluded);
+                    helper.deadline = self.deadline;
+
+                    let span = tracing::trace_span!(parent: &span, "helper", index = i);
+
+                    return scope.spawn(move || span.in_scope(|| helper.search(board, 1 + (i % 2) as u8, depth).stats));
+                })
+                .collect::<Vec<_>>();
+
+            let mut result = self.main.search(board, 1, depth);
+            helper_stop.store(true, Ordering::Relaxed);
+
+            for helper in helpers {
+                result.stats.merge(&helper.join().expect("helper thread to not panic"));
+            }
+
+            result.nodes = result.stats.total_nodes();
+
+            return result;
+        });
+    }
+
+    // lower skill levels play the weakest candidate that stays within a margin of the best line
+    fn pick_by_skill<'a>(&self, lines: &'a [PvLine]) -> Option<&'a PvLine> {
+        if self.skill_level >= MAX_SKILL_LEVEL {
+            return None;
+        }
+
+        let best = lines.first()?.score;
+        let margin = (MAX_SKILL_LEVEL - self.skill_level) as i32 * SKILL_MARGIN_PER_LEVEL;
+
+        return lines.iter().take(SKILL_CANDIDATES).rev().find(|line| best - line.score <= margin);
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Searcher {
+    tt: Arc<TranspositionTable>,
+    stop: Arc<AtomicBool>,
+    orderer: Box<MoveOrderer>,
+    pv: Vec<[Option<Move>; MAX_PLY]>,
+    pv_length: Vec<usize>,
+    stats: SearchStats,
+    listener: Option<SearchListener>,
+    stopped: bool,
+    contempt: i32,
+    pruning: Pruning,
+    root: Color,
+    excluded: Vec<Move>,
+    trace: Option<Box<TraceRecorder>>,
+    deadline: Option<Deadline>,
+}
+
+// what a searcher allocates for its tables, the engine keeps one and starts another for every helper thread
+pub(super) fn searcher_bytes() -> usize {
+    return std::mem::size_of::<MoveOrderer>() + MAX_PLY * std::mem::size_of::<[Option<Move>; MAX_PLY]>() + MAX_PLY * std::mem::size_of::<usize>();
+}
+
+impl Searcher {
+    fn new(tt: Arc<TranspositionTable>, stop: Arc<AtomicBool>) -> Self {
+        Searcher {
+            tt,
+            stop,
+            orderer: Box::new(MoveOrderer::new()),
+            pv: vec![[None; MAX_PLY]; MAX_PLY],
+            pv_length: vec![0; MAX_PLY],
+            stats: SearchStats::new(),
+            listener: None,
+            stopped: false,
+            contempt: 0,
+            pruning: Pruning::default(),
+            root: Color::White,
+            excluded: Vec::new(),
+            trace: None,
+            deadline: None,
+        }
+    }
+
+    fn configure(&mut self, contempt: i32, pruning: Pruning, root: Color, excluded: &[Move]) {
+        self.contempt = contempt;
+        self.pruning = pruning;
+        self.root = root;
+        self.excluded = excluded.to_vec();
+    }
+
+    fn draw_score(&self, board: &Board) -> i32 {
+        return if board.turn() == self.root { -self.contempt } else { self.contempt };
+    }
+
+    fn search(&mut self, board: &Board, start_depth: u8, depth: u8) -> SearchResult {
+        let mut board = board.clone();
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+            lines: Vec::new(),
+            stats: SearchStats::new(),
+        };
+
+        self.orderer.clear();
+        self.stats = SearchStats::new();
+        self.stopped = false;
+
+        let start = Instant::now();
+
+        for current_depth in start_depth..=depth {
+            // only the tree of the last iteration is kept
+            if let Some(trace) = self.trace.as_mut() {
+                trace.clear();
+            }
+
+            let score = self.negamax(&mut board, current_depth, 0, -INFINITY, INFINITY);
+
+            if self.stopped {
+                break;
+            }
+
+            result.score = score;
+            result.depth = current_depth;
+            result.pv = self.pv[0][0..self.pv_length[0]].iter().flatten().copied().collect();
+            result.best_move = result.pv.first().copied();
+
+            let info = IterationInfo {
+                depth: current_depth,
+                score,
+                nodes: self.stats.total_nodes(),
+                time_ms: start.elapsed().as_millis() as u64,
+                pv: result.pv.clone(),
+            };
+
+            tracing::trace!(depth = current_depth, score, nodes = info.nodes, time_ms = info.time_ms, "iteration finished");
+
+            if let Some(listener) = self.listener.as_mut() {
+                listener(&info);
+            }
+
+            self.stats.iterations.push(info);
+
+            // another iteration would take longer than the one before, so none is started after the soft limit
+            if self.deadline.is_some_and(|deadline| Instant::now() >= deadline.soft) {
+                break;
+            }
+        }
+
+        result.nodes = self.stats.total_nodes();
+        result.stats = std::mem::take(&mut self.stats);
+
+        return result;
+    }
+
+    fn negamax(&mut self, board: &mut Board, depth: u8, ply: usize, alpha: i32, beta: i32) -> i32 {
+        let Some(node) = self.trace.as_mut().and_then(|trace| trace.enter(board, ply, depth, alpha, beta)) else {
+            return self.search_node(board, depth, ply, alpha, beta);
+        };
+
+        let score = self.search_node(board, depth, ply, alpha, beta);
+
+        if let Some(trace) = self.trace.as_mut() {
+            trace.exit(node, score);
+        }
+
+        return score;
+    }
+
+    fn search_node(&mut self, board: &mut Board, depth: u8, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.pv_length[ply] = ply;
+
+        if depth == 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        self.stats.nodes += 1;
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        // a repetition inside the search is scored as a draw right away, the opponent could simply repeat again
+        if ply > 0 && board.is_repetition() {
+            return self.draw_score(board);
+        }
+
+        let pv_node = beta - alpha > 1;
+        let hash = board.hash();
+        let entry = self.tt.probe(hash, ply);
+
+        self.stats.tt_probes += 1;
+
+        if let Some(entry) = entry {
+            self.stats.tt_hits += 1;
+
+            if !pv_node && entry.depth >= depth {
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => entry.score >= beta,
+                    Bound::Upper => entry.score <= alpha,
+                };
+
+                if cutoff {
+                    self.stats.tt_cutoffs += 1;
+                    return entry.score;
+                }
+            }
+        }
+
+        let checked = board.turning_side().checked();
+        let static_eval = if pv_node || checked { None } else { Some(evaluate(board)) };
+
+        // giving the opponent a free move and still failing high means the position is good enough to skip,
+        // zugzwang is unlikely as long as the side to move has pieces besides pawns
+        if let Some(static_eval) = static_eval {
+            if self.pruning.null_move && ply > 0 && depth >= NULL_MOVE_MIN_DEPTH && static_eval >= beta && can_pass(board) {
+                board.make_null_move().expect("side to move to not be in check");
+
+                let score = -self.negamax(board, depth - 1 - NULL_MOVE_REDUCTION, ply + 1, -beta, -beta + 1);
+
+                board.undo_null_move().expect("null move to be undoable");
+
+                if self.stopped {
+                    return 0;
+                }
+
+                if score >= beta && score < MATE_SCORE - MAX_PLY as i32 {
+                    self.stats.null_move_cutoffs += 1;
+                    return beta;
+                }
+            }
+        }
+
+        // close to the horizon quiet moves cannot bring a hopeless static score back above alpha
+        let futile = self.pruning.futility
+            && (depth as usize) < FUTILITY_MARGINS.len()
+            && alpha.abs() < MATE_SCORE - MAX_PLY as i32
+            && static_eval.is_some_and(|static_eval| static_eval + FUTILITY_MARGINS[depth as usize] <= alpha);
+
+        let mut moves = MoveList::new();
+        get_moves_into(board.turn(), board, &mut moves);
+
+        if moves.is_empty() {
+            return terminal_score(board, ply, self.draw_score(board));
+        }
+
+        // a mate on the hundredth ply still counts, otherwise the fifty-move rule ends the game
+        if ply > 0 && board.halfmove_clock() >= FIFTY_MOVE_PLIES {
+            return self.draw_score(board);
+        }
+
+        if ply == 0 && !self.excluded.is_empty() {
+            moves.retain(|mv| !self.excluded.contains(mv));
+        }
+
+        let hash_move = entry.and_then(|entry| entry.best_move);
+        self.orderer.order(board, &mut moves, hash_move.as_ref(), ply);
+
+        let original_alpha = alpha;
+        let mut best_move = None;
+        let mut best_score = -INFINITY;
+
+        for (i, mv) in moves.iter().enumerate() {
+            let quiet = is_quiet(board, mv);
+
+            if board.exec_move(mv).is_err() {
+                continue;
+            }
+
+            let gives_check = board.turning_side().checked();
+
+            if futile && i > 0 && quiet && !gives_check {
+                board.undo_move().expect("move to be undoable");
+                self.stats.futility_prunes += 1;
+                continue;
+            }
+
+            // late quiet moves are searched shallower first and only get the full depth when they beat alpha
+            let reduction = match self.pruning.late_move_reductions && depth >= LMR_MIN_DEPTH && i >= LMR_MIN_MOVES && quiet && !checked && !gives_check {
+                true if depth >= 2 * LMR_MIN_DEPTH && i >= 2 * LMR_MIN_MOVES => 2,
+                true => 1,
+                false => 0,
+            };
+
+            let mut score;
+
+            if i == 0 {
+                score = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha);
+            } else {
+                score = -self.negamax(board, depth - 1 - reduction, ply + 1, -alpha - 1, -alpha);
+
+                if reduction > 0 && score > alpha {
+                    self.stats.reduction_researches += 1;
+                    score = -self.negamax(board, depth - 1, ply + 1, -alpha - 1, -alpha);
+                }
+
+                if score > alpha && score < beta {
+                    score = -self.negamax(board, depth - 1, ply + 1, -beta, -alpha);
+                }
+            }
+
+            board.undo_move().expect("move to be undoable");
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(*mv);
+            }
+
+            if score >= beta {
+                self.stats.beta_cutoffs += 1;
+
+                if i == 0 {
+                    self.stats.first_move_cutoffs += 1;
+                }
+
+                if quiet {
+                    self.orderer.add_killer(mv, ply);
+                    self.orderer.add_history(board.turn(), mv, depth);
+                }
+
+                break;
+            }
+
+            if score > alpha {
+                alpha = score;
+                self.update_pv(ply, mv);
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+
+        self.tt.store(
+            hash,
+            ply,
+            TtEntry {
+                best_move,
+                score: best_score,
+                depth,
+                bound,
+            },
+        );
+
+        return best_score;
+    }
+
+    fn quiescence(&mut self, board: &mut Board, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.stats.qnodes += 1;
+
+        if self.should_stop() {
+            return 0;
+        }
+
+        let checked = board.turning_side().checked();
+        let stand_pat = if checked { None } else { Some(evaluate(board)) };
+
+        if let Some(stand_pat) = stand_pat {
+            if stand_pat >= beta {
+                self.stats.stand_pat_cutoffs += 1;
+                return beta;
+            }
+
+            if stand_pat > alpha {
+                alpha = stand_pat;
+            }
+        }
+
+        if ply >= MAX_PLY - 1 {
+            return alpha;
+        }
+
+        let mut moves = MoveList::new();
+        get_moves_into(board.turn(), board, &mut moves);
+
+        if moves.is_empty() && (checked || board.winner().is_some()) {
+            return terminal_score(board, ply, self.draw_score(board));
+        }
+
+        self.orderer.order(board, &mut moves, None, ply);
+
+        for mv in &moves {
+            if !checked && is_quiet(board, mv) {
+                continue;
+            }
+
+            // a capture that cannot lift the score to alpha even with a margin is not worth searching
+            if let Some(stand_pat) = stand_pat.filter(|_| self.pruning.delta && !mv.promotion) {
+                if stand_pat + captured_value(board, mv) + DELTA_MARGIN <= alpha {
+                    self.stats.delta_prunes += 1;
+                    continue;
+                }
+            }
+
+            if board.exec_move(mv).is_err() {
+                continue;
+            }
+
+            let score = -self.quiescence(board, ply + 1, -beta, -alpha);
+
+            board.undo_move().expect("move to be undoable");
+
+            if self.stopped {
+                return 0;
+            }
+
+            if score >= beta {
+                return beta;
+            }
+
+            if score > alpha {
+                alpha = score;
+            }
+        }
+
+        return alpha;
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if !self.stopped && self.stats.total_nodes() & (STOP_CHECK_INTERVAL - 1) == 0 {
+            let timed_out = !self.stats.iterations.is_empty() && self.deadline.is_some_and(|deadline| Instant::now() >= deadline.hard);
+            self.stopped = timed_out || self.stop.load(Ordering::Relaxed);
+        }
+
+        return self.stopped;
+    }
+
+    fn update_pv(&mut self, ply: usize, mv: &Move) {
+        self.pv[ply][ply] = Some(*mv);
+
+        for next in (ply + 1)..self.pv_length[ply + 1] {
+            self.pv[ply][next] = self.pv[ply + 1][next];
+        }
+
+        self.pv_length[ply] = self.pv_length[ply + 1].max(ply + 1);
+    }
+}
+
+fn can_pass(board: &Board) -> bool {
+    let side = board.turning_side();
+    let pieces = side.all() & !side.pawns() & !side.king();
+
+    return board.variant() == Variant::Standard && board.last_move().is_some() && pieces.count_ones() > 0;
+}
+
+fn captured_value(board: &Board, mv: &Move) -> i32 {
+    if mv.en_passant {
+        return PAWN_VALUE;
+    }
+
+    return board.lookup(mv.to).map(piece_value).unwrap_or(0);
+}
+
+fn terminal_score(board: &Board, ply: usize, draw_score: i32) -> i32 {
+    return match board.winner() {
+        Some(winner) if winner == board.turn() => MATE_SCORE - ply as i32,
+        Some(_) => -MATE_SCORE + ply as i32,
+        None if board.turning_side().checked() => -MATE_SCORE + ply as i32,
+        None => draw_score,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coord;
+
+    fn best_move(fen: &str, depth: u8) -> SearchResult {
+        let board = Board::from_fen(fen).unwrap();
+        return Engine::new().search(&board, depth);
+    }
+
+    #[test]
+    fn finds_mate_in_one() {
+        let result = best_move("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - -", 2);
+
+        assert_eq!(Some(Move::new(Coord::new('a', 1), Coord::new('a', 8))), result.best_move);
+        assert_eq!(MATE_SCORE - 1, result.score);
+    }
+
+    #[test]
+    fn wins_hanging_queen() {
+        let result = best_move("4k3/8/8/3q4/8/8/3R4/4K3 w - -", 3);
+
+        assert_eq!(Some(Move::new(Coord::new('d', 2), Coord::new('d', 5))), result.best_move);
+    }
+
+    #[test]
+    fn stalemate_is_draw() {
+        let result = best_move("7k/5Q2/6K1/8/8/8/8/8 b - -", 1);
+
+        assert_eq!(None, result.best_move);
+        assert_eq!(0, result.score);
+    }
+
+    #[test]
+    fn stopped_search_keeps_last_complete_iteration() {
+        let mut engine = Engine::new();
+        let board = Board::new_game();
+
+        engine.stop_handle().store(true, Ordering::Relaxed);
+        let result = engine.search(&board, 5);
+
+        assert!(result.depth < 5);
+        assert!(!engine.stop_handle().load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn lazy_smp_finds_same_mate() {
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - -").unwrap();
+        let mut engine = Engine::new();
+        engine.set_threads(3);
+
+        let result = engine.search(&board, 4);
+
+        assert_eq!(Some(Move::new(Coord::new('a', 1), Coord::new('a', 8))), result.best_move);
+        assert_eq!(MATE_SCORE - 1, result.score);
+    }
+
+    #[test]
+    fn single_thread_is_deterministic() {
+        let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq -").unwrap();
+
+        let a = Engine::new().search(&board, 4);
+        let b = Engine::new().search(&board, 4);
+
+        assert_eq!(a.best_move, b.best_move);
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.pv, b.pv);
+        assert_eq!(a.nodes, b.nodes);
+    }
+
+    #[test]
+    fn collects_search_stats() {
+        let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq -").unwrap();
+        let depths = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut engine = Engine::new();
+
+        let listener_depths = depths.clone();
+        engine.set_listener(Some(Box::new(move |info| listener_depths.lock().unwrap().push(info.depth))));
+
+        let result = engine.search(&board, 4);
+
+        assert_eq!(result.nodes, result.stats.nodes + result.stats.qnodes);
+        assert!(result.stats.qnodes > 0);
+        assert!(result.stats.beta_cutoffs >= result.stats.first_move_cutoffs);
+        assert!(result.stats.tt_probes >= result.stats.tt_hits);
+        assert_eq!(vec![1, 2, 3, 4], result.stats.iterations.iter().map(|info| info.depth).collect::<Vec<_>>());
+        assert_eq!(vec![1, 2, 3, 4], *depths.lock().unwrap());
+    }
+
+    #[test]
+    fn multi_pv_returns_distinct_lines() {
+        let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq -").unwrap();
+        let mut engine = Engine::new();
+        engine.set_multi_pv(3);
+
+        let result = engine.search(&board, 3);
+        let first_moves = result.lines.iter().map(|line| line.pv[0]).collect::<Vec<_>>();
+
+        assert_eq!(3, result.lines.len());
+        assert_eq!(result.best_move, Some(first_moves[0]));
+        assert!(first_moves[1..].iter().all(|mv| *mv != first_moves[0]));
+        assert!(result.lines.windows(2).all(|lines| lines[0].score >= lines[1].score));
+    }
+
+    #[test]
+    fn batch_matches_single_searches() {
+        let boards = [
+            Board::new_game(),
+            Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -").unwrap(),
+            Board::from_fen("7k/8/8/8/8/1Q6/8/K7 w - - 99 80").unwrap(),
+        ];
+
+        let results = Engine::new().evaluate_batch(&boards, 3);
+
+        assert_eq!(boards.len(), results.len());
+        assert_eq!(Some(Move::new(Coord::new('a', 1), Coord::new('a', 8))), results[1].best_move);
+        assert_eq!(Engine::new().search(&boards[1], 3).score, results[1].score);
+        assert_eq!(0, results[2].score);
+        assert!(results.iter().all(|result| result.depth == 3 && result.lines.len() == 1));
+    }
+
+    #[test]
+    fn low_skill_picks_weaker_move() {
+        let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq -").unwrap();
+        let best = Engine::new().search(&board, 3);
+
+        let mut engine = Engine::new();
+        engine.set_skill_level(0);
+
+        let result = engine.search(&board, 3);
+
+        assert_ne!(best.best_move, result.best_move);
+        assert!(result.score <= best.score);
+        assert_eq!(1, result.lines.len());
+    }
+
+    #[test]
+    fn contempt_scores_draws() {
+        let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - -").unwrap();
+        let mut engine = Engine::new();
+        engine.set_contempt(50);
+
+        assert_eq!(-50, engine.search(&board, 1).score);
+    }
+
+    #[test]
+    fn fifty_move_rule_draws_in_search() {
+        let board = Board::from_fen("7k/8/8/8/8/1Q6/8/K7 w - - 99 80").unwrap();
+        let mut engine = Engine::new();
+
+        assert_eq!(0, engine.search(&board, 3).score);
+
+        engine.set_contempt(50);
+        engine.new_game();
+
+        assert_eq!(-50, engine.search(&board, 3).score);
+    }
+
+    // every tactic of the suite has to be found with and without pruning
+    #[test]
+    fn pruning_keeps_tactics() {
+        for line in include_str!("tactics.epd").lines().filter(|line| !line.trim().is_empty()) {
+            let (fen, operations) = line.split_once(" bm ").unwrap();
+            let (best, id) = operations.split_once(';').unwrap();
+            let board = Board::from_fen(fen).unwrap();
+            let expected = crate::san::parse_san(&board, best.trim());
+
+            for pruning in [Pruning::default(), Pruning::none()] {
+                let mut engine = Engine::new();
+                engine.set_pruning(pruning);
+
+                assert_eq!(expected, engine.search(&board, 4).best_move, "{} with {:?}", id.trim(), pruning);
+            }
+        }
+    }
+
+    #[test]
+    fn pruning_searches_fewer_nodes() {
+        let board = Board::from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq -").unwrap();
+        let mut engine = Engine::new();
+
+        engine.set_pruning(Pruning::none());
+        let full = engine.search(&board, 5);
+
+        let mut engine = Engine::new();
+        let pruned = engine.search(&board, 5);
+
+        assert!(pruned.nodes < full.nodes);
+        assert!(pruned.stats.null_move_cutoffs + pruned.stats.futility_prunes + pruned.stats.delta_prunes > 0);
+        assert_eq!(0, full.stats.null_move_cutoffs + full.stats.futility_prunes + full.stats.delta_prunes + full.stats.reduction_researches);
+    }
+
+    #[test]
+    fn principal_variation_starts_with_best_move() {
+        let result = best_move("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq -", 3);
+
+        assert_eq!(3, result.pv.len());
+        assert_eq!(result.best_move, result.pv.first().copied());
+    }
+}
@@ -0,0 +1,75 @@
+use serde::Serialize;
+
+use crate::Move;
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchStats {
+    pub nodes: u64,
+    pub qnodes: u64,
+    pub tt_probes: u64,
+    pub tt_hits: u64,
+    pub tt_cutoffs: u64,
+    pub beta_cutoffs: u64,
+    pub first_move_cutoffs: u64,
+    pub stand_pat_cutoffs: u64,
+    pub null_move_cutoffs: u64,
+    pub futility_prunes: u64,
+    pub reduction_researches: u64,
+    pub delta_prunes: u64,
+    pub iterations: Vec<IterationInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IterationInfo {
+    pub depth: u8,
+    pub score: i32,
+    pub nodes: u64,
+    pub time_ms: u64,
+    pub pv: Vec<Move>,
+}
+
+pub type SearchListener = Box<dyn FnMut(&IterationInfo) + Send>;
+
+impl SearchStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn total_nodes(&self) -> u64 {
+        return self.nodes + self.qnodes;
+    }
+
+    pub fn tt_hit_rate(&self) -> f64 {
+        if self.tt_probes == 0 {
+            return 0.0;
+        }
+
+        return self.tt_hits as f64 / self.tt_probes as f64;
+    }
+
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        if self.beta_cutoffs == 0 {
+            return 0.0;
+        }
+
+        return self.first_move_cutoffs as f64 / self.beta_cutoffs as f64;
+    }
+
+    // counters of helper threads are added up, the iterations are kept from the main thread only
+    pub fn merge(&mut self, other: &SearchStats) {
+        self.nodes += other.nodes;
+        self.qnodes += other.qnodes;
+        self.tt_probes += other.tt_probes;
+        self.tt_hits += other.tt_hits;
+        self.tt_cutoffs += other.tt_cutoffs;
+        self.beta_cutoffs += other.beta_cutoffs;
+        self.first_move_cutoffs += other.first_move_cutoffs;
+        self.stand_pat_cutoffs += other.stand_pat_cutoffs;
+        self.null_move_cutoffs += other.null_move_cutoffs;
+        self.futility_prunes += other.futility_prunes;
+        self.reduction_researches += other.reduction_researches;
+        self.delta_prunes += other.delta_prunes;
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{clock::ClockSnapshot, expand_promotions, get_moves, Board, Color, PieceType};
+
+// kept back for the time it takes to get the move from the search onto the clock
+const MOVE_OVERHEAD_MS: u64 = 50;
+
+// without a moves-to-go count the rest of the game is guessed from the pieces left,
+// a full board expects another 45 moves, a bare ending still 20
+const MIN_MOVES_LEFT: u64 = 20;
+const MAX_MOVES_LEFT: u64 = 45;
+const FULL_PHASE: u64 = 14;
+
+// a move may run over its share by this factor when the search is in the middle of an iteration,
+// but it never takes more than this part of the remaining time
+const HARD_FACTOR: u64 = 3;
+const HARD_SHARE: u64 = 4;
+
+// the clock of the side to move as the engine sees it
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeLeft {
+    pub remaining_ms: u64,
+    pub increment_ms: u64,
+    #[serde(default)]
+    pub moves_to_go: Option<u64>,
+}
+
+impl TimeLeft {
+    pub fn from_clock(snapshot: &ClockSnapshot, color: Color) -> Self {
+        TimeLeft {
+            remaining_ms: match color {
+                Color::White => snapshot.white_ms,
+                Color::Black => snapshot.black_ms,
+            },
+            increment_ms: snapshot.settings.side(color).increment_ms,
+            moves_to_go: None,
+        }
+    }
+}
+
+// no new iteration is started after the soft limit, the search is stopped at the hard limit
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimeBudget {
+    pub soft: Duration,
+    pub hard: Duration,
+}
+
+// an equal share of the remaining time plus most of the increment, more in positions with many moves,
+// a forced move is played right after the first iteration
+pub fn allocate(board: &Board, time: TimeLeft) -> TimeBudget {
+    let available = time.remaining_ms.saturating_sub(MOVE_OVERHEAD_MS);
+    let moves_left = time.moves_to_go.map_or_else(|| estimated_moves_left(board), |moves| moves.max(1));
+    let moves = expand_promotions(&get_moves(board.turn(), board)).len() as u64;
+
+    if moves <= 1 {
+        return TimeBudget {
+            soft: Duration::ZERO,
+            hard: Duration::from_millis(available / HARD_SHARE),
+        };
+    }
+
+    let share = available / moves_left + time.increment_ms * 3 / 4;
+
+    // from three quarters of the share with a handful of moves up to one and a quarter with forty or more
+    let percent = (75 + moves.min(40) * 50 / 40).min(125);
+    let soft = (share * percent / 100).min(available / HARD_SHARE);
+    let hard = (soft * HARD_FACTOR).min(available / HARD_SHARE).max(soft);
+
+    return TimeBudget {
+        soft: Duration::from_millis(soft),
+        hard: Duration::from_millis(hard),
+    };
+}
+
+fn estimated_moves_left(board: &Board) -> u64 {
+    let phase = board
+        .pieces()
+        .iter()
+        .filter(|piece| !matches!(piece.piece_type, PieceType::Pawn | PieceType::King))
+        .count() as u64;
+
+    return MIN_MOVES_LEFT + (MAX_MOVES_LEFT - MIN_MOVES_LEFT) * phase.min(FULL_PHASE) / FULL_PHASE;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+
+    fn time(remaining_ms: u64, increment_ms: u64) -> TimeLeft {
+        TimeLeft {
+            remaining_ms,
+            increment_ms,
+            moves_to_go: None,
+        }
+    }
+
+    #[test]
+    fn allocations() {
+        let start = Board::new_game();
+        let ending = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - -").unwrap();
+        let forced = Board::from_fen("7k/8/8/8/8/8/1q6/K7 w - -").unwrap();
+
+        let blitz = allocate(&start, time(180_000, 2_000));
+        let bullet = allocate(&start, time(1_000, 0));
+        let tight = allocate(&start, TimeLeft { moves_to_go: Some(1), ..time(10_000, 0) });
+
+        assert!(blitz.soft > Duration::from_secs(4) && blitz.soft < Duration::from_secs(7));
+        assert!(blitz.soft <= blitz.hard && blitz.hard <= Duration::from_millis(180_000 / HARD_SHARE));
+        assert!(bullet.hard <= Duration::from_millis(250));
+        assert!(tight.hard <= Duration::from_millis(10_000 / HARD_SHARE));
+
+        // fewer pieces leave fewer moves to play, so each gets more time
+        assert!(allocate(&ending, time(60_000, 0)).soft > allocate(&start, time(60_000, 0)).soft);
+        assert_eq!(Duration::ZERO, allocate(&forced, time(60_000, 0)).soft);
+    }
+
+    #[test]
+    fn timed_search_keeps_to_budget() {
+        let board = Board::new_game();
+        let mut engine = Engine::new();
+        let budget = TimeBudget {
+            soft: Duration::from_millis(50),
+            hard: Duration::from_millis(150),
+        };
+
+        let started = std::time::Instant::now();
+        let result = engine.search_timed(&board, budget);
+
+        assert!(result.best_move.is_some());
+        assert!(started.elapsed() < Duration::from_millis(1_000));
+
+        let forced = Board::from_fen("7k/8/8/8/8/8/1q6/K7 w - -").unwrap();
+        let result = engine.search_timed(&forced, allocate(&forced, time(60_000, 0)));
+
+        assert_eq!((1, "a1b2"), (result.depth, result.best_move.unwrap().to_string().as_str()));
+    }
+}
@@ -0,0 +1,452 @@
+use std::fmt::Write;
+
+use crate::{pgn, Board, Color};
+
+use super::{Adjudication, AutoPlay, EngineConfig, GameOutcome, PlayError, PlayerConfig};
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct MatchStats {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl MatchStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, outcome: GameOutcome, color: Color) {
+        match outcome {
+            GameOutcome::Checkmate(winner) | GameOutcome::Resignation(winner) if winner == color => self.wins += 1,
+            GameOutcome::Checkmate(_) | GameOutcome::Resignation(_) => self.losses += 1,
+            _ => self.draws += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        return self.wins + self.losses + self.draws;
+    }
+
+    pub fn score(&self) -> f64 {
+        if self.games() == 0 {
+            return 0.5;
+        }
+
+        return (self.wins as f64 + self.draws as f64 / 2.0) / self.games() as f64;
+    }
+
+    pub fn elo_difference(&self) -> f64 {
+        let score = self.score().clamp(0.001, 0.999);
+        return -400.0 * (1.0 / score - 1.0).log10();
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SprtDecision {
+    AcceptH0,
+    AcceptH1,
+    Continue,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Sprt {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    pub fn bounds(&self) -> (f64, f64) {
+        return ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln());
+    }
+
+    // trinomial approximation of the log-likelihood ratio, as used by fishtest and cutechess
+    pub fn llr(&self, stats: &MatchStats) -> f64 {
+        let games = stats.games() as f64;
+
+        if games == 0.0 {
+            return 0.0;
+        }
+
+        let wins = stats.wins as f64 / games;
+        let draws = stats.draws as f64 / games;
+        let score = wins + draws / 2.0;
+        let variance = (wins + draws / 4.0 - score * score) / games;
+
+        if variance <= 0.0 {
+            return 0.0;
+        }
+
+        let score0 = expected_score(self.elo0);
+        let score1 = expected_score(self.elo1);
+
+        return (score1 - score0) * (2.0 * score - score0 - score1) / (2.0 * variance);
+    }
+
+    pub fn decision(&self, stats: &MatchStats) -> SprtDecision {
+        let llr = self.llr(stats);
+        let (lower, upper) = self.bounds();
+
+        if llr >= upper {
+            return SprtDecision::AcceptH1;
+        }
+
+        if llr <= lower {
+            return SprtDecision::AcceptH0;
+        }
+
+        return SprtDecision::Continue;
+    }
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Self::new(0.0, 5.0)
+    }
+}
+
+pub struct Tournament {
+    first: EngineConfig,
+    second: EngineConfig,
+    openings: Vec<Board>,
+    games: usize,
+    adjudication: Adjudication,
+    sprt: Option<Sprt>,
+}
+
+impl Tournament {
+    pub fn new(first: EngineConfig, second: EngineConfig, openings: Vec<Board>, games: usize) -> Self {
+        let openings = if openings.is_empty() { vec![Board::new_game()] } else { openings };
+
+        Tournament {
+            first,
+            second,
+            openings,
+            games,
+            adjudication: Adjudication::default(),
+            sprt: None,
+        }
+    }
+
+    pub fn set_adjudication(&mut self, adjudication: Adjudication) {
+        self.adjudication = adjudication;
+    }
+
+    pub fn set_sprt(&mut self, sprt: Option<Sprt>) {
+        self.sprt = sprt;
+    }
+
+    // every opening is played twice with colors reversed, results are counted from the first engine's point of view
+    pub fn run<F>(&self, mut on_game: F) -> Result<MatchStats, PlayError>
+    where
+        F: FnMut(usize, GameOutcome, &MatchStats),
+    {
+        let mut stats = MatchStats::new();
+
+        for game in 0..self.games {
+            let opening = &self.openings[(game / 2) % self.openings.len()];
+            let first_color = if game % 2 == 0 { opening.turn() } else { opening.turn().invert() };
+
+            let (white, black) = match first_color {
+                Color::White => (self.first.clone(), self.second.clone()),
+                Color::Black => (self.second.clone(), self.first.clone()),
+            };
+
+            let outcome = AutoPlay::new(opening, white, black, self.adjudication.clone()).play()?;
+            stats.add(outcome, first_color);
+            on_game(game, outcome, &stats);
+
+            if let Some(sprt) = &self.sprt {
+                if sprt.decision(&stats) != SprtDecision::Continue {
+                    break;
+                }
+            }
+        }
+
+        return Ok(stats);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Participant {
+    pub name: String,
+    pub player: PlayerConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentGame {
+    pub round: usize,
+    pub white: usize,
+    pub black: usize,
+    pub outcome: GameOutcome,
+    pub board: Board,
+}
+
+impl TournamentGame {
+    pub fn result(&self) -> &'static str {
+        return match self.outcome {
+            GameOutcome::Checkmate(Color::White) | GameOutcome::Resignation(Color::White) => "1-0",
+            GameOutcome::Checkmate(Color::Black) | GameOutcome::Resignation(Color::Black) => "0-1",
+            _ => "1/2-1/2",
+        };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub participant: usize,
+    pub name: String,
+    pub stats: MatchStats,
+}
+
+impl Standing {
+    pub fn points(&self) -> f64 {
+        return self.stats.wins as f64 + self.stats.draws as f64 / 2.0;
+    }
+}
+
+pub struct RoundRobin {
+    participants: Vec<Participant>,
+    openings: Vec<Board>,
+    games_per_pairing: usize,
+    adjudication: Adjudication,
+}
+
+impl RoundRobin {
+    pub fn new(participants: Vec<Participant>, openings: Vec<Board>, games_per_pairing: usize) -> Self {
+        let openings = if openings.is_empty() { vec![Board::new_game()] } else { openings };
+
+        RoundRobin {
+            participants,
+            openings,
+            games_per_pairing,
+            adjudication: Adjudication::default(),
+        }
+    }
+
+    pub fn set_adjudication(&mut self, adjudication: Adjudication) {
+        self.adjudication = adjudication;
+    }
+
+    // every participant meets every other one, openings and colors rotate like in a two-engine match
+    pub fn run<F>(&self, mut on_game: F) -> Result<RoundRobinResults, PlayError>
+    where
+        F: FnMut(&TournamentGame),
+    {
+        let mut games = Vec::new();
+
+        for first in 0..self.participants.len() {
+            for second in first + 1..self.participants.len() {
+                for game in 0..self.games_per_pairing {
+                    let opening = &self.openings[(game / 2) % self.openings.len()];
+                    let first_color = if game % 2 == 0 { opening.turn() } else { opening.turn().invert() };
+
+                    let (white, black) = match first_color {
+                        Color::White => (first, second),
+                        Color::Black => (second, first),
+                    };
+
+                    let mut autoplay = AutoPlay::with_players(
+                        opening,
+                        &self.participants[white].player,
+                        &self.participants[black].player,
+                        self.adjudication.clone(),
+                    )?;
+
+                    let outcome = autoplay.play()?;
+                    let game = TournamentGame {
+                        round: games.len() + 1,
+                        white,
+                        black,
+                        outcome,
+                        board: autoplay.board().clone(),
+                    };
+
+                    on_game(&game);
+                    games.push(game);
+                }
+            }
+        }
+
+        return Ok(RoundRobinResults {
+            names: self.participants.iter().map(|participant| participant.name.clone()).collect(),
+            games,
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RoundRobinResults {
+    pub names: Vec<String>,
+    pub games: Vec<TournamentGame>,
+}
+
+impl RoundRobinResults {
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings = (0..self.names.len()).map(|participant| self.standing(participant, None)).collect::<Vec<_>>();
+        standings.sort_by(|a, b| b.points().total_cmp(&a.points()));
+
+        return standings;
+    }
+
+    // a crosstable with the participants ordered by their standing, each cell holds the row's points against the column
+    pub fn table(&self) -> String {
+        let standings = self.standings();
+        let width = self.names.iter().map(String::len).max().unwrap_or(0).max("Engine".len());
+
+        let mut table = format!("{:>2}  {:<width$}  {:>5}  {:>11}", "#", "Engine", "Pts", "+/-/=");
+
+        for rank in 1..=standings.len() {
+            let _ = write!(table, "  {:>4}", rank);
+        }
+
+        table.push('\n');
+
+        for (rank, standing) in standings.iter().enumerate() {
+            let stats = standing.stats;
+            let record = format!("{}/{}/{}", stats.wins, stats.losses, stats.draws);
+
+            let _ = write!(table, "{:>2}  {:<width$}  {:>5.1}  {:>11}", rank + 1, standing.name, standing.points(), record);
+
+            for opponent in &standings {
+                if opponent.participant == standing.participant {
+                    let _ = write!(table, "  {:>4}", "-");
+                } else {
+                    let _ = write!(table, "  {:>4.1}", self.standing(standing.participant, Some(opponent.participant)).points());
+                }
+            }
+
+            table.push('\n');
+        }
+
+        return table;
+    }
+
+    pub fn pgn(&self) -> String {
+        return self
+            .games
+            .iter()
+            .map(|game| {
+                let tags = [
+                    ("Event", "Round robin".to_string()),
+                    ("Round", game.round.to_string()),
+                    ("White", self.names[game.white].clone()),
+                    ("Black", self.names[game.black].clone()),
+                    ("Result", game.result().to_string()),
+                ]
+                .map(|(name, value)| (name.to_string(), value));
+
+                pgn::write_pgn(&game.board, &tags)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    fn standing(&self, participant: usize, opponent: Option<usize>) -> Standing {
+        let mut stats = MatchStats::new();
+
+        for game in &self.games {
+            let color = if game.white == participant {
+                Color::White
+            } else if game.black == participant {
+                Color::Black
+            } else {
+                continue;
+            };
+
+            let other = if color == Color::White { game.black } else { game.white };
+
+            if opponent.is_none_or(|opponent| opponent == other) {
+                stats.add(game.outcome, color);
+            }
+        }
+
+        return Standing {
+            participant,
+            name: self.names[participant].clone(),
+            stats,
+        };
+    }
+}
+
+fn expected_score(elo: f64) -> f64 {
+    return 1.0 / (1.0 + 10f64.powf(-elo / 400.0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(wins: u32, losses: u32, draws: u32) -> MatchStats {
+        MatchStats { wins, losses, draws }
+    }
+
+    #[test]
+    fn elo_difference() {
+        assert_eq!(0.0, stats(10, 10, 5).elo_difference());
+        assert!((stats(3, 1, 0).elo_difference() - 190.85).abs() < 0.01);
+        assert!(stats(1, 3, 0).elo_difference() < 0.0);
+    }
+
+    #[test]
+    fn sprt_decisions() {
+        let sprt = Sprt::default();
+
+        assert_eq!(SprtDecision::Continue, sprt.decision(&stats(10, 9, 20)));
+        assert_eq!(SprtDecision::AcceptH1, sprt.decision(&stats(900, 700, 1000)));
+        assert_eq!(SprtDecision::AcceptH0, sprt.decision(&stats(700, 900, 1000)));
+    }
+
+    #[test]
+    fn alternates_colors() {
+        let opening = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -").unwrap();
+        let config = EngineConfig { depth: 3, ..EngineConfig::default() };
+        let tournament = Tournament::new(config.clone(), config, vec![opening], 2);
+
+        let mut outcomes = Vec::new();
+        let stats = tournament.run(|_, outcome, _| outcomes.push(outcome)).unwrap();
+
+        assert_eq!(vec![GameOutcome::Checkmate(Color::White); 2], outcomes);
+        assert_eq!(MatchStats { wins: 1, losses: 1, draws: 0 }, stats);
+    }
+
+    #[test]
+    fn round_robin_table_and_pgn() {
+        let opening = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - -").unwrap();
+        let participants = [1, 2, 3]
+            .map(|depth| Participant {
+                name: format!("depth {depth}"),
+                player: PlayerConfig::Builtin(EngineConfig { depth, ..EngineConfig::default() }),
+            })
+            .to_vec();
+
+        let adjudication = Adjudication {
+            max_moves: 20,
+            ..Adjudication::default()
+        };
+
+        let mut tournament = RoundRobin::new(participants, vec![opening], 2);
+        tournament.set_adjudication(adjudication);
+
+        let results = tournament.run(|_| {}).unwrap();
+        let standings = results.standings();
+
+        assert_eq!(6, results.games.len());
+        assert_eq!(6.0, standings.iter().map(Standing::points).sum::<f64>());
+        assert!(standings.windows(2).all(|pair| pair[0].points() >= pair[1].points()));
+        assert!(results.table().starts_with(" #  Engine     Pts        +/-/=     1     2     3\n"));
+        assert_eq!(6, results.pgn().matches("[Event \"Round robin\"]").count());
+        assert!(results.pgn().contains("[Round \"6\"]"));
+    }
+}
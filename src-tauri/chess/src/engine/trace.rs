@@ -0,0 +1,190 @@
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Board, Move};
+
+// a full tree grows far too large to look at, so only the first nodes up to a few plies are kept
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TraceLimits {
+    pub max_nodes: usize,
+    pub max_ply: usize,
+}
+
+impl Default for TraceLimits {
+    fn default() -> Self {
+        TraceLimits { max_nodes: 2000, max_ply: 4 }
+    }
+}
+
+// how the score of a node relates to its window, the usual pv, cut and all nodes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NodeType {
+    Pv,
+    Cut,
+    All,
+}
+
+// the move is missing at the root and after a null move
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceNode {
+    pub id: usize,
+    pub parent: Option<usize>,
+    pub mv: Option<Move>,
+    pub ply: usize,
+    pub depth: u8,
+    pub alpha: i32,
+    pub beta: i32,
+    pub score: i32,
+    pub node_type: NodeType,
+}
+
+// the tree of the last iteration, scores are from the side to move at each node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchTrace {
+    pub fen: String,
+    pub depth: u8,
+    pub nodes: Vec<TraceNode>,
+    pub truncated: bool,
+}
+
+impl SearchTrace {
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph search {\n    node [shape=box, fontname=monospace];\n");
+
+        for node in &self.nodes {
+            let color = match node.node_type {
+                NodeType::Pv => "palegreen",
+                NodeType::Cut => "lightsalmon",
+                NodeType::All => "lightblue",
+            };
+            let name = match (node.mv, node.parent) {
+                (Some(mv), _) => mv.to_string(),
+                (None, None) => "root".to_string(),
+                (None, Some(_)) => "null".to_string(),
+            };
+
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\\nd{} {} [{}, {}]\", style=filled, fillcolor={}];",
+                node.id, name, node.depth, node.score, node.alpha, node.beta, color
+            );
+
+            if let Some(parent) = node.parent {
+                let _ = writeln!(dot, "    n{} -> n{};", parent, node.id);
+            }
+        }
+
+        dot.push_str("}\n");
+
+        return dot;
+    }
+}
+
+// nodes are opened when the search enters them and completed with their score when it leaves
+pub(super) struct TraceRecorder {
+    limits: TraceLimits,
+    nodes: Vec<TraceNode>,
+    open: Vec<usize>,
+    truncated: bool,
+}
+
+impl TraceRecorder {
+    pub(super) fn new(limits: TraceLimits) -> Self {
+        TraceRecorder {
+            limits,
+            nodes: Vec::new(),
+            open: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.nodes.clear();
+        self.open.clear();
+        self.truncated = false;
+    }
+
+    // children of a node that was left out are left out as well
+    pub(super) fn enter(&mut self, board: &Board, ply: usize, depth: u8, alpha: i32, beta: i32) -> Option<usize> {
+        if ply > self.limits.max_ply || self.open.len() != ply {
+            return None;
+        }
+
+        if self.nodes.len() >= self.limits.max_nodes {
+            self.truncated = true;
+            return None;
+        }
+
+        let id = self.nodes.len();
+
+        self.nodes.push(TraceNode {
+            id,
+            parent: self.open.last().copied(),
+            mv: if ply > 0 { board.last_move() } else { None },
+            ply,
+            depth,
+            alpha,
+            beta,
+            score: 0,
+            node_type: NodeType::Pv,
+        });
+        self.open.push(id);
+
+        return Some(id);
+    }
+
+    pub(super) fn exit(&mut self, id: usize, score: i32) {
+        self.open.pop();
+
+        let node = &mut self.nodes[id];
+        node.score = score;
+        node.node_type = if score >= node.beta {
+            NodeType::Cut
+        } else if score <= node.alpha {
+            NodeType::All
+        } else {
+            NodeType::Pv
+        };
+    }
+
+    pub(super) fn finish(self, board: &Board, depth: u8) -> SearchTrace {
+        return SearchTrace {
+            fen: board.to_fen(),
+            depth,
+            nodes: self.nodes,
+            truncated: self.truncated,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+
+    #[test]
+    fn records_bounded_tree() {
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -").unwrap();
+        let mut engine = Engine::new();
+
+        let (result, trace) = engine.trace_search(&board, 3, TraceLimits::default());
+        let root = &trace.nodes[0];
+
+        assert_eq!("a1a8", result.best_move.unwrap().to_string());
+        assert_eq!((None, None, 3), (root.parent, root.mv, root.depth));
+        assert_eq!(result.score, root.score);
+        assert!(!trace.truncated);
+        assert!(trace.to_dot().starts_with("digraph search {") && trace.to_dot().contains("a1a8"));
+
+        let (_, trace) = engine.trace_search(&Board::new_game(), 4, TraceLimits { max_nodes: 50, max_ply: 2 });
+
+        assert!(trace.truncated && trace.nodes.len() == 50);
+        assert!(trace.nodes.iter().all(|node| node.ply <= 2 && node.parent.map_or(node.id == 0, |parent| parent < node.id)));
+        assert!(trace.to_dot().contains("n0 -> n1;"));
+    }
+}
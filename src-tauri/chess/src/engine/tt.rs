@@ -0,0 +1,193 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::Move;
+
+use super::search::MATE_SCORE;
+
+pub const DEFAULT_HASH_SIZE_MB: usize = 16;
+
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1000;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TtEntry {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub depth: u8,
+    pub bound: Bound,
+}
+
+struct Slot {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+// entries are stored lock-free as (hash ^ data, data) so a torn write from another thread fails the key check
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let slots = (0..Self::slot_count(size_mb))
+            .map(|_| Slot {
+                key: AtomicU64::new(0),
+                data: AtomicU64::new(0),
+            })
+            .collect();
+
+        return TranspositionTable { slots };
+    }
+
+    pub fn size_bytes(&self) -> usize {
+        return self.slots.len() * std::mem::size_of::<Slot>();
+    }
+
+    // what a table of the given size takes without allocating it, the slot count is rounded down to a power of two
+    pub fn bytes_for(size_mb: usize) -> usize {
+        return Self::slot_count(size_mb) * std::mem::size_of::<Slot>();
+    }
+
+    fn slot_count(size_mb: usize) -> usize {
+        let count = size_mb.max(1) * 1024 * 1024 / std::mem::size_of::<Slot>();
+        return 1 << count.ilog2();
+    }
+
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.data.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn probe(&self, hash: u64, ply: usize) -> Option<TtEntry> {
+        let slot = &self.slots[self.index(hash)];
+        let data = slot.data.load(Ordering::Relaxed);
+        let key = slot.key.load(Ordering::Relaxed);
+
+        if data == 0 || key ^ data != hash {
+            return None;
+        }
+
+        let mut entry = unpack(data);
+        entry.score = score_from_tt(entry.score, ply);
+
+        return Some(entry);
+    }
+
+    pub fn store(&self, hash: u64, ply: usize, entry: TtEntry) {
+        let slot = &self.slots[self.index(hash)];
+        let data = pack(&TtEntry {
+            score: score_to_tt(entry.score, ply),
+            ..entry
+        });
+
+        slot.key.store(hash ^ data, Ordering::Relaxed);
+        slot.data.store(data, Ordering::Relaxed);
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        return hash as usize & (self.slots.len() - 1);
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_SIZE_MB)
+    }
+}
+
+fn pack(entry: &TtEntry) -> u64 {
+    let mv = entry.best_move.map(|mv| mv.to_u16()).unwrap_or(0) as u64;
+    let score = entry.score as u32 as u64;
+    let depth = entry.depth as u64;
+    let bound = match entry.bound {
+        Bound::Exact => 1,
+        Bound::Lower => 2,
+        Bound::Upper => 3,
+    };
+
+    return mv | score << 16 | depth << 48 | bound << 56;
+}
+
+fn unpack(data: u64) -> TtEntry {
+    let mv = (data & 0xffff) as u16;
+
+    return TtEntry {
+        best_move: if mv == 0 { None } else { Some(Move::from_u16(mv)) },
+        score: (data >> 16) as u32 as i32,
+        depth: (data >> 48) as u8,
+        bound: match data >> 56 & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            _ => Bound::Upper,
+        },
+    };
+}
+
+fn score_to_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE_THRESHOLD {
+        return score + ply as i32;
+    }
+
+    if score < -MATE_THRESHOLD {
+        return score - ply as i32;
+    }
+
+    return score;
+}
+
+fn score_from_tt(score: i32, ply: usize) -> i32 {
+    if score > MATE_THRESHOLD {
+        return score - ply as i32;
+    }
+
+    if score < -MATE_THRESHOLD {
+        return score + ply as i32;
+    }
+
+    return score;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coord;
+
+    #[test]
+    fn store_and_probe() {
+        let tt = TranspositionTable::new(1);
+        let entry = TtEntry {
+            best_move: Some(Move::new(Coord::new('e', 2), Coord::new('e', 4))),
+            score: -42,
+            depth: 7,
+            bound: Bound::Lower,
+        };
+
+        tt.store(0xdead_beef, 0, entry);
+
+        assert_eq!(Some(entry), tt.probe(0xdead_beef, 0));
+        assert_eq!(None, tt.probe(0xdead_beef ^ 1 << 40, 0));
+    }
+
+    #[test]
+    fn mate_scores_are_ply_relative() {
+        let tt = TranspositionTable::new(1);
+        let entry = TtEntry {
+            best_move: None,
+            score: MATE_SCORE - 5,
+            depth: 3,
+            bound: Bound::Exact,
+        };
+
+        tt.store(1, 2, entry);
+
+        assert_eq!(MATE_SCORE - 3, tt.probe(1, 0).unwrap().score);
+    }
+}
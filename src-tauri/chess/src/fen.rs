@@ -1,6 +1,6 @@
 use anyhow::Result;
 
-use crate::{Color, Coord, Piece, PieceType};
+use crate::{Board, Color, Coord, Piece, PieceType, Variant};
 
 #[derive(Debug, thiserror::Error)]
 pub enum FenError {
@@ -15,6 +15,24 @@ pub enum FenError {
 
     #[error("Unknown color '{0}'")]
     UnknownColor(String),
+
+    #[error("Invalid castling rights '{0}'")]
+    InvalidCastlingRights(char),
+
+    #[error("Invalid en passant square '{0}'")]
+    InvalidEnPassantSquare(String),
+
+    #[error("Invalid move counter '{0}'")]
+    InvalidMoveCounter(String),
+
+    #[error("Expected {expected} {color:?} king(s), found {found}")]
+    InvalidKingCount { color: Color, expected: usize, found: usize },
+
+    #[error("Pawn on back rank at {0}")]
+    PawnOnBackRank(Coord),
+
+    #[error("The side not to move is in check")]
+    OpponentInCheck,
 }
 
 pub struct FenResult {
@@ -22,6 +40,8 @@ pub struct FenResult {
     pub turn: Color,
     pub castling_rules: CastlingRules,
     pub en_passant_square: Option<Coord>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
 }
 
 pub struct CastlingRules {
@@ -40,19 +60,120 @@ pub fn parse_fen(fen_str: &str) -> Result<FenResult, FenError> {
     let castling = parts.next().ok_or(FenError::InvalidFenString)?;
     let en_passant_square = parts.next().ok_or(FenError::InvalidFenString)?;
 
+    // the move counters are optional, a lot of positions are shared without them
+    let halfmove_clock = parts.next().map(parse_move_counter).transpose()?.unwrap_or(0);
+    let fullmove_number = parts.next().map(parse_move_counter).transpose()?.unwrap_or(1);
+
+    if parts.next().is_some() {
+        return Err(FenError::InvalidFenString);
+    }
+
+    if fullmove_number == 0 {
+        return Err(FenError::InvalidMoveCounter(fullmove_number.to_string()));
+    }
+
     let pieces = parse_pieces(pieces)?;
     let turn = parse_turn(turn)?;
     let castling_rules = parse_castling_rules(castling)?;
-    let en_passant_square = parse_en_passant(en_passant_square);
+    let en_passant_square = parse_en_passant(en_passant_square, turn, &pieces)?;
 
     return Ok(FenResult {
         pieces,
         turn,
         castling_rules,
-        en_passant_square
+        en_passant_square,
+        halfmove_clock,
+        fullmove_number,
     });
 }
 
+pub fn to_fen(board: &Board) -> String {
+    let mut rows = Vec::with_capacity(8);
+
+    for row in (0..8).rev() {
+        let mut fen_row = String::new();
+        let mut empty = 0;
+
+        for column in 0..8 {
+            match board.piece_at(Coord::from_xy(column, row)) {
+                Some(piece) => {
+                    if empty > 0 {
+                        fen_row.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+
+                    fen_row.push(piece.to_fen_char());
+                }
+                None => empty += 1,
+            }
+        }
+
+        if empty > 0 {
+            fen_row.push_str(&empty.to_string());
+        }
+
+        rows.push(fen_row);
+    }
+
+    let turn = match board.turn() {
+        Color::White => "w",
+        Color::Black => "b",
+    };
+
+    let white = board.side(Color::White);
+    let black = board.side(Color::Black);
+
+    let castling = [
+        (white.can_castle_kingside(), 'K'),
+        (white.can_castle_queenside(), 'Q'),
+        (black.can_castle_kingside(), 'k'),
+        (black.can_castle_queenside(), 'q'),
+    ]
+    .into_iter()
+    .filter_map(|(allowed, c)| allowed.then_some(c))
+    .collect::<String>();
+
+    let castling = if castling.is_empty() { "-".to_string() } else { castling };
+    let en_passant = board.en_passant_square().map(|coord| coord.to_string()).unwrap_or_else(|| "-".to_string());
+
+    return format!(
+        "{} {} {} {} {} {}",
+        rows.join("/"),
+        turn,
+        castling,
+        en_passant,
+        board.halfmove_clock(),
+        board.fullmove_number()
+    );
+}
+
+pub fn validate(fen: &FenResult, variant: Variant) -> Result<(), FenError> {
+    for color in [Color::White, Color::Black] {
+        let expected = variant.king_count(color);
+        let found = fen.pieces.iter().filter(|p| p.color == color && p.piece_type == PieceType::King).count();
+
+        match expected {
+            Some(expected) if expected != found => return Err(FenError::InvalidKingCount { color, expected, found }),
+            _ => {}
+        }
+    }
+
+    for piece in fen.pieces.iter().filter(|p| p.piece_type == PieceType::Pawn) {
+        let row = piece.coord.row();
+
+        // horde pawns start on the first rank
+        let allowed = match (variant, piece.color) {
+            (Variant::Horde, Color::White) => row != 8,
+            _ => row != 1 && row != 8,
+        };
+
+        if !allowed {
+            return Err(FenError::PawnOnBackRank(piece.coord));
+        }
+    }
+
+    return Ok(());
+}
 
 fn parse_pieces(pieces_str: &str) -> Result<Vec<Piece>, FenError> {
     let rows = pieces_str.split('/').collect::<Vec<&str>>();
@@ -72,7 +193,7 @@ fn parse_pieces(pieces_str: &str) -> Result<Vec<Piece>, FenError> {
                 'A'..='Z' | 'a'..='z' => {
                     offset += 1;
 
-                    if let Some(piece) = get_piece(c, offset) {
+                    if let Some(piece) = Piece::from_fen_char(c, Coord::from_offset(offset as usize)) {
                         pieces.push(piece);
                     } else {
                         return Err(FenError::UnknownPiece(c));
@@ -116,7 +237,7 @@ fn parse_castling_rules(castling: &str) -> Result<CastlingRules, FenError> {
             'Q' => rules.white_queenside = true,
             'q' => rules.black_queenside = true,
             '-' => {}
-            _ => return Err(FenError::InvalidFenString)
+            _ => return Err(FenError::InvalidCastlingRights(c)),
         };
     }
 
@@ -124,31 +245,40 @@ fn parse_castling_rules(castling: &str) -> Result<CastlingRules, FenError> {
 
 }
 
-fn parse_en_passant(en_passant_square: &str) -> Option<Coord> {
-    match en_passant_square {
-        "-" => None,
-        s => Coord::from_str(s)
+fn parse_en_passant(en_passant_square: &str, turn: Color, pieces: &[Piece]) -> Result<Option<Coord>, FenError> {
+    if en_passant_square == "-" {
+        return Ok(None);
     }
-}
 
-fn get_piece(c: char, offset: isize) -> Option<Piece> {
-    if !c.is_ascii_alphabetic() {
-        return None;
+    let invalid = || FenError::InvalidEnPassantSquare(en_passant_square.into());
+    let coord = Coord::from_str(en_passant_square).ok_or_else(invalid)?;
+
+    if !is_en_passant_square(coord, turn, pieces) {
+        return Err(invalid());
     }
 
-    let color = if c.is_lowercase() { Color::Black } else { Color::White };
+    return Ok(Some(coord));
+}
+
+// the square is behind a pawn of the side that just moved, with the square itself and the start square empty
+pub fn is_en_passant_square(coord: Coord, turn: Color, pieces: &[Piece]) -> bool {
+    let (row, pushed, start) = match turn {
+        Color::White => (6, coord.mv(0, -1), coord.mv(0, 1)),
+        Color::Black => (3, coord.mv(0, 1), coord.mv(0, -1)),
+    };
 
-    let piece_type = match c.to_lowercase().next() {
-        Some('r') => Some(PieceType::Rook),
-        Some('n') => Some(PieceType::Knight),
-        Some('b') => Some(PieceType::Bishop),
-        Some('q') => Some(PieceType::Queen),
-        Some('k') => Some(PieceType::King),
-        Some('p') => Some(PieceType::Pawn),
-        _ => None,
+    let (Some(pushed), Some(start)) = (pushed, start) else {
+        return false;
     };
 
-    return piece_type.map(|t| Piece::new(Coord::from_offset(offset as usize), t, color));
+    let is_pushed_pawn = |p: &Piece| p.coord == pushed && p.piece_type == PieceType::Pawn && p.color == turn.invert();
+    let is_occupied = |p: &Piece| p.coord == coord || p.coord == start;
+
+    return coord.row() == row && pieces.iter().any(is_pushed_pawn) && !pieces.iter().any(is_occupied);
+}
+
+fn parse_move_counter(counter: &str) -> Result<u32, FenError> {
+    return counter.parse().map_err(|_| FenError::InvalidMoveCounter(counter.into()));
 }
 
 #[cfg(test)]
@@ -300,10 +430,67 @@ mod tests {
 
     #[test]
     fn ep_square() {
-        let result = parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - e3").unwrap();
+        let result = parse_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b - e3").unwrap();
         assert_eq!(Some(Coord::new('e', 3)), result.en_passant_square);
     }
 
+    #[test]
+    fn invalid_ep_square() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - e3",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w - e3",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b - d3",
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b - e9",
+        ] {
+            assert!(matches!(parse_fen(fen), Err(FenError::InvalidEnPassantSquare(_))), "{fen} was accepted");
+        }
+    }
+
+    #[test]
+    fn move_counters() {
+        let result = parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 12 34").unwrap();
+        assert_eq!(12, result.halfmove_clock);
+        assert_eq!(34, result.fullmove_number);
+
+        let result = parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -").unwrap();
+        assert_eq!(0, result.halfmove_clock);
+        assert_eq!(1, result.fullmove_number);
+
+        assert!(matches!(parse_fen("8/8/8/8/8/8/8/8 w - - x 1"), Err(FenError::InvalidMoveCounter(_))));
+        assert!(matches!(parse_fen("8/8/8/8/8/8/8/8 w - - 0 0"), Err(FenError::InvalidMoveCounter(_))));
+        assert!(matches!(parse_fen("8/8/8/8/8/8/8/8 w - - 0 1 x"), Err(FenError::InvalidFenString)));
+    }
+
+    #[test]
+    fn fen_round_trip() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 4 12",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            assert_eq!(fen, to_fen(&Board::from_fen(fen).unwrap()));
+        }
+    }
+
+    #[test]
+    fn validation() {
+        let validate_fen = |fen: &str, variant: Variant| validate(&parse_fen(fen).unwrap(), variant);
+
+        assert!(validate_fen("4k3/8/8/8/8/8/8/4K3 w - -", Variant::Standard).is_ok());
+        assert!(matches!(
+            validate_fen("4k3/8/8/8/8/8/8/8 w - -", Variant::Standard),
+            Err(FenError::InvalidKingCount { color: Color::White, expected: 1, found: 0 })
+        ));
+        assert!(matches!(
+            validate_fen("3kk3/8/8/8/8/8/8/4K3 w - -", Variant::Standard),
+            Err(FenError::InvalidKingCount { color: Color::Black, expected: 1, found: 2 })
+        ));
+        assert!(matches!(validate_fen("4k3/8/8/8/8/8/8/P3K3 w - -", Variant::Standard), Err(FenError::PawnOnBackRank(_))));
+        assert!(validate_fen("4k3/8/8/8/8/8/8/P7 w - -", Variant::Horde).is_ok());
+        assert!(validate_fen("3kk3/8/8/8/8/8/8/8 w - -", Variant::Antichess).is_ok());
+    }
+
     fn assert_piece(items: &Vec<Piece>, index: &mut usize, coord: &str, piece_type: PieceType, color: Color) {
         let item_coord = items[*index].coord;
         let item_type = items[*index].piece_type;
@@ -2,14 +2,38 @@
 extern crate lazy_static;
 
 pub use self::bitboard::BitBoard;
-pub use self::board::Board;
+pub use self::board::{Board, BoardBuilder, BoardDiff, BoardSnapshot, EnPassantMode, MoveErr, MovedPiece, StateChange};
 pub use self::coord::Coord;
+pub use self::fen::FenError;
 pub use self::moves::*;
+pub use self::odds::Odds;
 pub use self::piece::{Color, Piece, PieceType};
+pub use self::variant::Variant;
 
+pub mod animation;
 mod bitboard;
 mod board;
+pub mod book;
+pub mod bot;
+pub mod clock;
 mod coord;
+pub mod draw;
+pub mod engine;
 mod fen;
 mod moves;
+mod odds;
 mod piece;
+pub mod positions;
+pub mod perft;
+pub mod pgn;
+pub mod rcg;
+pub mod render;
+pub mod resync;
+pub mod review;
+pub mod san;
+pub mod study;
+pub mod tablebase;
+pub mod training;
+pub mod tree;
+mod variant;
+mod zobrist;
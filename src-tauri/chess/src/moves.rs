@@ -1,17 +1,23 @@
+mod list;
 mod lookup;
 mod sliding;
 
+pub use list::MoveList;
+pub use list::MAX_MOVES;
 pub use lookup::BLACK_KING;
 pub use lookup::DIAGONAL_PIN_RAYS;
 pub use lookup::KING_MOVES;
+pub use lookup::KING_MOVE_MAP;
+pub use lookup::KNIGHT_MOVE_MAP;
 pub use lookup::ORTHOGONAL_PIN_RAYS;
 pub use lookup::WHITE_KING;
+pub(crate) use sliding::{get_bishop_move_mask, get_rook_move_mask, sliding_table_bytes};
 
 use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-use crate::PieceType;
+use crate::{PieceType, Variant};
 use crate::bitboard::NORTH_EAST;
 use crate::bitboard::NORTH_WEST;
 use crate::bitboard::SOUTH_EAST;
@@ -20,28 +26,41 @@ use crate::{bitboard::BitBoard, Board, Color, Coord};
 use lookup::*;
 
 pub fn get_moves(color: Color, board: &Board) -> Vec<Move> {
+    let mut moves = MoveList::new();
+    get_moves_into(color, board, &mut moves);
+
+    return moves.to_vec();
+}
+
+pub fn get_moves_into(color: Color, board: &Board, moves: &mut MoveList) {
     let side = board.side(color);
     let opponent_side = board.side(color.invert());
 
-    let mut moves: Vec<Move> = Vec::with_capacity(50);
+    moves.clear();
+
+    // variants that restrict moves below have no checks, so evasions need no further filtering
+    if side.checked() {
+        get_evasions_into(color, board, moves);
+        return;
+    }
 
     for rook in side.rooks() {
-        into_moves(&mut moves, rook, get_rook_moves(color, rook, board, board.all()));
+        into_moves(moves, rook, get_rook_moves(color, rook, board, board.all()));
     }
 
     for bishop in side.bishops() {
-        into_moves(&mut moves, bishop, get_bishop_moves(color, bishop, board, board.all()));
+        into_moves(moves, bishop, get_bishop_moves(color, bishop, board, board.all()));
     }
 
     for queen in side.queens() {
-        into_moves(&mut moves, queen, get_queen_moves(color, queen, board, board.all()));
+        into_moves(moves, queen, get_queen_moves(color, queen, board, board.all()));
     }
 
     for knight in side.knights() {
         let knight_moves = get_knight_moves(color, knight, board);
         let knight_moves = filter(color, knight, knight_moves, board);
 
-        into_moves(&mut moves, knight, knight_moves);
+        into_moves(moves, knight, knight_moves);
     }
 
     for pawn in side.pawns() {
@@ -56,8 +75,8 @@ pub fn get_moves(color: Color, board: &Board) -> Vec<Move> {
         let f_pawn_moves = filter(color, pawn, pawn_moves, board);
         let f_pawn_attacks = filter(color, pawn, pawn_attacks, board);
 
-        into_moves(&mut moves, pawn, f_pawn_moves & !promotion_row);
-        into_moves(&mut moves, pawn, f_pawn_attacks & !promotion_row);
+        into_moves(moves, pawn, f_pawn_moves & !promotion_row);
+        into_moves(moves, pawn, f_pawn_attacks & !promotion_row);
 
         for promotion_move in (f_pawn_moves | f_pawn_attacks) & promotion_row {
             moves.push(Move::promotion(pawn, promotion_move));
@@ -73,17 +92,85 @@ pub fn get_moves(color: Color, board: &Board) -> Vec<Move> {
         }
     }
 
-    let king = side.king_coord();
-    let king_moves = get_king_moves(color, board);
-    let castling_moves = get_castling_moves(color, board);
+    for king in side.king() {
+        into_moves(moves, king, get_king_moves(color, king, board));
 
-    into_moves(&mut moves, king, king_moves);
+        for castling in get_castling_moves(color, board) {
+            moves.push(Move::castling(king, castling));
+        }
+    }
 
-    for castling in castling_moves {
-        moves.push(Move::castling(king, castling));
+    if board.variant() == Variant::RacingKings {
+        filter_racing_kings(color, board, moves);
     }
 
-    return moves;
+    if board.variant().forced_captures() && moves.iter().any(|mv| mv.en_passant || opponent_side.all().is_set(mv.to)) {
+        moves.retain(|mv| mv.en_passant || opponent_side.all().is_set(mv.to));
+    }
+}
+
+// in check the king may step away, a single checker can also be captured or blocked, so only the pieces able
+// to reach the checker or the squares in between are looked at
+fn get_evasions_into(color: Color, board: &Board, moves: &mut MoveList) {
+    let side = board.side(color);
+    let opponent_side = board.side(color.invert());
+
+    for king in side.king() {
+        into_moves(moves, king, get_king_moves(color, king, board));
+    }
+
+    let checkers = side.check_targets() & opponent_side.all();
+
+    if checkers.count_ones() != 1 {
+        return;
+    }
+
+    let (promotion_row, direction) = match color {
+        Color::White => (WHITE_PROMOTION_ROW, 1),
+        Color::Black => (BLACK_PROMOTION_ROW, -1),
+    };
+
+    let empty = BitBoard::new(0);
+
+    for target in side.check_targets() {
+        let mut attackers = (KNIGHT_MOVE_MAP[target.offset()] & side.knights())
+            | (sliding::get_rook_move_mask(target, board.all(), &empty) & (side.rooks() | side.queens()))
+            | (sliding::get_bishop_move_mask(target, board.all(), &empty) & (side.bishops() | side.queens()));
+
+        if checkers.is_set(target) {
+            attackers |= get_pawn_attacks(color.invert(), target) & side.pawns();
+        } else if let Some(single) = target.mv(0, -direction) {
+            attackers |= BitBoard::from_coord(single) & side.pawns();
+
+            // a double step passes the square in front of the pawn, which has to be empty
+            let start_row = if color == Color::White { 2 } else { 7 };
+
+            if let Some(double) = target.mv(0, -2 * direction).filter(|double| double.row() == start_row && !board.all().is_set(single)) {
+                attackers |= BitBoard::from_coord(double) & side.pawns();
+            }
+        }
+
+        for from in attackers {
+            if !filter(color, from, BitBoard::from_coord(target), board).is_set(target) {
+                continue;
+            }
+
+            if side.pawns().is_set(from) && promotion_row.is_set(target) {
+                moves.push(Move::promotion(from, target));
+            } else {
+                moves.push(Move::new(from, target));
+            }
+        }
+    }
+
+    // a pawn that gave check with a double step can be taken en passant
+    if let Some(en_passant_square) = board.en_passant_square() {
+        for pawn in get_pawn_attacks(color.invert(), en_passant_square) & side.pawns() {
+            for to in get_en_passant_move(color, pawn, en_passant_square, board) {
+                moves.push(Move::en_passant(pawn, to));
+            }
+        }
+    }
 }
 
 pub fn get_attacked_squares(color: Color, board: &Board) -> BitBoard {
@@ -113,7 +200,10 @@ pub fn get_attacked_squares(color: Color, board: &Board) -> BitBoard {
     }
 
     attacked_squares |= get_pawn_attacks_fast(color, side.pawns());
-    attacked_squares |= KING_MOVE_MAP[side.king_coord().offset()];
+
+    for king in side.king() {
+        attacked_squares |= KING_MOVE_MAP[king.offset()];
+    }
 
     return attacked_squares;
 }
@@ -128,13 +218,68 @@ pub fn get_move_mask(color: Color, board: &Board) -> BitBoard {
     return moves;
 }
 
+pub const PROMOTION_TYPES: [PieceType; 4] = [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight];
+
+// promotions are generated once per square, this lists every promotion piece as a move of its own
+pub fn expand_promotions(moves: &[Move]) -> Vec<Move> {
+    let mut expanded = Vec::with_capacity(moves.len());
+
+    for mv in moves {
+        if mv.promotion {
+            expanded.extend(PROMOTION_TYPES.iter().map(|promote_to| Move { promote_to: *promote_to, ..*mv }));
+        } else {
+            expanded.push(*mv);
+        }
+    }
+
+    return expanded;
+}
+
+// completes a move given only by its squares with the flags of the matching legal move, promotions default to a queen,
+// a piece the variant does not promote to makes the move unknown
+pub fn resolve_move(board: &Board, from: Coord, to: Coord, promote_to: Option<PieceType>) -> Option<Move> {
+    let mv = get_moves(board.turn(), board).into_iter().find(|mv| mv.from == from && mv.to == to)?;
+
+    if mv.promotion {
+        let promote_to = promote_to.unwrap_or(PieceType::Queen);
+
+        if !board.variant().can_promote_to(promote_to) {
+            return None;
+        }
+
+        return Some(Move { promote_to, ..mv });
+    }
+
+    return Some(mv);
+}
+
+// destinations of the legal moves from a square without generating every move, variants that restrict moves
+// across the whole board fall back to full generation
+pub fn get_move_targets(color: Color, board: &Board, from: Coord) -> BitBoard {
+    if !board.side(color).all().is_set(from) {
+        return BitBoard::new(0);
+    }
+
+    if board.variant() == Variant::RacingKings || board.variant().forced_captures() {
+        let mut targets = BitBoard::new(0);
+
+        for mv in get_moves(color, board).iter().filter(|mv| mv.from == from) {
+            targets.set(mv.to);
+        }
+
+        return targets;
+    }
+
+    return get_move_mask_from(color, from, board);
+}
+
 pub fn get_move_mask_from(color: Color, from: Coord, board: &Board) -> BitBoard {
     let moves = match board.lookup(from) {
         Some(super::PieceType::Rook) => get_rook_moves(color, from, board, board.all()),
         Some(super::PieceType::Bishop) => get_bishop_moves(color, from, board, board.all()),
         Some(super::PieceType::Queen) => get_queen_moves(color, from, board, board.all()),
-        Some(super::PieceType::Knight) => get_knight_moves(color, from, board),
-        Some(super::PieceType::King) => get_king_moves(color, board) | get_castling_moves(color, board),
+        Some(super::PieceType::Knight) => filter(color, from, get_knight_moves(color, from, board), board),
+        Some(super::PieceType::King) => get_king_moves(color, from, board) | get_castling_moves(color, board),
         Some(super::PieceType::Pawn) => {
             let moves = get_pawn_moves(color, from, board);
             let attacks = get_pawn_attacks(color, from);
@@ -190,6 +335,16 @@ fn get_pawn_moves(color: Color, from: Coord, board: &Board) -> BitBoard {
         }
     }
 
+    // horde pawns on the first rank may advance one or two squares
+    if board.variant() == Variant::Horde && color == Color::White && from.row() == 1 {
+        for distance in 1..=2 {
+            match from.mv(0, distance) {
+                Some(step) if !board.all().is_set(step) => pawn_moves.set(step),
+                _ => break,
+            }
+        }
+    }
+
     return pawn_moves;
 }
 
@@ -209,8 +364,13 @@ pub fn get_pawn_attacks_fast(color: Color, pawns: &BitBoard) -> BitBoard {
     }
 }
 
+// the en passant square is only there for the side to move, the other side never has an en passant capture
 fn get_en_passant_move(color: Color, from: Coord, en_passant_square: Coord, board: &Board) -> BitBoard {
     let mut moves = BitBoard::new(0);
+
+    if color != board.turn() {
+        return moves;
+    }
     let direction = match color {
         Color::White => 1,
         Color::Black => -1,
@@ -239,16 +399,19 @@ fn get_knight_moves(color: Color, from: Coord, board: &Board) -> BitBoard {
     return &KNIGHT_MOVE_MAP[from.offset()] & !board.side(color).all();
 }
 
-fn get_king_moves(color: Color, board: &Board) -> BitBoard {
+fn get_king_moves(color: Color, from: Coord, board: &Board) -> BitBoard {
     let side = board.side(color);
-    let opponent_side = board.side(color.invert());
-    let from = side.king_coord();
+    let moves = &KING_MOVE_MAP[from.offset()] & !side.all();
 
-    return &KING_MOVE_MAP[from.offset()] & !side.all() & !opponent_side.attacked_squares();
+    if !board.variant().has_checks() {
+        return moves;
+    }
+
+    return moves & !board.side(color.invert()).attacked_squares();
 }
 
 fn get_castling_moves(color: Color, board: &Board) -> BitBoard {
-    if board.side(color).checked() {
+    if !board.variant().can_castle() || board.side(color).checked() {
         return BitBoard::new(0);
     }
 
@@ -276,7 +439,9 @@ fn get_castling_moves(color: Color, board: &Board) -> BitBoard {
 
     let mut moves = BitBoard::new(0);
     let side = board.side(color);
-    let from = side.king_coord();
+    let Some(from) = side.king_coord() else {
+        return moves;
+    };
 
     if from != king_start {
         return moves;
@@ -307,6 +472,28 @@ fn get_castling_moves(color: Color, board: &Board) -> BitBoard {
     return moves;
 }
 
+// racing kings forbids giving check and ends once a king has reached the last rank
+// the moves of the side not to move are tried after passing the turn to it, which is always possible
+// as racing kings has no checks
+fn filter_racing_kings(color: Color, board: &Board, moves: &mut MoveList) {
+    let mut mover = board.clone();
+
+    if board.is_racing_kings_finished() || (mover.turn() != color && mover.make_null_move().is_err()) {
+        moves.clear();
+        return;
+    }
+
+    moves.retain(|mv| {
+        let mut test_board = mover.clone();
+
+        if test_board.exec_move(mv).is_err() {
+            return false;
+        }
+
+        return !test_board.side(color.invert()).checked();
+    });
+}
+
 fn filter(color: Color, from: Coord, moves: BitBoard, board: &Board) -> BitBoard {
     let mut moves = moves;
     let pin_rays = board.side(color).pin_rays();
@@ -325,13 +512,13 @@ fn filter(color: Color, from: Coord, moves: BitBoard, board: &Board) -> BitBoard
     return moves;
 }
 
-fn into_moves(moves: &mut Vec<Move>, from: Coord, board: BitBoard) {
+fn into_moves(moves: &mut MoveList, from: Coord, board: BitBoard) {
     for coord in board {
         moves.push(Move::new(from, coord));
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Move {
     pub from: Coord,
     pub to: Coord,
@@ -387,20 +574,184 @@ impl Move {
     }
 }
 
+impl Move {
+    pub fn to_u16(&self) -> u16 {
+        let kind = if self.castling {
+            1
+        } else if self.en_passant {
+            2
+        } else if self.promotion {
+            3
+        } else {
+            0
+        };
+
+        let promote_to = match self.promote_to {
+            PieceType::Knight => 0,
+            PieceType::Bishop => 1,
+            PieceType::Rook => 2,
+            _ => 3,
+        };
+
+        return self.from.offset() as u16 | (self.to.offset() as u16) << 6 | kind << 12 | promote_to << 14;
+    }
+
+    pub fn from_u16(value: u16) -> Self {
+        let from = Coord::from_offset((value & 0x3f) as usize);
+        let to = Coord::from_offset((value >> 6 & 0x3f) as usize);
+
+        let mut mv = match value >> 12 & 0b11 {
+            1 => Move::castling(from, to),
+            2 => Move::en_passant(from, to),
+            3 => Move::promotion(from, to),
+            _ => Move::new(from, to),
+        };
+
+        mv.promote_to = match value >> 14 {
+            0 => PieceType::Knight,
+            1 => PieceType::Bishop,
+            2 => PieceType::Rook,
+            _ => PieceType::Queen,
+        };
+
+        return mv;
+    }
+}
+
 impl Display for Move {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let promotion_piece = if self.promotion {
-            match self.promote_to {
-                PieceType::Rook => "r",
-                PieceType::Knight => "n",
-                PieceType::Bishop => "b",
-                PieceType::Queen => "q",
-                _ => "",
+        write!(f, "{}{}", self.from, self.to)?;
+
+        if self.promotion {
+            write!(f, "{}", self.promote_to.to_char().to_ascii_lowercase())?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn u16_round_trip() {
+        let mut underpromotion = Move::promotion(Coord::new('b', 7), Coord::new('a', 8));
+        underpromotion.promote_to = PieceType::Knight;
+
+        let moves = [
+            Move::new(Coord::new('e', 2), Coord::new('e', 4)),
+            Move::castling(Coord::new('e', 8), Coord::new('c', 8)),
+            Move::en_passant(Coord::new('d', 5), Coord::new('e', 6)),
+            Move::promotion(Coord::new('h', 2), Coord::new('h', 1)),
+            underpromotion,
+        ];
+
+        for mv in moves {
+            assert_eq!(mv, Move::from_u16(mv.to_u16()), "{mv}");
+        }
+    }
+
+    #[test]
+    fn evasions() {
+        let double_check = Board::from_fen("4r2k/8/8/8/8/3n4/8/1B2K3 w - -").unwrap();
+        let moves = get_moves(Color::White, &double_check);
+
+        assert_eq!(3, moves.len());
+        assert!(moves.iter().all(|mv| mv.from == Coord::new('e', 1)));
+
+        let en_passant = Board::from_fen("8/8/8/2k5/3Pp3/8/8/4K3 b - d3").unwrap();
+
+        assert!(get_moves(Color::Black, &en_passant).contains(&Move::en_passant(Coord::new('e', 4), Coord::new('d', 3))));
+
+        // only the rook can block, the knight is pinned
+        let blocks = Board::from_fen("4r2k/8/8/b7/8/R7/3N4/4K3 w - -").unwrap();
+        let mut targets = get_moves(Color::White, &blocks).iter().filter(|mv| mv.from != Coord::new('e', 1)).map(|mv| mv.to).collect::<Vec<_>>();
+        targets.sort_by_key(Coord::offset);
+
+        assert_eq!(vec![Coord::new('e', 3)], targets);
+    }
+
+    #[test]
+    fn resolves_move_flags() {
+        let board = Board::from_fen("r3k3/1P6/8/3pP3/8/8/8/4K2R w Kq d6").unwrap();
+
+        assert_eq!(
+            Some(Move::castling(Coord::new('e', 1), Coord::new('g', 1))),
+            resolve_move(&board, Coord::new('e', 1), Coord::new('g', 1), None)
+        );
+        assert_eq!(
+            Some(Move::en_passant(Coord::new('e', 5), Coord::new('d', 6))),
+            resolve_move(&board, Coord::new('e', 5), Coord::new('d', 6), None)
+        );
+
+        let promotion = resolve_move(&board, Coord::new('b', 7), Coord::new('a', 8), Some(PieceType::Knight)).unwrap();
+
+        assert!(promotion.promotion);
+        assert_eq!(PieceType::Knight, promotion.promote_to);
+        assert_eq!(None, resolve_move(&board, Coord::new('e', 1), Coord::new('e', 3), None));
+        assert_eq!(None, resolve_move(&board, Coord::new('b', 7), Coord::new('a', 8), Some(PieceType::King)));
+        assert_eq!(None, resolve_move(&board, Coord::new('b', 7), Coord::new('a', 8), Some(PieceType::Pawn)));
+
+        let antichess = Board::from_variant_fen(Variant::Antichess, "8/1P6/8/8/8/8/6p1/8 w - - 0 1").unwrap();
+        let king = resolve_move(&antichess, Coord::new('b', 7), Coord::new('b', 8), Some(PieceType::King)).unwrap();
+
+        assert_eq!(PieceType::King, king.promote_to);
+    }
+
+    #[test]
+    fn move_targets_match_generated_moves() {
+        let boards = [
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -").unwrap(),
+            Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -").unwrap(),
+            Board::from_fen("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6").unwrap(),
+            Board::from_variant_fen(Variant::Antichess, "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w - -").unwrap(),
+        ];
+
+        for board in boards {
+            let moves = get_moves(board.turn(), &board);
+
+            for from in 0..64 {
+                let from = Coord::from_xy(from % 8, from / 8);
+                let mut expected = BitBoard::new(0);
+
+                for mv in moves.iter().filter(|mv| mv.from == from) {
+                    expected.set(mv.to);
+                }
+
+                assert_eq!(expected, get_move_targets(board.turn(), &board, from), "{from}");
             }
-        } else {
-            ""
+        }
+    }
+
+    #[test]
+    fn generates_for_either_side() {
+        let boards = [
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -").unwrap(),
+            Board::from_fen("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6").unwrap(),
+            Board::from_fen("rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3").unwrap(),
+            Board::from_variant_fen(Variant::RacingKings, "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - -").unwrap(),
+        ];
+        let sorted = |mut moves: Vec<Move>| {
+            moves.sort_by_key(|mv| mv.to_string());
+            return moves;
         };
 
-        write!(f, "{}{}{}", self.from, self.to, promotion_piece)
+        // the side not to move gets the moves it would have if it were its turn
+        for board in boards {
+            let color = board.turn().invert();
+            let mut passed = board.clone();
+            passed.make_null_move().unwrap();
+
+            let moves = sorted(get_moves(color, &board));
+
+            assert!(!moves.is_empty());
+            assert_eq!(sorted(get_moves(color, &passed)), moves, "{}", board.to_fen());
+            assert!(moves.iter().all(|mv| !mv.en_passant));
+
+            for mv in &moves {
+                assert!(get_move_targets(color, &board, mv.from).is_set(mv.to));
+            }
+        }
     }
 }
@@ -0,0 +1,109 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{Coord, Move};
+
+pub const MAX_MOVES: usize = 256;
+
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    pub fn new() -> Self {
+        MoveList {
+            moves: [Move::new(Coord(0), Coord(0)); MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, mv: Move) {
+        debug_assert!(self.len < MAX_MOVES, "move list overflow");
+
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Move) -> bool,
+    {
+        let mut len = 0;
+
+        for i in 0..self.len {
+            if f(&self.moves[i]) {
+                self.moves[len] = self.moves[i];
+                len += 1;
+            }
+        }
+
+        self.len = len;
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &Self::Target {
+        return &self.moves[0..self.len];
+    }
+}
+
+impl DerefMut for MoveList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        return &mut self.moves[0..self.len];
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = std::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl std::fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{get_moves, get_moves_into, Board};
+
+    #[test]
+    fn matches_get_moves() {
+        let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -").unwrap();
+        let mut list = MoveList::new();
+
+        get_moves_into(board.turn(), &board, &mut list);
+
+        assert_eq!(get_moves(board.turn(), &board), list.to_vec());
+    }
+
+    #[test]
+    fn reuse_clears_previous_moves() {
+        let board = Board::new_game();
+        let mut list = MoveList::new();
+
+        get_moves_into(board.turn(), &board, &mut list);
+        get_moves_into(board.turn(), &board, &mut list);
+
+        assert_eq!(20, list.len());
+    }
+}
@@ -37,6 +37,14 @@ lazy_static! {
     static ref BLOCKED_BISHOP_MOVES: [Vec<BitBoard>; 64] = get_blocked_moves(BISHOP_DIRECTIONS, &BISHOP_MAGICS);
 }
 
+// the masks and the blocked move tables of both piece types
+pub fn sliding_table_bytes() -> usize {
+    let masks = (ROOK_MOVES.len() + BISHOP_MOVES.len()) * std::mem::size_of::<BitBoard>();
+    let blocked = BLOCKED_ROOK_MOVES.iter().chain(BLOCKED_BISHOP_MOVES.iter()).map(|moves| moves.capacity()).sum::<usize>();
+
+    return masks + blocked * std::mem::size_of::<BitBoard>();
+}
+
 const ROOK_DIRECTIONS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
 const BISHOP_DIRECTIONS: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
 
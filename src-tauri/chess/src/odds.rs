@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Odds {
+    PawnAndMove,
+    Knight,
+    Queen,
+}
+
+impl Odds {
+    // pawn and move is given by black, piece odds are given by white
+    pub fn start_fen(&self) -> &'static str {
+        match self {
+            Odds::PawnAndMove => "rnbqkbnr/ppppp1pp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            Odds::Knight => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R1BQKBNR w KQkq - 0 1",
+            Odds::Queen => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1",
+        }
+    }
+}
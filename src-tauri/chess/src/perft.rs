@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use crate::{engine::UciEngine, expand_promotions, get_moves_into, san::parse_san, Board, MoveList, PROMOTION_TYPES};
+
+// node counts per root move in uci notation, the format reference engines print for "go perft"
+pub type Divide = BTreeMap<String, u64>;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum PerftError {
+    #[error("The reference has no counts for '{fen}' at depth {depth}")]
+    UnknownPosition { fen: String, depth: usize },
+
+    #[error("The reference reported the illegal move '{0}'")]
+    IllegalMove(String),
+
+    #[error("Invalid perft table line '{0}'")]
+    InvalidLine(String),
+
+    #[error("The reference failed: {0}")]
+    Reference(String),
+}
+
+pub trait PerftReference {
+    fn divide(&mut self, board: &Board, depth: usize) -> Result<Divide, PerftError>;
+}
+
+impl<F> PerftReference for F
+where
+    F: FnMut(&Board, usize) -> Result<Divide, PerftError>,
+{
+    fn divide(&mut self, board: &Board, depth: usize) -> Result<Divide, PerftError> {
+        return self(board, depth);
+    }
+}
+
+impl PerftReference for UciEngine {
+    fn divide(&mut self, board: &Board, depth: usize) -> Result<Divide, PerftError> {
+        return self.perft(board, depth).map_err(|err| PerftError::Reference(err.to_string()));
+    }
+}
+
+// the first position, from the root along the moves with differing counts, whose moves themselves differ
+// or where only one ply is left
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub fen: String,
+    pub path: Vec<String>,
+    pub depth: usize,
+    pub missing: Vec<String>,
+    pub extra: Vec<String>,
+    pub mismatched: Vec<(String, u64, u64)>,
+}
+
+// known-good divides, one position per line as "<fen>;<depth>;<move> <count>,<move> <count>,..."
+#[derive(Debug, Clone, Default)]
+pub struct PerftTable {
+    entries: Vec<(String, usize, Divide)>,
+}
+
+impl PerftTable {
+    pub fn parse(text: &str) -> Result<Self, PerftError> {
+        let mut entries = Vec::new();
+
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let invalid = || PerftError::InvalidLine(line.to_string());
+            let mut fields = line.split(';');
+
+            let (Some(fen), Some(depth), Some(counts)) = (fields.next(), fields.next(), fields.next()) else {
+                return Err(invalid());
+            };
+
+            let depth = depth.trim().parse::<usize>().map_err(|_| invalid())?;
+            let mut divide = Divide::new();
+
+            for entry in counts.split(',') {
+                let (mv, count) = entry.trim().split_once(' ').ok_or_else(invalid)?;
+                divide.insert(mv.to_string(), count.parse().map_err(|_| invalid())?);
+            }
+
+            entries.push((normalize_fen(fen), depth, divide));
+        }
+
+        return Ok(PerftTable { entries });
+    }
+
+    // the start position and the chessprogramming wiki test positions
+    pub fn bundled() -> Self {
+        return Self::parse(include_str!("perft.txt")).expect("bundled perft table to be valid");
+    }
+}
+
+impl PerftReference for PerftTable {
+    fn divide(&mut self, board: &Board, depth: usize) -> Result<Divide, PerftError> {
+        let fen = normalize_fen(&board.to_fen());
+
+        return self
+            .entries
+            .iter()
+            .find(|(entry_fen, entry_depth, _)| *entry_fen == fen && *entry_depth == depth)
+            .map(|(_, _, divide)| divide.clone())
+            .ok_or(PerftError::UnknownPosition { fen: board.to_fen(), depth });
+    }
+}
+
+pub fn perft(board: &mut Board, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let mut moves = MoveList::new();
+    get_moves_into(board.turn(), board, &mut moves);
+
+    if depth == 1 {
+        return moves.iter().map(|mv| if mv.promotion { PROMOTION_TYPES.len() as u64 } else { 1 }).sum();
+    }
+
+    let mut count = 0;
+
+    for mv in expand_promotions(&moves) {
+        board.exec_move(&mv).expect("generated move to be playable");
+        count += perft(board, depth - 1);
+        board.undo_move().expect("move to be undoable");
+    }
+
+    return count;
+}
+
+pub fn divide(board: &Board, depth: usize) -> Divide {
+    let mut board = board.clone();
+    let mut moves = MoveList::new();
+    let mut divide = Divide::new();
+
+    get_moves_into(board.turn(), &board, &mut moves);
+
+    for mv in expand_promotions(&moves) {
+        board.exec_move(&mv).expect("generated move to be playable");
+        divide.insert(mv.to_string(), perft(&mut board, depth.saturating_sub(1)));
+        board.undo_move().expect("move to be undoable");
+    }
+
+    return divide;
+}
+
+// walks down the first move whose count differs until the move lists themselves differ
+pub fn find_divergence(board: &Board, depth: usize, reference: &mut dyn PerftReference) -> Result<Option<Divergence>, PerftError> {
+    let mut board = board.clone();
+    let mut path = Vec::new();
+
+    for depth in (1..=depth).rev() {
+        let ours = divide(&board, depth);
+        let theirs = reference.divide(&board, depth)?;
+
+        if ours == theirs {
+            return Ok(None);
+        }
+
+        let divergence = Divergence {
+            fen: board.to_fen(),
+            path: path.clone(),
+            depth,
+            missing: theirs.keys().filter(|mv| !ours.contains_key(*mv)).cloned().collect(),
+            extra: ours.keys().filter(|mv| !theirs.contains_key(*mv)).cloned().collect(),
+            mismatched: ours
+                .iter()
+                .filter_map(|(mv, count)| theirs.get(mv).filter(|expected| *expected != count).map(|expected| (mv.clone(), *count, *expected)))
+                .collect(),
+        };
+
+        if !divergence.missing.is_empty() || !divergence.extra.is_empty() || depth == 1 {
+            return Ok(Some(divergence));
+        }
+
+        let (next, _, _) = &divergence.mismatched[0];
+        let mv = parse_san(&board, next).ok_or_else(|| PerftError::IllegalMove(next.clone()))?;
+
+        // a reference that knows the parent but not the child still narrows things down to this position
+        let mut child = board.clone();
+        child.exec_move(&mv).map_err(|_| PerftError::IllegalMove(next.clone()))?;
+
+        if matches!(reference.divide(&child, depth - 1), Err(PerftError::UnknownPosition { .. })) {
+            return Ok(Some(divergence));
+        }
+
+        board = child;
+        path.push(next.clone());
+    }
+
+    return Ok(None);
+}
+
+// move counters do not change the moves, so they are left out when looking up positions
+fn normalize_fen(fen: &str) -> String {
+    return fen.split_whitespace().take(4).collect::<Vec<_>>().join(" ");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -";
+
+    #[test]
+    fn counts_and_bundled_table() {
+        let board = Board::new_game();
+
+        assert_eq!(8902, perft(&mut board.clone(), 3));
+        assert_eq!(400, divide(&board, 2).values().sum::<u64>());
+        assert_eq!(None, find_divergence(&Board::from_fen(KIWIPETE).unwrap(), 3, &mut PerftTable::bundled()).unwrap());
+    }
+
+    // a reference that knows the extra move e8e7 after 1. e4 d5 2. Bb5+
+    fn tampered(board: &mut Board, depth: usize, target: u64) -> Divide {
+        let mut counts = Divide::new();
+        let mut moves = MoveList::new();
+
+        get_moves_into(board.turn(), board, &mut moves);
+
+        for mv in expand_promotions(&moves) {
+            board.exec_move(&mv).unwrap();
+            let count = if depth == 1 { 1 } else { tampered(board, depth - 1, target).values().sum() };
+            counts.insert(mv.to_string(), count);
+            board.undo_move().unwrap();
+        }
+
+        if board.hash() == target {
+            counts.insert("e8e7".to_string(), 1);
+        }
+
+        return counts;
+    }
+
+    #[test]
+    fn walks_down_to_divergence() {
+        let target = Board::from_fen("rnbqkbnr/ppp1pppp/8/1B1p4/4P3/8/PPPP1PPP/RNBQK1NR b KQkq -").unwrap().hash();
+        let mut reference = |board: &Board, depth: usize| -> Result<Divide, PerftError> { Ok(tampered(&mut board.clone(), depth, target)) };
+
+        let divergence = find_divergence(&Board::new_game(), 4, &mut reference).unwrap().unwrap();
+
+        assert_eq!(vec!["e2e4", "d7d5", "f1b5"], divergence.path);
+        assert_eq!(vec!["e8e7"], divergence.missing);
+        assert_eq!(1, divergence.depth);
+    }
+}
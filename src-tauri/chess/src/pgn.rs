@@ -0,0 +1,853 @@
+use std::{fmt::Display, io::BufRead};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fen::FenError,
+    san::parse_san,
+    tree::{MoveNode, MoveTree, ROOT},
+    Board, Color, Variant,
+};
+
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+pub const ARMAGEDDON_TAG: &str = "Armageddon";
+
+// the glyphs for the nags 1 to 6
+const GLYPHS: [&str; 6] = ["!", "?", "!!", "??", "!?", "?!"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum PgnError {
+    #[error("Invalid tag '{0}'")]
+    InvalidTag(String),
+
+    #[error("Unknown variant '{0}'")]
+    UnknownVariant(String),
+
+    #[error("Illegal move '{0}'")]
+    IllegalMove(String),
+
+    #[error("Unterminated comment or variation")]
+    Unterminated,
+
+    #[error(transparent)]
+    Fen(#[from] FenError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+// the parts of the movetext that make up the tree, move numbers and results are left out
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Move(String),
+    Nag(u8),
+    Comment(String),
+    Open,
+    Close,
+}
+
+// how a game ended, the tag values are the ones of the pgn standard where it has one
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Termination {
+    Normal,
+    TimeForfeit,
+    Resignation,
+    Agreement,
+    Abandonment,
+    RulesInfraction,
+}
+
+impl Termination {
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            Termination::Normal => "normal",
+            Termination::TimeForfeit => "time forfeit",
+            Termination::Resignation => "resignation",
+            Termination::Agreement => "agreement",
+            Termination::Abandonment => "abandoned",
+            Termination::RulesInfraction => "rules infraction",
+        };
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        return match value.to_lowercase().as_str() {
+            "normal" => Some(Termination::Normal),
+            "time forfeit" => Some(Termination::TimeForfeit),
+            "resignation" => Some(Termination::Resignation),
+            "agreement" => Some(Termination::Agreement),
+            "abandoned" => Some(Termination::Abandonment),
+            "rules infraction" => Some(Termination::RulesInfraction),
+            _ => None,
+        };
+    }
+}
+
+// an engine evaluation as sites write it into the comments with [%eval], from white's side
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Eval {
+    Centipawns(i32),
+    Mate(i32),
+}
+
+// pawns with two decimals or the moves to mate after a #, the way the comment holds it
+impl Display for Eval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Eval::Centipawns(centipawns) => write!(f, "{:.2}", *centipawns as f64 / 100.0),
+            Eval::Mate(moves) => write!(f, "#{moves}"),
+        };
+    }
+}
+
+impl Display for Termination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}", self.as_str());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    // the board follows the main line, the tree holds the variations as well
+    pub board: Board,
+    pub tree: MoveTree,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        return self.tags.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+    }
+}
+
+pub fn result(board: &Board) -> &'static str {
+    return match board.winner() {
+        Some(Color::White) => "1-0",
+        Some(Color::Black) => "0-1",
+        None if crate::get_moves(board.turn(), board).is_empty() => "1/2-1/2",
+        None => "*",
+    };
+}
+
+// a result given in the tags is only used for games that are not decided on the board, like adjudicated ones,
+// black has draw odds in a game tagged as armageddon, so a draw is scored as a win for black
+pub fn scored_result<'a>(board: &Board, tags: &'a [(String, String)]) -> &'a str {
+    let result = match result(board) {
+        "*" => tags.iter().find(|(key, _)| key == "Result").map_or("*", |(_, value)| value.as_str()),
+        result => result,
+    };
+
+    return match result {
+        "1/2-1/2" if is_armageddon(tags) => "0-1",
+        result => result,
+    };
+}
+
+pub fn is_armageddon(tags: &[(String, String)]) -> bool {
+    return tags.iter().any(|(key, value)| key == ARMAGEDDON_TAG && value == "1");
+}
+
+// a termination given in the tags wins, a game decided on the board ended normally
+pub fn termination(board: &Board, tags: &[(String, String)]) -> Option<Termination> {
+    if let Some((_, value)) = tags.iter().find(|(key, _)| key == "Termination") {
+        return Termination::parse(value);
+    }
+
+    return match result(board) {
+        "*" => None,
+        _ => Some(Termination::Normal),
+    };
+}
+
+// the time left after a move is kept in its comment as [%clk h:mm:ss], in front of anything written there
+pub fn with_clock(comment: Option<&str>, remaining_ms: u64) -> String {
+    let seconds = remaining_ms / 1000;
+    let clock = format!("[%clk {}:{:02}:{:02}]", seconds / 3600, seconds / 60 % 60, seconds % 60);
+    let rest = comment.map(strip_clock).unwrap_or_default();
+
+    return match rest.is_empty() {
+        true => clock,
+        false => format!("{clock} {rest}"),
+    };
+}
+
+pub fn parse_clock(comment: &str) -> Option<u64> {
+    let start = comment.find("[%clk ")? + "[%clk ".len();
+    let end = start + comment[start..].find(']')?;
+    let mut ms = 0.0;
+
+    for part in comment[start..end].trim().split(':') {
+        ms = ms * 60.0 + part.parse::<f64>().ok()? * 1000.0;
+    }
+
+    return Some(ms.round() as u64);
+}
+
+// a depth after the evaluation, like [%eval 0.25,18], is ignored
+pub fn parse_eval(comment: &str) -> Option<Eval> {
+    let start = comment.find("[%eval ")? + "[%eval ".len();
+    let end = start + comment[start..].find(']')?;
+    let value = comment[start..end].split(',').next()?.trim();
+
+    return match value.strip_prefix('#') {
+        Some(moves) => moves.parse().ok().map(Eval::Mate),
+        None => value.parse::<f64>().ok().map(|pawns| Eval::Centipawns((pawns * 100.0).round() as i32)),
+    };
+}
+
+fn strip_clock(comment: &str) -> String {
+    let Some(start) = comment.find("[%clk ") else {
+        return comment.trim().to_string();
+    };
+
+    let end = comment[start..].find(']').map_or(comment.len(), |end| start + end + 1);
+
+    return format!("{} {}", &comment[..start], &comment[end..]).trim().to_string();
+}
+
+pub fn write_pgn(board: &Board, tags: &[(String, String)]) -> String {
+    return write_tree_pgn(&MoveTree::from_board(board), tags);
+}
+
+// writes the seven tag roster, filled with the given tags or placeholders, followed by the remaining tags and the moves
+// of the main line with its variations
+pub fn write_tree_pgn(tree: &MoveTree, tags: &[(String, String)]) -> String {
+    let start = tree.start();
+    let board = tree.board_at(tree.mainline().last().copied().unwrap_or(ROOT)).expect("main line to be in the tree");
+    let board = &board;
+
+    let result = scored_result(board, tags);
+
+    let mut pgn = String::new();
+
+    for name in SEVEN_TAG_ROSTER {
+        let value = match name {
+            "Result" => Some(result),
+            _ => tags.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str()),
+        };
+
+        let placeholder = if name == "Date" { "????.??.??" } else { "?" };
+
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, escape(value.unwrap_or(placeholder))));
+    }
+
+    if board.variant() != Variant::Standard {
+        pgn.push_str(&format!("[Variant \"{}\"]\n", variant_name(board.variant())));
+    }
+
+    if start.to_fen() != Board::new_variant_game(board.variant()).to_fen() {
+        pgn.push_str("[SetUp \"1\"]\n");
+        pgn.push_str(&format!("[FEN \"{}\"]\n", start.to_fen()));
+    }
+
+    for (name, value) in tags {
+        if !SEVEN_TAG_ROSTER.contains(&name.as_str()) && !["Variant", "SetUp", "FEN"].contains(&name.as_str()) {
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+        }
+    }
+
+    // a termination tag is written as it was given, even with a value that is not known here
+    let tagged = tags.iter().any(|(key, _)| key == "Termination");
+
+    if let Some(termination) = termination(board, tags).filter(|_| !tagged) {
+        pgn.push_str(&format!("[Termination \"{}\"]\n", termination));
+    }
+
+    pgn.push('\n');
+
+    let mut tokens = Vec::new();
+    let root = tree.node(ROOT).expect("the root to be in the tree");
+
+    push_comment(root, &mut tokens);
+    write_line(tree, ROOT, start, true, &mut tokens);
+    tokens.push(result.to_string());
+
+    // movetext lines are kept below 80 characters
+    let mut line_length = 0;
+
+    for token in tokens {
+        if line_length > 0 && line_length + token.len() + 1 > 79 {
+            pgn.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            pgn.push(' ');
+            line_length += 1;
+        }
+
+        line_length += token.len();
+        pgn.push_str(&token);
+    }
+
+    pgn.push('\n');
+
+    return pgn;
+}
+
+// the main continuation of every move comes first, its alternatives follow in parentheses before the line goes on,
+// a black move gets its number again after a variation or a comment
+fn write_line(tree: &MoveTree, parent: usize, board: &Board, numbered: bool, tokens: &mut Vec<String>) {
+    let node = |id: usize| tree.node(id).expect("children to be in the tree");
+    let mut board = board.clone();
+    let mut parent = parent;
+    let mut numbered = numbered;
+
+    while let Some((&main, variations)) = node(parent).children.split_first() {
+        push_move(&board, node(main), numbered, tokens);
+
+        for &variation in variations {
+            let mut after = board.clone();
+            after.exec_move(&node(variation).mv.expect("moves to be set below the root")).expect("moves in the tree to be legal");
+
+            // parentheses stick to the moves they enclose
+            let first = tokens.len();
+            push_move(&board, node(variation), true, tokens);
+            tokens[first].insert(0, '(');
+
+            write_line(tree, variation, &after, node(variation).comment.is_some(), tokens);
+            tokens.last_mut().expect("a variation to hold a move").push(')');
+        }
+
+        board.exec_move(&node(main).mv.expect("moves to be set below the root")).expect("moves in the tree to be legal");
+        numbered = !variations.is_empty() || node(main).comment.is_some();
+        parent = main;
+    }
+}
+
+// the first nag is written as a glyph after the move when it has one, the others as $n
+fn push_move(before: &Board, node: &MoveNode, numbered: bool, tokens: &mut Vec<String>) {
+    if before.turn() == Color::White {
+        tokens.push(format!("{}.", before.fullmove_number()));
+    } else if numbered {
+        tokens.push(format!("{}...", before.fullmove_number()));
+    }
+
+    let mut nags = node.nags.iter().copied().peekable();
+    let glyph = nags.next_if(|nag| glyph(*nag).is_some()).and_then(glyph);
+
+    tokens.push(format!("{}{}", node.san, glyph.unwrap_or("")));
+    tokens.extend(nags.map(|nag| format!("${nag}")));
+
+    push_comment(node, tokens);
+}
+
+// comments are split into words so long ones are wrapped like the moves
+fn push_comment(node: &MoveNode, tokens: &mut Vec<String>) {
+    let Some(comment) = &node.comment else {
+        return;
+    };
+
+    let comment = comment.replace('}', "");
+    let words = comment.split_whitespace().collect::<Vec<_>>();
+    let last = words.len().saturating_sub(1);
+
+    for (index, word) in words.into_iter().enumerate() {
+        let open = if index == 0 { "{" } else { "" };
+        let close = if index == last { "}" } else { "" };
+
+        tokens.push(format!("{open}{word}{close}"));
+    }
+}
+
+fn glyph(nag: u8) -> Option<&'static str> {
+    return GLYPHS.get((nag as usize).checked_sub(1)?).copied();
+}
+
+// reads the tags, the main line and the variations of the first game with their comments and annotations
+pub fn read_pgn(pgn: &str) -> Result<PgnGame, PgnError> {
+    let mut tags = Vec::new();
+    let mut movetext = String::new();
+
+    for line in pgn.lines().map(str::trim) {
+        if line.starts_with('%') {
+            continue;
+        }
+
+        if line.starts_with('[') && movetext.trim().is_empty() {
+            tags.push(parse_tag(line)?);
+        } else {
+            movetext.push_str(line);
+            movetext.push('\n');
+        }
+    }
+
+    let variant = match tags.iter().find(|(key, _)| key == "Variant") {
+        Some((_, name)) => parse_variant(name)?,
+        None => Variant::Standard,
+    };
+
+    let mut board = match tags.iter().find(|(key, _)| key == "FEN") {
+        Some((_, fen)) => {
+            let mut board = Board::new_variant_game(variant);
+            board.apply_fen(fen)?;
+            board
+        }
+        None => Board::new_variant_game(variant),
+    };
+
+    let mut tree = MoveTree::new(&board);
+    let mut node = ROOT;
+
+    // the move and position to go back to once a variation closes
+    let mut variations: Vec<(usize, Board)> = Vec::new();
+
+    // an illegal move in a variation drops the rest of that variation, the game itself can still be read
+    let mut skipped_from: Option<usize> = None;
+
+    // a comment at the start of a variation belongs to its first move
+    let mut opened = false;
+    let mut pending_comment: Option<String> = None;
+
+    for token in movetext_tokens(&movetext)? {
+        match token {
+            Token::Open => {
+                variations.push((node, board.clone()));
+
+                // a variation replaces the move before it, so one that opens before any move has nothing to replace
+                match tree.node(node).ok().and_then(|current| current.parent) {
+                    Some(parent) if skipped_from.is_none() && !opened => {
+                        node = parent;
+                        board.undo_move().expect("moves of the line to be undoable");
+                    }
+                    _ => {
+                        skipped_from.get_or_insert(variations.len());
+                    }
+                }
+
+                opened = true;
+            }
+            Token::Close => {
+                if skipped_from == Some(variations.len()) {
+                    skipped_from = None;
+                }
+
+                (node, board) = variations.pop().ok_or(PgnError::Unterminated)?;
+                opened = false;
+                pending_comment = None;
+            }
+            _ if skipped_from.is_some() => {}
+            Token::Comment(comment) if opened => {
+                pending_comment = Some(join_comments(pending_comment, comment));
+            }
+            Token::Comment(comment) => {
+                let existing = tree.node(node).expect("the current move to be in the tree").comment.clone();
+                tree.set_comment(node, Some(join_comments(existing, comment))).expect("the current move to be in the tree");
+            }
+            Token::Nag(nag) if node != ROOT => {
+                let mut nags = tree.node(node).expect("the current move to be in the tree").nags.clone();
+                nags.push(nag);
+                tree.set_nags(node, nags).expect("moves to take glyphs");
+            }
+            Token::Nag(_) => {}
+            Token::Move(san) => {
+                let Some(mv) = parse_san(&board, &san) else {
+                    if variations.is_empty() {
+                        return Err(PgnError::IllegalMove(san));
+                    }
+
+                    skipped_from = Some(variations.len());
+                    continue;
+                };
+
+                let before = board.clone();
+                board.exec_move(&mv).map_err(|_| PgnError::IllegalMove(san))?;
+                node = tree.add_move(node, &before, mv);
+                opened = false;
+
+                if let Some(comment) = pending_comment.take() {
+                    tree.set_comment(node, Some(comment)).expect("the new move to be in the tree");
+                }
+            }
+        }
+    }
+
+    tree.sync(&board);
+
+    return Ok(PgnGame { tags, board, tree });
+}
+
+// reads the games of a multi-game file one after the other, only the current game is held in memory,
+// a game that cannot be read is reported and the reader moves on to the next one
+pub struct PgnReader<R> {
+    reader: R,
+    line: String,
+    next_tag: Option<String>,
+    failed: bool,
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        PgnReader {
+            reader,
+            line: String::new(),
+            next_tag: None,
+            failed: false,
+        }
+    }
+
+    // a game ends at the first tag line after its moves, tag-like lines in comments and variations are movetext
+    fn next_game_text(&mut self) -> Result<Option<String>, PgnError> {
+        let mut text = self.next_tag.take().unwrap_or_default();
+        let mut has_moves = false;
+        let mut comment = false;
+        let mut depth = 0;
+
+        loop {
+            self.line.clear();
+
+            if self.reader.read_line(&mut self.line)? == 0 {
+                break;
+            }
+
+            let line = self.line.trim_start_matches('\u{feff}').trim();
+
+            if line.starts_with('[') && !comment && depth == 0 {
+                if has_moves {
+                    self.next_tag = Some(format!("{line}\n"));
+                    break;
+                }
+            } else if !line.starts_with('%') {
+                for c in line.chars() {
+                    match c {
+                        '}' if comment => comment = false,
+                        _ if comment => {}
+                        '{' => comment = true,
+                        ';' => break,
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        c if !c.is_whitespace() => has_moves = true,
+                        _ => {}
+                    }
+                }
+            }
+
+            text.push_str(line);
+            text.push('\n');
+        }
+
+        return Ok((!text.trim().is_empty()).then_some(text));
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        return match self.next_game_text() {
+            Ok(text) => text.map(|text| read_pgn(&text)),
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        };
+    }
+}
+
+fn movetext_tokens(movetext: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = movetext.chars().peekable();
+    let mut depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | ';' => {
+                let end = if c == '{' { '}' } else { '\n' };
+                let mut comment = String::new();
+
+                loop {
+                    match chars.next() {
+                        Some(c) if c == end => break,
+                        Some(c) => comment.push(c),
+                        None if end == '}' => return Err(PgnError::Unterminated),
+                        None => break,
+                    }
+                }
+
+                let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+
+                if !comment.is_empty() {
+                    tokens.push(Token::Comment(comment));
+                }
+            }
+            '(' | ')' => {
+                depth += if c == '(' { 1 } else { -1 };
+
+                if depth < 0 {
+                    return Err(PgnError::Unterminated);
+                }
+
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+
+                while let Some(next) = chars.peek().filter(|next| !next.is_whitespace() && !"{}();".contains(**next)) {
+                    word.push(*next);
+                    chars.next();
+                }
+
+                tokens.extend(word_tokens(&word));
+            }
+        }
+    }
+
+    if depth != 0 {
+        return Err(PgnError::Unterminated);
+    }
+
+    return Ok(tokens);
+}
+
+// results and the e.p. suffix some files add are skipped, glyphs can stand apart or follow the move directly
+fn word_tokens(word: &str) -> Vec<Token> {
+    if ["1-0", "0-1", "1/2-1/2", "*", "e.p."].contains(&word) {
+        return Vec::new();
+    }
+
+    if let Some(nag) = word.strip_prefix('$') {
+        return nag.parse().map(Token::Nag).into_iter().collect();
+    }
+
+    // move numbers like 12. or 12... can be glued to the move itself
+    let word = word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let san = word.trim_end_matches(['!', '?']);
+    let nag = GLYPHS.iter().position(|glyph| *glyph == &word[san.len()..]).map(|index| Token::Nag(index as u8 + 1));
+
+    return match san.is_empty() {
+        true => nag.into_iter().collect(),
+        false => std::iter::once(Token::Move(san.to_string())).chain(nag).collect(),
+    };
+}
+
+fn join_comments(existing: Option<String>, comment: String) -> String {
+    return match existing {
+        Some(existing) => format!("{existing} {comment}"),
+        None => comment,
+    };
+}
+
+fn parse_tag(line: &str) -> Result<(String, String), PgnError> {
+    let invalid = || PgnError::InvalidTag(line.to_string());
+    let inner = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')).ok_or_else(invalid)?;
+    let (name, value) = inner.split_once(' ').ok_or_else(invalid)?;
+    let value = value.trim().strip_prefix('"').and_then(|value| value.strip_suffix('"')).ok_or_else(invalid)?;
+
+    return Ok((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")));
+}
+
+fn escape(value: &str) -> String {
+    return value.replace('\\', "\\\\").replace('"', "\\\"");
+}
+
+fn variant_name(variant: Variant) -> &'static str {
+    match variant {
+        Variant::Standard => "Standard",
+        Variant::Antichess => "Antichess",
+        Variant::Horde => "Horde",
+        Variant::RacingKings => "Racing Kings",
+    }
+}
+
+fn parse_variant(name: &str) -> Result<Variant, PgnError> {
+    match name.to_lowercase().as_str() {
+        "standard" | "chess" | "from position" | "fromposition" => Ok(Variant::Standard),
+        "antichess" | "giveaway" => Ok(Variant::Antichess),
+        "horde" => Ok(Variant::Horde),
+        "racing kings" | "racingkings" => Ok(Variant::RacingKings),
+        _ => Err(PgnError::UnknownVariant(name.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coord, Move};
+
+    #[test]
+    fn write_and_read() {
+        let mut board = Board::new_game();
+
+        for (from, to) in [("f2", "f3"), ("e7", "e5"), ("g2", "g4"), ("d8", "h4")] {
+            board.exec_move(&Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap())).unwrap();
+        }
+
+        let tags = vec![("White".to_string(), "Fool".to_string()), ("Opening".to_string(), "Barnes".to_string())];
+        let pgn = write_pgn(&board, &tags);
+
+        assert!(pgn.contains("[White \"Fool\"]\n[Black \"?\"]\n[Result \"0-1\"]\n[Opening \"Barnes\"]\n"));
+        assert!(pgn.ends_with("\n1. f3 e5 2. g4 Qh4# 0-1\n"));
+
+        let game = read_pgn(&pgn).unwrap();
+
+        assert_eq!(board, game.board);
+        assert_eq!(Some("Barnes"), game.tag("Opening"));
+        assert_eq!(Some(Termination::Normal), termination(&game.board, &game.tags));
+    }
+
+    #[test]
+    fn clock_comments() {
+        assert_eq!("[%clk 0:04:52]", with_clock(None, 292_400));
+        assert_eq!("[%clk 1:00:05] good move", with_clock(Some("[%clk 0:04:52] good move"), 3_605_000));
+        assert_eq!(Some(292_000), parse_clock("[%clk 0:04:52] good move"));
+        assert_eq!(Some(1_500), parse_clock("{ [%clk 0:00:01.5] }"));
+        assert_eq!(None, parse_clock("no clock"));
+
+        let game = read_pgn("1. e4 { [%clk 0:04:52] } e5 { [%clk 0:04:58] }").unwrap();
+        let node = game.tree.node(game.tree.mainline()[1]).unwrap();
+
+        assert_eq!(Some(298_000), node.comment.as_deref().and_then(parse_clock));
+    }
+
+    #[test]
+    fn eval_comments() {
+        assert_eq!(Some(Eval::Centipawns(17)), parse_eval("[%eval 0.17] [%clk 0:00:30]"));
+        assert_eq!(Some(Eval::Centipawns(-250)), parse_eval("[%eval -2.5,22]"));
+        assert_eq!(Some(Eval::Mate(-3)), parse_eval("{ [%eval #-3] }"));
+        assert_eq!(None, parse_eval("[%clk 0:00:30]"));
+
+        assert_eq!("-0.05", Eval::Centipawns(-5).to_string());
+        assert_eq!("#4", Eval::Mate(4).to_string());
+        assert_eq!(Some(Eval::Centipawns(-5)), parse_eval(&format!("[%eval {}]", Eval::Centipawns(-5))));
+    }
+
+    #[test]
+    fn writes_terminations() {
+        let game = read_pgn("1. e4 e5").unwrap();
+        let tags = vec![("Result".to_string(), "1-0".to_string()), ("Termination".to_string(), "time forfeit".to_string())];
+
+        assert!(!write_pgn(&game.board, &[]).contains("Termination"));
+        assert!(write_pgn(&game.board, &tags).contains("[Result \"1-0\"]\n[Termination \"time forfeit\"]\n"));
+        assert_eq!(Some(Termination::TimeForfeit), termination(&game.board, &tags));
+
+        for termination in [Termination::Resignation, Termination::Abandonment, Termination::RulesInfraction] {
+            assert_eq!(Some(termination), Termination::parse(termination.as_str()));
+        }
+    }
+
+    #[test]
+    fn scores_armageddon_draws_for_black() {
+        let stalemate = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        let agreed = read_pgn("1. e4 e5").unwrap().board;
+        let armageddon = vec![(ARMAGEDDON_TAG.to_string(), "1".to_string())];
+        let drawn = vec![(ARMAGEDDON_TAG.to_string(), "1".to_string()), ("Result".to_string(), "1/2-1/2".to_string())];
+
+        assert_eq!("1/2-1/2", scored_result(&stalemate, &[]));
+        assert_eq!("0-1", scored_result(&stalemate, &armageddon));
+        assert_eq!("0-1", scored_result(&agreed, &drawn));
+        assert_eq!("*", scored_result(&agreed, &armageddon));
+        assert!(write_pgn(&stalemate, &armageddon).contains("[Result \"0-1\"]\n[SetUp \"1\"]\n[FEN \"7k/5Q2/6K1/8/8/8/8/8 b - - 0 1\"]\n[Armageddon \"1\"]\n"));
+    }
+
+    #[test]
+    fn comments_variations_and_setup() {
+        let pgn = r#"[Event "Test"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 b - - 0 30"]
+
+30... Kd7 {the king steps aside} 31.e4 (31. e3 Kd6 $2) Ke6 ; rest of line
+32. Kf2 *"#;
+
+        let game = read_pgn(pgn).unwrap();
+
+        assert_eq!(Some("Test"), game.tag("Event"));
+        assert_eq!(4, game.board.history().len() - 1);
+        assert_eq!(Some(Move::new(Coord::new('e', 1), Coord::new('f', 2))), game.board.last_move());
+        assert!(write_pgn(&game.board, &game.tags).contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 30\"]\n\n30... Kd7 31. e4 Ke6 32. Kf2 *\n"));
+    }
+
+    #[test]
+    fn streams_multiple_games() {
+        let pgn = "\u{feff}[Event \"First\"]
+[Result \"1-0\"]
+
+1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0
+
+[Event \"Second\"]
+
+1. d4 {a comment
+[Event \"Not a tag\"]
+that spans lines} d5 (1... Nf6 2. c4
+[not a tag either]
+e6) 2. c4 ! e6 $1 3. Nc3 ?! *
+
+[Event \"Broken\"]
+
+1. e4 e4 *
+[Event \"Last\"]
+1. f4 e.p. e5 *
+";
+
+        let games = PgnReader::new(pgn.as_bytes()).collect::<Vec<_>>();
+
+        assert_eq!(4, games.len());
+        assert_eq!(Some(Color::White), games[0].as_ref().unwrap().board.winner());
+        assert_eq!(Some("First"), games[0].as_ref().unwrap().tag("Event"));
+
+        let second = games[1].as_ref().unwrap();
+
+        assert_eq!(Some("Second"), second.tag("Event"));
+        assert_eq!(5, second.board.history().len() - 1);
+        assert!(matches!(&games[2], Err(PgnError::IllegalMove(mv)) if mv == "e4"));
+        assert_eq!(Some("Last"), games[3].as_ref().unwrap().tag("Event"));
+    }
+
+    #[test]
+    fn variations_round_trip() {
+        let pgn = "1. e4 (1. d4 d5 (1... Nf6 2. c4) 2. c4) 1... e5 (1... c5 2. Nf3 (2. Nc3) 2... d6) 2. Nf3 (2. Bc4 Qxh2) Nc6 *";
+        let game = read_pgn(pgn).unwrap();
+
+        assert_eq!(4, game.board.history().len() - 1);
+        assert_eq!(vec!["e4", "e5", "Nf3", "Nc6"], game.tree.mainline().iter().map(|id| game.tree.node(*id).unwrap().san.clone()).collect::<Vec<_>>());
+        assert_eq!(game.tree.mainline().last().copied(), Some(game.tree.current()));
+
+        // the illegal Qxh2 drops the rest of its variation, Bc4 stays
+        let written = write_tree_pgn(&game.tree, &[]);
+        let movetext = "1. e4 (1. d4 d5 (1... Nf6 2. c4) 2. c4) 1... e5 (1... c5 2. Nf3 (2. Nc3) 2... d6) 2. Nf3 (2. Bc4) 2... Nc6 *";
+
+        assert!(written.split_whitespace().collect::<Vec<_>>().join(" ").ends_with(movetext), "{written}");
+        assert_eq!(game.tree.mainline().len(), read_pgn(&written).unwrap().tree.mainline().len());
+        assert_eq!(written, write_tree_pgn(&read_pgn(&written).unwrap().tree, &[]));
+
+        assert!(matches!(read_pgn("1. e4 (e5) e5"), Ok(game) if game.board.history().len() == 3));
+        assert!(matches!(read_pgn("1. e4 e5) Nf3"), Err(PgnError::Unterminated)));
+    }
+
+    #[test]
+    fn comments_and_glyphs_round_trip() {
+        let pgn = "{A short game} 1. e4! {the best move} e5 $1 $14 (1... c5?! {a long comment that has to be wrapped over more than one line of the movetext}) 2. Qh5?? ; bad
+        Nc6 (2... ({no} Ke7 ?? 3. Qxe5#) g6) *";
+        let game = read_pgn(pgn).unwrap();
+        let tree = &game.tree;
+        let mainline = tree.mainline();
+        let node = |id: usize| tree.node(id).unwrap();
+
+        assert_eq!(Some("A short game"), node(ROOT).comment.as_deref());
+        assert_eq!(vec![1], node(mainline[0]).nags);
+        assert_eq!(Some("the best move"), node(mainline[0]).comment.as_deref());
+        assert_eq!(vec![1, 14], node(mainline[1]).nags);
+        assert_eq!(Some("bad"), node(mainline[2]).comment.as_deref());
+        assert_eq!(vec![4], node(mainline[2]).nags);
+
+        // the inner variation starts before any move of its own and is dropped, its comment goes with it
+        let g6 = node(node(mainline[2]).children[1]);
+        assert_eq!(("g6", None), (g6.san.as_str(), g6.comment.as_deref()));
+
+        let written = write_tree_pgn(tree, &[]);
+        let movetext = "{A short game} 1. e4! {the best move} 1... e5! $14 (1... c5?! {a long comment that has to be wrapped over more than one line of the movetext}) 2. Qh5?? {bad} 2... Nc6 (2... g6) *";
+
+        assert!(written.split_whitespace().collect::<Vec<_>>().join(" ").ends_with(movetext), "{written}");
+        assert!(written.lines().all(|line| line.len() < 80));
+        assert_eq!(written, write_tree_pgn(&read_pgn(&written).unwrap().tree, &[]));
+    }
+
+    #[test]
+    fn illegal_move() {
+        assert!(matches!(read_pgn("1. e4 e5 2. Ke3"), Err(PgnError::IllegalMove(mv)) if mv == "Ke3"));
+    }
+}
@@ -2,7 +2,7 @@ use serde::{Serialize, Deserialize};
 
 use super::Coord;
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, strum_macros::IntoStaticStr, strum_macros::Display)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, strum_macros::IntoStaticStr, strum_macros::Display)]
 pub enum Color {
     White,
     Black,
@@ -27,6 +27,31 @@ pub enum PieceType {
     King,
 }
 
+impl PieceType {
+    pub fn to_char(&self) -> char {
+        match self {
+            PieceType::Pawn => 'P',
+            PieceType::Rook => 'R',
+            PieceType::Knight => 'N',
+            PieceType::Bishop => 'B',
+            PieceType::Queen => 'Q',
+            PieceType::King => 'K',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'P' => Some(PieceType::Pawn),
+            'R' => Some(PieceType::Rook),
+            'N' => Some(PieceType::Knight),
+            'B' => Some(PieceType::Bishop),
+            'Q' => Some(PieceType::Queen),
+            'K' => Some(PieceType::King),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Piece {
@@ -39,4 +64,36 @@ impl Piece {
     pub fn new(coord: Coord, piece_type: PieceType, color: Color) -> Self {
         Self { coord, piece_type, color }
     }
+
+    pub fn to_fen_char(&self) -> char {
+        let c = self.piece_type.to_char();
+
+        match self.color {
+            Color::White => c,
+            Color::Black => c.to_ascii_lowercase(),
+        }
+    }
+
+    pub fn from_fen_char(c: char, coord: Coord) -> Option<Self> {
+        let color = if c.is_ascii_lowercase() { Color::Black } else { Color::White };
+        let piece_type = PieceType::from_char(c)?;
+
+        return Some(Self::new(coord, piece_type, color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fen_char_round_trip() {
+        for c in "PRNBQKprnbqk".chars() {
+            let piece = Piece::from_fen_char(c, Coord::new('a', 1)).unwrap();
+            assert_eq!(c, piece.to_fen_char());
+        }
+
+        assert_eq!(None, Piece::from_fen_char('x', Coord::new('a', 1)).map(|p| p.piece_type));
+        assert_eq!(Some(PieceType::Knight), PieceType::from_char('n'));
+    }
 }
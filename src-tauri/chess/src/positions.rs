@@ -0,0 +1,167 @@
+use serde::Serialize;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PositionCategory {
+    Opening,
+    Endgame,
+    Tactic,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamedPosition {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub category: PositionCategory,
+    pub description: &'static str,
+    pub fen: &'static str,
+}
+
+const fn position(
+    id: &'static str,
+    name: &'static str,
+    category: PositionCategory,
+    description: &'static str,
+    fen: &'static str,
+) -> NamedPosition {
+    NamedPosition {
+        id,
+        name,
+        category,
+        description,
+        fen,
+    }
+}
+
+pub static POSITIONS: &[NamedPosition] = &[
+    position(
+        "ruy-lopez",
+        "Ruy Lopez",
+        PositionCategory::Opening,
+        "1. e4 e5 2. Nf3 Nc6 3. Bb5, pressuring the knight that defends e5.",
+        "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+    ),
+    position(
+        "italian-game",
+        "Italian Game",
+        PositionCategory::Opening,
+        "1. e4 e5 2. Nf3 Nc6 3. Bc4, aiming the bishop at f7.",
+        "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+    ),
+    position(
+        "sicilian-najdorf",
+        "Sicilian Defence, Najdorf Variation",
+        PositionCategory::Opening,
+        "1. e4 c5 2. Nf3 d6 3. d4 cxd4 4. Nxd4 Nf6 5. Nc3 a6.",
+        "rnbqkb1r/1p2pppp/p2p1n2/8/3NP3/2N5/PPP2PPP/R1BQKB1R w KQkq - 0 6",
+    ),
+    position(
+        "queens-gambit",
+        "Queen's Gambit",
+        PositionCategory::Opening,
+        "1. d4 d5 2. c4, offering the c-pawn for central control.",
+        "rnbqkbnr/ppp1pppp/8/3p4/2PP4/8/PP2PPPP/RNBQKBNR b KQkq - 0 2",
+    ),
+    position(
+        "kings-indian",
+        "King's Indian Defence",
+        PositionCategory::Opening,
+        "1. d4 Nf6 2. c4 g6 3. Nc3 Bg7 4. e4 d6, a hypermodern setup against the centre.",
+        "rnbqk2r/ppp1ppbp/3p1np1/8/2PPP3/2N5/PP3PPP/R1BQKBNR w KQkq - 0 5",
+    ),
+    position(
+        "french-defence",
+        "French Defence",
+        PositionCategory::Opening,
+        "1. e4 e6 2. d4 d5, a solid but cramped defence.",
+        "rnbqkbnr/ppp2ppp/4p3/3p4/3PP3/8/PPP2PPP/RNBQKBNR w KQkq - 0 3",
+    ),
+    position(
+        "lucena",
+        "Lucena Position",
+        PositionCategory::Endgame,
+        "Rook and pawn against rook, won for white by building a bridge with the rook.",
+        "1K1k4/1P6/8/8/8/8/r7/2R5 w - - 0 1",
+    ),
+    position(
+        "philidor",
+        "Philidor Position",
+        PositionCategory::Endgame,
+        "Rook and pawn against rook, held by black keeping the rook on the third rank.",
+        "4k3/8/r7/4PK2/8/8/8/1R6 b - - 0 1",
+    ),
+    position(
+        "vancura",
+        "Vancura Position",
+        PositionCategory::Endgame,
+        "Rook and rook pawn against rook, drawn by attacking the pawn from the side.",
+        "R7/6k1/P4r2/8/8/8/6K1/8 w - - 0 1",
+    ),
+    position(
+        "reti-study",
+        "Reti Study",
+        PositionCategory::Endgame,
+        "White's king catches the pawn or supports its own, drawing an apparently lost position.",
+        "7K/8/k1P5/7p/8/8/8/8 w - - 0 1",
+    ),
+    position(
+        "saavedra",
+        "Saavedra Position",
+        PositionCategory::Endgame,
+        "White wins by underpromoting to a rook to avoid stalemate.",
+        "8/8/1KP5/3r4/8/8/8/k7 w - - 0 1",
+    ),
+    position(
+        "back-rank-mate",
+        "Back Rank Mate",
+        PositionCategory::Tactic,
+        "The king is trapped behind its own pawns, Ra8 is mate.",
+        "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1",
+    ),
+    position(
+        "smothered-mate",
+        "Smothered Mate",
+        PositionCategory::Tactic,
+        "Qg8+ Rxg8 Nf7# mates the king surrounded by its own pieces.",
+        "r5rk/6pp/7N/8/8/1Q6/6PP/6K1 w - - 0 1",
+    ),
+    position(
+        "legals-mate",
+        "Legal's Mate",
+        PositionCategory::Tactic,
+        "Nxe5 gives up the queen, Bxd1 Bxf7+ Ke7 Nd5# mates with the minor pieces.",
+        "rn1qkbnr/ppp2p1p/3p2p1/4p3/2B1P1b1/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 0 5",
+    ),
+    position(
+        "greek-gift",
+        "Greek Gift Sacrifice",
+        PositionCategory::Tactic,
+        "Bxh7+ followed by Ng5+ and Qh5 breaks open the castled king.",
+        "rnbq1rk1/pppn1ppp/4p3/3pP3/1b1P4/2NB1N2/PPP2PPP/R1BQK2R w KQ - 3 7",
+    ),
+];
+
+pub fn find_position(id: &str) -> Option<&'static NamedPosition> {
+    return POSITIONS.iter().find(|position| position.id == id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Board;
+
+    #[test]
+    fn positions_are_valid() {
+        for position in POSITIONS {
+            assert!(Board::from_fen(position.fen).is_ok(), "{} has an invalid fen", position.id);
+        }
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        for position in POSITIONS {
+            assert!(std::ptr::eq(position, find_position(position.id).unwrap()), "{} is not unique", position.id);
+        }
+    }
+}
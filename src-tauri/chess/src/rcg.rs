@@ -0,0 +1,286 @@
+use std::io::{ErrorKind, Read, Write};
+
+use crate::{expand_promotions, get_moves, Board, Coord, FenError, Move, PieceType, Variant};
+
+// every game starts with the magic and the format version, so a file of games is just one game after the other
+pub const MAGIC: [u8; 3] = *b"RCG";
+pub const VERSION: u8 = 1;
+
+const CUSTOM_START: u8 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RcgError {
+    #[error("Not a game in the compact format")]
+    InvalidMagic,
+
+    #[error("Unsupported format version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Unknown variant {0}")]
+    UnknownVariant(u8),
+
+    #[error("The game ends in the middle of a record")]
+    Truncated,
+
+    #[error("The text in the game is not valid UTF-8")]
+    InvalidText,
+
+    #[error("The text '{0}' is too long to be stored")]
+    TooLong(String),
+
+    #[error("The stored move {0:#06x} is illegal")]
+    IllegalMove(u16),
+
+    #[error(transparent)]
+    Fen(#[from] FenError),
+
+    #[error(transparent)]
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for RcgError {
+    fn from(err: std::io::Error) -> Self {
+        return match err.kind() {
+            ErrorKind::UnexpectedEof => RcgError::Truncated,
+            _ => RcgError::Io(err),
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RcgGame {
+    pub tags: Vec<(String, String)>,
+    pub board: Board,
+}
+
+// the header holds the variant, the start position unless it is the one of the variant and the tags,
+// the moves follow as two bytes each, numbers are little endian and text is prefixed with its length
+pub fn write_rcg<W: Write>(writer: &mut W, board: &Board, tags: &[(String, String)]) -> Result<(), RcgError> {
+    let positions = board.history();
+    let start_fen = positions[0].to_fen();
+    let custom_start = start_fen != board.variant().start_fen();
+
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[VERSION, variant_code(board.variant()), if custom_start { CUSTOM_START } else { 0 }])?;
+
+    if custom_start {
+        write_text(writer, &start_fen)?;
+    }
+
+    writer.write_all(&(tags.len().min(u16::MAX as usize) as u16).to_le_bytes())?;
+
+    for (key, value) in tags.iter().take(u16::MAX as usize) {
+        write_text(writer, key)?;
+        write_text(writer, value)?;
+    }
+
+    let moves = positions.iter().skip(1).filter_map(Board::last_move).collect::<Vec<_>>();
+    writer.write_all(&(moves.len() as u32).to_le_bytes())?;
+
+    for mv in moves {
+        writer.write_all(&pack_move(&mv).to_le_bytes())?;
+    }
+
+    return Ok(());
+}
+
+pub fn to_rcg(board: &Board, tags: &[(String, String)]) -> Result<Vec<u8>, RcgError> {
+    let mut bytes = Vec::new();
+    write_rcg(&mut bytes, board, tags)?;
+
+    return Ok(bytes);
+}
+
+pub fn read_rcg(mut bytes: &[u8]) -> Result<RcgGame, RcgError> {
+    return read_game(&mut bytes)?.ok_or(RcgError::Truncated);
+}
+
+// none when the reader is at its end before a new game starts
+fn read_game<R: Read>(reader: &mut R) -> Result<Option<RcgGame>, RcgError> {
+    let mut magic = [0; 3];
+
+    match reader.read(&mut magic[..1])? {
+        0 => return Ok(None),
+        _ => reader.read_exact(&mut magic[1..])?,
+    }
+
+    if magic != MAGIC {
+        return Err(RcgError::InvalidMagic);
+    }
+
+    let [version, variant, flags] = read_array(reader)?;
+
+    if version != VERSION {
+        return Err(RcgError::UnsupportedVersion(version));
+    }
+
+    let variant = variant_from_code(variant).ok_or(RcgError::UnknownVariant(variant))?;
+    let mut board = match flags & CUSTOM_START {
+        0 => Board::new_variant_game(variant),
+        _ => {
+            let mut board = Board::empty();
+            board.set_variant(variant);
+            board.apply_fen(&read_text(reader)?)?;
+            board
+        }
+    };
+
+    let tag_count = u16::from_le_bytes(read_array(reader)?);
+    let mut tags = Vec::with_capacity(tag_count as usize);
+
+    for _ in 0..tag_count {
+        tags.push((read_text(reader)?, read_text(reader)?));
+    }
+
+    let move_count = u32::from_le_bytes(read_array(reader)?);
+
+    for _ in 0..move_count {
+        let packed = u16::from_le_bytes(read_array(reader)?);
+        let mv = unpack_move(&board, packed).ok_or(RcgError::IllegalMove(packed))?;
+
+        board.exec_move(&mv).map_err(|_| RcgError::IllegalMove(packed))?;
+    }
+
+    return Ok(Some(RcgGame { tags, board }));
+}
+
+// reads the games of a file one after the other, a broken game ends the file since the next one cannot be found
+pub struct RcgReader<R> {
+    reader: R,
+    failed: bool,
+}
+
+impl<R: Read> RcgReader<R> {
+    pub fn new(reader: R) -> Self {
+        RcgReader { reader, failed: false }
+    }
+}
+
+impl<R: Read> Iterator for RcgReader<R> {
+    type Item = Result<RcgGame, RcgError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let game = read_game(&mut self.reader);
+        self.failed = game.is_err();
+
+        return game.transpose();
+    }
+}
+
+// the squares take six bits each, the promotion piece the four above them, the flags of the move are taken
+// from the legal move with the same squares when it is read
+fn pack_move(mv: &Move) -> u16 {
+    let promotion = match mv.promotion {
+        true => promotion_code(mv.promote_to),
+        false => 0,
+    };
+
+    return mv.from.offset() as u16 | (mv.to.offset() as u16) << 6 | promotion << 12;
+}
+
+fn unpack_move(board: &Board, packed: u16) -> Option<Move> {
+    let from = Coord::from_offset((packed & 0x3f) as usize);
+    let to = Coord::from_offset((packed >> 6 & 0x3f) as usize);
+    let promotion = packed >> 12;
+
+    return expand_promotions(&get_moves(board.turn(), board))
+        .into_iter()
+        .find(|mv| mv.from == from && mv.to == to && if mv.promotion { promotion_code(mv.promote_to) == promotion } else { promotion == 0 });
+}
+
+fn promotion_code(piece_type: PieceType) -> u16 {
+    return match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+}
+
+fn variant_code(variant: Variant) -> u8 {
+    return match variant {
+        Variant::Standard => 0,
+        Variant::Antichess => 1,
+        Variant::Horde => 2,
+        Variant::RacingKings => 3,
+    };
+}
+
+fn variant_from_code(code: u8) -> Option<Variant> {
+    return match code {
+        0 => Some(Variant::Standard),
+        1 => Some(Variant::Antichess),
+        2 => Some(Variant::Horde),
+        3 => Some(Variant::RacingKings),
+        _ => None,
+    };
+}
+
+fn write_text<W: Write>(writer: &mut W, text: &str) -> Result<(), RcgError> {
+    let length = u16::try_from(text.len()).map_err(|_| RcgError::TooLong(text.chars().take(20).collect()))?;
+
+    writer.write_all(&length.to_le_bytes())?;
+    writer.write_all(text.as_bytes())?;
+
+    return Ok(());
+}
+
+fn read_text<R: Read>(reader: &mut R) -> Result<String, RcgError> {
+    let length = u16::from_le_bytes(read_array(reader)?);
+    let mut bytes = vec![0; length as usize];
+
+    reader.read_exact(&mut bytes)?;
+
+    return String::from_utf8(bytes).map_err(|_| RcgError::InvalidText);
+}
+
+fn read_array<R: Read, const N: usize>(reader: &mut R) -> Result<[u8; N], RcgError> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+
+    return Ok(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::{read_pgn, write_pgn};
+
+    #[test]
+    fn round_trips_games() {
+        let game = read_pgn("[White \"Alice\"]\n[Black \"Bob\"]\n\n1. e4 d5 2. exd5 Qxd5 3. Nc3 Qa5 4. d4 c6 5. Nf3 Nf6 6. Bc4 Bf5 7. O-O e6").unwrap();
+        let mut promoted = Board::from_variant_fen(Variant::Antichess, "8/1P6/8/8/8/8/6p1/8 w - - 0 1").unwrap();
+        let mv = *expand_promotions(&get_moves(promoted.turn(), &promoted)).iter().find(|mv| mv.promote_to == PieceType::Knight).unwrap();
+        promoted.exec_move(&mv).unwrap();
+
+        let bytes = to_rcg(&game.board, &game.tags).unwrap();
+        let read = read_rcg(&bytes).unwrap();
+
+        assert_eq!(game.board, read.board);
+        assert_eq!(game.tags, read.tags);
+        assert!(bytes.len() * 2 < write_pgn(&game.board, &game.tags).len());
+
+        // the header of a game without tags from the start position, then two bytes for every move
+        assert_eq!(12 + 2 * 14, to_rcg(&game.board, &[]).unwrap().len());
+
+        let mut file = Vec::new();
+        write_rcg(&mut file, &promoted, &[]).unwrap();
+        write_rcg(&mut file, &game.board, &game.tags).unwrap();
+
+        let games = RcgReader::new(file.as_slice()).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(2, games.len());
+        assert_eq!(promoted, games[0].board);
+        assert_eq!(game.board, games[1].board);
+
+        assert!(matches!(read_rcg(&bytes[..bytes.len() - 1]), Err(RcgError::Truncated)));
+        assert!(matches!(read_rcg(b"PGN"), Err(RcgError::InvalidMagic)));
+        assert!(matches!(read_rcg(&[b'R', b'C', b'G', 9, 0, 0]), Err(RcgError::UnsupportedVersion(9))));
+    }
+}
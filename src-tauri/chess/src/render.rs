@@ -0,0 +1,166 @@
+use std::fmt::Write;
+
+use crate::{Board, Color, Coord, Piece, PieceType};
+
+const LIGHT_SQUARE: u8 = 180;
+const DARK_SQUARE: u8 = 137;
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Glyphs {
+    #[default]
+    Ascii,
+    Unicode,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderOptions {
+    pub glyphs: Glyphs,
+    pub colored: bool,
+    pub perspective: Color,
+    pub all_edges: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            glyphs: Glyphs::Ascii,
+            colored: false,
+            perspective: Color::White,
+            all_edges: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn unicode() -> Self {
+        Self {
+            glyphs: Glyphs::Unicode,
+            ..Self::default()
+        }
+    }
+}
+
+pub fn render(board: &Board, options: &RenderOptions) -> String {
+    let mut out = String::new();
+
+    let (rows, columns): (Vec<u8>, Vec<u8>) = match options.perspective {
+        Color::White => ((0..8).rev().collect(), (0..8).collect()),
+        Color::Black => ((0..8).collect(), (0..8).rev().collect()),
+    };
+
+    // colored cells are padded on both sides so the background forms a square
+    let files = columns
+        .iter()
+        .map(|column| {
+            let file = (b'A' + column) as char;
+
+            if options.colored {
+                format!(" {file} ")
+            } else {
+                format!(" {file}")
+            }
+        })
+        .collect::<String>();
+
+    let _ = writeln!(out, "  {files}\n");
+
+    for &row in &rows {
+        let _ = write!(out, "{} ", row + 1);
+
+        for &column in &columns {
+            let coord = Coord::from_xy(column, row);
+            let glyph = glyph(board.piece_at(coord), options.glyphs);
+
+            if options.colored {
+                let background = if (row + column) % 2 == 0 { DARK_SQUARE } else { LIGHT_SQUARE };
+                let _ = write!(out, "\x1b[48;5;{background}m\x1b[38;5;16m {glyph} \x1b[0m");
+            } else {
+                let _ = write!(out, " {glyph}");
+            }
+        }
+
+        if options.all_edges {
+            let _ = write!(out, "  {}", row + 1);
+        }
+
+        out.push('\n');
+    }
+
+    if options.all_edges {
+        let _ = writeln!(out, "\n  {files}");
+    }
+
+    return out;
+}
+
+fn glyph(piece: Option<Piece>, glyphs: Glyphs) -> char {
+    let Some(piece) = piece else {
+        return match glyphs {
+            Glyphs::Ascii => '.',
+            Glyphs::Unicode => '·',
+        };
+    };
+
+    if glyphs == Glyphs::Ascii {
+        return piece.to_fen_char();
+    }
+
+    match (piece.color, piece.piece_type) {
+        (Color::White, PieceType::King) => '♔',
+        (Color::White, PieceType::Queen) => '♕',
+        (Color::White, PieceType::Rook) => '♖',
+        (Color::White, PieceType::Bishop) => '♗',
+        (Color::White, PieceType::Knight) => '♘',
+        (Color::White, PieceType::Pawn) => '♙',
+        (Color::Black, PieceType::King) => '♚',
+        (Color::Black, PieceType::Queen) => '♛',
+        (Color::Black, PieceType::Rook) => '♜',
+        (Color::Black, PieceType::Bishop) => '♝',
+        (Color::Black, PieceType::Knight) => '♞',
+        (Color::Black, PieceType::Pawn) => '♟',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_from_both_sides() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q -").unwrap();
+
+        let white = render(&board, &RenderOptions::new());
+        let black = render(
+            &board,
+            &RenderOptions {
+                perspective: Color::Black,
+                ..RenderOptions::new()
+            },
+        );
+
+        assert!(white.starts_with("   A B C D E F G H\n\n8  . . . . k . . .\n"));
+        assert!(white.ends_with("1  R . . . K . . .\n"));
+
+        assert!(black.starts_with("   H G F E D C B A\n\n1  . . . K . . . R\n"));
+        assert!(black.ends_with("8  . . . k . . . .\n"));
+    }
+
+    #[test]
+    fn unicode_with_all_edges() {
+        let board = Board::new_game();
+        let options = RenderOptions {
+            all_edges: true,
+            ..RenderOptions::unicode()
+        };
+
+        let rendered = render(&board, &options);
+
+        assert!(rendered.contains("8  ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜  8\n"));
+        assert!(rendered.contains("4  · · · · · · · ·  4\n"));
+        assert!(rendered.ends_with("\n   A B C D E F G H\n"));
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Board, Move};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ResyncError {
+    #[error("The peer belongs to session {theirs}, not {ours}")]
+    WrongSession { ours: u64, theirs: u64 },
+
+    #[error("The peer's game differs from ours at ply {0}")]
+    Diverged(usize),
+
+    #[error("The peer sent the illegal move {0}")]
+    IllegalMove(Move),
+}
+
+// what a peer sends after reconnecting, the game it belongs to and the last position it knows
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPoint {
+    pub session: u64,
+    pub ply: usize,
+    pub hash: u64,
+}
+
+impl SyncPoint {
+    pub fn new(session: u64, board: &Board) -> Self {
+        SyncPoint {
+            session,
+            ply: board.history().len() - 1,
+            hash: board.hash(),
+        }
+    }
+}
+
+// the moves that follow the ply of the peer's sync point, empty when the peer is not behind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resync {
+    pub session: u64,
+    pub ply: usize,
+    pub moves: Vec<Move>,
+}
+
+// both peers send their sync point, each one answers with the moves the other one is missing
+pub fn missing_moves(session: u64, board: &Board, theirs: SyncPoint) -> Result<Resync, ResyncError> {
+    check_session(session, theirs.session)?;
+
+    let positions = board.history();
+    let mut resync = Resync { session, ply: theirs.ply, moves: Vec::new() };
+
+    let Some(position) = positions.get(theirs.ply) else {
+        return Ok(resync);
+    };
+
+    if position.hash() != theirs.hash {
+        return Err(ResyncError::Diverged(theirs.ply));
+    }
+
+    resync.moves = positions[theirs.ply + 1..].iter().filter_map(Board::last_move).collect();
+
+    return Ok(resync);
+}
+
+// moves the board already has are checked against the peer's, the rest are played, returns how many were played
+pub fn apply_resync(session: u64, board: &mut Board, resync: &Resync) -> Result<usize, ResyncError> {
+    check_session(session, resync.session)?;
+
+    let positions = board.history();
+
+    if resync.ply >= positions.len() {
+        return Err(ResyncError::Diverged(positions.len()));
+    }
+
+    let mut played = 0;
+
+    for (index, mv) in resync.moves.iter().enumerate() {
+        let ply = resync.ply + index + 1;
+
+        match positions.get(ply) {
+            Some(position) if position.last_move() != Some(*mv) => return Err(ResyncError::Diverged(ply)),
+            Some(_) => {}
+            None => {
+                board.exec_move(mv).map_err(|_| ResyncError::IllegalMove(*mv))?;
+                played += 1;
+            }
+        }
+    }
+
+    return Ok(played);
+}
+
+fn check_session(ours: u64, theirs: u64) -> Result<(), ResyncError> {
+    if ours != theirs {
+        return Err(ResyncError::WrongSession { ours, theirs });
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::read_pgn;
+
+    #[test]
+    fn replays_missing_moves_both_ways() {
+        let ahead = read_pgn("1. e4 e5 2. Nf3 Nc6 3. Bb5").unwrap().board;
+        let mut behind = read_pgn("1. e4 e5").unwrap().board;
+
+        // the peer that is behind gets the moves it missed, the other one gets nothing
+        let resync = missing_moves(7, &ahead, SyncPoint::new(7, &behind)).unwrap();
+        let nothing = missing_moves(7, &behind, SyncPoint::new(7, &ahead)).unwrap();
+
+        assert_eq!(3, resync.moves.len());
+        assert!(nothing.moves.is_empty());
+
+        assert_eq!(Ok(3), apply_resync(7, &mut behind, &resync));
+        assert_eq!(ahead.to_fen(), behind.to_fen());
+
+        // a repeated answer is already known and changes nothing
+        assert_eq!(Ok(0), apply_resync(7, &mut behind, &resync));
+    }
+
+    #[test]
+    fn rejects_other_games() {
+        let board = read_pgn("1. e4 e5").unwrap().board;
+        let mut other = read_pgn("1. d4 d5").unwrap().board;
+
+        assert_eq!(Err(ResyncError::WrongSession { ours: 1, theirs: 2 }), missing_moves(1, &board, SyncPoint::new(2, &board)).map(|_| ()));
+        assert_eq!(Err(ResyncError::Diverged(2)), missing_moves(1, &board, SyncPoint::new(1, &other)).map(|_| ()));
+
+        let resync = missing_moves(1, &board, SyncPoint::new(1, &Board::new_game())).unwrap();
+
+        assert_eq!(Err(ResyncError::Diverged(1)), apply_resync(1, &mut other, &resync));
+    }
+}
@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{engine::Engine, Board, Color, Move};
+
+// the centipawns a move gives away compared to the best one, for each class
+const INACCURACY: i32 = 50;
+const MISTAKE: i32 = 100;
+const BLUNDER: i32 = 300;
+
+// guessing the move that was played scores full, any other move scores by how much it gives away compared to it
+pub const EXACT_GUESS_POINTS: u32 = 3;
+const GOOD_GUESS_POINTS: u32 = 2;
+const INACCURATE_GUESS_POINTS: u32 = 1;
+
+// beyond this a position is won or lost anyway, so a slower mate is not counted as a mistake
+const SCORE_CAP: i32 = 1000;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum ReviewError {
+    #[error("The move {0} is illegal in the puzzle position")]
+    IllegalMove(Move),
+
+    #[error("The puzzle position '{0}' is invalid")]
+    InvalidPosition(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MoveClass {
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveClass {
+    pub fn from_loss(loss: i32) -> Self {
+        return match loss {
+            loss if loss >= BLUNDER => MoveClass::Blunder,
+            loss if loss >= MISTAKE => MoveClass::Mistake,
+            loss if loss >= INACCURACY => MoveClass::Inaccuracy,
+            _ => MoveClass::Good,
+        };
+    }
+}
+
+// scores are from the side that played the move, before it and after it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveReview {
+    pub ply: usize,
+    pub color: Color,
+    pub played: Move,
+    pub best: Option<Move>,
+    pub score_before: i32,
+    pub score_after: i32,
+    pub loss: i32,
+    pub class: MoveClass,
+}
+
+// a position where a move was a mistake, the task is to find a better one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MistakePuzzle {
+    pub ply: usize,
+    pub fen: String,
+    pub played: Move,
+    pub best: Move,
+    pub loss: i32,
+}
+
+// every position of the game is searched once, the loss of a move is the score before it minus the score after it
+pub fn review_game(engine: &Engine, board: &Board, depth: u8) -> Vec<MoveReview> {
+    let positions = board.history();
+    let results = engine.evaluate_batch(&positions, depth);
+
+    return positions
+        .windows(2)
+        .zip(results.windows(2))
+        .enumerate()
+        .filter_map(|(ply, (positions, results))| {
+            let played = positions[1].last_move()?;
+            let score_before = results[0].score.clamp(-SCORE_CAP, SCORE_CAP);
+            let score_after = (-results[1].score).clamp(-SCORE_CAP, SCORE_CAP);
+            let loss = (score_before - score_after).max(0);
+
+            return Some(MoveReview {
+                ply: ply + 1,
+                color: positions[0].turn(),
+                played,
+                best: results[0].best_move,
+                score_before,
+                score_after,
+                loss,
+                class: MoveClass::from_loss(loss),
+            });
+        })
+        .collect();
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuessScore {
+    pub exact: bool,
+    pub loss: i32,
+    pub points: u32,
+}
+
+// the loss is measured against the move that was played, so a guess that the engine likes better loses nothing
+pub fn score_guess(engine: &Engine, board: &Board, guess: &Move, actual: &Move, depth: u8) -> Result<GuessScore, ReviewError> {
+    if guess == actual {
+        return Ok(GuessScore {
+            exact: true,
+            loss: 0,
+            points: EXACT_GUESS_POINTS,
+        });
+    }
+
+    let (mut guessed, mut played) = (board.clone(), board.clone());
+    guessed.exec_move(guess).map_err(|_| ReviewError::IllegalMove(*guess))?;
+    played.exec_move(actual).map_err(|_| ReviewError::IllegalMove(*actual))?;
+
+    let results = engine.evaluate_batch(&[guessed, played], depth);
+    let loss = ((-results[1].score).clamp(-SCORE_CAP, SCORE_CAP) - (-results[0].score).clamp(-SCORE_CAP, SCORE_CAP)).max(0);
+
+    let points = match MoveClass::from_loss(loss) {
+        MoveClass::Good => GOOD_GUESS_POINTS,
+        MoveClass::Inaccuracy => INACCURATE_GUESS_POINTS,
+        _ => 0,
+    };
+
+    return Ok(GuessScore { exact: false, loss, points });
+}
+
+// the mistakes and blunders of one side, the review is the one of the same game
+pub fn mistake_puzzles(board: &Board, reviews: &[MoveReview], color: Color) -> Vec<MistakePuzzle> {
+    let positions = board.history();
+
+    return reviews
+        .iter()
+        .filter(|review| review.color == color && review.class >= MoveClass::Mistake)
+        .filter_map(|review| {
+            let best = review.best.filter(|best| *best != review.played)?;
+
+            return Some(MistakePuzzle {
+                ply: review.ply,
+                fen: positions.get(review.ply - 1)?.to_fen(),
+                played: review.played,
+                best,
+                loss: review.loss,
+            });
+        })
+        .collect();
+}
+
+// any move that would not count as an inaccuracy solves the puzzle, not just the engine's choice
+pub fn judge_attempt(engine: &Engine, puzzle: &MistakePuzzle, mv: &Move, depth: u8) -> Result<bool, ReviewError> {
+    if *mv == puzzle.best {
+        return Ok(true);
+    }
+
+    let board = Board::from_fen(&puzzle.fen).map_err(|_| ReviewError::InvalidPosition(puzzle.fen.clone()))?;
+    let mut after = board.clone();
+    after.exec_move(mv).map_err(|_| ReviewError::IllegalMove(*mv))?;
+
+    let results = engine.evaluate_batch(&[board, after], depth);
+    let best = results[0].score.clamp(-SCORE_CAP, SCORE_CAP);
+    let reached = (-results[1].score).clamp(-SCORE_CAP, SCORE_CAP);
+
+    return Ok(MoveClass::from_loss(best - reached) == MoveClass::Good);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pgn::read_pgn;
+
+    #[test]
+    fn finds_mistakes_and_judges_attempts() {
+        // 2. Qg4 puts the queen where the bishop takes it, and 2... a6 misses that
+        let board = read_pgn("1. e4 d5 2. Qg4 a6").unwrap().board;
+        let engine = Engine::new();
+
+        let reviews = review_game(&engine, &board, 3);
+
+        assert_eq!(4, reviews.len());
+        assert_eq!((Color::White, MoveClass::Blunder), (reviews[2].color, reviews[2].class));
+        assert_eq!((Color::Black, MoveClass::Blunder), (reviews[3].color, reviews[3].class));
+
+        let puzzles = mistake_puzzles(&board, &reviews, Color::Black);
+        let puzzle = puzzles.iter().find(|puzzle| puzzle.played.to_string() == "a7a6").unwrap();
+
+        assert_eq!("c8g4", puzzle.best.to_string());
+        assert_eq!(Ok(true), judge_attempt(&engine, puzzle, &puzzle.best, 3));
+        assert_eq!(Ok(false), judge_attempt(&engine, puzzle, &puzzle.played, 3));
+        assert!(matches!(judge_attempt(&engine, puzzle, &reviews[0].played, 3), Err(ReviewError::IllegalMove(_))));
+    }
+
+    #[test]
+    fn scores_guesses() {
+        let board = read_pgn("1. e4 d5").unwrap().board;
+        let engine = Engine::new();
+        let mv = |uci: &str| *crate::get_moves(board.turn(), &board).iter().find(|mv| mv.to_string() == uci).unwrap();
+
+        let exact = score_guess(&engine, &board, &mv("e4d5"), &mv("e4d5"), 3).unwrap();
+        let blunder = score_guess(&engine, &board, &mv("d1g4"), &mv("e4d5"), 3).unwrap();
+
+        assert_eq!((true, EXACT_GUESS_POINTS), (exact.exact, exact.points));
+        assert_eq!((false, 0), (blunder.exact, blunder.points));
+        assert!(blunder.loss >= BLUNDER);
+    }
+}
@@ -0,0 +1,396 @@
+use crate::{moves, Board, Coord, Move, PieceType};
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum MoveInputError {
+    #[error("'{0}' is not a move")]
+    Unreadable(String),
+
+    #[error("'{input}' could be {}", .candidates.join(" or "))]
+    Ambiguous { input: String, candidates: Vec<String> },
+
+    #[error("'{0}' is not a legal move in this position")]
+    Illegal(String),
+}
+
+// a typed move, every part but the destination may be left out
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct MoveInput {
+    piece: Option<PieceType>,
+    from_column: Option<char>,
+    from_row: Option<u8>,
+    to: Coord,
+    promote_to: Option<PieceType>,
+}
+
+pub fn to_san(board: &Board, mv: &Move) -> String {
+    let mut san = san_without_suffix(board, mv, &legal_moves(board));
+
+    let mut after = board.clone();
+
+    if after.exec_move(mv).is_ok() && after.turning_side().checked() {
+        san.push(if after.winner() == Some(board.turn()) { '#' } else { '+' });
+    }
+
+    return san;
+}
+
+// accepts standard algebraic notation as well as coordinate notation like e2e4 or e7e8q
+pub fn parse_san(board: &Board, san: &str) -> Option<Move> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']).replace('0', "O");
+    let legal_moves = legal_moves(board);
+
+    return legal_moves
+        .iter()
+        .find(|mv| san_without_suffix(board, mv, &legal_moves) == san || mv.to_string() == san)
+        .copied();
+}
+
+// typed moves in standard algebraic, long algebraic like Ng1-f3 or e7xd8=Q, or coordinate notation,
+// a promotion without a piece becomes a queen
+pub fn parse_move_input(board: &Board, text: &str) -> Result<Move, MoveInputError> {
+    let text = text.trim();
+    let cleaned = text.trim_end_matches(['+', '#', '!', '?']).trim_end_matches("e.p.").trim();
+    let legal_moves = legal_moves(board);
+
+    let candidates = match cleaned.to_uppercase().replace('0', "O").as_str() {
+        "O-O" | "OO" => legal_moves.iter().filter(|mv| mv.castling && mv.to.column() == 'g').copied().collect(),
+        "O-O-O" | "OOO" => legal_moves.iter().filter(|mv| mv.castling && mv.to.column() == 'c').copied().collect(),
+        _ => {
+            let input = read_move_input(cleaned).ok_or_else(|| MoveInputError::Unreadable(text.to_string()))?;
+            legal_moves.iter().filter(|mv| input_matches(board, &input, mv)).copied().collect::<Vec<_>>()
+        }
+    };
+
+    return single_candidate(board, text, candidates);
+}
+
+// spoken phrases like "knight f3", "pawn takes e5", "rook a to d1", "e8 promote to knight" or "castle kingside",
+// ranks may be said as words and a square may be split like "e four", a piece named after a square is the promotion
+pub fn parse_spoken_move(board: &Board, phrase: &str) -> Result<Move, MoveInputError> {
+    let unreadable = || MoveInputError::Unreadable(phrase.trim().to_string());
+    let words = spoken_words(phrase);
+    let legal_moves = legal_moves(board);
+
+    if words.iter().any(|word| matches!(word.as_str(), "castle" | "castles" | "castling")) {
+        let column = match words.iter().find_map(|word| match word.as_str() {
+            "kingside" | "short" | "king" => Some('g'),
+            "queenside" | "long" | "queen" => Some('c'),
+            _ => None,
+        }) {
+            Some(column) => column,
+            None => return Err(unreadable()),
+        };
+
+        let candidates = legal_moves.iter().filter(|mv| mv.castling && mv.to.column() == column).copied().collect();
+        return single_candidate(board, phrase.trim(), candidates);
+    }
+
+    let (mut piece, mut promote_to, mut capture) = (None, None, false);
+    let (mut from_column, mut squares) = (None, Vec::new());
+
+    for word in &words {
+        let spoken = spoken_piece(word);
+
+        match word.as_str() {
+            "takes" | "take" | "captures" | "capture" | "x" => capture = true,
+            "to" | "on" | "the" | "square" | "promote" | "promotes" | "promotion" | "equals" => (),
+            _ if spoken.is_some() && squares.is_empty() && piece.is_none() => piece = spoken,
+            _ if spoken.is_some() && !squares.is_empty() && promote_to.is_none() => promote_to = spoken,
+            "a" | "b" | "c" | "d" | "e" | "f" | "g" | "h" if squares.is_empty() && from_column.is_none() => from_column = word.chars().next(),
+            _ => match Coord::from_str(word) {
+                Some(coord) if coord.row() >= 1 => squares.push(coord),
+                _ => return Err(unreadable()),
+            },
+        }
+    }
+
+    let (from, to) = match squares.as_slice() {
+        [to] => (None, *to),
+        [from, to] => (Some(*from), *to),
+        _ => return Err(unreadable()),
+    };
+
+    let input = MoveInput {
+        piece: piece.or(from.map_or(Some(PieceType::Pawn), |_| None)),
+        from_column: from.map(|from| from.column()).or(from_column),
+        from_row: from.map(|from| from.row()),
+        to,
+        promote_to,
+    };
+
+    let candidates = legal_moves
+        .iter()
+        .filter(|mv| input_matches(board, &input, mv) && (!capture || mv.en_passant || board.opponent_side().all().is_set(mv.to)))
+        .copied()
+        .collect();
+
+    return single_candidate(board, phrase.trim(), candidates);
+}
+
+fn spoken_words(phrase: &str) -> Vec<String> {
+    let mut words = Vec::<String>::new();
+
+    for word in phrase.to_lowercase().split(|c: char| c.is_whitespace() || matches!(c, ',' | '.' | '-' | '!' | '?')) {
+        let word = match word {
+            "one" => "1",
+            "two" => "2",
+            "three" => "3",
+            "four" | "for" => "4",
+            "five" => "5",
+            "six" => "6",
+            "seven" => "7",
+            "eight" => "8",
+            "side" => continue,
+            word => word,
+        };
+
+        // a rank said on its own belongs to the file before it
+        match words.last_mut() {
+            Some(last) if last.len() == 1 && word.len() == 1 && ('1'..='8').contains(&word.chars().next().unwrap_or(' ')) => last.push_str(word),
+            _ if !word.is_empty() => words.push(word.to_string()),
+            _ => (),
+        }
+    }
+
+    return words;
+}
+
+fn spoken_piece(word: &str) -> Option<PieceType> {
+    return match word {
+        "king" => Some(PieceType::King),
+        "queen" => Some(PieceType::Queen),
+        "rook" => Some(PieceType::Rook),
+        "bishop" => Some(PieceType::Bishop),
+        "knight" | "night" | "horse" => Some(PieceType::Knight),
+        "pawn" => Some(PieceType::Pawn),
+        _ => None,
+    };
+}
+
+fn single_candidate(board: &Board, input: &str, candidates: Vec<Move>) -> Result<Move, MoveInputError> {
+    return match candidates.as_slice() {
+        [] => Err(MoveInputError::Illegal(input.to_string())),
+        [mv] => Ok(*mv),
+        _ => Err(MoveInputError::Ambiguous {
+            input: input.to_string(),
+            candidates: candidates.iter().map(|mv| to_san(board, mv)).collect(),
+        }),
+    };
+}
+
+fn read_move_input(text: &str) -> Option<MoveInput> {
+    let mut chars = text.chars().filter(|c| !matches!(c, 'x' | 'X' | '-' | ':' | '=')).collect::<Vec<_>>();
+
+    // pieces are upper case only, a lower case b is the b-file
+    let piece = match chars.first() {
+        Some(c) if "KQRBN".contains(*c) => PieceType::from_char(chars.remove(0)),
+        _ => None,
+    };
+
+    let promote_to = match chars.as_slice() {
+        [.., row, promotion] if row.is_ascii_digit() && "QRBNqrbn".contains(*promotion) => PieceType::from_char(chars.pop()?),
+        _ => None,
+    };
+
+    if !(2..=4).contains(&chars.len()) || !('1'..='8').contains(chars.last()?) {
+        return None;
+    }
+
+    let to = Coord::from_str(&chars[chars.len() - 2..].iter().collect::<String>())?;
+    let (mut from_column, mut from_row) = (None, None);
+
+    for c in &chars[..chars.len() - 2] {
+        match c {
+            'a'..='h' if from_column.is_none() && from_row.is_none() => from_column = Some(*c),
+            '1'..='8' if from_row.is_none() => from_row = Some(c.to_digit(10)? as u8),
+            _ => return None,
+        }
+    }
+
+    return Some(MoveInput {
+        piece,
+        from_column,
+        from_row,
+        to,
+        promote_to,
+    });
+}
+
+// without a piece letter a move is a pawn move, unless the whole origin square is given
+fn input_matches(board: &Board, input: &MoveInput, mv: &Move) -> bool {
+    let piece = board.lookup(mv.from);
+    let piece_matches = match input.piece {
+        Some(piece_type) => piece == Some(piece_type),
+        None if input.from_column.is_some() && input.from_row.is_some() => true,
+        None => piece == Some(PieceType::Pawn),
+    };
+    let promotion_matches = match mv.promotion {
+        true => mv.promote_to == input.promote_to.unwrap_or(PieceType::Queen),
+        false => input.promote_to.is_none(),
+    };
+
+    return mv.to == input.to
+        && piece_matches
+        && promotion_matches
+        && input.from_column.is_none_or(|column| mv.from.column() == column)
+        && input.from_row.is_none_or(|row| mv.from.row() == row);
+}
+
+// every promotion piece is a separate move in notation
+fn legal_moves(board: &Board) -> Vec<Move> {
+    return moves::expand_promotions(&moves::get_moves(board.turn(), board));
+}
+
+fn san_without_suffix(board: &Board, mv: &Move, legal_moves: &[Move]) -> String {
+    if mv.castling {
+        return if mv.to.column() == 'g' { "O-O".to_string() } else { "O-O-O".to_string() };
+    }
+
+    let piece_type = board.lookup(mv.from).unwrap_or(PieceType::Pawn);
+    let is_capture = mv.en_passant || board.opponent_side().all().is_set(mv.to);
+
+    let mut san = String::new();
+
+    if piece_type == PieceType::Pawn {
+        if is_capture {
+            san.push(mv.from.column());
+        }
+    } else {
+        san.push(piece_type.to_char());
+        san.push_str(&disambiguation(board, mv, piece_type, legal_moves));
+    }
+
+    if is_capture {
+        san.push('x');
+    }
+
+    san.push_str(&mv.to.to_string());
+
+    if mv.promotion {
+        san.push('=');
+        san.push(mv.promote_to.to_char());
+    }
+
+    return san;
+}
+
+fn disambiguation(board: &Board, mv: &Move, piece_type: PieceType, legal_moves: &[Move]) -> String {
+    let others = legal_moves
+        .iter()
+        .filter(|other| other.to == mv.to && other.from != mv.from && board.lookup(other.from) == Some(piece_type))
+        .collect::<Vec<_>>();
+
+    if others.is_empty() {
+        return String::new();
+    }
+
+    if others.iter().all(|other| other.from.column() != mv.from.column()) {
+        return mv.from.column().to_string();
+    }
+
+    if others.iter().all(|other| other.from.row() != mv.from.row()) {
+        return mv.from.row().to_string();
+    }
+
+    return mv.from.to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coord;
+
+    #[test]
+    fn pieces_and_pawns() {
+        let board = Board::new_game();
+
+        assert_eq!("e4", to_san(&board, &Move::new(Coord::new('e', 2), Coord::new('e', 4))));
+        assert_eq!("Nf3", to_san(&board, &Move::new(Coord::new('g', 1), Coord::new('f', 3))));
+        assert_eq!(Some(Move::new(Coord::new('g', 1), Coord::new('f', 3))), parse_san(&board, "Nf3"));
+        assert_eq!(Some(Move::new(Coord::new('e', 2), Coord::new('e', 4))), parse_san(&board, "e2e4"));
+        assert_eq!(None, parse_san(&board, "e5"));
+    }
+
+    #[test]
+    fn disambiguation_and_castling() {
+        let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq -").unwrap();
+
+        assert_eq!("O-O", to_san(&board, &Move::castling(Coord::new('e', 1), Coord::new('g', 1))));
+        assert_eq!("O-O-O", to_san(&board, &Move::castling(Coord::new('e', 1), Coord::new('c', 1))));
+        assert_eq!("Rb1", to_san(&board, &Move::new(Coord::new('a', 1), Coord::new('b', 1))));
+        assert_eq!("Qxf6", to_san(&board, &Move::new(Coord::new('f', 3), Coord::new('f', 6))));
+        assert_eq!(Some(Move::castling(Coord::new('e', 1), Coord::new('c', 1))), parse_san(&board, "0-0-0"));
+
+        let board = Board::from_fen("R7/8/7k/8/8/8/8/R4RK1 w - -").unwrap();
+
+        assert_eq!("Rad1", to_san(&board, &Move::new(Coord::new('a', 1), Coord::new('d', 1))));
+        assert_eq!("R1a4", to_san(&board, &Move::new(Coord::new('a', 1), Coord::new('a', 4))));
+        assert_eq!(Some(Move::new(Coord::new('f', 1), Coord::new('d', 1))), parse_san(&board, "Rfd1"));
+    }
+
+    #[test]
+    fn promotion_and_mate() {
+        let board = Board::from_fen("8/1P4k1/8/8/8/8/8/K7 w - -").unwrap();
+        let mut underpromotion = Move::promotion(Coord::new('b', 7), Coord::new('b', 8));
+        underpromotion.promote_to = PieceType::Knight;
+
+        assert_eq!("b8=Q", to_san(&board, &Move::promotion(Coord::new('b', 7), Coord::new('b', 8))));
+        assert_eq!(Some(underpromotion), parse_san(&board, "b8=N"));
+
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - -").unwrap();
+
+        assert_eq!("Ra8#", to_san(&board, &Move::new(Coord::new('a', 1), Coord::new('a', 8))));
+    }
+
+    #[test]
+    fn typed_moves() {
+        let board = Board::new_game();
+        let knight = Move::new(Coord::new('g', 1), Coord::new('f', 3));
+
+        for text in ["Nf3", "Ng1f3", "Ng1-f3", "g1f3", "g1-f3 ", "Nf3+"] {
+            assert_eq!(Ok(knight), parse_move_input(&board, text), "{text}");
+        }
+
+        assert_eq!(Err(MoveInputError::Illegal("e5".to_string())), parse_move_input(&board, "e5"));
+        assert_eq!(Err(MoveInputError::Unreadable("hello".to_string())), parse_move_input(&board, "hello"));
+
+        let board = Board::from_fen("R7/8/7k/8/8/8/8/R4RK1 w - -").unwrap();
+        let ambiguous = parse_move_input(&board, "Rd1");
+
+        assert_eq!(Err(MoveInputError::Ambiguous { input: "Rd1".to_string(), candidates: vec!["Rad1".to_string(), "Rfd1".to_string()] }), ambiguous);
+        assert_eq!("'Rd1' could be Rad1 or Rfd1", ambiguous.unwrap_err().to_string());
+        assert_eq!(Ok(Move::new(Coord::new('a', 1), Coord::new('a', 4))), parse_move_input(&board, "R1a4"));
+
+        let board = Board::from_fen("r3k3/1P4p1/8/8/8/8/8/4K2R w K -").unwrap();
+        let mut underpromotion = Move::promotion(Coord::new('b', 7), Coord::new('a', 8));
+        underpromotion.promote_to = PieceType::Knight;
+
+        assert_eq!(Ok(underpromotion), parse_move_input(&board, "bxa8=N"));
+        assert_eq!(Ok(underpromotion), parse_move_input(&board, "b7a8n"));
+        assert_eq!(Ok(Move::promotion(Coord::new('b', 7), Coord::new('b', 8))), parse_move_input(&board, "b8"));
+        assert_eq!(Ok(Move::castling(Coord::new('e', 1), Coord::new('g', 1))), parse_move_input(&board, "0-0"));
+        assert_eq!(Err(MoveInputError::Illegal("O-O-O".to_string())), parse_move_input(&board, "O-O-O"));
+    }
+
+    #[test]
+    fn spoken_moves() {
+        let board = Board::from_fen("r3k3/1P4p1/8/3p4/4P3/5N2/8/R3K2R w KQ -").unwrap();
+        let spoken = |phrase: &str| parse_spoken_move(&board, phrase).map(|mv| to_san(&board, &mv));
+
+        assert_eq!(Ok("Nd4".to_string()), spoken("knight d4"));
+        assert_eq!(Ok("exd5".to_string()), spoken("pawn takes d5"));
+        assert_eq!(Ok("e5".to_string()), spoken("Pawn to e five"));
+        assert_eq!(Ok("O-O".to_string()), spoken("castle king side"));
+        assert_eq!(Ok("O-O-O".to_string()), spoken("castles queenside"));
+        assert_eq!(Ok("bxa8=N".to_string()), spoken("b takes a8 knight"));
+        assert_eq!(Ok("b8=Q+".to_string()), spoken("b8 promote to queen"));
+        assert_eq!(Ok("Rf1".to_string()), spoken("rook h1 f1"));
+        assert_eq!(Err(MoveInputError::Illegal("knight takes d4".to_string())), spoken("knight takes d4"));
+        assert_eq!(Err(MoveInputError::Unreadable("knight jumps".to_string())), spoken("knight jumps"));
+
+        let board = Board::from_fen("R7/8/7k/8/8/8/8/R4RK1 w - -").unwrap();
+
+        assert!(matches!(parse_spoken_move(&board, "rook d1"), Err(MoveInputError::Ambiguous { .. })));
+        assert_eq!(Ok(Move::new(Coord::new('a', 1), Coord::new('d', 1))), parse_spoken_move(&board, "rook a to d1"));
+    }
+}
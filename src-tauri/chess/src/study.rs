@@ -0,0 +1,158 @@
+use std::io::BufRead;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    pgn::{self, PgnError, PgnGame, PgnReader},
+    tree::MoveTree,
+    Board,
+};
+
+// the tags that tie a game to its study, as lichess writes them
+const STUDY_TAGS: [&str; 2] = ["StudyName", "ChapterName"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum StudyError {
+    #[error("There is no chapter {0}")]
+    UnknownChapter(usize),
+
+    #[error("Chapter {index} could not be read: {source}")]
+    InvalidChapter { index: usize, source: PgnError },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chapter {
+    pub name: String,
+    // the tags besides the study ones, like the players of an annotated game
+    pub tags: Vec<(String, String)>,
+    pub tree: MoveTree,
+}
+
+impl Chapter {
+    pub fn new(name: &str, start: &Board) -> Self {
+        Chapter {
+            name: name.to_string(),
+            tags: Vec::new(),
+            tree: MoveTree::new(start),
+        }
+    }
+
+    fn from_game(index: usize, game: PgnGame) -> Self {
+        let name = ["ChapterName", "Event"]
+            .iter()
+            .find_map(|tag| game.tag(tag).filter(|name| *name != "?"))
+            .map_or_else(|| format!("Chapter {}", index + 1), str::to_string);
+
+        Chapter {
+            name,
+            tags: game.tags.into_iter().filter(|(key, _)| !STUDY_TAGS.contains(&key.as_str())).collect(),
+            tree: game.tree,
+        }
+    }
+}
+
+// a study is kept as multi-game pgn, one game per chapter, so a saved study opens in other tools as well
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Study {
+    pub name: String,
+    pub chapters: Vec<Chapter>,
+}
+
+impl Study {
+    pub fn new(name: &str) -> Self {
+        Study {
+            name: name.to_string(),
+            chapters: Vec::new(),
+        }
+    }
+
+    // returns the index of the new chapter
+    pub fn add_chapter(&mut self, chapter: Chapter) -> usize {
+        self.chapters.push(chapter);
+        return self.chapters.len() - 1;
+    }
+
+    pub fn chapter(&self, index: usize) -> Result<&Chapter, StudyError> {
+        return self.chapters.get(index).ok_or(StudyError::UnknownChapter(index));
+    }
+
+    pub fn chapter_mut(&mut self, index: usize) -> Result<&mut Chapter, StudyError> {
+        return self.chapters.get_mut(index).ok_or(StudyError::UnknownChapter(index));
+    }
+
+    pub fn remove_chapter(&mut self, index: usize) -> Result<Chapter, StudyError> {
+        self.chapter(index)?;
+        return Ok(self.chapters.remove(index));
+    }
+
+    // the study name is taken from the first chapter that has one
+    pub fn read_pgn<R: BufRead>(reader: R) -> Result<Self, StudyError> {
+        let mut study = Study::new("Study");
+        let mut named = false;
+
+        for (index, game) in PgnReader::new(reader).enumerate() {
+            let game = game.map_err(|source| StudyError::InvalidChapter { index, source })?;
+
+            if let Some(name) = game.tag("StudyName").filter(|_| !named) {
+                study.name = name.to_string();
+                named = true;
+            }
+
+            study.chapters.push(Chapter::from_game(index, game));
+        }
+
+        return Ok(study);
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let games = self.chapters.iter().map(|chapter| {
+            let mut tags = chapter.tags.clone();
+
+            if !tags.iter().any(|(key, _)| key == "Event") {
+                tags.push(("Event".to_string(), format!("{}: {}", self.name, chapter.name)));
+            }
+
+            tags.push(("StudyName".to_string(), self.name.clone()));
+            tags.push(("ChapterName".to_string(), chapter.name.clone()));
+
+            return pgn::write_tree_pgn(&chapter.tree, &tags);
+        });
+
+        return games.collect::<Vec<_>>().join("\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chapters_round_trip_through_pgn() {
+        let mut study = Study::new("Endgames");
+        let mut rook_ending = Chapter::new("Lucena", &Board::from_fen("1K1k4/1P6/8/8/8/8/r7/2R5 w - - 0 1").unwrap());
+        rook_ending.tree.set_comment(0, Some("build a bridge".to_string())).unwrap();
+
+        let opening = pgn::read_pgn("[White \"Morphy\"]\n\n1. e4 e5 (1... c5 $2) 2. Nf3 {developing} *").unwrap();
+
+        study.add_chapter(rook_ending);
+        study.add_chapter(Chapter::from_game(1, opening));
+
+        let pgn = study.to_pgn();
+        let read = Study::read_pgn(pgn.as_bytes()).unwrap();
+
+        assert_eq!("Endgames", read.name);
+        assert_eq!(vec!["Lucena", "Chapter 2"], read.chapters.iter().map(|chapter| chapter.name.as_str()).collect::<Vec<_>>());
+        assert_eq!(Some("build a bridge"), read.chapter(0).unwrap().tree.node(0).unwrap().comment.as_deref());
+        assert_eq!("1K1k4/1P6/8/8/8/8/r7/2R5 w - - 0 1", read.chapter(0).unwrap().tree.start().to_fen());
+        assert!(read.chapter(1).unwrap().tags.contains(&("White".to_string(), "Morphy".to_string())));
+        assert_eq!(pgn, read.to_pgn());
+
+        let mut read = read;
+
+        assert_eq!("Lucena", read.remove_chapter(0).unwrap().name);
+        assert!(matches!(read.remove_chapter(1), Err(StudyError::UnknownChapter(1))));
+        assert!(matches!(Study::read_pgn("1. e4 e5 2. Ke3 *".as_bytes()), Err(StudyError::InvalidChapter { index: 0, .. })));
+    }
+}
@@ -0,0 +1,359 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bitboard::BitBoard,
+    moves::{get_bishop_move_mask, get_pawn_attacks, get_rook_move_mask, KING_MOVE_MAP},
+    Board, Color, Coord, Move, PieceType, Variant,
+};
+
+// positions are stored from the view of the stronger side playing white, indexed by the side to move,
+// both kings and the extra piece
+const POSITIONS: usize = 2 * 64 * 64 * 64;
+const NO_WIN: u8 = u8::MAX;
+
+// the tables are only built on the first probe of their ending
+static LOADED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Ending {
+    QueenVsKing,
+    RookVsKing,
+    PawnVsKing,
+}
+
+impl Ending {
+    pub fn piece_type(&self) -> PieceType {
+        match self {
+            Ending::QueenVsKing => PieceType::Queen,
+            Ending::RookVsKing => PieceType::Rook,
+            Ending::PawnVsKing => PieceType::Pawn,
+        }
+    }
+
+    fn from_piece_type(piece_type: PieceType) -> Option<Self> {
+        match piece_type {
+            PieceType::Queen => Some(Ending::QueenVsKing),
+            PieceType::Rook => Some(Ending::RookVsKing),
+            PieceType::Pawn => Some(Ending::PawnVsKing),
+            _ => None,
+        }
+    }
+
+    fn table(&self) -> &'static [u8] {
+        match self {
+            Ending::QueenVsKing => &QUEEN_TABLE,
+            Ending::RookVsKing => &ROOK_TABLE,
+            Ending::PawnVsKing => &PAWN_TABLE,
+        }
+    }
+}
+
+// the result for the side to move with the distance to mate in plies, a mated side has lost in zero plies
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Outcome {
+    Win { plies: u32 },
+    Loss { plies: u32 },
+    Draw,
+}
+
+lazy_static! {
+    static ref QUEEN_TABLE: Vec<u8> = solve(Ending::QueenVsKing);
+    static ref ROOK_TABLE: Vec<u8> = solve(Ending::RookVsKing);
+    static ref PAWN_TABLE: Vec<u8> = solve(Ending::PawnVsKing);
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Position {
+    strong_to_move: bool,
+    king: usize,
+    defender: usize,
+    piece: usize,
+}
+
+impl Position {
+    fn index(&self) -> usize {
+        return ((!self.strong_to_move as usize * 64 + self.king) * 64 + self.defender) * 64 + self.piece;
+    }
+}
+
+// the ending on the board and the side playing for the win, castling rights are not considered
+pub fn classify(board: &Board) -> Option<(Ending, Color)> {
+    let (ending, strong, _) = normalize(board)?;
+    return Some((ending, strong));
+}
+
+pub fn probe(board: &Board) -> Option<Outcome> {
+    let (ending, _, position) = normalize(board)?;
+
+    return Some(match (ending.table()[position.index()], position.strong_to_move) {
+        (NO_WIN, _) => Outcome::Draw,
+        (plies, true) => Outcome::Win { plies: plies as u32 },
+        (plies, false) => Outcome::Loss { plies: plies as u32 },
+    });
+}
+
+// the winning side mates as fast as it can, the losing side holds out as long as possible
+pub fn best_move(board: &Board) -> Option<Move> {
+    probe(board)?;
+
+    let mut best: Option<(i64, Move)> = None;
+
+    for mv in crate::get_moves(board.turn(), board) {
+        let candidates = match mv.promotion {
+            true => vec![mv, Move { promote_to: PieceType::Rook, ..mv }],
+            false => vec![mv],
+        };
+
+        for mv in candidates {
+            let mut after = board.clone();
+
+            if after.exec_move(&mv).is_err() {
+                continue;
+            }
+
+            // a capture leaves two bare kings behind
+            let score = match probe(&after) {
+                Some(Outcome::Loss { plies }) => 1000 - plies as i64,
+                Some(Outcome::Win { plies }) => plies as i64 - 1000,
+                _ => 0,
+            };
+
+            if best.is_none_or(|(best_score, _)| score > best_score) {
+                best = Some((score, mv));
+            }
+        }
+    }
+
+    return best.map(|(_, mv)| mv);
+}
+
+fn normalize(board: &Board) -> Option<(Ending, Color, Position)> {
+    let pieces = board.pieces();
+
+    if board.variant() != Variant::Standard || pieces.len() != 3 {
+        return None;
+    }
+
+    let extra = pieces.iter().find(|piece| piece.piece_type != PieceType::King)?;
+    let ending = Ending::from_piece_type(extra.piece_type)?;
+    let strong = extra.color;
+
+    // black pawns run down the board, mirroring the ranks turns them into white ones
+    let square = |coord: Coord| match strong {
+        Color::White => coord.offset(),
+        Color::Black => coord.offset() ^ 56,
+    };
+
+    let position = Position {
+        strong_to_move: board.turn() == strong,
+        king: square(board.side(strong).king_coord()?),
+        defender: square(board.side(strong.invert()).king_coord()?),
+        piece: square(extra.coord),
+    };
+
+    return Some((ending, strong, position));
+}
+
+// retrograde by depth, every pass finds the positions won in exactly that many plies
+fn solve(ending: Ending) -> Vec<u8> {
+    let mut table = vec![NO_WIN; POSITIONS];
+
+    // a pawn promotes into the other tables, their longest mates bound how far the passes have to go
+    let deepest_promotion = match ending {
+        Ending::PawnVsKing => [Ending::QueenVsKing, Ending::RookVsKing]
+            .iter()
+            .flat_map(|promoted| promoted.table().iter().copied().filter(|plies| *plies != NO_WIN))
+            .max()
+            .unwrap_or(0),
+        _ => 0,
+    };
+
+    let mut setups = Vec::new();
+
+    for (king, king_moves) in KING_MOVE_MAP.iter().enumerate() {
+        for defender in 0..64 {
+            for piece in 0..64 {
+                let distinct = king != defender && king != piece && defender != piece;
+                let pawn_on_back_rank = ending == Ending::PawnVsKing && !(8..56).contains(&piece);
+
+                if distinct && !pawn_on_back_rank && !king_moves.is_set(Coord::from_offset(defender)) {
+                    setups.push((king, defender, piece));
+                }
+            }
+        }
+    }
+
+    let mut children = Vec::with_capacity(32);
+
+    for plies in 0..NO_WIN {
+        let strong_to_move = plies % 2 == 1;
+        let mut found = false;
+
+        for &(king, defender, piece) in &setups {
+            let position = Position { strong_to_move, king, defender, piece };
+
+            if table[position.index()] != NO_WIN {
+                continue;
+            }
+
+            let value = |(child_ending, child): &(Ending, Position)| match *child_ending == ending {
+                true => table[child.index()],
+                false => child_ending.table()[child.index()],
+            };
+
+            let won = match strong_to_move {
+                true => {
+                    strong_moves(ending, position, &mut children);
+                    children.iter().any(|child| value(child) == plies - 1)
+                }
+                false => match defender_moves(ending, position, &mut children) {
+                    Some(0) => plies == 0 && attacks(ending, king, piece).is_set(Coord::from_offset(defender)),
+                    Some(_) => plies > 0 && children.iter().all(|child| value(child) < plies),
+                    None => false,
+                },
+            };
+
+            if won {
+                table[position.index()] = plies;
+                found = true;
+            }
+        }
+
+        if !found && plies > deepest_promotion + 1 {
+            break;
+        }
+    }
+
+    LOADED_BYTES.fetch_add(table.capacity(), Ordering::Relaxed);
+
+    return table;
+}
+
+pub fn loaded_bytes() -> usize {
+    return LOADED_BYTES.load(Ordering::Relaxed);
+}
+
+fn strong_moves(ending: Ending, position: Position, children: &mut Vec<(Ending, Position)>) {
+    let Position { king, defender, piece, .. } = position;
+    let own = BitBoard::from_coord(Coord::from_offset(king)) | BitBoard::from_coord(Coord::from_offset(piece));
+    let occupied = own | BitBoard::from_coord(Coord::from_offset(defender));
+
+    let child = |ending, king, piece| {
+        return (ending, Position { strong_to_move: false, king, defender, piece });
+    };
+
+    children.clear();
+
+    for to in KING_MOVE_MAP[king] & !own & !KING_MOVE_MAP[defender] {
+        if to.offset() != defender {
+            children.push(child(ending, to.offset(), piece));
+        }
+    }
+
+    let from = Coord::from_offset(piece);
+    let targets = match ending {
+        Ending::QueenVsKing => get_rook_move_mask(from, &occupied, &own) | get_bishop_move_mask(from, &occupied, &own),
+        Ending::RookVsKing => get_rook_move_mask(from, &occupied, &own),
+        Ending::PawnVsKing => {
+            let mut pushes = BitBoard::new(0);
+            let single = piece + 8;
+
+            if !occupied.is_set(Coord::from_offset(single)) {
+                pushes.set(Coord::from_offset(single));
+
+                if (8..16).contains(&piece) && !occupied.is_set(Coord::from_offset(single + 8)) {
+                    pushes.set(Coord::from_offset(single + 8));
+                }
+            }
+
+            pushes
+        }
+    };
+
+    for to in targets {
+        if to.offset() == defender {
+            continue;
+        }
+
+        if ending == Ending::PawnVsKing && to.offset() >= 56 {
+            children.push(child(Ending::QueenVsKing, king, to.offset()));
+            children.push(child(Ending::RookVsKing, king, to.offset()));
+        } else {
+            children.push(child(ending, king, to.offset()));
+        }
+    }
+}
+
+// the number of legal replies, none when the defender can take the piece and draw
+fn defender_moves(ending: Ending, position: Position, children: &mut Vec<(Ending, Position)>) -> Option<usize> {
+    let Position { king, defender, piece, .. } = position;
+    let attacked = attacks(ending, king, piece);
+
+    children.clear();
+
+    for to in KING_MOVE_MAP[defender] & !attacked {
+        if to.offset() == king {
+            continue;
+        }
+
+        if to.offset() == piece {
+            return None;
+        }
+
+        children.push((ending, Position { strong_to_move: true, king, defender: to.offset(), piece }));
+    }
+
+    return Some(children.len());
+}
+
+// squares covered by the stronger side, the defending king does not block the lines it stands on
+fn attacks(ending: Ending, king: usize, piece: usize) -> BitBoard {
+    let from = Coord::from_offset(piece);
+    let blockers = BitBoard::from_coord(Coord::from_offset(king));
+    let none = BitBoard::new(0);
+
+    let piece_attacks = match ending {
+        Ending::QueenVsKing => get_rook_move_mask(from, &blockers, &none) | get_bishop_move_mask(from, &blockers, &none),
+        Ending::RookVsKing => get_rook_move_mask(from, &blockers, &none),
+        Ending::PawnVsKing => get_pawn_attacks(Color::White, from),
+    };
+
+    return KING_MOVE_MAP[king] | piece_attacks;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_mates() {
+        let longest = |ending: Ending| {
+            ending.table()[..POSITIONS / 2].iter().copied().filter(|plies| *plies != NO_WIN).max().unwrap_or(0)
+        };
+
+        assert_eq!(19, longest(Ending::QueenVsKing));
+        assert_eq!(31, longest(Ending::RookVsKing));
+    }
+
+    #[test]
+    fn probes_and_best_moves() {
+        let board = Board::from_fen("6k1/8/6K1/8/8/8/8/R7 w - -").unwrap();
+
+        assert_eq!(Some(Outcome::Win { plies: 1 }), probe(&board));
+        assert_eq!(Some(Move::new(Coord::new('a', 1), Coord::new('a', 8))), best_move(&board));
+
+        assert_eq!(Some(Outcome::Draw), probe(&Board::from_fen("4k3/4P3/4K3/8/8/8/8/8 b - -").unwrap()));
+        assert!(matches!(probe(&Board::from_fen("4k3/8/4K3/4P3/8/8/8/8 w - -").unwrap()), Some(Outcome::Win { .. })));
+
+        // mirrored for black, the defender to move is losing
+        let board = Board::from_fen("8/8/8/8/4p3/4k3/8/4K3 w - -").unwrap();
+
+        assert_eq!(Some((Ending::PawnVsKing, Color::Black)), classify(&board));
+        assert!(matches!(probe(&board), Some(Outcome::Loss { .. })));
+        assert_eq!(None, probe(&Board::new_game()));
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    book::Book,
+    moves::KNIGHT_MOVE_MAP,
+    positions::POSITIONS,
+    tablebase::{self, Ending, Outcome},
+    Board, Color, Coord, Move, PieceType,
+};
+
+// endgame tasks start a few moves away from mate, so there is something left to get wrong
+const MIN_ENDGAME_PLIES: u8 = 9;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum TrainingError {
+    #[error("The answer does not fit the task")]
+    MismatchedAnswer,
+
+    #[error("Invalid training position '{0}'")]
+    InvalidPosition(String),
+
+    #[error("No training position has a checking move")]
+    NoPosition,
+
+    #[error("The position is not a won tablebase ending")]
+    NotWinning,
+
+    #[error("The move {0} is illegal")]
+    IllegalMove(Move),
+
+    #[error("The position is not in the book")]
+    OutOfBook,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrainingKind {
+    NameSquare,
+    KnightPath,
+    FindChecks,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TrainingTask {
+    NameSquare { coord: Coord },
+    KnightPath { from: Coord, to: Coord },
+    FindChecks { fen: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TrainingAnswer {
+    Square { coord: Coord },
+    Path { squares: Vec<Coord> },
+    Moves { moves: Vec<Move> },
+}
+
+// the expected answer is sent back either way, so the frontend can show the solution
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainingResult {
+    pub correct: bool,
+    pub expected: TrainingAnswer,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndgameTask {
+    pub ending: Ending,
+    pub fen: String,
+    pub plies: u32,
+}
+
+// distances are the plies to mate at the player's next turn, assuming the defence holds out as long as it can
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum EndgameVerdict {
+    Mate,
+    Progress { plies: u32 },
+    Slower { before: u32, after: u32 },
+    WinLost,
+}
+
+// the book's moves for the player are the expected ones, the move to play is the legal one the player's input names,
+// the reply is the opponent's answer from the book, a line is complete when the book has nothing left for the player after it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DrillVerdict {
+    InBook { played: Move, reply: Option<Move>, complete: bool },
+    Deviation { expected: Vec<Move> },
+}
+
+// tasks are derived from a seed, so the caller decides how random they are
+pub fn new_task(kind: TrainingKind, seed: u64) -> Result<TrainingTask, TrainingError> {
+    let mut state = seed | 1;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        return state;
+    };
+
+    return match kind {
+        TrainingKind::NameSquare => Ok(TrainingTask::NameSquare {
+            coord: Coord::from_offset(next() as usize % 64),
+        }),
+        TrainingKind::KnightPath => {
+            let from = Coord::from_offset(next() as usize % 64);
+            let to = Coord::from_offset((from.offset() + 1 + next() as usize % 63) % 64);
+
+            Ok(TrainingTask::KnightPath { from, to })
+        }
+        TrainingKind::FindChecks => {
+            let candidates = POSITIONS
+                .iter()
+                .filter(|position| Board::from_fen(position.fen).is_ok_and(|board| !checking_moves(&board).is_empty()))
+                .collect::<Vec<_>>();
+
+            if candidates.is_empty() {
+                return Err(TrainingError::NoPosition);
+            }
+
+            Ok(TrainingTask::FindChecks {
+                fen: candidates[next() as usize % candidates.len()].fen.to_string(),
+            })
+        }
+    };
+}
+
+// white plays for the win from a random setup of the ending
+pub fn new_endgame(ending: Ending, seed: u64) -> Result<EndgameTask, TrainingError> {
+    let mut state = seed | 1;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        return state as usize;
+    };
+
+    loop {
+        let piece = match ending {
+            Ending::PawnVsKing => 8 + next() % 48,
+            _ => next() % 64,
+        };
+        let (king, defender) = (next() % 64, next() % 64);
+
+        if king == defender || king == piece || defender == piece {
+            continue;
+        }
+
+        let Ok(board) = Board::builder()
+            .piece(Coord::from_offset(king), PieceType::King, Color::White)
+            .piece(Coord::from_offset(defender), PieceType::King, Color::Black)
+            .piece(Coord::from_offset(piece), ending.piece_type(), Color::White)
+            .build()
+        else {
+            continue;
+        };
+        let fen = board.to_fen();
+
+        // adjacent kings are no legal setup and probe as a draw
+        match tablebase::probe(&board) {
+            Some(Outcome::Win { plies }) if !board.black_checked() && plies >= MIN_ENDGAME_PLIES as u32 => {
+                return Ok(EndgameTask { ending, fen, plies });
+            }
+            _ => continue,
+        }
+    }
+}
+
+// progress means the distance to mate does not grow from one turn of the player to the next
+pub fn judge_endgame_move(board: &Board, mv: &Move) -> Result<EndgameVerdict, TrainingError> {
+    let Some(Outcome::Win { plies: before }) = tablebase::probe(board) else {
+        return Err(TrainingError::NotWinning);
+    };
+
+    if crate::resolve_move(board, mv.from, mv.to, mv.promotion.then_some(mv.promote_to)).as_ref() != Some(mv) {
+        return Err(TrainingError::IllegalMove(*mv));
+    }
+
+    let mut after = board.clone();
+    after.exec_move(mv).map_err(|_| TrainingError::IllegalMove(*mv))?;
+
+    return Ok(match tablebase::probe(&after) {
+        Some(Outcome::Loss { plies: 0 }) => EndgameVerdict::Mate,
+        Some(Outcome::Loss { plies }) if plies - 1 <= before => EndgameVerdict::Progress { plies: plies - 1 },
+        Some(Outcome::Loss { plies }) => EndgameVerdict::Slower { before, after: plies - 1 },
+        _ => EndgameVerdict::WinLost,
+    });
+}
+
+// the book has to hold the opponent's moves as well, the seed picks among the opponent's book moves
+pub fn judge_drill_move(book: &Book, board: &Board, mv: &Move, seed: u64) -> Result<DrillVerdict, TrainingError> {
+    let expected = book.moves(board).into_iter().map(|(mv, _)| mv).collect::<Vec<_>>();
+
+    if expected.is_empty() {
+        return Err(TrainingError::OutOfBook);
+    }
+
+    let Some(mv) = crate::resolve_move(board, mv.from, mv.to, mv.promotion.then_some(mv.promote_to)) else {
+        return Err(TrainingError::IllegalMove(*mv));
+    };
+
+    if !expected.contains(&mv) {
+        return Ok(DrillVerdict::Deviation { expected });
+    }
+
+    let mut after = board.clone();
+    after.exec_move(&mv).map_err(|_| TrainingError::IllegalMove(mv))?;
+
+    let reply = book.pick(&after, seed);
+
+    if let Some(reply) = reply {
+        after.exec_move(&reply).map_err(|_| TrainingError::IllegalMove(reply))?;
+    }
+
+    return Ok(DrillVerdict::InBook {
+        played: mv,
+        reply,
+        complete: reply.is_none() || book.moves(&after).is_empty(),
+    });
+}
+
+// the moves from the start of the game, which names the line for keeping track of it
+pub fn line_key(board: &Board) -> String {
+    return board
+        .history()
+        .iter()
+        .filter_map(|position| position.last_move())
+        .map(|mv| mv.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+}
+
+pub fn check_answer(task: &TrainingTask, answer: &TrainingAnswer) -> Result<TrainingResult, TrainingError> {
+    return match (task, answer) {
+        (TrainingTask::NameSquare { coord }, TrainingAnswer::Square { coord: answer }) => Ok(TrainingResult {
+            correct: coord == answer,
+            expected: TrainingAnswer::Square { coord: *coord },
+        }),
+        (TrainingTask::KnightPath { from, to }, TrainingAnswer::Path { squares }) => {
+            let shortest = knight_path(*from, *to);
+
+            // any route is accepted as long as it is a chain of knight moves no longer than the shortest one
+            let is_route = squares.first() == Some(from)
+                && squares.last() == Some(to)
+                && squares.windows(2).all(|hop| KNIGHT_MOVE_MAP[hop[0].offset()].is_set(hop[1]));
+
+            Ok(TrainingResult {
+                correct: is_route && squares.len() == shortest.len(),
+                expected: TrainingAnswer::Path { squares: shortest },
+            })
+        }
+        (TrainingTask::FindChecks { fen }, TrainingAnswer::Moves { moves }) => {
+            let board = Board::from_fen(fen).map_err(|_| TrainingError::InvalidPosition(fen.clone()))?;
+            let checks = checking_moves(&board);
+
+            let found = |mv: &Move| moves.iter().any(|answer| answer.from == mv.from && answer.to == mv.to);
+
+            Ok(TrainingResult {
+                correct: moves.len() == checks.len() && checks.iter().all(found),
+                expected: TrainingAnswer::Moves { moves: checks },
+            })
+        }
+        _ => Err(TrainingError::MismatchedAnswer),
+    };
+}
+
+// breadth-first search over the knight move map, the route includes both the start and the target square
+pub fn knight_path(from: Coord, to: Coord) -> Vec<Coord> {
+    let mut previous: [Option<Coord>; 64] = [None; 64];
+    let mut queue = VecDeque::from([from]);
+
+    previous[from.offset()] = Some(from);
+
+    while let Some(square) = queue.pop_front() {
+        if square == to {
+            break;
+        }
+
+        for next in KNIGHT_MOVE_MAP[square.offset()] {
+            if previous[next.offset()].is_none() {
+                previous[next.offset()] = Some(square);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    let mut path = vec![to];
+
+    while let Some(square) = path.last().copied().filter(|square| *square != from) {
+        path.push(previous[square.offset()].expect("every square is reachable by a knight"));
+    }
+
+    path.reverse();
+
+    return path;
+}
+
+// promotions are generated once per square, so a checking promotion is listed once
+pub fn checking_moves(board: &Board) -> Vec<Move> {
+    return crate::get_moves(board.turn(), board)
+        .into_iter()
+        .filter(|mv| {
+            let mut after = board.clone();
+            return after.exec_move(mv).is_ok() && after.turning_side().checked();
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{book::BookBuilder, pgn::read_pgn};
+
+    #[test]
+    fn knight_paths() {
+        assert_eq!(vec![Coord::new('a', 1)], knight_path(Coord::new('a', 1), Coord::new('a', 1)));
+        assert_eq!(3, knight_path(Coord::new('g', 1), Coord::new('e', 5)).len());
+
+        // the corner to corner route along the diagonal is famously long
+        assert_eq!(7, knight_path(Coord::new('a', 1), Coord::new('h', 8)).len());
+
+        let task = TrainingTask::KnightPath {
+            from: Coord::new('g', 1),
+            to: Coord::new('e', 5),
+        };
+        let route = |squares: &[&str]| TrainingAnswer::Path {
+            squares: squares.iter().map(|square| Coord::from_str(square).unwrap()).collect(),
+        };
+
+        assert!(check_answer(&task, &route(&["g1", "f3", "e5"])).unwrap().correct);
+        assert!(!check_answer(&task, &route(&["g1", "h3", "f4", "e6", "f4"])).unwrap().correct);
+        assert_eq!(Err(TrainingError::MismatchedAnswer), check_answer(&task, &TrainingAnswer::Moves { moves: vec![] }));
+    }
+
+    #[test]
+    fn checks_and_squares() {
+        let fen = "4k3/8/8/8/8/8/8/R3K2R w - -".to_string();
+        let board = Board::from_fen(&fen).unwrap();
+        let checks = checking_moves(&board);
+
+        assert_eq!(2, checks.len());
+        assert!(check_answer(&TrainingTask::FindChecks { fen: fen.clone() }, &TrainingAnswer::Moves { moves: checks.clone() }).unwrap().correct);
+        assert!(!check_answer(&TrainingTask::FindChecks { fen }, &TrainingAnswer::Moves { moves: checks[..1].to_vec() }).unwrap().correct);
+
+        let task = new_task(TrainingKind::NameSquare, 7).unwrap();
+
+        assert_eq!(task, new_task(TrainingKind::NameSquare, 7).unwrap());
+        assert!(matches!(new_task(TrainingKind::FindChecks, 3), Ok(TrainingTask::FindChecks { .. })));
+    }
+
+    #[test]
+    fn endgame_progress() {
+        let board = Board::from_fen("6k1/8/6K1/8/8/8/8/R7 w - -").unwrap();
+        let mv = |from: &str, to: &str| Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap());
+
+        assert_eq!(Ok(EndgameVerdict::Mate), judge_endgame_move(&board, &mv("a1", "a8")));
+        assert!(matches!(judge_endgame_move(&board, &mv("a1", "a2")), Ok(EndgameVerdict::Progress { .. } | EndgameVerdict::Slower { .. })));
+        assert_eq!(Ok(EndgameVerdict::WinLost), judge_endgame_move(&Board::from_fen("6k1/8/5K2/8/8/8/8/7R w - -").unwrap(), &mv("h1", "h8")));
+        assert_eq!(Err(TrainingError::IllegalMove(mv("a1", "b2"))), judge_endgame_move(&board, &mv("a1", "b2")));
+
+        let task = new_endgame(Ending::RookVsKing, 11).unwrap();
+
+        assert!(task.plies >= MIN_ENDGAME_PLIES as u32);
+        assert!(matches!(tablebase::probe(&Board::from_fen(&task.fen).unwrap()), Some(Outcome::Win { .. })));
+    }
+
+    #[test]
+    fn opening_drill() {
+        let mut builder = BookBuilder::new(8);
+        builder.add_game(&read_pgn("1. e4 e5 2. Nf3 Nc6").unwrap().board, "1/2-1/2", None);
+        let book = builder.build();
+
+        let board = Board::new_game();
+        let mv = |from: &str, to: &str| Move::new(Coord::from_str(from).unwrap(), Coord::from_str(to).unwrap());
+
+        assert_eq!(Ok(DrillVerdict::Deviation { expected: vec![mv("e2", "e4")] }), judge_drill_move(&book, &board, &mv("d2", "d4"), 1));
+        assert!(matches!(
+            judge_drill_move(&book, &board, &mv("e2", "e4"), 1),
+            Ok(DrillVerdict::InBook { played, reply: Some(reply), complete: false })
+                if Some(played) == crate::resolve_move(&board, played.from, played.to, None) && reply.to_string() == "e7e5"
+        ));
+
+        let line = read_pgn("1. e4 e5").unwrap().board;
+
+        assert!(matches!(judge_drill_move(&book, &line, &mv("g1", "f3"), 1), Ok(DrillVerdict::InBook { reply: Some(_), complete: true, .. })));
+        assert_eq!(Err(TrainingError::IllegalMove(mv("e1", "e3"))), judge_drill_move(&book, &line, &mv("e1", "e3"), 1));
+        assert_eq!(Err(TrainingError::OutOfBook), judge_drill_move(&book, &read_pgn("1. d4").unwrap().board, &mv("d7", "d5"), 1));
+        assert_eq!("e2e4 e7e5", line_key(&line));
+    }
+}
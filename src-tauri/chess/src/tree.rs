@@ -0,0 +1,488 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{san::to_san, Board, Move, Variant};
+
+pub const ROOT: usize = 0;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum TreeError {
+    #[error("There is no move with id {0}")]
+    UnknownNode(usize),
+
+    #[error("There is no variation {0} after the current move")]
+    UnknownVariation(usize),
+
+    #[error("The current move is part of the main line")]
+    NotInVariation,
+
+    #[error("Only moves can be annotated with glyphs")]
+    NoMove,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveNode {
+    pub id: usize,
+    pub mv: Option<Move>,
+    pub san: String,
+    pub parent: Option<usize>,
+    // the first child continues the line, the others are variations
+    pub children: Vec<usize>,
+    // the comment after the move, on the root it describes the whole game
+    pub comment: Option<String>,
+    // numeric annotation glyphs, 1 to 6 stand for !, ?, !!, ??, !? and ?!
+    pub nags: Vec<u8>,
+}
+
+// every line played from the start position, the root stands for the start position itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "TreeRecord", try_from = "TreeRecord")]
+pub struct MoveTree {
+    start: Board,
+    start_fen: String,
+    nodes: Vec<MoveNode>,
+    current: usize,
+}
+
+// the start board is stored as its fen and variant, loading a tree plays every line again from there
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TreeRecord {
+    #[serde(default)]
+    variant: Variant,
+    start_fen: String,
+    nodes: Vec<MoveNode>,
+    current: usize,
+}
+
+impl From<MoveTree> for TreeRecord {
+    fn from(tree: MoveTree) -> Self {
+        TreeRecord {
+            variant: tree.start.variant(),
+            start_fen: tree.start_fen,
+            nodes: tree.nodes,
+            current: tree.current,
+        }
+    }
+}
+
+impl TryFrom<TreeRecord> for MoveTree {
+    type Error = anyhow::Error;
+
+    // the nodes have to form one tree below the root with every move legal where it is played
+    fn try_from(record: TreeRecord) -> Result<Self, Self::Error> {
+        let start = Board::from_variant_fen(record.variant, &record.start_fen)?;
+        let mut nodes = record.nodes;
+
+        if !nodes.first().is_some_and(|root| root.id == ROOT && root.mv.is_none() && root.parent.is_none()) {
+            return Err(anyhow!("The stored tree has no root"));
+        }
+
+        let mut seen = vec![false; nodes.len()];
+        let mut stack = vec![(ROOT, start.clone())];
+
+        seen[ROOT] = true;
+
+        while let Some((id, board)) = stack.pop() {
+            for child in nodes[id].children.clone() {
+                let stored = nodes.get(child).ok_or(TreeError::UnknownNode(child))?;
+
+                if seen[child] || stored.id != child || stored.parent != Some(id) {
+                    return Err(anyhow!("The stored node {child} is out of place"));
+                }
+
+                let mv = stored.mv.ok_or_else(|| anyhow!("The stored node {child} has no move"))?;
+                let mut after = board.clone();
+                after.exec_move(&mv).map_err(|_| anyhow!("The move {mv} is illegal in the stored tree"))?;
+
+                nodes[child].san = to_san(&board, &mv);
+                seen[child] = true;
+                stack.push((child, after));
+            }
+        }
+
+        if seen.contains(&false) {
+            return Err(anyhow!("The stored tree has nodes that are not reachable"));
+        }
+
+        if record.current >= nodes.len() {
+            return Err(TreeError::UnknownNode(record.current).into());
+        }
+
+        return Ok(MoveTree {
+            start_fen: start.to_fen(),
+            start,
+            nodes,
+            current: record.current,
+        });
+    }
+}
+
+impl MoveTree {
+    pub fn new(start: &Board) -> Self {
+        let root = MoveNode {
+            id: ROOT,
+            mv: None,
+            san: String::new(),
+            parent: None,
+            children: Vec::new(),
+            comment: None,
+            nags: Vec::new(),
+        };
+
+        MoveTree {
+            start: start.clone(),
+            start_fen: start.to_fen(),
+            nodes: vec![root],
+            current: ROOT,
+        }
+    }
+
+    pub fn from_board(board: &Board) -> Self {
+        let mut tree = Self::new(&board.history()[0]);
+        tree.sync(board);
+
+        return tree;
+    }
+
+    // follows the moves of the board from the start, moves that are not in the tree yet become new lines,
+    // a board from another start position starts a new tree
+    pub fn sync(&mut self, board: &Board) {
+        let positions = board.history();
+
+        if positions[0].to_fen() != self.start_fen || positions[0].variant() != self.start.variant() {
+            *self = Self::new(&positions[0]);
+        }
+
+        let mut node = ROOT;
+
+        for window in positions.windows(2) {
+            let Some(mv) = window[1].last_move() else {
+                break;
+            };
+
+            node = self.add_move(node, &window[0], mv);
+        }
+
+        self.current = node;
+    }
+
+    // the child of parent playing mv, which is added as the last variation if it is new
+    pub fn add_move(&mut self, parent: usize, before: &Board, mv: Move) -> usize {
+        if let Some(child) = self.nodes[parent].children.iter().copied().find(|child| self.nodes[*child].mv == Some(mv)) {
+            return child;
+        }
+
+        let id = self.nodes.len();
+
+        self.nodes.push(MoveNode {
+            id,
+            mv: Some(mv),
+            san: to_san(before, &mv),
+            parent: Some(parent),
+            children: Vec::new(),
+            comment: None,
+            nags: Vec::new(),
+        });
+
+        self.nodes[parent].children.push(id);
+
+        return id;
+    }
+
+    pub fn start(&self) -> &Board {
+        return &self.start;
+    }
+
+    pub fn current(&self) -> usize {
+        return self.current;
+    }
+
+    pub fn node(&self, id: usize) -> Result<&MoveNode, TreeError> {
+        return self.nodes.get(id).ok_or(TreeError::UnknownNode(id));
+    }
+
+    // an empty comment removes it
+    pub fn set_comment(&mut self, id: usize, comment: Option<String>) -> Result<(), TreeError> {
+        self.nodes.get_mut(id).ok_or(TreeError::UnknownNode(id))?.comment = comment.filter(|comment| !comment.trim().is_empty());
+        return Ok(());
+    }
+
+    pub fn set_nags(&mut self, id: usize, nags: Vec<u8>) -> Result<(), TreeError> {
+        let node = self.nodes.get_mut(id).ok_or(TreeError::UnknownNode(id))?;
+
+        if node.mv.is_none() && !nags.is_empty() {
+            return Err(TreeError::NoMove);
+        }
+
+        node.nags = nags;
+
+        return Ok(());
+    }
+
+    pub fn mainline(&self) -> Vec<usize> {
+        let mut line = Vec::new();
+        let mut node = ROOT;
+
+        while let Some(child) = self.nodes[node].children.first() {
+            line.push(*child);
+            node = *child;
+        }
+
+        return line;
+    }
+
+    // the moves leading from the start position to the node
+    pub fn line(&self, id: usize) -> Result<Vec<Move>, TreeError> {
+        let mut moves = Vec::new();
+        let mut node = self.node(id)?;
+
+        while let (Some(mv), Some(parent)) = (node.mv, node.parent) {
+            moves.push(mv);
+            node = &self.nodes[parent];
+        }
+
+        moves.reverse();
+
+        return Ok(moves);
+    }
+
+    pub fn board_at(&self, id: usize) -> Result<Board, TreeError> {
+        let mut board = self.start.clone();
+
+        for mv in self.line(id)? {
+            board.exec_move(&mv).expect("moves in the tree to be legal");
+        }
+
+        return Ok(board);
+    }
+
+    pub fn goto(&mut self, id: usize) -> Result<Board, TreeError> {
+        let board = self.board_at(id)?;
+        self.current = id;
+
+        return Ok(board);
+    }
+
+    // index 0 follows the line of the current move, higher indices enter its variations
+    pub fn enter_variation(&mut self, index: usize) -> Result<Board, TreeError> {
+        let child = *self.nodes[self.current].children.get(index).ok_or(TreeError::UnknownVariation(index))?;
+        return self.goto(child);
+    }
+
+    // goes back to the position the current variation branched off from
+    pub fn exit_variation(&mut self) -> Result<Board, TreeError> {
+        let start = self.variation_start(self.current).ok_or(TreeError::NotInVariation)?;
+        return self.goto(self.nodes[start].parent.unwrap_or(ROOT));
+    }
+
+    // removes the current variation with everything that follows it and goes back to where it branched off
+    pub fn delete_variation(&mut self) -> Result<Board, TreeError> {
+        let start = self.variation_start(self.current).ok_or(TreeError::NotInVariation)?;
+        let parent = self.nodes[start].parent.unwrap_or(ROOT);
+
+        self.nodes[parent].children.retain(|child| *child != start);
+        self.current = parent;
+        self.compact();
+
+        return self.board_at(self.current);
+    }
+
+    // makes the line of the current move the main line at every point it branches off
+    pub fn promote_variation(&mut self) -> Result<(), TreeError> {
+        if self.variation_start(self.current).is_none() {
+            return Err(TreeError::NotInVariation);
+        }
+
+        let mut node = self.current;
+
+        while let Some(parent) = self.nodes[node].parent {
+            let children = &mut self.nodes[parent].children;
+            let index = children.iter().position(|child| *child == node).expect("child to be listed in its parent");
+
+            children[..=index].rotate_right(1);
+            node = parent;
+        }
+
+        return Ok(());
+    }
+
+    // the first move of the innermost variation the node is part of
+    fn variation_start(&self, id: usize) -> Option<usize> {
+        let mut node = id;
+
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].children[0] != node {
+                return Some(node);
+            }
+
+            node = parent;
+        }
+
+        return None;
+    }
+
+    // drops nodes that are no longer reachable from the root and renumbers the rest in depth-first order
+    fn compact(&mut self) {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![ROOT];
+
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.nodes[id].children.iter().rev());
+        }
+
+        let mut ids = vec![usize::MAX; self.nodes.len()];
+
+        for (new_id, old_id) in order.iter().enumerate() {
+            ids[*old_id] = new_id;
+        }
+
+        let mut nodes = order.iter().map(|id| self.nodes[*id].clone()).collect::<Vec<_>>();
+
+        for node in nodes.iter_mut() {
+            node.id = ids[node.id];
+            node.parent = node.parent.map(|parent| ids[parent]);
+            node.children.iter_mut().for_each(|child| *child = ids[*child]);
+        }
+
+        self.nodes = nodes;
+        self.current = ids[self.current];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::san::parse_san;
+
+    fn play(board: &mut Board, moves: &[&str]) {
+        for san in moves {
+            let mv = parse_san(board, san).unwrap();
+            board.exec_move(&mv).unwrap();
+        }
+    }
+
+    fn sans(tree: &MoveTree, ids: &[usize]) -> Vec<String> {
+        return ids.iter().map(|id| tree.node(*id).unwrap().san.clone()).collect();
+    }
+
+    #[test]
+    fn follows_the_board_into_variations() {
+        let mut board = Board::new_game();
+        play(&mut board, &["e4", "e5", "Nf3"]);
+
+        let mut tree = MoveTree::from_board(&board);
+
+        board.undo_move().unwrap();
+        tree.sync(&board);
+
+        assert_eq!(vec!["e4", "e5", "Nf3"], sans(&tree, &tree.mainline()));
+        assert_eq!("e5", tree.node(tree.current()).unwrap().san);
+
+        play(&mut board, &["Bc4", "Nf6"]);
+        tree.sync(&board);
+
+        let bc4 = tree.node(tree.current()).unwrap().parent.unwrap();
+
+        assert_eq!(vec!["Nf3", "Bc4"], sans(&tree, &tree.node(tree.node(bc4).unwrap().parent.unwrap()).unwrap().children));
+        assert_eq!(board, tree.board_at(tree.current()).unwrap());
+
+        // a different start position starts over
+        tree.sync(&Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap());
+
+        assert!(tree.mainline().is_empty());
+    }
+
+    #[test]
+    fn enter_exit_promote_and_delete() {
+        let mut board = Board::new_game();
+        play(&mut board, &["e4", "e5", "Nf3"]);
+
+        let mut tree = MoveTree::from_board(&board);
+        let e5 = tree.mainline()[1];
+
+        tree.goto(e5).unwrap();
+        let mut variation = tree.board_at(e5).unwrap();
+        play(&mut variation, &["Bc4", "Nc6"]);
+        tree.sync(&variation);
+
+        tree.goto(e5).unwrap();
+
+        assert_eq!(Err(TreeError::UnknownVariation(2)), tree.enter_variation(2).map(|_| ()));
+
+        let board = tree.enter_variation(1).unwrap();
+        assert_eq!("Bc4", tree.node(tree.current()).unwrap().san);
+        assert_eq!(Some(Move::new(crate::Coord::new('f', 1), crate::Coord::new('c', 4))), board.last_move());
+
+        assert_eq!(tree.board_at(e5).unwrap(), tree.exit_variation().unwrap());
+        assert_eq!(Err(TreeError::NotInVariation), tree.exit_variation().map(|_| ()));
+
+        tree.enter_variation(1).unwrap();
+        tree.promote_variation().unwrap();
+
+        assert_eq!(vec!["e4", "e5", "Bc4", "Nc6"], sans(&tree, &tree.mainline()));
+
+        let nf3 = tree.node(e5).unwrap().children[1];
+        tree.goto(nf3).unwrap();
+
+        let board = tree.delete_variation().unwrap();
+
+        assert_eq!(tree.board_at(tree.current()).unwrap(), board);
+        assert_eq!(4, tree.mainline().len());
+        assert_eq!(5, tree.nodes.len());
+        assert!(tree.nodes.iter().all(|node| node.children.iter().all(|child| tree.nodes[*child].parent == Some(node.id))));
+    }
+
+    #[test]
+    fn annotations_survive_new_moves() {
+        let mut board = Board::new_game();
+        play(&mut board, &["e4", "e5"]);
+
+        let mut tree = MoveTree::from_board(&board);
+        let e4 = tree.mainline()[0];
+
+        tree.set_comment(e4, Some("best by test".to_string())).unwrap();
+        tree.set_nags(e4, vec![1]).unwrap();
+
+        assert_eq!(Err(TreeError::NoMove), tree.set_nags(ROOT, vec![1]));
+        assert_eq!(Err(TreeError::UnknownNode(9)), tree.set_comment(9, None));
+
+        play(&mut board, &["Nf3"]);
+        tree.sync(&board);
+
+        assert_eq!(Some("best by test"), tree.node(e4).unwrap().comment.as_deref());
+        assert_eq!(vec![1], tree.node(e4).unwrap().nags);
+
+        tree.set_comment(e4, Some(" ".to_string())).unwrap();
+        assert_eq!(None, tree.node(e4).unwrap().comment);
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut board = Board::new_variant_game(Variant::Antichess);
+        play(&mut board, &["e4", "e5", "Nf3"]);
+
+        let mut tree = MoveTree::from_board(&board);
+        let e5 = tree.mainline()[1];
+        let mut variation = tree.board_at(e5).unwrap();
+
+        play(&mut variation, &["Bc4"]);
+        tree.sync(&variation);
+        tree.promote_variation().unwrap();
+        tree.set_comment(e5, Some("open game".to_string())).unwrap();
+        tree.set_nags(e5, vec![3]).unwrap();
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let restored: MoveTree = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(tree, restored);
+        assert_eq!(Variant::Antichess, restored.start().variant());
+        assert_eq!(vec!["e4", "e5", "Bc4"], sans(&restored, &restored.mainline()));
+
+        // the stored moves are played again, so they have to fit the start position
+        let bare = json.replace(&tree.start_fen, "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(serde_json::from_str::<MoveTree>(&bare).is_err());
+    }
+}
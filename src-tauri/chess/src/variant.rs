@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Color, PieceType};
+
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Variant {
+    #[default]
+    Standard,
+    Antichess,
+    Horde,
+    RacingKings,
+}
+
+impl Variant {
+    pub fn start_fen(&self) -> &'static str {
+        match self {
+            Variant::Standard => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            Variant::Antichess => "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1",
+            Variant::Horde => "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1",
+            Variant::RacingKings => "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1",
+        }
+    }
+
+    pub fn has_checks(&self) -> bool {
+        return *self != Variant::Antichess;
+    }
+
+    pub fn can_castle(&self) -> bool {
+        return *self != Variant::Antichess && *self != Variant::RacingKings;
+    }
+
+    pub fn forced_captures(&self) -> bool {
+        return *self == Variant::Antichess;
+    }
+
+    // antichess kings are ordinary pieces, so a pawn may become one there
+    pub fn can_promote_to(&self, piece_type: PieceType) -> bool {
+        return match piece_type {
+            PieceType::Queen | PieceType::Rook | PieceType::Bishop | PieceType::Knight => true,
+            PieceType::King => *self == Variant::Antichess,
+            PieceType::Pawn => false,
+        };
+    }
+
+    // the number of kings a side must have, antichess kings are ordinary pieces
+    pub fn king_count(&self, color: Color) -> Option<usize> {
+        match (self, color) {
+            (Variant::Antichess, _) => None,
+            (Variant::Horde, Color::White) => Some(0),
+            _ => Some(1),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{bitboard::BitBoard, Board, Color, Coord, EnPassantMode, PieceType};
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Rook,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+struct ZobristKeys {
+    pieces: [[[u64; 64]; 6]; 2],
+    black_to_move: u64,
+    castling: [u64; 4],
+    en_passant: [u64; 8],
+}
+
+lazy_static! {
+    static ref KEYS: ZobristKeys = generate_keys();
+}
+
+pub fn table_bytes() -> usize {
+    return std::mem::size_of::<ZobristKeys>();
+}
+
+fn generate_keys() -> ZobristKeys {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        return state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+    };
+
+    let mut keys = ZobristKeys {
+        pieces: [[[0; 64]; 6]; 2],
+        black_to_move: 0,
+        castling: [0; 4],
+        en_passant: [0; 8],
+    };
+
+    for key in keys.pieces.iter_mut().flatten().flatten() {
+        *key = next();
+    }
+
+    keys.black_to_move = next();
+
+    for key in keys.castling.iter_mut().chain(keys.en_passant.iter_mut()) {
+        *key = next();
+    }
+
+    return keys;
+}
+
+// the full hash is only computed for a new position, moves update it with the keys of what they changed
+pub fn hash(board: &Board) -> u64 {
+    let mut hash = 0;
+
+    for color in [Color::White, Color::Black] {
+        let side = board.side(color);
+
+        for piece_type in PIECE_TYPES {
+            let pieces: &BitBoard = match piece_type {
+                PieceType::Pawn => side.pawns(),
+                PieceType::Rook => side.rooks(),
+                PieceType::Knight => side.knights(),
+                PieceType::Bishop => side.bishops(),
+                PieceType::Queen => side.queens(),
+                PieceType::King => side.king(),
+            };
+
+            for coord in pieces {
+                hash ^= piece(color, piece_type, coord);
+            }
+        }
+    }
+
+    if board.turn() == Color::Black {
+        hash ^= black_to_move();
+    }
+
+    return hash ^ rights(board);
+}
+
+pub fn piece(color: Color, piece_type: PieceType, coord: Coord) -> u64 {
+    let color_index = match color {
+        Color::White => 0,
+        Color::Black => 1,
+    };
+    let type_index = match piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Rook => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+
+    return KEYS.pieces[color_index][type_index][coord.offset()];
+}
+
+pub fn black_to_move() -> u64 {
+    return KEYS.black_to_move;
+}
+
+// the castling rights and the en passant square that can be captured on, a move XORs them out before and back in after
+pub fn rights(board: &Board) -> u64 {
+    let keys = &*KEYS;
+    let mut hash = 0;
+
+    let rights = [
+        board.side(Color::White).can_castle_kingside(),
+        board.side(Color::White).can_castle_queenside(),
+        board.side(Color::Black).can_castle_kingside(),
+        board.side(Color::Black).can_castle_queenside(),
+    ];
+
+    for (key, right) in keys.castling.iter().zip(rights) {
+        if right {
+            hash ^= key;
+        }
+    }
+
+    if let Some(en_passant_square) = board.en_passant_square_for(EnPassantMode::Capturable) {
+        hash ^= keys.en_passant[en_passant_square.column_index() as usize];
+    }
+
+    return hash;
+}
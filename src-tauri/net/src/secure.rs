@@ -0,0 +1,195 @@
+use std::io::{self, ErrorKind, Read, Write};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use snow::{Builder, TransportState};
+
+// both sides prove they know the passcode, which is mixed into the handshake as a pre-shared key
+const NOISE_PATTERN: &str = "Noise_NNpsk0_25519_ChaChaPoly_BLAKE2s";
+const MAX_FRAME: usize = 65_535;
+const TAG_LENGTH: usize = 16;
+
+// the first handshake message can be used to test guesses of the passcode offline, so the key is stretched with
+// argon2id over a salt of its own for every session, each guess then costs 19 MiB and two passes
+const SALT_LENGTH: usize = 16;
+const KDF_MEMORY_KIB: u32 = 19 * 1024;
+const KDF_PASSES: u32 = 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecureError {
+    #[error("The other side does not know the passcode")]
+    WrongPasscode,
+
+    #[error("A message of {0} bytes is too long to be sent")]
+    TooLong(usize),
+
+    #[error("The other side sent a salt of {0} bytes")]
+    InvalidSalt(usize),
+
+    #[error("The key could not be derived from the passcode: {0}")]
+    Kdf(argon2::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Noise(#[from] snow::Error),
+}
+
+// an encrypted connection over any stream, messages are sent as frames with a two byte length,
+// a frame that was tampered with fails to decrypt
+pub struct SecureChannel<S> {
+    stream: S,
+    transport: TransportState,
+    buffer: Vec<u8>,
+}
+
+impl<S: Read + Write> SecureChannel<S> {
+    pub fn connect(stream: S, passcode: &str) -> Result<Self, SecureError> {
+        return Self::handshake(stream, passcode, true);
+    }
+
+    pub fn accept(stream: S, passcode: &str) -> Result<Self, SecureError> {
+        return Self::handshake(stream, passcode, false);
+    }
+
+    pub fn send(&mut self, message: &[u8]) -> Result<(), SecureError> {
+        if message.len() > MAX_FRAME - TAG_LENGTH {
+            return Err(SecureError::TooLong(message.len()));
+        }
+
+        let length = self.transport.write_message(message, &mut self.buffer)?;
+        write_frame(&mut self.stream, &self.buffer[..length])?;
+
+        return Ok(());
+    }
+
+    pub fn receive(&mut self) -> Result<Vec<u8>, SecureError> {
+        let frame = read_frame(&mut self.stream)?;
+        let length = self.transport.read_message(&frame, &mut self.buffer)?;
+
+        return Ok(self.buffer[..length].to_vec());
+    }
+
+    // with a wrong passcode the first message cannot be decrypted, so the accepting side notices right away
+    // and the connecting side sees the connection close, the salt is picked by the connecting side and sent in the clear
+    fn handshake(mut stream: S, passcode: &str, initiator: bool) -> Result<Self, SecureError> {
+        let salt = if initiator {
+            let mut salt = [0; SALT_LENGTH];
+            getrandom::getrandom(&mut salt).map_err(io::Error::from)?;
+            write_frame(&mut stream, &salt)?;
+
+            salt
+        } else {
+            let frame = read_frame(&mut stream)?;
+            <[u8; SALT_LENGTH]>::try_from(frame.as_slice()).map_err(|_| SecureError::InvalidSalt(frame.len()))?
+        };
+
+        let key = passcode_key(passcode, &salt)?;
+        let builder = Builder::new(NOISE_PATTERN.parse()?).psk(0, &key);
+        let mut state = if initiator { builder.build_initiator()? } else { builder.build_responder()? };
+        let mut buffer = vec![0; MAX_FRAME];
+
+        while !state.is_handshake_finished() {
+            if state.is_my_turn() {
+                let length = state.write_message(&[], &mut buffer)?;
+                write_frame(&mut stream, &buffer[..length])?;
+            } else {
+                let frame = match read_frame(&mut stream) {
+                    Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Err(SecureError::WrongPasscode),
+                    frame => frame?,
+                };
+
+                state.read_message(&frame, &mut buffer).map_err(|_| SecureError::WrongPasscode)?;
+            }
+        }
+
+        return Ok(SecureChannel {
+            stream,
+            transport: state.into_transport_mode()?,
+            buffer,
+        });
+    }
+}
+
+fn passcode_key(passcode: &str, salt: &[u8; SALT_LENGTH]) -> Result<[u8; 32], SecureError> {
+    let params = Params::new(KDF_MEMORY_KIB, KDF_PASSES, 1, Some(32)).map_err(SecureError::Kdf)?;
+    let mut key = [0; 32];
+
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passcode.as_bytes(), salt, &mut key)
+        .map_err(SecureError::Kdf)?;
+
+    return Ok(key);
+}
+
+fn write_frame<W: Write>(writer: &mut W, frame: &[u8]) -> io::Result<()> {
+    writer.write_all(&(frame.len() as u16).to_be_bytes())?;
+    writer.write_all(frame)?;
+    return writer.flush();
+}
+
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut length = [0; 2];
+    reader.read_exact(&mut length)?;
+
+    let mut frame = vec![0; u16::from_be_bytes(length) as usize];
+    reader.read_exact(&mut frame)?;
+
+    return Ok(frame);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        net::{TcpListener, TcpStream},
+        thread,
+    };
+
+    type Connection = Result<SecureChannel<TcpStream>, SecureError>;
+
+    fn connect(host_passcode: &'static str, guest_passcode: &str) -> (Connection, Connection) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let host = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            return SecureChannel::accept(stream, host_passcode);
+        });
+
+        let guest = SecureChannel::connect(TcpStream::connect(address).unwrap(), guest_passcode);
+
+        return (host.join().unwrap(), guest);
+    }
+
+    #[test]
+    fn messages_after_handshake() {
+        let (host, guest) = connect("knight f3", "knight f3");
+        let (mut host, mut guest) = (host.unwrap(), guest.unwrap());
+
+        guest.send(b"e2e4").unwrap();
+        assert_eq!(b"e2e4".to_vec(), host.receive().unwrap());
+
+        host.send(b"e7e5").unwrap();
+        assert_eq!(b"e7e5".to_vec(), guest.receive().unwrap());
+
+        assert!(matches!(host.send(&vec![0; MAX_FRAME]), Err(SecureError::TooLong(_))));
+    }
+
+    #[test]
+    fn wrong_passcode() {
+        let (host, guest) = connect("knight f3", "bishop c4");
+
+        assert!(matches!(host, Err(SecureError::WrongPasscode)));
+        assert!(matches!(guest, Err(SecureError::WrongPasscode)));
+    }
+
+    #[test]
+    fn salted_passcode_keys() {
+        let key = passcode_key("knight f3", &[0; SALT_LENGTH]).unwrap();
+
+        assert_eq!(key, passcode_key("knight f3", &[0; SALT_LENGTH]).unwrap());
+        assert_ne!(key, passcode_key("knight f3", &[1; SALT_LENGTH]).unwrap());
+        assert_ne!(key, passcode_key("bishop c4", &[0; SALT_LENGTH]).unwrap());
+    }
+}
@@ -1,37 +1,47 @@
-use std::{env, time::Instant};
+use std::{env, fs, time::Instant};
 
-use chess::{Board, PieceType, Move};
+use chess::{
+    engine::{Engine, ExternalEngine, UciEngine, BENCH_DEPTH},
+    perft::{self, PerftReference, PerftTable},
+    render::{self, RenderOptions},
+    Board, Color, Move, MoveList, PieceType,
+};
 use rayon::prelude::*;
 
+// the number of times the moves of a position are generated in movegen mode, unless another count is given
+const MOVEGEN_REPETITIONS: usize = 100_000;
+const POSITION_PERFT_DEPTH: usize = 4;
+
 fn test_move_count(depth: usize, board: &mut Board, log: bool) -> u128 {
     if depth == 0 {
         return 1;
     }
 
-    let moves = chess::get_moves(board.turn(), &board);
+    let mut moves = MoveList::new();
+    chess::get_moves_into(board.turn(), board, &mut moves);
 
     return moves
-        .into_par_iter()
+        .par_iter()
         .map(|mv| {
             if mv.promotion {
                 return [
                     {
-                        let mut mv = mv.clone();
+                        let mut mv = *mv;
                         mv.promote_to = PieceType::Rook;
                         mv
                     },
                     {
-                        let mut mv = mv.clone();
+                        let mut mv = *mv;
                         mv.promote_to = PieceType::Bishop;
                         mv
                     },
                     {
-                        let mut mv = mv.clone();
+                        let mut mv = *mv;
                         mv.promote_to = PieceType::Knight;
                         mv
                     },
                     {
-                        let mut mv = mv.clone();
+                        let mut mv = *mv;
                         mv.promote_to = PieceType::Queen;
                         mv
                     },
@@ -40,7 +50,7 @@ fn test_move_count(depth: usize, board: &mut Board, log: bool) -> u128 {
                 .map(|pmv| test_move_count_iter(&mut board.clone(), &pmv, depth, log))
                 .sum();
             } else {
-                return test_move_count_iter(&mut board.clone(), &mv, depth, log);
+                return test_move_count_iter(&mut board.clone(), mv, depth, log);
             }
         })
         .sum();
@@ -73,8 +83,211 @@ fn test_move_count_depth(depth: usize) {
     println!("took {} ms", duration.as_millis());
 }
 
+fn bench(depth: u8) {
+    println!("running bench at depth {depth} ...");
+
+    let result = Engine::new().bench(depth);
+
+    println!("nodes {}", result.nodes);
+    println!("took {} ms", result.elapsed.as_millis());
+    println!("nps {}", result.nps());
+}
+
+// compares the divide against a uci engine or the bundled table and walks down to the first position that differs
+fn perft_diff(fen: &str, depth: usize, engine: Option<&String>) {
+    let board = if fen == "startpos" { Board::new_game() } else { Board::from_fen(fen).unwrap() };
+
+    let mut reference: Box<dyn PerftReference> = match engine {
+        Some(path) => Box::new(
+            UciEngine::start(&ExternalEngine {
+                path: path.into(),
+                args: Vec::new(),
+                depth: 1,
+                options: Vec::new(),
+            })
+            .unwrap(),
+        ),
+        None => Box::new(PerftTable::bundled()),
+    };
+
+    match perft::find_divergence(&board, depth, reference.as_mut()) {
+        Ok(None) => println!("no divergence at depth {depth}"),
+        Ok(Some(divergence)) => {
+            println!("diverges after {} at depth {}", divergence.path.join(" "), divergence.depth);
+            println!("fen {}", divergence.fen);
+
+            for mv in divergence.missing {
+                println!("missing {mv}");
+            }
+
+            for mv in divergence.extra {
+                println!("extra {mv}");
+            }
+
+            for (mv, ours, theirs) in divergence.mismatched {
+                println!("{mv}: {ours} expected {theirs}");
+            }
+        }
+        Err(err) => println!("{err}"),
+    }
+}
+
+// one fen per line, blank lines and lines starting with # are skipped, the csv goes to stdout
+fn eval_batch(path: &str, depth: u8) {
+    let text = fs::read_to_string(path).unwrap();
+    let fens = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')).collect::<Vec<_>>();
+    let boards = fens.iter().map(|fen| Board::from_fen(fen).unwrap()).collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let results = Engine::new().evaluate_batch(&boards, depth);
+
+    println!("fen,score,best_move,depth,nodes");
+
+    for (fen, result) in fens.iter().zip(results) {
+        let best_move = result.best_move.map(|mv| mv.to_string()).unwrap_or_default();
+        println!("{},{},{},{},{}", fen, result.score, best_move, result.depth, result.nodes);
+    }
+
+    eprintln!("evaluated {} positions in {} ms", boards.len(), start.elapsed().as_millis());
+}
+
+// one position per line as a fen or an epd line, whose operations are ignored, blank lines and lines starting with # are skipped
+fn read_positions(path: &str) -> Vec<(String, Board)> {
+    let text = fs::read_to_string(path).unwrap();
+
+    return text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let counters = fields.len() >= 6 && fields[4..6].iter().all(|field| field.parse::<u32>().is_ok());
+            let fen = fields[..if counters { 6 } else { 4 }].join(" ");
+            let board = Board::from_fen(&fen).unwrap_or_else(|err| panic!("invalid position '{line}': {err}"));
+
+            return (fen, board);
+        })
+        .collect();
+}
+
+// times every position of the file on its own, the csv goes to stdout, for movegen the depth is the number of repetitions
+fn profile_positions(path: &str, mode: &str, depth: Option<usize>, flamegraph: Option<&str>) {
+    let positions = read_positions(path);
+    let mut engine = Engine::new();
+
+    println!("fen,mode,depth,nodes,ms,nps");
+
+    profiled(flamegraph, || {
+        for (fen, board) in &positions {
+            let start = Instant::now();
+
+            let (depth, nodes) = match mode {
+                "perft" => {
+                    let depth = depth.unwrap_or(POSITION_PERFT_DEPTH);
+                    (depth, perft::perft(&mut board.clone(), depth))
+                }
+                "search" => {
+                    let depth = depth.unwrap_or(BENCH_DEPTH as usize);
+                    engine.new_game();
+                    (depth, engine.search(board, depth as u8).nodes)
+                }
+                "movegen" => {
+                    let repetitions = depth.unwrap_or(MOVEGEN_REPETITIONS);
+                    let mut moves = MoveList::new();
+                    let mut generated = 0;
+
+                    for _ in 0..repetitions {
+                        chess::get_moves_into(board.turn(), board, &mut moves);
+                        generated += moves.len() as u64;
+                    }
+
+                    (repetitions, generated)
+                }
+                _ => panic!("unknown mode '{mode}', expected perft, search or movegen"),
+            };
+
+            let elapsed = start.elapsed();
+            let nps = (nodes as f64 / elapsed.as_secs_f64().max(1e-9)) as u64;
+
+            println!("{},{},{},{},{:.3},{}", fen, mode, depth, nodes, elapsed.as_secs_f64() * 1000.0, nps);
+        }
+    });
+}
+
+#[cfg(feature = "flamegraph")]
+fn profiled(output: Option<&str>, run: impl FnOnce()) {
+    let Some(output) = output else {
+        return run();
+    };
+
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(1000)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .unwrap();
+
+    run();
+
+    let report = guard.report().build().unwrap();
+    report.flamegraph(fs::File::create(output).unwrap()).unwrap();
+
+    eprintln!("flamegraph written to {output}");
+}
+
+#[cfg(not(feature = "flamegraph"))]
+fn profiled(output: Option<&str>, run: impl FnOnce()) {
+    if output.is_some() {
+        eprintln!("built without the flamegraph feature, no flamegraph is written");
+    }
+
+    run();
+}
+
+fn show(fen: &str, perspective: Color) {
+    let board = Board::from_fen(fen).unwrap();
+    let options = RenderOptions {
+        colored: true,
+        perspective,
+        all_edges: true,
+        ..RenderOptions::unicode()
+    };
+
+    print!("{}", render::render(&board, &options));
+}
+
 fn main() {
     let args = env::args().collect::<Vec<_>>();
+
+    if args[1] == "show" {
+        let perspective = if args.get(3).is_some_and(|side| side == "black") { Color::Black } else { Color::White };
+        show(&args[2], perspective);
+        return;
+    }
+
+    if args[1] == "bench" {
+        let depth = args.get(2).map(|depth| depth.parse::<u8>().unwrap()).unwrap_or(BENCH_DEPTH);
+        bench(depth);
+        return;
+    }
+
+    if args[1] == "eval-batch" {
+        let depth = args.get(3).map(|depth| depth.parse::<u8>().unwrap()).unwrap_or(BENCH_DEPTH);
+        eval_batch(&args[2], depth);
+        return;
+    }
+
+    if args[1] == "positions" {
+        let flamegraph = args.iter().position(|arg| arg == "--flamegraph").map(|index| args[index + 1].as_str());
+        let depth = args.get(4).filter(|arg| !arg.starts_with("--")).map(|depth| depth.parse::<usize>().unwrap());
+        profile_positions(&args[2], &args[3], depth, flamegraph);
+        return;
+    }
+
+    if args[1] == "perft-diff" {
+        perft_diff(&args[2], args[3].parse::<usize>().unwrap(), args.get(4));
+        return;
+    }
+
     let depth = args[1].parse::<usize>().unwrap();
 
     test_move_count_depth(depth);
@@ -0,0 +1,185 @@
+use std::env;
+
+use chess::{
+    engine::{Engine, MATE_SCORE},
+    expand_promotions, get_moves, Board, Move,
+};
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Response, Server};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+const DEFAULT_DEPTH: u8 = 6;
+const MAX_DEPTH: u8 = 12;
+
+// scores this close to the mate score are mates, the distance is the number of plies
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1000;
+
+#[derive(Debug, thiserror::Error)]
+enum ApiError {
+    #[error("Unknown endpoint {0} {1}")]
+    NotFound(Method, String),
+
+    #[error("Invalid request body: {0}")]
+    InvalidBody(#[from] serde_json::Error),
+
+    #[error("Invalid fen: {0}")]
+    InvalidFen(String),
+
+    #[error("The depth has to be between 1 and {MAX_DEPTH}")]
+    InvalidDepth,
+}
+
+impl ApiError {
+    fn status(&self) -> u16 {
+        match self {
+            ApiError::NotFound(..) => 404,
+            _ => 400,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeRequest {
+    fen: String,
+    #[serde(default = "default_depth")]
+    depth: u8,
+}
+
+fn default_depth() -> u8 {
+    return DEFAULT_DEPTH;
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegalMovesRequest {
+    fen: String,
+}
+
+// the score is in centipawns from the view of the side to move, mates are given in moves instead,
+// negative when the side to move gets mated
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeResponse {
+    score: Option<i32>,
+    mate: Option<i32>,
+    best_move: Option<String>,
+    pv: Vec<String>,
+    depth: u8,
+    nodes: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LegalMovesResponse {
+    moves: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorResponse {
+    error: String,
+}
+
+fn parse_board(fen: &str) -> Result<Board, ApiError> {
+    return Board::from_fen(fen).map_err(|err| ApiError::InvalidFen(err.to_string()));
+}
+
+fn uci(moves: &[Move]) -> Vec<String> {
+    return moves.iter().map(Move::to_string).collect();
+}
+
+// every request searches with a fresh engine, nothing is kept between requests
+fn analyze(request: AnalyzeRequest) -> Result<AnalyzeResponse, ApiError> {
+    if !(1..=MAX_DEPTH).contains(&request.depth) {
+        return Err(ApiError::InvalidDepth);
+    }
+
+    let board = parse_board(&request.fen)?;
+    let result = Engine::new().search(&board, request.depth);
+
+    let mate = match result.score {
+        score if score > MATE_THRESHOLD => Some((MATE_SCORE - score + 1) / 2),
+        score if score < -MATE_THRESHOLD => Some(-(MATE_SCORE + score) / 2),
+        _ => None,
+    };
+
+    return Ok(AnalyzeResponse {
+        score: mate.is_none().then_some(result.score),
+        mate,
+        best_move: result.best_move.map(|mv| mv.to_string()),
+        pv: uci(&result.pv),
+        depth: result.depth,
+        nodes: result.nodes,
+    });
+}
+
+fn legal_moves(request: LegalMovesRequest) -> Result<LegalMovesResponse, ApiError> {
+    let board = parse_board(&request.fen)?;
+    let moves = expand_promotions(&get_moves(board.turn(), &board));
+
+    return Ok(LegalMovesResponse { moves: uci(&moves) });
+}
+
+fn handle(method: &Method, url: &str, body: &str) -> Result<String, ApiError> {
+    return match (method, url) {
+        (Method::Post, "/analyze") => Ok(serde_json::to_string(&analyze(serde_json::from_str(body)?)?)?),
+        (Method::Post, "/legal-moves") => Ok(serde_json::to_string(&legal_moves(serde_json::from_str(body)?)?)?),
+        _ => Err(ApiError::NotFound(method.clone(), url.to_string())),
+    };
+}
+
+fn main() {
+    let address = env::args().nth(1).unwrap_or(DEFAULT_ADDRESS.to_string());
+    let server = Server::http(&address).expect("address to be available");
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+
+    println!("listening on http://{address}");
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+
+        let (status, json) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => match handle(request.method(), request.url(), &body) {
+                Ok(json) => (200, json),
+                Err(err) => (err.status(), serde_json::to_string(&ErrorResponse { error: err.to_string() }).unwrap()),
+            },
+            Err(err) => (400, serde_json::to_string(&ErrorResponse { error: err.to_string() }).unwrap()),
+        };
+
+        let response = Response::from_string(json).with_status_code(status).with_header(content_type.clone());
+
+        if let Err(err) = request.respond(response) {
+            eprintln!("failed to respond: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn analyze_finds_mate() {
+        let json = handle(&Method::Post, "/analyze", r#"{"fen": "6k1/5ppp/8/8/8/8/8/R5K1 w - -", "depth": 3}"#).unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!("a1a8", response["bestMove"]);
+        assert_eq!(1, response["mate"]);
+        assert!(response["score"].is_null());
+    }
+
+    #[test]
+    fn legal_moves_and_errors() {
+        let json = handle(&Method::Post, "/legal-moves", r#"{"fen": "8/4P3/8/8/8/8/8/k1K5 w - -"}"#).unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let moves = response["moves"].as_array().unwrap();
+
+        assert_eq!(7, moves.len());
+        assert!(moves.iter().any(|mv| mv == "e7e8n"));
+
+        assert!(matches!(handle(&Method::Post, "/analyze", r#"{"fen": "8/8/8 w"}"#), Err(ApiError::InvalidFen(_))));
+        assert!(matches!(handle(&Method::Post, "/analyze", r#"{"fen": "8/8/8/8/8/8/8/k1K5 w - -", "depth": 40}"#), Err(ApiError::InvalidDepth)));
+        assert!(matches!(handle(&Method::Get, "/analyze", ""), Err(ApiError::NotFound(..))));
+    }
+}
@@ -0,0 +1,169 @@
+use std::{env, fs, path::PathBuf};
+
+use chess::{
+    bot::BotKind,
+    engine::{BotConfig, EngineConfig, ExternalEngine, Participant, PlayerConfig, Pruning, RoundRobin, Sprt, SprtDecision, Tournament},
+    Board,
+};
+
+fn read_openings(path: &str) -> Vec<Board> {
+    let content = fs::read_to_string(path).expect("openings file to be readable");
+
+    return content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|fen| Board::from_fen(fen).expect("openings to be valid fens"))
+        .collect();
+}
+
+// a builtin engine is given as <depth>, optionally followed by the pruning techniques to switch off,
+// like 5:lmr,nullmove,futility,delta
+fn parse_builtin(spec: &str) -> (EngineConfig, String) {
+    let (depth, disabled) = spec.split_once(':').unwrap_or((spec, ""));
+    let depth = depth.parse::<u8>().expect("depth to be a number");
+    let mut pruning = Pruning::default();
+    let mut name = format!("builtin d{}", depth);
+
+    for technique in disabled.split(',').filter(|technique| !technique.is_empty()) {
+        match technique {
+            "nullmove" => pruning.null_move = false,
+            "lmr" => pruning.late_move_reductions = false,
+            "futility" => pruning.futility = false,
+            "delta" => pruning.delta = false,
+            _ => panic!("unknown pruning technique '{}'", technique),
+        }
+
+        name.push_str(&format!(" -{}", technique));
+    }
+
+    return (EngineConfig { depth, threads: 1, pruning }, name);
+}
+
+// engines are given as builtin:<depth>[:<disabled pruning>], uci:<path>:<depth> or bot:<random|greedy>[:<seed>]
+fn parse_participant(spec: &str) -> Participant {
+    if let Some(spec) = spec.strip_prefix("bot:") {
+        let (id, seed) = spec.split_once(':').unwrap_or((spec, "0"));
+        let kind = BotKind::ALL.into_iter().find(|kind| kind.info().id == id).expect("bot to be random or greedy");
+        let seed = seed.parse::<u64>().expect("seed to be a number");
+
+        return Participant {
+            name: format!("{} bot", id),
+            player: PlayerConfig::Bot(BotConfig { kind, seed }),
+        };
+    }
+
+    if let Some(spec) = spec.strip_prefix("builtin:") {
+        let (config, name) = parse_builtin(spec);
+
+        return Participant {
+            name,
+            player: PlayerConfig::Builtin(config),
+        };
+    }
+
+    let (path, depth) = spec
+        .strip_prefix("uci:")
+        .and_then(|spec| spec.rsplit_once(':'))
+        .expect("engine to be builtin:<depth> or uci:<path>:<depth>");
+
+    let path = PathBuf::from(path);
+    let depth = depth.parse::<u8>().expect("depth to be a number");
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+
+    return Participant {
+        name: format!("{} d{}", stem, depth),
+        player: PlayerConfig::External(ExternalEngine {
+            path,
+            args: Vec::new(),
+            depth,
+            options: Vec::new(),
+        }),
+    };
+}
+
+fn round_robin(args: &[String]) {
+    let mut openings = Vec::new();
+    let mut pgn_path = None;
+    let mut specs = Vec::new();
+    let mut args = args.iter();
+
+    let games = args.next().and_then(|games| games.parse::<usize>().ok()).expect("games per pairing to be a number");
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--openings" => openings = read_openings(args.next().expect("openings file")),
+            "--pgn" => pgn_path = Some(args.next().expect("pgn file").clone()),
+            spec => specs.push(parse_participant(spec)),
+        }
+    }
+
+    let tournament = RoundRobin::new(specs, openings, games);
+
+    let results = tournament
+        .run(|game| {
+            println!("game {}: {} - {} {}", game.round, game.white + 1, game.black + 1, game.result());
+        })
+        .unwrap();
+
+    println!();
+    print!("{}", results.table());
+
+    if let Some(path) = pgn_path {
+        fs::write(&path, results.pgn()).expect("pgn file to be writable");
+        println!("\ngames written to {}", path);
+    }
+}
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+
+    if args.get(1).map(String::as_str) == Some("round-robin") && args.len() > 4 {
+        round_robin(&args[2..]);
+        return;
+    }
+
+    if args.len() < 4 {
+        eprintln!("usage: {} <games> <depth first>[:<disabled pruning>] <depth second>[:<disabled pruning>] [openings file]", args[0]);
+        eprintln!("       {} round-robin <games per pairing> <engine> <engine>... [--openings file] [--pgn file]", args[0]);
+        return;
+    }
+
+    let games = args[1].parse::<usize>().unwrap();
+    let (first, _) = parse_builtin(&args[2]);
+    let (second, _) = parse_builtin(&args[3]);
+    let openings = args.get(4).map(|path| read_openings(path)).unwrap_or_default();
+
+    let sprt = Sprt::default();
+    let mut tournament = Tournament::new(first, second, openings, games);
+    tournament.set_sprt(Some(sprt));
+
+    let stats = tournament
+        .run(|game, outcome, stats| {
+            println!(
+                "game {}: {:?} | +{} -{} ={} | elo {:+.1} | llr {:.2}",
+                game + 1,
+                outcome,
+                stats.wins,
+                stats.losses,
+                stats.draws,
+                stats.elo_difference(),
+                sprt.llr(stats)
+            );
+        })
+        .unwrap();
+
+    let (lower, upper) = sprt.bounds();
+
+    println!();
+    println!("games: {}", stats.games());
+    println!("score: +{} -{} ={} ({:.1}%)", stats.wins, stats.losses, stats.draws, stats.score() * 100.0);
+    println!("elo difference: {:+.1}", stats.elo_difference());
+    println!("sprt [{}, {}]: llr {:.2} ({:.2}, {:.2})", sprt.elo0, sprt.elo1, sprt.llr(&stats), lower, upper);
+
+    match sprt.decision(&stats) {
+        SprtDecision::AcceptH0 => println!("H0 accepted"),
+        SprtDecision::AcceptH1 => println!("H1 accepted"),
+        SprtDecision::Continue => println!("inconclusive"),
+    }
+}
@@ -1,30 +1,1100 @@
 import { invoke } from "@tauri-apps/api/tauri";
-import { Color, Coord, Move, Piece } from "./chess";
+import { Color, Coord, Move, Piece, PieceType } from "./chess";
+
+export type Variant = 'standard' | 'antichess' | 'horde' | 'racingKings';
+
+export type Visibility =
+    | { type: 'full' }
+    | { type: 'blindfold' }
+    | { type: 'ownPieces'; color: Color }
+    | { type: 'silhouettes' };
 
 export interface BoardPayload {
+    variant: Variant;
     turn: Color;
     pieces: Piece[];
+    occupied: Coord[];
+    visibility: Visibility;
     whiteChecked: boolean;
     blackChecked: boolean;
     winner?: string;
+    orientation: Color;
+    files: string[];
+    ranks: number[];
 }
 
 export async function getBoard(): Promise<BoardPayload> {
     return await invoke<BoardPayload>('get_board_cmd');
 }
 
+export type Access = 'readWrite' | 'readOnly';
+
+export async function getSessionAccess() {
+    return await invoke<Access>('get_session_access');
+}
+
+export async function openReadOnlySession(label: string) {
+    return await invoke('open_read_only_session', { label });
+}
+
+export async function closeReadOnlySession(label: string) {
+    return await invoke('close_read_only_session', { label });
+}
+
+export async function flipBoard() {
+    return await invoke('flip_board');
+}
+
+export async function setVisibility(visibility: Visibility) {
+    return await invoke('set_visibility', { visibility });
+}
+
 export async function getAvailableMoves(coord: Coord) {
     return await invoke<Move[]>('get_available_moves', { coord });
 }
 
+export interface ArbiterSettings {
+    touchMove: boolean;
+    illegalMoveLimit: number;
+}
+
+export interface ArbiterStatus {
+    settings: ArbiterSettings;
+    touched?: Coord;
+    whiteIllegalMoves: number;
+    blackIllegalMoves: number;
+    forfeited?: Color;
+}
+
+export async function getArbiter() {
+    return await invoke<ArbiterStatus | null>('get_arbiter');
+}
+
+export async function setArbiter(settings?: ArbiterSettings) {
+    return await invoke<ArbiterStatus | null>('set_arbiter', { settings });
+}
+
+export async function touchPiece(coord: Coord) {
+    return await invoke<Move[]>('touch_piece', { coord });
+}
+
+export async function getMoveTargets(coord: Coord) {
+    return await invoke<Coord[]>('get_move_targets', { coord });
+}
+
 export async function executeMove(move: Move) {
     return await invoke<Move[]>('exec_move', { mv: move });
 }
 
+export async function parseMoveInput(text: string) {
+    return await invoke<Move>('parse_move_input', { text });
+}
+
+export async function parseSpokenMove(phrase: string) {
+    return await invoke<Move>('parse_spoken_move', { phrase });
+}
+
+// without a piece a promotion is not played unless auto queen is on, the phase waits for the piece instead
+export async function tryMove(from: Coord, to: Coord, promotion?: PieceType) {
+    return await invoke<Move | null>('try_move', { from, to, promotion });
+}
+
 export async function applyFen(fen: string) {
     return await invoke<Move[]>('apply_fen', { fen });
 }
 
+export async function copyFen() {
+    return await invoke<string>('copy_fen');
+}
+
+export async function copyPgn() {
+    return await invoke<string>('copy_pgn');
+}
+
+export async function pasteFenOrPgn(text: string) {
+    return await invoke('paste_fen_or_pgn', { text });
+}
+
+export interface DiscoveredGame {
+    name: string;
+    address: string;
+}
+
+export async function startAdvertising(name: string, port: number) {
+    return await invoke('start_advertising', { name, port });
+}
+
+export async function stopAdvertising() {
+    return await invoke('stop_advertising');
+}
+
+export async function discoverGames(timeoutMs: number = 2000) {
+    return await invoke<DiscoveredGame[]>('discover_games', { timeoutMs });
+}
+
+export interface OverlaySettings {
+    directory: string | null;
+    port: number | null;
+    image?: AnimationOptions;
+}
+
+export async function startOverlay(settings: OverlaySettings) {
+    return await invoke('start_overlay', { settings });
+}
+
+export async function stopOverlay() {
+    return await invoke('stop_overlay');
+}
+
+export interface ChatMessage {
+    game: number;
+    from: Color;
+    text: string;
+    sentMs: number;
+}
+
+export async function getChat() {
+    return await invoke<ChatMessage[]>('get_chat');
+}
+
+export async function sendChat(from: Color, text: string) {
+    return await invoke<ChatMessage>('send_chat', { from, text });
+}
+
+export interface MoveNode {
+    id: number;
+    mv: Move | null;
+    san: string;
+    parent: number | null;
+    children: number[];
+    comment: string | null;
+    nags: number[];
+}
+
+export interface MoveTree {
+    variant: Variant;
+    startFen: string;
+    nodes: MoveNode[];
+    current: number;
+}
+
+export async function getMoveTree() {
+    return await invoke<MoveTree>('get_move_tree');
+}
+
+export async function gotoMove(id: number) {
+    return await invoke('goto_move', { id });
+}
+
+export async function forkGame(ply: number) {
+    return await invoke('fork_game', { ply });
+}
+
+export async function leaveFork() {
+    return await invoke('leave_fork');
+}
+
+export async function enterVariation(index: number) {
+    return await invoke('enter_variation', { index });
+}
+
+export async function exitVariation() {
+    return await invoke('exit_variation');
+}
+
+export async function deleteVariation() {
+    return await invoke('delete_variation');
+}
+
+export async function promoteVariation() {
+    return await invoke('promote_variation');
+}
+
+export async function setMoveComment(id: number, comment: string | null) {
+    return await invoke('set_move_comment', { id, comment });
+}
+
+export async function setMoveNags(id: number, nags: number[]) {
+    return await invoke('set_move_nags', { id, nags });
+}
+
+export interface Chapter {
+    name: string;
+    tags: [string, string][];
+    tree: MoveTree;
+}
+
+export interface Study {
+    name: string;
+    chapters: Chapter[];
+}
+
+export interface OpenStudy {
+    study: Study;
+    chapter: number;
+}
+
+export async function getStudy() {
+    return await invoke<OpenStudy | null>('get_study');
+}
+
+export async function newStudy(name: string) {
+    return await invoke<OpenStudy>('new_study', { name });
+}
+
+export async function openStudy(path: string) {
+    return await invoke<OpenStudy>('open_study', { path });
+}
+
+export async function saveStudy(path: string) {
+    return await invoke('save_study', { path });
+}
+
+export async function addStudyChapter(name: string, fen?: string) {
+    return await invoke<OpenStudy>('add_study_chapter', { name, fen });
+}
+
+export async function selectStudyChapter(index: number) {
+    return await invoke<OpenStudy>('select_study_chapter', { index });
+}
+
+export async function removeStudyChapter(index: number) {
+    return await invoke<OpenStudy>('remove_study_chapter', { index });
+}
+
+export async function closeStudy() {
+    return await invoke('close_study');
+}
+
+export type Odds = 'pawnAndMove' | 'knight' | 'queen';
+
+export type IncrementMode = 'fischer' | 'bronstein' | 'delay';
+
+export interface TimeControl {
+    baseMs: number;
+    incrementMs: number;
+    mode?: IncrementMode;
+}
+
+export interface ClockSettings {
+    white: TimeControl;
+    black: TimeControl;
+}
+
+export interface ClockSnapshot {
+    settings: ClockSettings;
+    whiteMs: number;
+    blackMs: number;
+    turn: Color;
+    running: boolean;
+    flagged?: Color;
+}
+
+export async function newGame(variant: Variant, odds?: Odds, clock?: ClockSettings, bot?: string, rated: boolean = false, armageddon: boolean = false) {
+    return await invoke('new_game', { variant, odds, clock, bot, rated, armageddon });
+}
+
+export async function getClock() {
+    return await invoke<ClockSnapshot | null>('get_clock');
+}
+
+export interface MoveTime {
+    color: Color;
+    spentMs: number;
+    remainingMs: number;
+}
+
+export interface TimeUsage {
+    moves: number;
+    totalMs: number;
+    averageMs: number;
+    longestMs: number;
+    longestMove: number | null;
+}
+
+export interface TimeReport {
+    moves: MoveTime[];
+    white: TimeUsage;
+    black: TimeUsage;
+}
+
+export async function getTimeUsage() {
+    return await invoke<TimeReport | null>('get_time_usage');
+}
+
+export interface CorrespondenceGame {
+    daysPerMove: number;
+    turn: Color;
+    deadlineMs: number;
+    warned: boolean;
+    flagged?: Color;
+    pgn: string;
+}
+
+export async function getCorrespondence() {
+    return await invoke<CorrespondenceGame | null>('get_correspondence');
+}
+
+export async function startCorrespondence(daysPerMove: number) {
+    return await invoke<CorrespondenceGame>('start_correspondence', { daysPerMove });
+}
+
+export async function stopCorrespondence() {
+    return await invoke('stop_correspondence');
+}
+
+export type PositionCategory = 'opening' | 'endgame' | 'tactic';
+
+export interface NamedPosition {
+    id: string;
+    name: string;
+    category: PositionCategory;
+    description: string;
+    fen: string;
+}
+
+export async function listPositions() {
+    return await invoke<NamedPosition[]>('list_positions');
+}
+
+export interface BotInfo {
+    id: string;
+    name: string;
+    description: string;
+}
+
+export async function listBots() {
+    return await invoke<BotInfo[]>('list_bots');
+}
+
+export async function getBot() {
+    return await invoke<string | null>('get_bot');
+}
+
+export async function loadPosition(id: string) {
+    return await invoke('load_position', { id });
+}
+
+export type Locale = 'en' | 'de';
+
+export async function getLocale() {
+    return await invoke<Locale>('get_locale');
+}
+
+export async function setLocale(locale: Locale) {
+    return await invoke('set_locale', { locale });
+}
+
+export type TrainingKind = 'nameSquare' | 'knightPath' | 'findChecks';
+
+export type TrainingTask =
+    | { type: 'nameSquare'; coord: Coord }
+    | { type: 'knightPath'; from: Coord; to: Coord }
+    | { type: 'findChecks'; fen: string };
+
+export type TrainingAnswer =
+    | { type: 'square'; coord: Coord }
+    | { type: 'path'; squares: Coord[] }
+    | { type: 'moves'; moves: Move[] };
+
+export interface TrainingResult {
+    correct: boolean;
+    expected: TrainingAnswer;
+}
+
+export async function getTrainingTask(kind: TrainingKind) {
+    return await invoke<TrainingTask>('get_training_task', { kind });
+}
+
+export async function checkTrainingAnswer(task: TrainingTask, answer: TrainingAnswer) {
+    return await invoke<TrainingResult>('check_training_answer', { task, answer });
+}
+
+export type Ending = 'queenVsKing' | 'rookVsKing' | 'pawnVsKing';
+
+export interface EndgameTask {
+    ending: Ending;
+    fen: string;
+    plies: number;
+}
+
+export type EndgameVerdict =
+    | { type: 'mate' }
+    | { type: 'progress'; plies: number }
+    | { type: 'slower'; before: number; after: number }
+    | { type: 'winLost' };
+
+export async function startEndgameTraining(ending: Ending) {
+    return await invoke<EndgameTask>('start_endgame_training', { ending });
+}
+
+export async function playEndgameMove(mv: Move) {
+    return await invoke<EndgameVerdict>('play_endgame_move', { mv });
+}
+
+export type DrillVerdict =
+    | { type: 'inBook'; played: Move; reply: Move | null; complete: boolean }
+    | { type: 'deviation'; expected: Move[] };
+
+export interface LineProgress {
+    line: string;
+    player: Color;
+    completed: number;
+    deviations: number;
+}
+
+export interface DrillReport {
+    verdict: DrillVerdict;
+    progress: LineProgress | null;
+}
+
+export async function startOpeningDrill(player: Color, path?: string) {
+    return await invoke<Move | null>('start_opening_drill', { player, path });
+}
+
+export async function playDrillMove(mv: Move) {
+    return await invoke<DrillReport>('play_drill_move', { mv });
+}
+
+export async function getDrillProgress() {
+    return await invoke<LineProgress[]>('get_drill_progress');
+}
+
 export async function undo() {
     return await invoke('undo');
 }
+
+export type Phase =
+    | { phase: 'setup' }
+    | { phase: 'playing' }
+    | { phase: 'awaitingPromotion'; from: Coord; to: Coord }
+    | { phase: 'gameOver' }
+    | { phase: 'reviewing' };
+
+export async function getPhase() {
+    return await invoke<Phase>('get_phase');
+}
+
+export async function startSetup() {
+    return await invoke('start_setup');
+}
+
+export async function startReview() {
+    return await invoke('start_review');
+}
+
+export async function resumePlay() {
+    return await invoke('resume_play');
+}
+
+export async function cancelPromotion() {
+    return await invoke('cancel_promotion');
+}
+
+export type DrawReason = 'fiftyMoves' | 'repetition';
+
+export async function offerDraw(by: Color) {
+    return await invoke<boolean>('offer_draw', { by });
+}
+
+export async function acceptDraw() {
+    return await invoke('accept_draw');
+}
+
+export async function claimDraw(mv?: Move) {
+    return await invoke<DrawReason>('claim_draw', { mv });
+}
+
+export async function requestTakeback(by: Color) {
+    return await invoke('request_takeback', { by });
+}
+
+export async function acceptTakeback(by: Color) {
+    return await invoke('accept_takeback', { by });
+}
+
+export async function declineTakeback(by: Color) {
+    return await invoke('decline_takeback', { by });
+}
+
+export interface EngineSettings {
+    depth: number;
+    book: string | null;
+    hashMb: number;
+    threads: number;
+    contempt: number;
+    multiPv: number;
+    skillLevel: number;
+    ponder: boolean;
+    nullMove: boolean;
+    lateMoveReductions: boolean;
+    futilityPruning: boolean;
+    deltaPruning: boolean;
+    mcts: boolean;
+    conduct: ConductSettings;
+}
+
+export interface ConductSettings {
+    resign: boolean;
+    resignScore: number;
+    resignMoves: number;
+    draws: boolean;
+    drawScore: number;
+    drawMoves: number;
+    minDrawPly: number;
+}
+
+export interface PvLine {
+    score: number;
+    pv: Move[];
+}
+
+export interface IterationInfo {
+    depth: number;
+    score: number;
+    nodes: number;
+    timeMs: number;
+    pv: Move[];
+}
+
+export interface SearchStats {
+    nodes: number;
+    qnodes: number;
+    ttProbes: number;
+    ttHits: number;
+    ttCutoffs: number;
+    betaCutoffs: number;
+    firstMoveCutoffs: number;
+    standPatCutoffs: number;
+    nullMoveCutoffs: number;
+    futilityPrunes: number;
+    reductionResearches: number;
+    deltaPrunes: number;
+    iterations: IterationInfo[];
+}
+
+export async function engineMove() {
+    return await invoke<Move | null>('engine_move');
+}
+
+export async function cancelEngineMove() {
+    return await invoke('cancel_engine_move');
+}
+
+export interface Pruning {
+    nullMove: boolean;
+    lateMoveReductions: boolean;
+    futility: boolean;
+    delta: boolean;
+}
+
+export interface EngineConfig {
+    depth: number;
+    threads: number;
+    pruning?: Pruning;
+}
+
+export interface Adjudication {
+    resignScore: number;
+    resignMoves: number;
+    drawScore: number;
+    drawMoves: number;
+    maxMoves: number;
+}
+
+export interface AutoPlaySettings {
+    white: EngineConfig;
+    black: EngineConfig;
+    moveDelayMs: number;
+    adjudication: Adjudication;
+}
+
+export type GameOutcome =
+    | { type: 'checkmate'; winner: Color }
+    | { type: 'resignation'; winner: Color }
+    | { type: 'stalemate' }
+    | { type: 'drawAdjudicated' }
+    | { type: 'moveLimit' };
+
+export async function startAutoPlay(settings: AutoPlaySettings) {
+    return await invoke('start_auto_play', { settings });
+}
+
+export async function stopAutoPlay() {
+    return await invoke('stop_auto_play');
+}
+
+export interface PlaybackStatus {
+    playing: boolean;
+    delayMs: number;
+}
+
+export async function getPlayback() {
+    return await invoke<PlaybackStatus>('get_playback');
+}
+
+export async function startPlayback() {
+    return await invoke<PlaybackStatus>('start_playback');
+}
+
+export async function pausePlayback() {
+    return await invoke<PlaybackStatus>('pause_playback');
+}
+
+export async function setPlaybackSpeed(delayMs: number) {
+    return await invoke<PlaybackStatus>('set_playback_speed', { delayMs });
+}
+
+export interface PieceScore {
+    piece: Piece;
+    score: number;
+}
+
+export interface EvalBreakdown {
+    material: number;
+    pieceSquares: number;
+    pawnStructure: number;
+    kingSafety: number;
+    mobility: number;
+    total: number;
+    pieces: PieceScore[];
+}
+
+export async function explainEval() {
+    return await invoke<EvalBreakdown>('explain_eval');
+}
+
+export interface AnimationOptions {
+    squareSize?: number;
+    frameDelayMs?: number;
+    finalDelayMs?: number;
+    perspective?: Color;
+}
+
+export async function exportAnimation(path: string, options: AnimationOptions = {}) {
+    return await invoke('export_animation', { path, options });
+}
+
+export interface TraceLimits {
+    maxNodes?: number;
+    maxPly?: number;
+}
+
+export async function exportSearchTree(path: string, depth?: number, limits: TraceLimits = {}) {
+    return await invoke('export_search_tree', { path, depth, limits });
+}
+
+export interface GameSummary {
+    id: number;
+    white: string;
+    black: string;
+    date: string;
+    result: string;
+    eco?: string;
+    termination: Termination | null;
+}
+
+export type Termination = 'normal' | 'timeForfeit' | 'resignation' | 'agreement' | 'abandonment' | 'rulesInfraction';
+
+export interface GameFilter {
+    player?: string;
+    eco?: string;
+    result?: string;
+    search?: string;
+    limit?: number;
+}
+
+export async function saveGame(tags: [string, string][]) {
+    return await invoke<number>('save_game', { tags });
+}
+
+export interface ImportSummary {
+    imported: number;
+    skipped: number;
+}
+
+export async function importPgn(path: string) {
+    return await invoke<ImportSummary>('import_pgn', { path });
+}
+
+export async function importLichessGames(path: string) {
+    return await invoke<ImportSummary>('import_lichess_games', { path });
+}
+
+export async function listGames(filter: GameFilter = {}) {
+    return await invoke<GameSummary[]>('list_games', { filter });
+}
+
+export async function loadGame(id: number) {
+    return await invoke('load_game', { id });
+}
+
+export async function deleteGame(id: number) {
+    return await invoke<boolean>('delete_game', { id });
+}
+
+export async function buildBook(path: string, maxPlies: number, filter: GameFilter = {}) {
+    return await invoke<number>('build_book', { path, filter, maxPlies });
+}
+
+export interface ExplorerMove {
+    san: string;
+    uci: string;
+    count: number;
+    whiteWins: number;
+    draws: number;
+    blackWins: number;
+    score: number;
+}
+
+export interface ExplorerStats {
+    count: number;
+    moves: ExplorerMove[];
+}
+
+export async function getExplorerStats(fen: string) {
+    return await invoke<ExplorerStats>('get_explorer_stats', { fen });
+}
+
+export interface RatedGame {
+    engineDepth: number;
+    engineRating: number;
+    score: number;
+    ratingAfter: number;
+}
+
+export interface PlayerStats {
+    rating: number;
+    peakRating: number;
+    games: number;
+    wins: number;
+    draws: number;
+    losses: number;
+    history: RatedGame[];
+}
+
+export async function recordRatedGame(player: Color, resigned: boolean = false) {
+    return await invoke<PlayerStats>('record_rated_game', { player, resigned });
+}
+
+export async function getPlayerStats() {
+    return await invoke<PlayerStats>('get_player_stats');
+}
+
+export type MoveClass = 'good' | 'inaccuracy' | 'mistake' | 'blunder';
+
+export interface MoveReview {
+    ply: number;
+    color: Color;
+    played: Move;
+    best: Move | null;
+    scoreBefore: number;
+    scoreAfter: number;
+    loss: number;
+    class: MoveClass;
+}
+
+export interface GuessScore {
+    exact: boolean;
+    loss: number;
+    points: number;
+}
+
+export interface GuessStatus {
+    player: Color;
+    ply: number;
+    plies: number;
+    score: number;
+    maxScore: number;
+    guesses: number;
+    finished: boolean;
+}
+
+export async function startGuessTheMove(id: number, player: Color) {
+    return await invoke<GuessStatus>('start_guess_the_move', { id, player });
+}
+
+export async function guessMove(mv: Move) {
+    return await invoke<GuessScore>('guess_move', { mv });
+}
+
+export type MatchFormat = { kind: 'bestOf'; games: number } | { kind: 'firstTo'; games: number };
+
+export interface MatchSettings {
+    format: MatchFormat;
+    firstColor: Color;
+    variant: Variant;
+    clock: ClockSettings | null;
+    bot: string | null;
+}
+
+export interface MatchGame {
+    round: number;
+    playerColor: Color;
+    result: string;
+    pgn: string;
+}
+
+export interface MatchStatus {
+    settings: MatchSettings;
+    opponent: string;
+    games: MatchGame[];
+    playerScore: number;
+    opponentScore: number;
+    playerColor: Color;
+    finished: boolean;
+}
+
+export async function startMatch(settings: MatchSettings) {
+    return await invoke<MatchStatus>('start_match', { settings });
+}
+
+export async function recordMatchGame() {
+    return await invoke<MatchStatus>('record_match_game');
+}
+
+export async function getMatch() {
+    return await invoke<MatchStatus | null>('get_match');
+}
+
+export async function exportMatch(path: string) {
+    return await invoke('export_match', { path });
+}
+
+export async function endMatch() {
+    return await invoke('end_match');
+}
+
+export type TournamentFormat = { kind: 'roundRobin' } | { kind: 'swiss'; rounds: number };
+
+export interface TournamentSettings {
+    name: string;
+    players: string[];
+    format: TournamentFormat;
+    variant: Variant;
+    clock: ClockSettings | null;
+}
+
+export interface Pairing {
+    white: number;
+    black: number;
+    result: string | null;
+}
+
+export interface Round {
+    number: number;
+    pairings: Pairing[];
+    bye: number | null;
+}
+
+export interface Standing {
+    rank: number;
+    player: number;
+    name: string;
+    points: number;
+    wins: number;
+    draws: number;
+    losses: number;
+    buchholz: number;
+    sonnebornBerger: number;
+}
+
+export interface TournamentStatus {
+    settings: TournamentSettings;
+    rounds: Round[];
+    totalRounds: number;
+    standings: Standing[];
+    finished: boolean;
+}
+
+export async function createTournament(settings: TournamentSettings) {
+    return await invoke<TournamentStatus>('create_tournament', { settings });
+}
+
+export async function nextTournamentRound() {
+    return await invoke<TournamentStatus>('next_tournament_round');
+}
+
+export async function playTournamentGame(pairing: number) {
+    return await invoke('play_tournament_game', { pairing });
+}
+
+export async function recordTournamentGame() {
+    return await invoke<TournamentStatus>('record_tournament_game');
+}
+
+export async function setTournamentResult(pairing: number, result: string) {
+    return await invoke<TournamentStatus>('set_tournament_result', { pairing, result });
+}
+
+export async function getTournament() {
+    return await invoke<TournamentStatus | null>('get_tournament');
+}
+
+export async function exportCrossTable(path: string) {
+    return await invoke('export_cross_table', { path });
+}
+
+export async function endTournament() {
+    return await invoke('end_tournament');
+}
+
+export interface SimulSettings {
+    boards: number;
+    engineColor: Color;
+    roundTimeMs: number;
+}
+
+export type SimulResult = 'engineWon' | 'userWon' | 'draw';
+
+export interface SimulBoardStatus {
+    index: number;
+    fen: string;
+    turn: Color;
+    lastMove: Move | null;
+    result: SimulResult | null;
+}
+
+export interface SimulProgress {
+    settings: SimulSettings;
+    boards: SimulBoardStatus[];
+    engineWins: number;
+    userWins: number;
+    draws: number;
+    waitingForEngine: number;
+    finished: boolean;
+}
+
+export async function startSimul(settings: SimulSettings) {
+    return await invoke<SimulProgress>('start_simul', { settings });
+}
+
+export async function playSimulMove(index: number, mv: Move) {
+    return await invoke<SimulProgress>('play_simul_move', { index, mv });
+}
+
+export async function getSimulProgress() {
+    return await invoke<SimulProgress | null>('get_simul_progress');
+}
+
+export async function stopSimul() {
+    return await invoke('stop_simul');
+}
+
+export interface StoredMistake {
+    game: number;
+    ply: number;
+    fen: string;
+    played: Move;
+    best: Move;
+    loss: number;
+    attempts: number;
+    solved: number;
+    streak: number;
+}
+
+export interface MistakeTask {
+    index: number;
+    mistake: StoredMistake;
+}
+
+export interface MistakeAttempt {
+    correct: boolean;
+    best: Move;
+    mistake: StoredMistake;
+}
+
+export async function reviewGameMistakes(id: number, player: Color, depth?: number) {
+    return await invoke<MoveReview[]>('review_game_mistakes', { id, player, depth });
+}
+
+export async function getMistakes() {
+    return await invoke<StoredMistake[]>('get_mistakes');
+}
+
+export async function startMistakePuzzle(index?: number) {
+    return await invoke<MistakeTask>('start_mistake_puzzle', { index });
+}
+
+export async function attemptMistakePuzzle(index: number, mv: Move) {
+    return await invoke<MistakeAttempt>('attempt_mistake_puzzle', { index, mv });
+}
+
+export interface BoardTheme {
+    id: string;
+    name: string;
+    light: string;
+    dark: string;
+}
+
+export interface PieceSet {
+    id: string;
+    name: string;
+    path: string;
+    extension: string;
+}
+
+export interface Appearance {
+    theme: string;
+    pieceSet: string;
+}
+
+export interface ThemeCatalog {
+    themes: BoardTheme[];
+    pieceSets: PieceSet[];
+    selected: Appearance;
+}
+
+export async function listThemes() {
+    return await invoke<ThemeCatalog>('list_themes');
+}
+
+export async function setTheme(theme: string, pieceSet: string) {
+    return await invoke<Appearance>('set_theme', { theme, pieceSet });
+}
+
+export interface MovePreferences {
+    autoQueen: boolean;
+    confirmMoves: boolean;
+    premoves: boolean;
+    legalMoveDots: boolean;
+}
+
+export async function getMovePreferences() {
+    return await invoke<MovePreferences>('get_move_preferences');
+}
+
+export async function setMovePreferences(preferences: MovePreferences) {
+    return await invoke<MovePreferences>('set_move_preferences', { preferences });
+}
+
+export async function getEngineSettings() {
+    return await invoke<EngineSettings>('get_engine_settings');
+}
+
+export interface MemoryUsage {
+    slidingTables: number;
+    zobristKeys: number;
+    tablebases: number;
+    transpositionTable: number;
+    searchTables: number;
+    total: number;
+}
+
+export async function getEngineMemory() {
+    return await invoke<MemoryUsage>('get_engine_memory');
+}
+
+export async function setEngineSettings(settings: EngineSettings) {
+    return await invoke('set_engine_settings', { settings });
+}
+
+export async function setEngineOption(name: string, value: string) {
+    return await invoke<EngineSettings>('set_engine_option', { name, value });
+}
+
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+export async function setLogLevel(level: LogLevel) {
+    return await invoke('set_log_level', { level });
+}
@@ -1,56 +1,59 @@
 import { useEffect, useState } from "react";
-import { Color, Coord, Move, Piece, toCoordFromXY } from "../chess";
-import { BoardPayload, executeMove, getAvailableMoves, getBoard } from "../commands";
+import { Color, Coord, Move, Piece } from "../chess";
+import { WebviewWindow } from "@tauri-apps/api/window";
+import {
+    BoardPayload,
+    acceptTakeback,
+    closeReadOnlySession,
+    declineTakeback,
+    executeMove,
+    flipBoard,
+    getBoard,
+    getSessionAccess,
+    openReadOnlySession,
+    requestTakeback,
+    touchPiece,
+} from "../commands";
 import { Square } from "./Square";
-import { listen } from "@tauri-apps/api/event";
+import { applyDelta, onAppEvent } from "../events";
 import swal from 'sweetalert2';
 
 interface Row {
-    row: number,
+    rank: number,
     pieces: Piece[];
 }
 
-function transformRows(allPieces: Piece[]): Row[] {
-    const rows = [];
-
-    for (let r = 0; r < 8; r++) {
-        let pieces = new Array(8);
-
-        for (let c = 0; c < 8; c++) {
-            const coord = toCoordFromXY(c, r);
-            const piece = allPieces.find(p => p.coord == coord);
-
-            if (piece) {
-                pieces[c] = piece;
-            }
-            else {
-                pieces[c] = { coord };
-            }
-        }
-
-        rows.push({
-            row: r,
-            pieces
-        });
-    }
-
-    rows.sort((a, b) => b.row - a.row);
-
-    return rows;
+function transformRows(allPieces: Piece[], files: string[], ranks: number[]): Row[] {
+    return ranks.map(rank => ({
+        rank,
+        pieces: files.map(file => {
+            const coord = `${file}${rank}`;
+            return allPieces.find(p => p.coord == coord) ?? { coord };
+        })
+    }));
 };
 
 export function Game() {
-    const [rows, setRows] = useState<Row[]>([]);
+    const [pieces, setPieces] = useState<Piece[]>([]);
+    const [files, setFiles] = useState<string[]>([]);
+    const [occupied, setOccupied] = useState<Coord[]>([]);
+    const [ranks, setRanks] = useState<number[]>([]);
+    const [orientation, setOrientation] = useState<Color>('White');
     const [selected, setSelected] = useState<Coord | null>(null);
     const [moves, setMoves] = useState<Move[]>([]);
     const [turn, setTurn] = useState<Color>('White');
     const [whiteChecked, setWhiteChecked] = useState<boolean>(false);
     const [blackChecked, setBlackChecked] = useState<boolean>(false);
     const [winner, setWinner] = useState<string | undefined>();
+    const [readOnly, setReadOnly] = useState<boolean>(false);
 
     useEffect(() => {
         function setState(payload: BoardPayload) {
-            setRows(transformRows(payload.pieces));
+            setPieces(payload.pieces);
+            setOccupied(payload.occupied);
+            setFiles(payload.files);
+            setRanks(payload.ranks);
+            setOrientation(payload.orientation);
             setTurn(payload.turn);
             setWhiteChecked(payload.whiteChecked);
             setBlackChecked(payload.blackChecked);
@@ -59,10 +62,40 @@ export function Game() {
 
         async function init() {
             setState(await getBoard());
+            setReadOnly(await getSessionAccess() == 'readOnly');
 
-            await listen<BoardPayload>('update', function({ payload }) {
+            await onAppEvent('boardUpdated', function({ payload }) {
                 setState(payload);
             })
+
+            await onAppEvent('boardDelta', function({ payload }) {
+                setPieces(pieces => applyDelta(pieces, payload));
+                setTurn(payload.turn);
+                setWhiteChecked(payload.whiteChecked);
+                setBlackChecked(payload.blackChecked);
+                setWinner(payload.winner);
+            })
+
+            await onAppEvent('correspondenceDeadlineNear', function({ payload }) {
+                swal.fire(`${payload.turn} has to move before ${new Date(payload.deadlineMs).toLocaleString()}`);
+            })
+
+            // in local games both sides share the screen, so the opponent answers right here
+            await onAppEvent('takebackRequested', async function({ payload }) {
+                const opponent: Color = payload.by == 'White' ? 'Black' : 'White';
+                const answer = await swal.fire({
+                    title: `${payload.by} asks to take back ${payload.plies == 1 ? 'a move' : 'two moves'}`,
+                    showCancelButton: true,
+                    confirmButtonText: 'Accept',
+                    cancelButtonText: 'Decline',
+                });
+
+                if (answer.isConfirmed) {
+                    await acceptTakeback(opponent);
+                } else {
+                    await declineTakeback(opponent);
+                }
+            })
         }
 
         init();
@@ -76,7 +109,12 @@ export function Game() {
         swal.fire(`${winner} has won!`);
     }, [winner]);
 
+    // the backend rejects commands from read-only windows, the board only stops offering moves here
     const handleSquareClick = async (piece: Piece) => {
+        if (readOnly) {
+            return;
+        }
+
         if (selected) {
             if (selected == piece.coord) {
                 setSelected(null);
@@ -95,7 +133,15 @@ export function Game() {
             }
         }
 
-        const availableMoves = await getAvailableMoves(piece.coord);
+        // with an arbiter, touching a second piece is rejected by the backend
+        let availableMoves: Move[];
+
+        try {
+            availableMoves = await touchPiece(piece.coord);
+        } catch (error) {
+            swal.fire(`${error}`);
+            return;
+        }
 
         if (availableMoves.length === 0) {
             setSelected(null);
@@ -107,34 +153,45 @@ export function Game() {
         setMoves(availableMoves);
     }
 
+    const openAnalysisWindow = async () => {
+        const label = `analysis-${Date.now()}`;
+        await openReadOnlySession(label);
+
+        const analysis = new WebviewWindow(label, { url: 'index.html', title: 'Analysis' });
+        await analysis.once('tauri://destroyed', () => closeReadOnlySession(label));
+    }
+
+    const rows = transformRows(pieces, files, ranks);
+
+    // the side at the top of the board is listed first
+    const sides: Color[] = orientation == 'White' ? ['Black', 'White'] : ['White', 'Black'];
+
     return (
         <div className="game">
             <div className="game-info">
-                <div className="turn-indicator">
-                    {turn == 'Black' ? <div><strong>Black's turn</strong></div> : null}
-                    {blackChecked ? <div><strong>Check!</strong></div> : null}
-                </div>
-                <div className="turn-indicator">
-                    {turn == 'White' ? <div><strong>White's turn</strong></div> : null}
-                    {whiteChecked ? <div><strong>Check!</strong></div> : null}
-                </div>
+                {sides.map(side => (
+                    <div className="turn-indicator" key={side}>
+                        {turn == side ? <div><strong>{side}'s turn</strong></div> : null}
+                        {(side == 'White' ? whiteChecked : blackChecked) ? <div><strong>Check!</strong></div> : null}
+                    </div>
+                ))}
+                {readOnly ? null : (
+                    <>
+                        <button onClick={() => flipBoard()}>Flip board</button>
+                        <button onClick={() => requestTakeback(turn == 'White' ? 'Black' : 'White')}>Take back</button>
+                        <button onClick={openAnalysisWindow}>Open analysis window</button>
+                    </>
+                )}
             </div>
             <div className="board">
                 <div className="row labels">
                     <div className="rank-label"></div>
-                    <div className="file-label">A</div>
-                    <div className="file-label">B</div>
-                    <div className="file-label">C</div>
-                    <div className="file-label">D</div>
-                    <div className="file-label">E</div>
-                    <div className="file-label">F</div>
-                    <div className="file-label">G</div>
-                    <div className="file-label">H</div>
+                    {files.map(file => <div className="file-label" key={file}>{file.toUpperCase()}</div>)}
                 </div>
 
                 {rows.map(row => (
-                    <div className="row" key={row.row}>
-                        <div className="rank-label">{row.row + 1}</div>
+                    <div className="row" key={row.rank}>
+                        <div className="rank-label">{row.rank}</div>
 
                         {row.pieces.map(piece =>
                             <Square
@@ -143,6 +200,7 @@ export function Game() {
                                 pieceType={piece.pieceType}
                                 color={piece.color}
                                 isSelected={selected === piece.coord}
+                                isSilhouette={occupied.includes(piece.coord)}
                                 isTarget={moves.some(m => m.to === piece.coord)}
                                 onClick={() => handleSquareClick(piece)}
                             />
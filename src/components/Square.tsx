@@ -3,6 +3,7 @@ import { Color, Coord, PieceType } from "../chess";
 interface SquareProps {
     isTarget: boolean;
     isSelected: boolean;
+    isSilhouette: boolean;
     coord: Coord;
     pieceType?: PieceType;
     color?: Color;
@@ -13,7 +14,7 @@ function getImageName(color: Color, piece: PieceType) {
     return `${color.toLowerCase()}_${piece.toLowerCase()}`;
 }
 
-export function Square({ isTarget, isSelected, pieceType, color, onClick }: SquareProps) {
+export function Square({ isTarget, isSelected, isSilhouette, pieceType, color, onClick }: SquareProps) {
     const classes = ['square'];
 
     if (isSelected) {
@@ -24,10 +25,14 @@ export function Square({ isTarget, isSelected, pieceType, color, onClick }: Squa
         classes.push('target');
     }
 
-    if (pieceType || color) {
+    if (pieceType || color || isSilhouette) {
         classes.push('occupied');
     }
 
+    if (isSilhouette) {
+        classes.push('silhouette');
+    }
+
     return (
         <div className={classes.join(' ')} onClick={onClick}>
             <div>
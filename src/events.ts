@@ -0,0 +1,82 @@
+import { listen } from "@tauri-apps/api/event";
+import { Color, Coord, Move, Piece } from "./chess";
+import { ArbiterStatus, BoardPayload, ChatMessage, ClockSnapshot, CorrespondenceGame, DrawReason, GameOutcome, GuessScore, GuessStatus, IterationInfo, MatchStatus, MoveTree, OpenStudy, Phase, PlaybackStatus, PvLine, SearchStats, SimulProgress, TournamentStatus } from "./commands";
+
+export const EVENT_VERSION = 1;
+
+export type MoveCue = 'move' | 'capture' | 'castle' | 'promotion' | 'check' | 'gameEnd';
+
+export interface BoardDelta {
+    mv: Move;
+    piece: Piece;
+    captured?: Coord;
+    promoted?: Piece;
+    rook?: [Coord, Coord];
+    turn: Color;
+    whiteChecked: boolean;
+    blackChecked: boolean;
+    winner?: Color;
+}
+
+export type AppEvent =
+    | { type: 'boardUpdated'; payload: BoardPayload }
+    | { type: 'boardDelta'; payload: BoardDelta }
+    | { type: 'movePlayed'; payload: { mv: Move; cues: MoveCue[] } }
+    | { type: 'takebackRequested'; payload: { by: Color; plies: number } }
+    | { type: 'takebackAnswered'; payload: { by: Color; accepted: boolean } }
+    | { type: 'clockUpdated'; payload: ClockSnapshot | null }
+    | { type: 'correspondenceUpdated'; payload: CorrespondenceGame | null }
+    | { type: 'correspondenceDeadlineNear'; payload: { turn: Color; deadlineMs: number } }
+    | { type: 'gameEnded'; payload: { winner?: Color; result: string } }
+    | { type: 'drawClaimed'; payload: { reason: DrawReason } }
+    | { type: 'drawOffered'; payload: { by: Color } }
+    | { type: 'drawOfferAnswered'; payload: { by: Color; accepted: boolean } }
+    | { type: 'resigned'; payload: { by: Color } }
+    | { type: 'chatReceived'; payload: ChatMessage }
+    | { type: 'arbiterUpdated'; payload: ArbiterStatus | null }
+    | { type: 'moveTreeUpdated'; payload: MoveTree }
+    | { type: 'phaseChanged'; payload: Phase }
+    | { type: 'studyUpdated'; payload: OpenStudy | null }
+    | { type: 'engineStarted' }
+    | { type: 'engineFinished' }
+    | { type: 'analysisInfo'; payload: IterationInfo }
+    | { type: 'searchStats'; payload: SearchStats }
+    | { type: 'searchLines'; payload: PvLine[] }
+    | { type: 'autoPlayFinished'; payload: GameOutcome }
+    | { type: 'playbackUpdated'; payload: PlaybackStatus }
+    | { type: 'guessScored'; payload: { guess: GuessScore; actual: Move; status: GuessStatus } }
+    | { type: 'simulUpdated'; payload: SimulProgress }
+    | { type: 'matchUpdated'; payload: MatchStatus | null }
+    | { type: 'tournamentUpdated'; payload: TournamentStatus | null }
+    | { type: 'error'; payload: { message: string } };
+
+export type AppEventOf<T extends AppEvent['type']> = Extract<AppEvent, { type: T }>;
+
+export function applyDelta(pieces: Piece[], delta: BoardDelta): Piece[] {
+    return pieces
+        .filter(p => p.coord !== delta.captured && p.coord !== delta.mv.to)
+        .map(p => {
+            if (p.coord === delta.mv.from) {
+                return { ...(delta.promoted ?? p), coord: delta.mv.to };
+            }
+
+            if (delta.rook && p.coord === delta.rook[0]) {
+                return { ...p, coord: delta.rook[1] };
+            }
+
+            return p;
+        });
+}
+
+// all backend events arrive on one channel, tagged with their type and the version of the catalogue
+export async function onAppEvent<T extends AppEvent['type']>(type: T, handler: (event: AppEventOf<T>) => void) {
+    return await listen<AppEvent & { version: number }>('app-event', function({ payload }) {
+        if (payload.version !== EVENT_VERSION) {
+            console.warn(`Unsupported event version ${payload.version}, expected ${EVENT_VERSION}`);
+        }
+
+        if (payload.type === type) {
+            handler(payload as AppEventOf<T>);
+        }
+    });
+}